@@ -1,7 +1,25 @@
-use crate::data::{DataType, Endianness, decode_blob, encode_values, is_binary};
-use crate::redis_client::{KeyInfo, RedisClient, RedisValue, StreamEntry};
+use crate::data::{
+    BitField, DataType, Detection, Endianness, RecordLayout, SampleScaling, bit_count, bit_grid_column,
+    bit_pos, cosine_similarity, decode_blob, decode_record_field, detect_data_type, detect_embedding,
+    encode_values, format_bit_grid, format_records, get_bit, is_binary, sparkline, vector_norm,
+    BITMAP_ROW_BITS,
+};
+use crate::diff::{condense, diff_lines, merge3, DiffLine};
+use crate::editor::{EditTarget, ExternalEdit};
+use crate::export::{ExportFormat, Exporter};
+use crate::import::{ConflictPolicy, CsvOptions, Importer, CONFLICT_POLICIES};
+use crate::textarea::TextArea;
+use crate::config::{server_label, DecodeProfile, LayoutProfile, ProfileStore, SavedServer, ServerStore};
+use crate::formats::{FormatSettings, ProtoSchema, Rendered, ValueFormat, format_epoch_millis, now_epoch_millis};
+use crate::json_tree::{JsonRow, json_rows, value_at};
+use crate::redis_client::{
+    GeoCenter, GeoMatch, GeoMember, KeyInfo, KeyWrite, ProbKind, ProbStructure, RedisClient, RedisValue,
+    SearchIndexInfo, SearchResults, StreamEntry, StreamTrim, TimeSeries, TsQuery, VectorInfo,
+};
 use ratatui::widgets::ListState;
 use rustfft::{FftPlanner, num_complex::Complex};
+use std::collections::HashSet;
+use std::rc::Rc;
 use std::sync::mpsc;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -17,8 +35,64 @@ pub enum EditOperation {
     NewKey,
     SetTTL,
     RenameKey,
+    JsonSet,
+    JsonDel,
+    JsonArrAppend,
+    JsonNumIncrBy,
+    TsAdd,
+    ProbAdd,
+    HDel,
+    LRem,
+    LPop,
+    RPop,
+    LTrim,
+    SRem,
+    ZRem,
+    ZRemRangeByScore,
+    XDel,
+    XTrim,
 }
 
+/// JSON.* operations offered by the edit popup, in Left/Right cycling order
+pub const JSON_OPS: &[EditOperation] = &[
+    EditOperation::JsonSet,
+    EditOperation::JsonDel,
+    EditOperation::JsonArrAppend,
+    EditOperation::JsonNumIncrBy,
+];
+
+/// Element removal and trim operations for a key type, in Left/Right cycling order
+fn remove_ops(key_type: &str) -> &'static [EditOperation] {
+    match key_type {
+        "hash" => &[EditOperation::HDel],
+        "list" => &[EditOperation::LRem, EditOperation::LPop, EditOperation::RPop, EditOperation::LTrim],
+        "set" => &[EditOperation::SRem],
+        "zset" => &[EditOperation::ZRem, EditOperation::ZRemRangeByScore],
+        "stream" => &[EditOperation::XDel, EditOperation::XTrim],
+        _ => &[],
+    }
+}
+
+/// ID, trim and NOMKSTREAM rows before the field/value pairs of the XADD popup
+pub const XADD_HEADER_FIELDS: usize = 3;
+
+/// Most entries counted when previewing an XTRIM MINID
+const TRIM_PREVIEW_CAP: usize = 10_000;
+
+/// Most snapshots kept in the undo history; the oldest go first
+const UNDO_LIMIT: usize = 50;
+
+/// Keys handled per event loop turn by a bulk action, so the server is never
+/// held up for long and the progress bar keeps moving
+const BULK_BATCH: usize = 100;
+
+/// Most keys measured with MEMORY USAGE for a delete-by-pattern preview;
+/// beyond that the total is estimated from an evenly spread sample
+const MEMORY_SAMPLE_CAP: usize = 10_000;
+
+/// The connected server in the copy popup's server field
+const THIS_SERVER: &str = "this server";
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Panel {
     KeyList,
@@ -53,6 +127,180 @@ pub enum InputMode {
     Edit,
     PlotLimit,
     SignalGen,
+    RecordLayout,
+    Scaling,
+    Profiles,
+    ValueFormat,
+    Query,
+    VectorCompare,
+    SearchIndexes,
+    SearchQuery,
+    SearchResults,
+    Conflict,
+    UndoHistory,
+    Bulk,
+    BulkProgress,
+    PatternDelete,
+    Export,
+    Import,
+    Copy,
+}
+
+/// What the export popup writes out, in Left/Right cycling order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportScope {
+    Keys,     // the marked keys, else the selected one
+    Filter,   // every key the filter pattern matches
+    Database, // every key of the current db
+}
+
+pub const EXPORT_SCOPES: &[ExportScope] = &[ExportScope::Keys, ExportScope::Filter, ExportScope::Database];
+
+/// Keys a delete-by-pattern found, waiting for their count to be typed
+#[derive(Debug, Clone)]
+pub struct PatternDelete {
+    pub pattern: String,
+    pub keys: Vec<String>,
+    pub memory: i64,             // bytes, summed over the keys
+    pub memory_estimated: bool,  // extrapolated from a sample
+    pub typed: String,           // must be the key count to go ahead
+}
+
+/// Keys the copy popup resolved, shown before they are copied
+pub struct CopyPlan {
+    pub keys: Vec<String>,
+    pub server: String,     // target server name or host:port
+    pub same_server: bool,  // COPY/MOVE rather than DUMP + RESTORE
+    pub db: i64,
+    pub moving: bool,
+    pub replace: bool,
+    pub existing: usize,    // keys already at the target
+    target: Option<RedisClient>, // connection to the other server, in the target db
+}
+
+/// Actions that run over all marked keys
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BulkAction {
+    Delete,
+    SetTtl,
+    ClearTtl,
+    MoveDb,
+    RenamePrefix,
+    Export,
+    Import, // from the import popup, not the bulk menu
+    CopyTo, // from the copy popup, like MoveTo
+    MoveTo,
+}
+
+/// Bulk actions in Left/Right cycling order
+pub const BULK_ACTIONS: &[BulkAction] = &[
+    BulkAction::Delete,
+    BulkAction::SetTtl,
+    BulkAction::ClearTtl,
+    BulkAction::MoveDb,
+    BulkAction::RenamePrefix,
+    BulkAction::Export,
+];
+
+impl BulkAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            BulkAction::Delete => "Delete (UNLINK)",
+            BulkAction::SetTtl => "Set TTL (EXPIRE)",
+            BulkAction::ClearTtl => "Clear TTL (PERSIST)",
+            BulkAction::MoveDb => "Move to db (MOVE)",
+            BulkAction::RenamePrefix => "Rename prefix (RENAMENX)",
+            BulkAction::Export => "Export to file",
+            BulkAction::Import => "Import from file",
+            BulkAction::CopyTo => "Copy keys",
+            BulkAction::MoveTo => "Move keys",
+        }
+    }
+
+    fn done_label(&self) -> &'static str {
+        match self {
+            BulkAction::Delete => "deleted",
+            BulkAction::SetTtl => "given a TTL",
+            BulkAction::ClearTtl => "made persistent",
+            BulkAction::MoveDb => "moved",
+            BulkAction::RenamePrefix => "renamed",
+            BulkAction::Export => "exported",
+            BulkAction::Import => "imported",
+            BulkAction::CopyTo => "copied",
+            BulkAction::MoveTo => "moved",
+        }
+    }
+}
+
+/// A confirmed bulk action, run a batch of keys per event loop turn
+pub struct BulkJob {
+    pub action: BulkAction,
+    pub keys: Vec<String>,
+    pub args: Vec<String>, // form values: seconds, db or prefixes
+    pub done: usize,       // keys handled so far
+    pub changed: usize,    // keys the action applied to
+    pub failed: usize,
+    pub last_error: Option<String>,
+    pub exporter: Option<Exporter>,
+    pub importer: Option<Importer>,
+    pub target: Option<RedisClient>, // other server of a copy or move
+}
+
+/// A key as it was before a destructive operation, restored by undo
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub at_ms: i64,
+    pub db: i64,
+    pub key: String,
+    pub action: String,            // what was done to it, e.g. "DEL" or "HSET"
+    pub payload: Option<Vec<u8>>,  // DUMP; None when the key did not exist yet
+    pub expires_at: Option<i64>,   // ms since the epoch
+    pub renamed_to: Option<String>, // RENAME target, removed again on undo
+}
+
+/// The value changed on the server between loading it and saving an edit
+#[derive(Debug, Clone)]
+pub struct EditConflict {
+    pub server: RedisValue,
+    pub diff: Vec<DiffLine>, // loaded → server, condensed
+    pub scroll: usize,
+}
+
+/// Result of a GEOSEARCH from the query popup
+#[derive(Debug, Clone)]
+pub struct GeoSearch {
+    pub summary: String,
+    pub center_text: String,        // as entered: member or "lon,lat"
+    pub center: Option<(f64, f64)>, // lon, lat
+    pub radius: f64,
+    pub unit: String,
+    pub count: usize,
+    pub matches: Vec<GeoMatch>,
+}
+
+/// Everything the decoded lines of a string or collection depend on; the
+/// lines are kept between frames while it stays the same
+#[derive(Clone, PartialEq)]
+struct RenderKey {
+    value_version: u64,
+    data_type: DataType,
+    endianness: Endianness,
+    scaling: SampleScaling,
+    record_layout: Option<RecordLayout>,
+    format: ValueFormat,
+    field_formats: Vec<(String, ValueFormat)>,
+    proto: (String, String, bool), // schema path, message, loaded
+}
+
+/// Units accepted by GEOSEARCH BYRADIUS
+pub const GEO_UNITS: &[&str] = &["m", "km", "mi", "ft"];
+
+/// What the signal plot's x values are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum XAxis {
+    Index, // sample index
+    Time,  // milliseconds since the epoch
+    Value, // a measured quantity (histogram bins)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -67,6 +315,23 @@ pub const WAVE_TYPES: &[&str] = &["sine", "square", "sawtooth", "triangle"];
 /// Default number of data points to show in auto-range plot mode
 pub const PLOT_WINDOW: usize = 2000;
 
+/// Lines before the first grid row of the bitmap view
+const BITMAP_HEADER_LINES: usize = 4;
+
+/// Column header and rule above the rows of an element table
+const ELEMENT_HEADER_LINES: usize = 2;
+
+/// Widest an element table column gets before its cells are cut
+const ELEMENT_COLUMN_WIDTH: usize = 40;
+
+/// Aggregators accepted by TS.RANGE AGGREGATION
+pub const TS_AGGREGATORS: &[&str] = &[
+    "avg", "min", "max", "sum", "count", "first", "last", "range", "std.p", "std.s", "var.p", "var.s", "twa",
+];
+
+/// Minimum detection confidence to switch data type automatically on load
+pub const AUTO_DETECT_MIN_CONFIDENCE: f64 = 0.5;
+
 pub struct App {
     pub running: bool,
     pub active_panel: Panel,
@@ -78,12 +343,52 @@ pub struct App {
     pub key_list_state: ListState,
     pub filter_text: String,
     pub filter_pattern: String,
+    pub marked_keys: HashSet<String>, // picked for bulk actions
 
     // Value display
     pub current_key_info: Option<KeyInfo>,
     pub current_value: Option<RedisValue>,
     pub value_scroll: u16,
 
+    // RedisJSON tree view
+    pub json_cursor: usize,            // selected row in the tree
+    pub json_collapsed: HashSet<String>, // JSONPaths of collapsed containers
+
+    // RedisTimeSeries range query (TS.RANGE / TS.REVRANGE)
+    pub ts_query: TsQuery,
+
+    // Geo sets: sorted sets whose scores are geohashes
+    pub geo_view: Option<bool>, // user override of the detection ([G])
+    pub geo_points: Option<Vec<GeoMember>>, // set while the geo view is shown
+    pub geo_search: Option<GeoSearch>,
+
+    // Last query on a probabilistic structure (BF.MEXISTS, TDIGEST.QUANTILE, ...)
+    pub prob_query_text: String,
+    pub prob_results: Vec<(String, String)>, // (item, reply)
+
+    // RediSearch index browser ([I])
+    pub search_indexes: Vec<String>,
+    pub search_cursor: usize,
+    pub search_info: Option<SearchIndexInfo>,
+    pub search_form: Vec<(String, String)>, // last FT.SEARCH/FT.AGGREGATE form
+    pub search_results: Option<SearchResults>,
+    pub search_result_cursor: usize,
+
+    // Vector similarity popup ([V])
+    pub vector_form: Vec<(String, String)>, // last keys/fields compared
+    pub vector_result: Vec<String>,
+
+    // Bitmap view of string values ([B])
+    pub bitmap_view: bool,
+    pub bit_cursor: u64,             // selected bit offset
+    pub bitfields: Vec<BitField>,    // BITFIELD inspector fields
+    pub bitfield_spec: String,
+
+    // Collection element table
+    pub element_cursor: usize,
+    pub element_detail: bool, // detail pane of the selected element
+    pub detail_scroll: u16,
+
     // Stream state
     pub expanded_stream_entries: Vec<bool>,
     pub last_stream_id: Option<String>, // for XREAD tracking
@@ -92,6 +397,9 @@ pub struct App {
     pub data_type: DataType,
     pub endianness: Endianness,
     pub plot_data: Vec<f64>,
+    pub plot_x: Vec<f64>, // explicit x values (time series, histogram bins); empty = sample index
+    pub plot_x_axis: XAxis,
+    pub plot_bars: bool,  // draw as bars (histograms, counts)
     pub plot_auto_limits: bool,
     pub plot_y_min: f64,
     pub plot_y_max: f64,
@@ -107,6 +415,34 @@ pub struct App {
     pub plot_visible: bool,
     pub help_scroll: u16,
 
+    // Record layout (array of C structs) decoding
+    pub record_layout: Option<RecordLayout>,
+    pub record_layout_def: String, // definition text as entered
+    pub record_field_idx: usize,   // member selected for plotting
+
+    // Header skip, decimation and gain/offset for decoded samples
+    pub scaling: SampleScaling,
+
+    // Per-key decode profiles
+    pub profiles: ProfileStore,
+    pub profile_load_error: Option<String>,
+    pub active_profile: Option<String>, // pattern of the profile applied to the current key
+    manual_settings: Option<DecodeProfile>, // settings from before a profile was applied, restored without one
+    pub profile_cursor: usize,
+    pub profile_pattern_input: String,
+
+    // Automatic data type detection for blobs
+    pub auto_detect: bool,
+    pub detection: Option<Detection>,
+    manual_type_key: Option<String>, // key whose type/byte order was picked with t/e; detection only suggests there
+
+    // Structured formatters for the value view (MessagePack, CBOR, ...)
+    pub formats: FormatSettings,
+    value_version: u64, // bumped whenever current_value is replaced or extended
+    value_lines_cache: Option<(RenderKey, Rc<Vec<String>>)>,
+    detail_lines_cache: Option<(RenderKey, usize, Rc<Vec<String>>)>, // with the element cursor
+    json_rows_cache: Option<(u64, Rc<Vec<JsonRow>>)>, // by value_version; dropped when a container is toggled
+
     // Plot viewport (x-axis panning/zooming)
     pub plot_x_min: f64,
     pub plot_x_max: f64,
@@ -136,20 +472,44 @@ pub struct App {
     pub db: i64,
     pub db_size: i64,
     pub connected: bool,
+    pub rdb_path: Option<String>, // browsing this RDB file instead of a server
     pub status_message: String,
 
     // Confirmation dialog
     pub confirm_action: Option<String>,
+    pub confirm_element: Option<usize>, // element row to delete instead of the key
+    pub confirm_edit: bool,             // run the pending removal/trim edit
+    pub external_edit: Option<ExternalEdit>, // $EDITOR result awaiting an overwrite
+    pub edit_conflict: Option<EditConflict>, // server-side change found on save
+    pub confirm_bulk: bool,             // start the bulk action being previewed
+
+    // Undo history (oldest first)
+    pub undo_stack: Vec<UndoEntry>,
+    pub undo_cursor: usize, // row in the history popup, newest first
+
+    // Bulk actions
+    pub bulk_action_idx: usize, // index into BULK_ACTIONS
+    pub bulk_job: Option<BulkJob>,
+    pub pattern_delete: Option<PatternDelete>, // preview of a delete-by-pattern
+    pub copy_plan: Option<CopyPlan>,           // preview of a copy or move
+    pub copy_servers: Vec<String>,             // saved server names, for Left/Right
+    pub export_scope_idx: usize, // index into EXPORT_SCOPES
+    pub export_path: String,     // last file exported to
+    pub import_path: String,     // last file imported
 
     // Edit state
     pub edit_operation: Option<EditOperation>,
     pub edit_fields: Vec<(String, String)>, // (label, value)
     pub edit_focus: usize,
     pub edit_key: String,          // the key being edited
+    pub edit_element: Option<Vec<u8>>, // original field/member of an in-place edit
     pub edit_multi_count: usize,   // how many entries submitted in this session
     pub new_key_type_idx: usize,   // index into KEY_TYPES for new key creation
     pub edit_binary_mode: bool,    // encode values as binary blobs
     pub edit_binary_dtype_idx: usize, // index into DataType::all() for binary encoding
+    pub xadd_encodings: Vec<Option<DataType>>, // per XADD field/value pair, None = text
+    pub edit_text: TextArea, // cursor, selection and undo of the edit popup's fields
+    pub clipboard: String,   // Ctrl+C/Ctrl+X in edit fields
 
     // Signal generator state
     pub signal_gen_fields: Vec<(String, String)>,
@@ -160,6 +520,10 @@ pub struct App {
 
 impl App {
     pub fn new() -> Self {
+        let (profiles, profile_load_error) = match ProfileStore::load() {
+            Ok(store) => (store, None),
+            Err(e) => (ProfileStore::empty(), Some(format!("{:#}", e))),
+        };
         Self {
             running: true,
             active_panel: Panel::KeyList,
@@ -170,17 +534,52 @@ impl App {
             key_list_state: ListState::default(),
             filter_text: String::new(),
             filter_pattern: String::from("*"),
+            marked_keys: HashSet::new(),
 
             current_key_info: None,
             current_value: None,
             value_scroll: 0,
 
+            json_cursor: 0,
+            json_collapsed: HashSet::new(),
+
+            ts_query: TsQuery::default(),
+
+            geo_view: None,
+            geo_points: None,
+            geo_search: None,
+
+            prob_query_text: String::new(),
+            prob_results: Vec::new(),
+
+            search_indexes: Vec::new(),
+            search_cursor: 0,
+            search_info: None,
+            search_form: Vec::new(),
+            search_results: None,
+            search_result_cursor: 0,
+
+            vector_form: Vec::new(),
+            vector_result: Vec::new(),
+
+            bitmap_view: false,
+            bit_cursor: 0,
+            bitfields: Vec::new(),
+            bitfield_spec: String::new(),
+
+            element_cursor: 0,
+            element_detail: false,
+            detail_scroll: 0,
+
             expanded_stream_entries: Vec::new(),
             last_stream_id: None,
 
             data_type: DataType::UInt8,
             endianness: Endianness::Little,
             plot_data: Vec::new(),
+            plot_x: Vec::new(),
+            plot_x_axis: XAxis::Index,
+            plot_bars: false,
             plot_auto_limits: true,
             plot_y_min: 0.0,
             plot_y_max: 1.0,
@@ -196,6 +595,28 @@ impl App {
             plot_visible: false,
             help_scroll: 0,
 
+            record_layout: None,
+            record_layout_def: String::new(),
+            record_field_idx: 0,
+
+            scaling: SampleScaling::default(),
+
+            profiles,
+            profile_load_error,
+            active_profile: None,
+            manual_settings: None,
+            profile_cursor: 0,
+            profile_pattern_input: String::new(),
+
+            auto_detect: true,
+            manual_type_key: None,
+            detection: None,
+            formats: FormatSettings::default(),
+            value_version: 0,
+            value_lines_cache: None,
+            detail_lines_cache: None,
+            json_rows_cache: None,
+
             plot_x_min: 0.0,
             plot_x_max: 0.0, // 0 means auto (full range)
             fft_x_min: 0.0,
@@ -220,18 +641,39 @@ impl App {
             db: 0,
             db_size: 0,
             connected: false,
+            rdb_path: None,
             status_message: String::from("Connecting..."),
 
             confirm_action: None,
+            confirm_element: None,
+            confirm_edit: false,
+            external_edit: None,
+            edit_conflict: None,
+            confirm_bulk: false,
+            undo_stack: Vec::new(),
+            undo_cursor: 0,
+
+            bulk_action_idx: 0,
+            bulk_job: None,
+            pattern_delete: None,
+            copy_plan: None,
+            copy_servers: Vec::new(),
+            export_scope_idx: 0,
+            export_path: "redis-export.ndjson".to_string(),
+            import_path: "redis-export.ndjson".to_string(),
 
             edit_operation: None,
             edit_fields: Vec::new(),
             edit_focus: 0,
             edit_key: String::new(),
+            edit_element: None,
             edit_multi_count: 0,
             new_key_type_idx: 0,
             edit_binary_mode: false,
             edit_binary_dtype_idx: 6, // Float32 default
+            xadd_encodings: Vec::new(),
+            edit_text: TextArea::default(),
+            clipboard: String::new(),
 
             signal_gen_fields: Vec::new(),
             signal_gen_focus: 0,
@@ -280,6 +722,7 @@ impl App {
         if let Some(idx) = self.key_list_state.selected() {
             if idx < self.keys.len() {
                 let key = &self.keys[idx].clone();
+                let same_key = self.current_key_info.as_ref().is_some_and(|i| &i.name == key);
 
                 match client.get_key_info(key) {
                     Ok(info) => self.current_key_info = Some(info),
@@ -289,7 +732,9 @@ impl App {
                     }
                 }
 
-                match client.get_value(key) {
+                self.apply_matching_profile(key);
+
+                match self.fetch_value(client, key) {
                     Ok(value) => {
                         // Track last stream ID for XREAD polling
                         if let RedisValue::Stream(ref entries) = value {
@@ -298,13 +743,40 @@ impl App {
                         } else {
                             self.last_stream_id = None;
                         }
+                        // Profiles take precedence over guessing
+                        if self.active_profile.is_none() {
+                            self.detect_value_type(&value);
+                        } else {
+                            self.detection = None;
+                        }
                         self.update_plot_data(&value);
+                        if self.fft_enabled {
+                            self.compute_fft();
+                        }
                         self.current_value = Some(value);
+                        self.value_version += 1;
                         self.value_scroll = 0;
+                        if !same_key {
+                            self.json_cursor = 0;
+                            self.json_collapsed.clear();
+                            self.json_tree_changed();
+                            self.prob_query_text.clear();
+                            self.prob_results.clear();
+                            self.geo_view = None;
+                            self.geo_search = None;
+                            self.bit_cursor = 0;
+                            self.element_cursor = 0;
+                            self.detail_scroll = 0;
+                        }
+                        self.clamp_element_cursor();
+                        self.clamp_json_cursor();
+                        self.clamp_bit_cursor();
+                        self.load_geo_points(client);
                     }
                     Err(e) => {
                         self.status_message = format!("Error reading value: {}", e);
                         self.current_value = None;
+                        self.geo_points = None;
                         self.plot_data.clear();
                         self.last_stream_id = None;
                     }
@@ -326,6 +798,7 @@ impl App {
         // Append to existing stream value
         if let Some(RedisValue::Stream(ref mut entries)) = self.current_value {
             entries.extend(new_entries);
+            self.value_version += 1;
             // Recompute plot from updated stream
             let value = RedisValue::Stream(entries.clone());
             self.update_plot_data(&value);
@@ -349,7 +822,7 @@ impl App {
                     self.current_key_info = Some(info);
                 }
 
-                match client.get_value(&key) {
+                match self.fetch_value(client, &key) {
                     Ok(value) => {
                         if let RedisValue::Stream(ref entries) = value {
                             self.last_stream_id =
@@ -360,6 +833,7 @@ impl App {
                             self.compute_fft();
                         }
                         self.current_value = Some(value);
+                        self.value_version += 1;
                     }
                     Err(_) => {}
                 }
@@ -367,6 +841,16 @@ impl App {
         }
     }
 
+    /// Read the value of `key`; time series are read with the current range query
+    fn fetch_value(&self, client: &mut RedisClient, key: &str) -> anyhow::Result<RedisValue> {
+        match &self.current_key_info {
+            Some(info) if info.key_type == "TSDB-TYPE" => {
+                client.get_timeseries(key, &self.ts_query).map(RedisValue::TimeSeries)
+            }
+            _ => client.get_value(key),
+        }
+    }
+
     pub fn is_viewing_stream(&self) -> bool {
         matches!(
             &self.current_key_info,
@@ -375,14 +859,21 @@ impl App {
     }
 
     fn update_plot_data(&mut self, value: &RedisValue) {
+        self.plot_x.clear();
+        self.plot_x_axis = XAxis::Index;
+        self.plot_bars = false;
         self.plot_data = match value {
-            RedisValue::String(bytes) => {
-                decode_blob(bytes, self.data_type, self.endianness)
+            RedisValue::String(bytes) if self.bitmap_view && !self.bitfields.is_empty() => {
+                self.bitfields.iter().flat_map(|f| f.decode(bytes)).map(|v| v as f64).collect()
             }
+            RedisValue::String(bytes) => self.decode_plot_blob(bytes),
             RedisValue::Stream(entries) => {
                 // Extract _ fields from stream entries and decode
                 self.expanded_stream_entries = vec![false; entries.len()];
-                extract_stream_plot_data(entries, self.data_type, self.endianness)
+                match extract_stream_plot_blob(entries) {
+                    Some(blob) => self.decode_plot_blob(blob),
+                    None => Vec::new(),
+                }
             }
             RedisValue::List(items) => {
                 // Try to parse list items as numbers or decode as blobs
@@ -394,8 +885,7 @@ impl App {
                             continue;
                         }
                     }
-                    let decoded = decode_blob(item, self.data_type, self.endianness);
-                    data.extend(decoded);
+                    data.extend(self.decode_plot_blob(item));
                 }
                 data
             }
@@ -413,13 +903,44 @@ impl App {
                             continue;
                         }
                     }
-                    let decoded = decode_blob(val, self.data_type, self.endianness);
-                    data.extend(decoded);
+                    data.extend(self.decode_plot_blob(val));
                 }
                 data
             }
+            RedisValue::TimeSeries(series) => {
+                // Plot chronologically against the sample timestamps
+                let mut samples = series.samples.clone();
+                samples.sort_by_key(|s| s.0);
+                self.plot_x = samples.iter().map(|s| s.0 as f64).collect();
+                self.plot_x_axis = XAxis::Time;
+                samples.iter().map(|s| s.1).collect()
+            }
+            RedisValue::Probabilistic(prob) => {
+                self.plot_bars = true;
+                match prob.kind {
+                    ProbKind::TopK => prob.top_items.iter().map(|(_, count)| *count as f64).collect(),
+                    ProbKind::TDigest => {
+                        self.plot_x = prob.histogram.iter().map(|b| b.0).collect();
+                        self.plot_x_axis = XAxis::Value;
+                        prob.histogram.iter().map(|b| b.1).collect()
+                    }
+                    // Estimated counts of the last queried items
+                    ProbKind::CountMinSketch => {
+                        self.prob_results.iter().filter_map(|(_, c)| c.parse().ok()).collect()
+                    }
+                    _ => Vec::new(),
+                }
+            }
             _ => Vec::new(),
         };
+        if !self.scaling.is_identity() {
+            self.plot_data = self.scaling.apply(std::mem::take(&mut self.plot_data));
+            if !self.plot_x.is_empty() {
+                // Same truncation and decimation, without gain/offset
+                let unscaled = SampleScaling { gain: 1.0, offset: 0.0, ..self.scaling.clone() };
+                self.plot_x = unscaled.apply(std::mem::take(&mut self.plot_x));
+            }
+        }
         // Sanitize: replace NaN/Infinity with 0.0 to prevent chart panics
         for v in &mut self.plot_data {
             if !v.is_finite() {
@@ -428,6 +949,16 @@ impl App {
         }
     }
 
+    /// Decode a blob for plotting: the selected record member when a layout
+    /// is active, otherwise a homogeneous array of the current data type.
+    fn decode_plot_blob(&self, bytes: &[u8]) -> Vec<f64> {
+        let bytes = self.scaling.skip_header(bytes);
+        match &self.record_layout {
+            Some(layout) => decode_record_field(bytes, layout, self.record_field_idx, self.endianness),
+            None => decode_blob(bytes, self.data_type, self.endianness),
+        }
+    }
+
     pub fn recompute_plot(&mut self) {
         if let Some(value) = &self.current_value.clone() {
             self.update_plot_data(value);
@@ -501,17 +1032,37 @@ impl App {
         }
     }
 
+    /// Signal samples as chart points: (index, value), or (x, value) when the
+    /// data has explicit x values
+    pub fn signal_points(&self) -> Vec<(f64, f64)> {
+        if self.plot_x.is_empty() {
+            self.plot_data.iter().enumerate().map(|(i, v)| (i as f64, *v)).collect()
+        } else {
+            self.plot_x.iter().copied().zip(self.plot_data.iter().copied()).collect()
+        }
+    }
+
+    /// Full x range of the signal data
+    pub fn signal_x_extent(&self) -> (f64, f64) {
+        match (self.plot_x.first(), self.plot_x.last()) {
+            (Some(&first), Some(&last)) => (first, last.max(first + 1.0)),
+            _ => (0.0, self.plot_data.len() as f64),
+        }
+    }
+
     /// Get the x-axis bounds for the signal chart.
     /// In auto mode, show the newest data (last PLOT_WINDOW points or fewer).
     pub fn signal_x_bounds(&self) -> (f64, f64) {
-        let full = self.plot_data.len() as f64;
+        let (lo, hi) = self.signal_x_extent();
         if self.plot_x_max <= self.plot_x_min {
             // Auto: show last PLOT_WINDOW points
-            let window = PLOT_WINDOW as f64;
-            if full <= window {
-                (0.0, full)
+            let n = self.plot_data.len();
+            if n <= PLOT_WINDOW {
+                (lo, hi)
+            } else if self.plot_x.len() == n {
+                (self.plot_x[n - PLOT_WINDOW], hi)
             } else {
-                (full - window, full)
+                (hi - PLOT_WINDOW as f64, hi)
             }
         } else {
             (self.plot_x_min, self.plot_x_max)
@@ -555,8 +1106,8 @@ impl App {
             } else {
                 (self.plot_y_min, self.plot_y_max)
             };
-            let full_x = self.plot_data.len() as f64;
-            let (nx0, nx1) = zoom_range(x0, x1, factor, center_frac_x, 0.0, full_x);
+            let (lo_x, hi_x) = self.signal_x_extent();
+            let (nx0, nx1) = zoom_range(x0, x1, factor, center_frac_x, lo_x, hi_x);
             let (ny0, ny1) = zoom_range(y0, y1, factor, center_frac_y, f64::NEG_INFINITY, f64::INFINITY);
             self.plot_x_min = nx0;
             self.plot_x_max = nx1;
@@ -694,260 +1245,3299 @@ impl App {
         Ok(())
     }
 
-    pub fn auto_signal_bounds(&self) -> (f64, f64) {
-        auto_bounds(&self.plot_data)
+    pub fn start_set_scaling(&mut self) {
+        let sc = &self.scaling;
+        self.edit_fields = vec![
+            ("Byte offset".to_string(), sc.byte_offset.to_string()),
+            ("Max samples (0=all)".to_string(), sc.max_samples.to_string()),
+            ("Decimation".to_string(), sc.decimation.to_string()),
+            ("Channels (interleaved)".to_string(), sc.channels.to_string()),
+            ("Channel (0-based)".to_string(), sc.channel.to_string()),
+            ("Gain".to_string(), sc.gain.to_string()),
+            ("Offset".to_string(), sc.offset.to_string()),
+            ("Unit".to_string(), sc.unit.clone()),
+        ];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::Scaling;
     }
 
-    pub fn auto_fft_bounds(&self) -> (f64, f64) {
-        let data = self.fft_display_data();
-        auto_bounds(&data)
+    pub fn apply_scaling(&mut self) -> Result<(), String> {
+        let byte_offset: usize = self.edit_fields[0]
+            .1
+            .trim()
+            .parse()
+            .map_err(|_| "Invalid byte offset".to_string())?;
+        let max_samples: usize = self.edit_fields[1]
+            .1
+            .trim()
+            .parse()
+            .map_err(|_| "Invalid max samples".to_string())?;
+        let decimation: usize = self.edit_fields[2]
+            .1
+            .trim()
+            .parse()
+            .map_err(|_| "Invalid decimation".to_string())?;
+        if decimation == 0 {
+            return Err("Decimation must be at least 1".to_string());
+        }
+        let channels: usize = self.edit_fields[3]
+            .1
+            .trim()
+            .parse()
+            .map_err(|_| "Invalid channel count".to_string())?;
+        let channel: usize = self.edit_fields[4]
+            .1
+            .trim()
+            .parse()
+            .map_err(|_| "Invalid channel".to_string())?;
+        if channels == 0 {
+            return Err("Channels must be at least 1".to_string());
+        }
+        if channel >= channels {
+            return Err(format!("Channel must be below {}", channels));
+        }
+        let gain: f64 = self.edit_fields[5]
+            .1
+            .trim()
+            .parse()
+            .map_err(|_| "Invalid gain".to_string())?;
+        let offset: f64 = self.edit_fields[6]
+            .1
+            .trim()
+            .parse()
+            .map_err(|_| "Invalid offset".to_string())?;
+        if !gain.is_finite() || !offset.is_finite() {
+            return Err("Gain and offset must be finite".to_string());
+        }
+        self.scaling = SampleScaling {
+            byte_offset,
+            max_samples,
+            decimation,
+            channels,
+            channel,
+            gain,
+            offset,
+            unit: self.edit_fields[7].1.trim().to_string(),
+        };
+        self.recompute_plot();
+        Ok(())
     }
 
-    /// Get FFT data for display (applies log scale if enabled)
-    pub fn fft_display_data(&self) -> Vec<f64> {
-        if self.fft_log_scale {
-            self.fft_data
-                .iter()
-                .map(|&v| if v > 0.0 { v.log10() } else { -10.0 })
-                .collect()
+    /// One-line description of the active scaling, e.g. `skip 16B, ch 1/2, 1/2, x0.001+0, mV`
+    pub fn scaling_summary(&self) -> String {
+        let sc = &self.scaling;
+        let mut parts = Vec::new();
+        if sc.byte_offset > 0 {
+            parts.push(format!("skip {}B", sc.byte_offset));
+        }
+        if sc.channels > 1 {
+            parts.push(format!("ch {}/{}", sc.channel, sc.channels));
+        }
+        if sc.max_samples > 0 {
+            parts.push(format!("max {}", sc.max_samples));
+        }
+        if sc.decimation > 1 {
+            parts.push(format!("1/{}", sc.decimation));
+        }
+        if sc.is_scaled() {
+            parts.push(format!("x{}{:+}", sc.gain, sc.offset));
+        }
+        if !sc.unit.is_empty() {
+            parts.push(sc.unit.clone());
+        }
+        if parts.is_empty() {
+            "raw".to_string()
         } else {
-            self.fft_data.clone()
+            parts.join(", ")
         }
     }
 
-    pub fn select_next_key(&mut self) {
-        if self.keys.is_empty() {
-            return;
-        }
-        let i = match self.key_list_state.selected() {
-            Some(i) => {
-                if i >= self.keys.len() - 1 {
-                    0
-                } else {
-                    i + 1
+    // ─── Data type detection ─────────────────────────────────
+
+    /// Guess the data type of a binary blob (string value or newest stream
+    /// `_` field). In auto mode a confident guess is selected immediately,
+    /// unless the type of this key was picked by hand; otherwise it is only
+    /// offered as a suggestion.
+    fn detect_value_type(&mut self, value: &RedisValue) {
+        let blob = match value {
+            RedisValue::String(bytes) if is_binary(bytes) => Some(bytes.as_slice()),
+            RedisValue::Stream(entries) => extract_stream_plot_blob(entries),
+            _ => None,
+        };
+        self.detection = blob
+            .map(|b| self.scaling.skip_header(b))
+            .filter(|b| !b.is_empty() && self.record_layout.is_none())
+            .and_then(|b| detect_data_type(b).first().copied());
+        let manual = self.manual_type_key.is_some() && self.manual_type_key.as_deref() == self.selected_key_name();
+        if self.auto_detect && !manual {
+            if let Some(d) = self.detection {
+                if d.confidence >= AUTO_DETECT_MIN_CONFIDENCE {
+                    self.data_type = d.data_type;
+                    self.endianness = d.endianness;
                 }
             }
-            None => 0,
-        };
-        self.key_list_state.select(Some(i));
+        }
     }
 
-    pub fn select_prev_key(&mut self) {
-        if self.keys.is_empty() {
-            return;
-        }
-        let i = match self.key_list_state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.keys.len() - 1
-                } else {
-                    i - 1
+    /// Toggle auto-detect; turning it on re-detects and applies the guess for
+    /// the current value regardless of confidence.
+    pub fn toggle_auto_detect(&mut self) {
+        self.auto_detect = !self.auto_detect;
+        if self.auto_detect {
+            self.manual_type_key = None;
+            if let Some(value) = self.current_value.clone() {
+                self.detect_value_type(&value);
+                if let Some(d) = self.detection {
+                    self.data_type = d.data_type;
+                    self.endianness = d.endianness;
                 }
+                self.recompute_plot();
             }
-            None => 0,
-        };
-        self.key_list_state.select(Some(i));
+        }
     }
 
-    pub fn scroll_value_down(&mut self) {
-        self.value_scroll = self.value_scroll.saturating_add(1);
+    /// Remember that the data type or byte order of the selected key was
+    /// picked by hand, so reloading it keeps the choice
+    pub fn mark_manual_type(&mut self) {
+        self.manual_type_key = self.selected_key_name().map(|k| k.to_string());
     }
 
-    pub fn scroll_value_up(&mut self) {
-        self.value_scroll = self.value_scroll.saturating_sub(1);
+    /// Label for the plot title, e.g. `auto float32 LE 87%` or `guess? int16 BE 40%`
+    pub fn detection_label(&self) -> Option<String> {
+        let d = self.detection?;
+        let in_use = d.data_type == self.data_type && d.endianness == self.endianness;
+        let type_label = if d.data_type.byte_size() == Some(1) {
+            d.data_type.to_string()
+        } else {
+            format!("{} {}", d.data_type, d.endianness)
+        };
+        Some(format!(
+            "{} {} {:.0}%",
+            if in_use { "auto" } else { "guess?" },
+            type_label,
+            d.confidence * 100.0
+        ))
     }
 
-    pub fn selected_key_name(&self) -> Option<&str> {
-        self.key_list_state
-            .selected()
-            .and_then(|i| self.keys.get(i).map(|s| s.as_str()))
+    // ─── Value formatters ─────────────────────────────────────
+
+    pub fn start_value_format(&mut self) {
+        self.edit_fields = vec![
+            ("Format".to_string(), self.formats.format.to_string()),
+            ("Field (blank = whole value)".to_string(), String::new()),
+            ("Proto schema (.proto/.desc)".to_string(), self.formats.proto_path.clone()),
+            ("Proto message".to_string(), self.formats.proto_message.clone()),
+        ];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::ValueFormat;
     }
 
-    pub fn apply_filter(&mut self) {
-        if self.filter_text.is_empty() {
-            self.filter_pattern = "*".to_string();
+    /// Apply the formatter popup: the format goes to the whole value, or to
+    /// one hash/stream field (or list index) when a field is given
+    pub fn apply_value_format(&mut self) -> Result<String, String> {
+        let format = ValueFormat::from_name(&self.edit_fields[0].1).ok_or_else(|| {
+            let names: Vec<String> = ValueFormat::all().iter().map(|f| f.to_string()).collect();
+            format!("Unknown format (one of: {})", names.join(", "))
+        })?;
+        let field = self.edit_fields[1].1.trim().to_string();
+        let path = self.edit_fields[2].1.trim().to_string();
+        let message = self.edit_fields[3].1.trim().to_string();
+        if path != self.formats.proto_path {
+            self.formats.proto = if path.is_empty() {
+                None
+            } else {
+                Some(ProtoSchema::load(&path)?)
+            };
+            self.formats.proto_path = path;
+        }
+        if format == ValueFormat::Protobuf {
+            if let Some(schema) = &self.formats.proto {
+                schema.resolve_message(&message)?;
+            }
+        }
+        self.formats.proto_message = message;
+        if field.is_empty() {
+            self.formats.format = format;
+            Ok(format!("Format: {}", self.formats.summary()))
         } else {
-            self.filter_pattern = format!("*{}*", self.filter_text);
+            self.formats.set_field_format(&field, format);
+            Ok(format!("Format for '{}': {}", field, format))
         }
     }
 
-    /// Format the current value for display
-    pub fn format_value(&self) -> Vec<String> {
+    // ─── Query popup ─────────────────────────────────────────
+
+    /// Open the query popup for the loaded value's type
+    pub fn start_query(&mut self) {
         match &self.current_value {
-            None => vec!["(no value loaded)".to_string()],
-            Some(RedisValue::String(bytes)) => {
-                if is_binary(bytes) {
-                    let mut lines = Vec::new();
-                    // Show decoded values using current data type
-                    lines.push(format!("── Decoded as {} ({}) ──", self.data_type, self.endianness));
-                    let decoded = crate::data::format_blob(bytes, self.data_type, self.endianness);
-                    for l in decoded.lines() {
-                        lines.push(l.to_string());
-                    }
-                    lines.push(String::new());
-                    lines.push(format!("── Hex dump ({} bytes) ──", bytes.len()));
-                    for l in crate::data::format_hex(bytes).lines() {
-                        lines.push(l.to_string());
-                    }
-                    lines
-                } else {
-                    let s = String::from_utf8_lossy(bytes).to_string();
-                    // Try to pretty-print JSON
-                    if let Ok(json) = serde_json::from_str::<serde_json::Value>(&s) {
-                        if let Ok(pretty) = serde_json::to_string_pretty(&json) {
-                            return pretty.lines().map(|l| l.to_string()).collect();
-                        }
-                    }
-                    s.lines().map(|l| l.to_string()).collect()
-                }
-            }
-            Some(RedisValue::List(items)) => {
-                items
-                    .iter()
-                    .enumerate()
-                    .map(|(i, item)| {
-                        let s = String::from_utf8_lossy(item);
-                        format!("[{}] {}", i, s)
-                    })
-                    .collect()
-            }
-            Some(RedisValue::Set(items)) => {
-                items
-                    .iter()
-                    .map(|item| {
-                        let s = String::from_utf8_lossy(item);
-                        format!("- {}", s)
-                    })
-                    .collect()
+            Some(RedisValue::TimeSeries(_)) => self.start_ts_query(),
+            Some(RedisValue::Probabilistic(prob)) => self.start_prob_query(prob.kind),
+            Some(RedisValue::ZSet(_)) if self.geo_points.is_some() => self.start_geo_search(),
+            Some(RedisValue::String(_)) if self.bitmap_view => self.start_bitfield_query(),
+            _ => self.status_message = "No query for this key type".to_string(),
+        }
+    }
+
+    /// Run the query popup; returns the status message
+    pub fn run_query(&mut self, client: &mut RedisClient) -> Result<String, String> {
+        match &self.current_value {
+            Some(RedisValue::TimeSeries(_)) => {
+                let msg = self.apply_ts_query()?;
+                self.set_auto_limits();
+                self.load_selected_value(client);
+                Ok(msg)
             }
-            Some(RedisValue::ZSet(pairs)) => {
-                pairs
-                    .iter()
-                    .map(|(member, score)| {
-                        let s = String::from_utf8_lossy(member);
-                        format!("{:.4}  {}", score, s)
-                    })
-                    .collect()
+            Some(RedisValue::Probabilistic(prob)) => {
+                let kind = prob.kind;
+                self.run_prob_query(client, kind)
             }
-            Some(RedisValue::Hash(pairs)) => {
-                pairs
-                    .iter()
-                    .map(|(field, val)| {
-                        let s = String::from_utf8_lossy(val);
-                        format!("{}  =>  {}", field, s)
-                    })
-                    .collect()
+            Some(RedisValue::ZSet(_)) if self.geo_points.is_some() => self.run_geo_search(client),
+            Some(RedisValue::String(_)) if self.bitmap_view => self.apply_bitfields(),
+            _ => Err("No query for this key type".to_string()),
+        }
+    }
+
+    // ─── Time series range query ─────────────────────────────
+
+    pub fn is_viewing_timeseries(&self) -> bool {
+        matches!(self.current_value, Some(RedisValue::TimeSeries(_)))
+    }
+
+    pub fn start_ts_query(&mut self) {
+        let q = &self.ts_query;
+        let (agg, bucket) = match &q.aggregation {
+            Some((agg, bucket)) => (agg.clone(), bucket.to_string()),
+            None => ("none".to_string(), String::new()),
+        };
+        self.edit_fields = vec![
+            ("From (ms or -)".to_string(), q.from.clone()),
+            ("To (ms or +)".to_string(), q.to.clone()),
+            ("Aggregation".to_string(), agg),
+            ("Bucket (ms)".to_string(), bucket),
+            ("Count (0 = all)".to_string(), q.count.to_string()),
+            ("Order (asc/desc)".to_string(), if q.reverse { "desc" } else { "asc" }.to_string()),
+        ];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::Query;
+    }
+
+    /// Validate the range query popup and store it; the caller reloads the value
+    pub fn apply_ts_query(&mut self) -> Result<String, String> {
+        let bound = |text: &str, open: &str, name: &str| -> Result<String, String> {
+            match text.trim() {
+                "" => Ok(open.to_string()),
+                t if t == open || t.parse::<u64>().is_ok() => Ok(t.to_string()),
+                _ => Err(format!("Invalid {} (ms since epoch, or {})", name, open)),
             }
-            Some(RedisValue::Stream(entries)) => format_stream_entries(entries, self.data_type, self.endianness),
-            Some(RedisValue::Unknown(msg)) => vec![msg.clone()],
+        };
+        let from = bound(&self.edit_fields[0].1, "-", "From")?;
+        let to = bound(&self.edit_fields[1].1, "+", "To")?;
+        let agg = self.edit_fields[2].1.trim().to_lowercase();
+        let aggregation = match agg.as_str() {
+            "" | "none" => None,
+            a if TS_AGGREGATORS.contains(&a) => {
+                let bucket: u64 = self.edit_fields[3]
+                    .1
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|b| *b > 0)
+                    .ok_or_else(|| "Bucket must be a positive number of ms".to_string())?;
+                Some((agg.clone(), bucket))
+            }
+            _ => {
+                return Err(format!("Unknown aggregation (none, {})", TS_AGGREGATORS.join(", ")));
+            }
+        };
+        let count = match self.edit_fields[4].1.trim() {
+            "" => 0,
+            c => c.parse().map_err(|_| "Invalid count".to_string())?,
+        };
+        let reverse = match self.edit_fields[5].1.trim().to_lowercase().as_str() {
+            "desc" | "rev" => true,
+            "asc" | "" => false,
+            _ => return Err("Order must be asc or desc".to_string()),
+        };
+        self.ts_query = TsQuery { from, to, aggregation, count, reverse };
+        Ok(format!("Range: {}", self.ts_query.summary()))
+    }
+
+    // ─── Probabilistic structures ────────────────────────────
+
+    pub fn prob_kind(&self) -> Option<ProbKind> {
+        match &self.current_value {
+            Some(RedisValue::Probabilistic(prob)) => Some(prob.kind),
+            _ => None,
         }
     }
 
-    // ─── Edit operations ─────────────────────────────────────
+    fn start_prob_query(&mut self, kind: ProbKind) {
+        let label = match kind {
+            ProbKind::HyperLogLog => "Keys to union (blank = this key)",
+            ProbKind::TDigest => "Quantiles (0..1)",
+            _ => "Items",
+        };
+        let last = self.prob_query_text.split_once(' ').map(|(_, args)| args).unwrap_or("");
+        self.edit_fields = vec![(label.to_string(), last.to_string())];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::Query;
+    }
 
-    pub fn start_edit(&mut self) {
-        let key_type = self
-            .current_key_info
-            .as_ref()
-            .map(|i| i.key_type.as_str())
-            .unwrap_or("");
-        let key = match self.selected_key_name() {
-            Some(k) => k.to_string(),
-            None => return,
+    fn run_prob_query(&mut self, client: &mut RedisClient, kind: ProbKind) -> Result<String, String> {
+        let items: Vec<String> = self.edit_fields[0].1.split_whitespace().map(|s| s.to_string()).collect();
+        if items.is_empty() && kind != ProbKind::HyperLogLog {
+            return Err("Enter at least one item (space separated)".to_string());
+        }
+        if kind == ProbKind::TDigest
+            && !items.iter().all(|q| q.parse::<f64>().is_ok_and(|q| (0.0..=1.0).contains(&q)))
+        {
+            return Err("Quantiles must be numbers between 0 and 1".to_string());
+        }
+        let key = self.selected_key_name().ok_or("No key selected")?.to_string();
+        let results = client.prob_query(&key, kind, &items).map_err(|e| e.to_string())?;
+        self.prob_query_text = format!("{} {}", kind.query_command(), items.join(" "));
+        self.prob_results = results;
+        if kind == ProbKind::CountMinSketch {
+            self.recompute_plot();
+        }
+        Ok(format!("{}: {} result(s)", kind.query_command(), self.prob_results.len()))
+    }
+
+    // ─── Geo sets ────────────────────────────────────────────
+
+    pub fn is_geo_view(&self) -> bool {
+        self.geo_points.is_some()
+    }
+
+    /// Fetch positions when the loaded zset is (or is toggled to be) a geo set
+    fn load_geo_points(&mut self, client: &mut RedisClient) {
+        self.geo_points = None;
+        let Some(RedisValue::ZSet(pairs)) = &self.current_value else {
+            return;
         };
-        self.edit_key = key.clone();
+        if !self.geo_view.unwrap_or_else(|| looks_like_geo(pairs)) {
+            return;
+        }
+        let Some(key) = self.selected_key_name().map(|k| k.to_string()) else {
+            return;
+        };
+        let members: Vec<Vec<u8>> = pairs.iter().map(|(m, _)| m.clone()).collect();
+        match client.geo_members(&key, &members) {
+            Ok(points) => self.geo_points = Some(points),
+            Err(e) => self.status_message = format!("Error reading positions: {}", e),
+        }
+    }
+
+    /// Switch a sorted set between the geo view and the plain score list
+    pub fn toggle_geo_view(&mut self, client: &mut RedisClient) {
+        if !matches!(self.current_value, Some(RedisValue::ZSet(_))) {
+            self.status_message = "Geo view applies to sorted sets".to_string();
+            return;
+        }
+        self.geo_view = Some(!self.is_geo_view());
+        self.geo_search = None;
+        self.load_geo_points(client);
+        self.status_message = format!("Geo view: {}", if self.is_geo_view() { "ON" } else { "OFF" });
+    }
+
+    /// Member table with coordinates and geohashes, then the last search
+    fn format_geo(&self) -> Vec<String> {
+        let points = self.geo_points.as_deref().unwrap_or_default();
+        let width = points.iter().map(|p| p.member.chars().count()).max().unwrap_or(6).max(6);
+        let mut lines = vec![
+            format!("── Geo set ({} members) ──", points.len()),
+            format!("{:<width$}  {:>11}  {:>10}  {}", "member", "longitude", "latitude", "geohash", width = width),
+        ];
+        for p in points {
+            lines.push(format!(
+                "{:<width$}  {:>11.6}  {:>10.6}  {}",
+                p.member, p.lon, p.lat, p.hash,
+                width = width
+            ));
+        }
+        if let Some(search) = &self.geo_search {
+            lines.push(String::new());
+            lines.push(format!("── {} ({} matches) ──", search.summary, search.matches.len()));
+            for m in &search.matches {
+                lines.push(format!(
+                    "{:<width$}  {:>11.6}  {:>10.6}  {:.3}",
+                    m.member, m.lon, m.lat, m.dist,
+                    width = width
+                ));
+            }
+        }
+        lines
+    }
+
+    fn start_geo_search(&mut self) {
+        let (center, radius, unit, count) = match &self.geo_search {
+            Some(s) => (s.center_text.clone(), s.radius.to_string(), s.unit.clone(), s.count.to_string()),
+            None => (String::new(), "10".to_string(), "km".to_string(), "0".to_string()),
+        };
+        self.edit_fields = vec![
+            ("Center (member or lon,lat)".to_string(), center),
+            ("Radius".to_string(), radius),
+            ("Unit (m/km/mi/ft)".to_string(), unit),
+            ("Count (0 = all)".to_string(), count),
+        ];
         self.edit_focus = 0;
-        self.edit_multi_count = 0;
+        self.input_mode = InputMode::Query;
+    }
 
-        match key_type {
-            "string" => {
-                let current = match &self.current_value {
-                    Some(RedisValue::String(b)) => String::from_utf8_lossy(b).to_string(),
-                    _ => String::new(),
-                };
-                self.edit_operation = Some(EditOperation::SetString);
-                self.edit_fields = vec![("Value".to_string(), current)];
+    fn run_geo_search(&mut self, client: &mut RedisClient) -> Result<String, String> {
+        let center_text = self.edit_fields[0].1.trim().to_string();
+        if center_text.is_empty() {
+            return Err("Center is required".to_string());
+        }
+        let center = match center_text.split_once(',') {
+            Some((lon, lat)) => {
+                let lon: f64 = lon.trim().parse().map_err(|_| "Invalid longitude".to_string())?;
+                let lat: f64 = lat.trim().parse().map_err(|_| "Invalid latitude".to_string())?;
+                GeoCenter::LonLat(lon, lat)
             }
-            "hash" => {
-                self.edit_operation = Some(EditOperation::HSet);
-                self.edit_fields = vec![
-                    ("Field".to_string(), String::new()),
-                    ("Value".to_string(), String::new()),
-                ];
+            None => GeoCenter::Member(center_text.clone()),
+        };
+        let radius: f64 = self.edit_fields[1]
+            .1
+            .trim()
+            .parse()
+            .ok()
+            .filter(|r: &f64| *r > 0.0)
+            .ok_or_else(|| "Radius must be a positive number".to_string())?;
+        let unit = self.edit_fields[2].1.trim().to_lowercase();
+        if !GEO_UNITS.contains(&unit.as_str()) {
+            return Err(format!("Unit must be one of {}", GEO_UNITS.join(", ")));
+        }
+        let count: usize = match self.edit_fields[3].1.trim() {
+            "" => 0,
+            c => c.parse().map_err(|_| "Invalid count".to_string())?,
+        };
+        let key = self.selected_key_name().ok_or("No key selected")?.to_string();
+        let matches = client
+            .geo_search(&key, &center, radius, &unit, count)
+            .map_err(|e| e.to_string())?;
+        let center_pos = match &center {
+            GeoCenter::LonLat(lon, lat) => Some((*lon, *lat)),
+            GeoCenter::Member(m) => self
+                .geo_points
+                .as_deref()
+                .unwrap_or_default()
+                .iter()
+                .find(|p| &p.member == m)
+                .map(|p| (p.lon, p.lat)),
+        };
+        let from = match center {
+            GeoCenter::LonLat(lon, lat) => format!("FROMLONLAT {} {}", lon, lat),
+            GeoCenter::Member(m) => format!("FROMMEMBER {}", m),
+        };
+        let n = matches.len();
+        self.geo_search = Some(GeoSearch {
+            summary: format!("GEOSEARCH {} BYRADIUS {} {}", from, radius, unit),
+            center_text,
+            center: center_pos,
+            radius,
+            unit: unit.clone(),
+            count,
+            matches,
+        });
+        Ok(format!("GEOSEARCH: {} match(es) within {} {}", n, radius, unit))
+    }
+
+    // ─── Decode profiles ──────────────────────────────────────
+
+    /// Apply the profile matching `key`, or go back to the settings in use
+    /// before a profile took over when none matches
+    fn apply_matching_profile(&mut self, key: &str) {
+        let profile = self.profiles.find(key).cloned();
+        self.active_profile = profile.as_ref().map(|p| p.pattern.clone());
+        match profile {
+            Some(profile) => {
+                self.save_manual_settings();
+                if let Err(e) = self.apply_profile(&profile) {
+                    self.status_message = format!("Profile '{}': {}", profile.pattern, e);
+                }
             }
-            "list" => {
-                self.edit_operation = Some(EditOperation::RPush);
-                self.edit_fields = vec![("Value (appended)".to_string(), String::new())];
+            None => {
+                if let Some(manual) = self.manual_settings.take() {
+                    if let Err(e) = self.apply_profile(&manual) {
+                        self.status_message = format!("Restoring decode settings: {}", e);
+                    }
+                }
             }
-            "set" => {
-                self.edit_operation = Some(EditOperation::SAdd);
-                self.edit_fields = vec![("Member".to_string(), String::new())];
+        }
+    }
+
+    /// Remember the current settings unless a profile already replaced them
+    fn save_manual_settings(&mut self) {
+        if self.manual_settings.is_none() {
+            self.manual_settings = Some(self.profile_from_current(""));
+        }
+    }
+
+    /// Load a profile's decode settings into the current view state. The
+    /// layout and protobuf schema are read first, so a profile that fails
+    /// leaves the current settings untouched.
+    pub fn apply_profile(&mut self, profile: &DecodeProfile) -> Result<(), String> {
+        let layout = match &profile.layout {
+            Some(lp) => Some(RecordLayout::parse(&lp.definition, lp.header_size, lp.packed)?),
+            None => None,
+        };
+        let proto = if profile.proto_schema != self.formats.proto_path && !profile.proto_schema.is_empty() {
+            Some(ProtoSchema::load(&profile.proto_schema)?)
+        } else {
+            None
+        };
+        self.data_type = profile.data_type;
+        self.endianness = profile.endianness;
+        self.scaling = profile.scaling.clone();
+        match (layout, &profile.layout) {
+            (Some(layout), Some(lp)) => {
+                self.record_field_idx = layout
+                    .fields
+                    .iter()
+                    .position(|f| f.name == lp.field && f.is_numeric())
+                    .unwrap_or_else(|| {
+                        if layout.fields[0].is_numeric() {
+                            0
+                        } else {
+                            layout.next_numeric_field(0, true)
+                        }
+                    });
+                self.record_layout_def = lp.definition.clone();
+                self.record_layout = Some(layout);
             }
-            "zset" => {
-                self.edit_operation = Some(EditOperation::ZAdd);
-                self.edit_fields = vec![
-                    ("Score".to_string(), "0".to_string()),
-                    ("Member".to_string(), String::new()),
-                ];
+            _ => {
+                self.record_layout = None;
+                self.record_layout_def.clear();
             }
-            "stream" => {
-                self.edit_operation = Some(EditOperation::XAdd);
-                self.edit_fields = vec![
-                    ("Field".to_string(), String::new()),
-                    ("Value".to_string(), String::new()),
-                ];
+        }
+        match profile.plot_y_limits {
+            Some((lo, hi)) => {
+                self.plot_y_min = lo;
+                self.plot_y_max = hi;
+                self.plot_auto_limits = false;
             }
-            _ => return,
+            None => self.plot_auto_limits = true,
         }
-        self.input_mode = InputMode::Edit;
+        if profile.fft_enabled != self.fft_enabled {
+            self.toggle_fft();
+            if !self.fft_enabled {
+                self.plot_focus = PlotFocus::Signal;
+            }
+        }
+        self.fft_log_scale = profile.fft_log_scale;
+        self.formats.format = profile.value_format;
+        self.formats.field_formats = profile.field_formats.clone();
+        self.formats.proto_message = profile.proto_message.clone();
+        if profile.proto_schema != self.formats.proto_path {
+            self.formats.proto_path = profile.proto_schema.clone();
+            self.formats.proto = proto;
+        }
+        Ok(())
     }
 
-    pub fn start_set_ttl(&mut self) {
-        let key = match self.selected_key_name() {
-            Some(k) => k.to_string(),
-            None => return,
+    /// Capture the current decode settings as a profile for `pattern`
+    pub fn profile_from_current(&self, pattern: &str) -> DecodeProfile {
+        DecodeProfile {
+            pattern: pattern.to_string(),
+            data_type: self.data_type,
+            endianness: self.endianness,
+            layout: self.record_layout.as_ref().map(|l| LayoutProfile {
+                definition: self.record_layout_def.clone(),
+                header_size: l.header_size,
+                packed: l.packed,
+                field: self.record_field_name().unwrap_or("").to_string(),
+            }),
+            scaling: self.scaling.clone(),
+            plot_y_limits: if self.plot_auto_limits {
+                None
+            } else {
+                Some((self.plot_y_min, self.plot_y_max))
+            },
+            fft_enabled: self.fft_enabled,
+            fft_log_scale: self.fft_log_scale,
+            value_format: self.formats.format,
+            field_formats: self.formats.field_formats.clone(),
+            proto_schema: self.formats.proto_path.clone(),
+            proto_message: self.formats.proto_message.clone(),
+        }
+    }
+
+    pub fn start_profiles(&mut self) {
+        self.profile_pattern_input = self
+            .active_profile
+            .clone()
+            .or_else(|| self.selected_key_name().map(|s| s.to_string()))
+            .unwrap_or_default();
+        self.profile_cursor = 0;
+        self.input_mode = InputMode::Profiles;
+    }
+
+    /// Save the current decode settings under the typed pattern
+    pub fn save_current_profile(&mut self) -> Result<String, String> {
+        let pattern = self.profile_pattern_input.trim().to_string();
+        if pattern.is_empty() {
+            return Err("Pattern is required".to_string());
+        }
+        self.check_profiles_writable()?;
+        let profile = self.profile_from_current(&pattern);
+        self.profiles.upsert(profile);
+        self.profiles.save().map_err(|e| format!("{:#}", e))?;
+        if let Some(key) = self.selected_key_name() {
+            if self.profiles.find(key).map(|p| p.pattern == pattern).unwrap_or(false) {
+                self.active_profile = Some(pattern.clone());
+            }
+        }
+        self.profile_cursor = self
+            .profiles
+            .profiles
+            .iter()
+            .position(|p| p.pattern == pattern)
+            .unwrap_or(0);
+        Ok(pattern)
+    }
+
+    /// Refuse to overwrite a profiles file that could not be parsed at startup
+    fn check_profiles_writable(&self) -> Result<(), String> {
+        match &self.profile_load_error {
+            Some(_) => Err(format!("{} could not be loaded; fix or move it first", self.profiles.path.display())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn delete_selected_profile(&mut self) -> Result<String, String> {
+        if self.profile_cursor >= self.profiles.profiles.len() {
+            return Err("No profile selected".to_string());
+        }
+        self.check_profiles_writable()?;
+        let removed = self.profiles.profiles.remove(self.profile_cursor);
+        self.profiles.save().map_err(|e| format!("{:#}", e))?;
+        if self.active_profile.as_deref() == Some(removed.pattern.as_str()) {
+            self.active_profile = None;
+        }
+        if self.profile_cursor > 0 && self.profile_cursor >= self.profiles.profiles.len() {
+            self.profile_cursor -= 1;
+        }
+        Ok(removed.pattern)
+    }
+
+    /// Apply the profile under the cursor to the current view
+    pub fn apply_selected_profile(&mut self) -> Result<String, String> {
+        let profile = self
+            .profiles
+            .profiles
+            .get(self.profile_cursor)
+            .cloned()
+            .ok_or_else(|| "No profile selected".to_string())?;
+        self.save_manual_settings();
+        self.apply_profile(&profile)?;
+        self.active_profile = Some(profile.pattern.clone());
+        self.recompute_plot();
+        Ok(profile.pattern)
+    }
+
+    pub fn start_record_layout(&mut self) {
+        let (header, packed) = match &self.record_layout {
+            Some(l) => (l.header_size.to_string(), if l.packed { "y" } else { "n" }),
+            None => ("0".to_string(), "n"),
         };
-        let current_ttl = self
-            .current_key_info
+        self.edit_fields = vec![
+            ("Layout".to_string(), self.record_layout_def.clone()),
+            ("Header bytes".to_string(), header),
+            ("Packed (y/n)".to_string(), packed.to_string()),
+            ("Load from file".to_string(), String::new()),
+        ];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::RecordLayout;
+    }
+
+    /// Apply the record layout popup. An empty layout clears it.
+    pub fn apply_record_layout(&mut self) -> Result<(), String> {
+        let path = self.edit_fields[3].1.trim().to_string();
+        let definition = if path.is_empty() {
+            self.edit_fields[0].1.trim().to_string()
+        } else {
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Cannot read '{}': {}", path, e))?
+        };
+        if definition.trim().is_empty() {
+            self.record_layout = None;
+            self.record_layout_def.clear();
+            self.recompute_plot();
+            return Ok(());
+        }
+        let header_str = self.edit_fields[1].1.trim();
+        let header: usize = if header_str.is_empty() {
+            0
+        } else {
+            header_str.parse().map_err(|_| "Invalid header size".to_string())?
+        };
+        let packed = matches!(self.edit_fields[2].1.trim(), "y" | "Y" | "yes" | "packed");
+        let layout = RecordLayout::parse(&definition, header, packed)?;
+        self.record_field_idx = if layout.fields[0].is_numeric() {
+            0
+        } else {
+            layout.next_numeric_field(0, true)
+        };
+        // Keep a single-line definition so it can be edited in the popup
+        self.record_layout_def = definition
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        self.record_layout = Some(layout);
+        self.recompute_plot();
+        Ok(())
+    }
+
+    /// Select the next/previous numeric record member for plotting
+    pub fn cycle_record_field(&mut self, forward: bool) {
+        if let Some(layout) = &self.record_layout {
+            self.record_field_idx = layout.next_numeric_field(self.record_field_idx, forward);
+            self.recompute_plot();
+        }
+    }
+
+    /// Name of the record member being plotted, if a layout is active
+    pub fn record_field_name(&self) -> Option<&str> {
+        self.record_layout
             .as_ref()
-            .map(|i| {
-                if i.ttl < 0 {
-                    String::new()
+            .and_then(|l| l.fields.get(self.record_field_idx))
+            .map(|f| f.name.as_str())
+    }
+
+    pub fn auto_signal_bounds(&self) -> (f64, f64) {
+        auto_bounds(&self.plot_data)
+    }
+
+    pub fn auto_fft_bounds(&self) -> (f64, f64) {
+        let data = self.fft_display_data();
+        auto_bounds(&data)
+    }
+
+    /// Get FFT data for display (applies log scale if enabled)
+    pub fn fft_display_data(&self) -> Vec<f64> {
+        if self.fft_log_scale {
+            self.fft_data
+                .iter()
+                .map(|&v| if v > 0.0 { v.log10() } else { -10.0 })
+                .collect()
+        } else {
+            self.fft_data.clone()
+        }
+    }
+
+    pub fn select_next_key(&mut self) {
+        if self.keys.is_empty() {
+            return;
+        }
+        let i = match self.key_list_state.selected() {
+            Some(i) => {
+                if i >= self.keys.len() - 1 {
+                    0
                 } else {
-                    i.ttl.to_string()
+                    i + 1
                 }
-            })
-            .unwrap_or_default();
-        self.edit_key = key;
-        self.edit_operation = Some(EditOperation::SetTTL);
-        self.edit_fields = vec![("TTL (seconds, empty=persist)".to_string(), current_ttl)];
-        self.edit_focus = 0;
-        self.input_mode = InputMode::Edit;
+            }
+            None => 0,
+        };
+        self.key_list_state.select(Some(i));
+    }
+
+    pub fn select_prev_key(&mut self) {
+        if self.keys.is_empty() {
+            return;
+        }
+        let i = match self.key_list_state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.keys.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.key_list_state.select(Some(i));
+    }
+
+    pub fn scroll_value_down(&mut self) {
+        self.value_scroll = self.value_scroll.saturating_add(1);
+    }
+
+    pub fn scroll_value_up(&mut self) {
+        self.value_scroll = self.value_scroll.saturating_sub(1);
+    }
+
+    pub fn selected_key_name(&self) -> Option<&str> {
+        self.key_list_state
+            .selected()
+            .and_then(|i| self.keys.get(i).map(|s| s.as_str()))
+    }
+
+    pub fn apply_filter(&mut self) {
+        if self.filter_text.is_empty() {
+            self.filter_pattern = "*".to_string();
+        } else {
+            self.filter_pattern = format!("*{}*", self.filter_text);
+        }
+    }
+
+    /// Format the current value for display. Strings and collections go
+    /// through the value formats for every element, so their lines are
+    /// reused until the value or a decode setting changes.
+    pub fn format_value(&mut self) -> Rc<Vec<String>> {
+        let cached = match &self.current_value {
+            Some(RedisValue::String(_)) => !self.bitmap_view,
+            Some(RedisValue::ZSet(_)) => self.geo_points.is_none(),
+            Some(
+                RedisValue::List(_) | RedisValue::Set(_) | RedisValue::Hash(_) | RedisValue::Stream(_) | RedisValue::Json(_),
+            ) => true,
+            _ => false,
+        };
+        if !cached {
+            return Rc::new(self.render_value());
+        }
+        let key = self.render_key();
+        if let Some((k, lines)) = &self.value_lines_cache {
+            if *k == key {
+                return lines.clone();
+            }
+        }
+        let lines = Rc::new(self.render_value());
+        self.value_lines_cache = Some((key, lines.clone()));
+        lines
+    }
+
+    fn render_key(&self) -> RenderKey {
+        RenderKey {
+            value_version: self.value_version,
+            data_type: self.data_type,
+            endianness: self.endianness,
+            scaling: self.scaling.clone(),
+            record_layout: self.record_layout.clone(),
+            format: self.formats.format,
+            field_formats: self.formats.field_formats.clone(),
+            proto: (
+                self.formats.proto_path.clone(),
+                self.formats.proto_message.clone(),
+                self.formats.proto.is_some(),
+            ),
+        }
+    }
+
+    fn render_value(&self) -> Vec<String> {
+        match &self.current_value {
+            None => vec!["(no value loaded)".to_string()],
+            Some(RedisValue::String(bytes)) if self.bitmap_view => self.format_bitmap(bytes),
+            Some(RedisValue::String(bytes)) => self.format_bytes(bytes, None),
+            Some(RedisValue::ZSet(_)) if self.geo_points.is_some() => self.format_geo(),
+            Some(
+                RedisValue::List(_) | RedisValue::Set(_) | RedisValue::ZSet(_) | RedisValue::Hash(_) | RedisValue::Stream(_),
+            ) => self.format_elements(),
+            Some(RedisValue::Json(root)) => json_rows(root, &self.json_collapsed).into_iter().map(|r| r.text).collect(),
+            Some(RedisValue::TimeSeries(series)) => format_timeseries(series),
+            Some(RedisValue::Probabilistic(prob)) => {
+                format_probabilistic(prob, &self.prob_query_text, &self.prob_results)
+            }
+            Some(RedisValue::Unknown(msg)) => vec![msg.clone()],
+        }
+    }
+
+    /// Lines for a string value or one collection element: the value format,
+    /// record layout or data type decoding with a hex dump, or text
+    fn format_bytes(&self, bytes: &[u8], field: Option<&str>) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.record_layout.is_none() {
+            match self.formats.render(bytes, field) {
+                Some(Ok(rendered)) => {
+                    lines.push(format!("── {} ({} bytes) ──", rendered.label, bytes.len()));
+                    lines.extend(rendered.lines);
+                    if is_binary(bytes) {
+                        lines.push(String::new());
+                        lines.push(format!("── Hex dump ({} bytes) ──", bytes.len()));
+                        lines.extend(crate::data::format_hex(bytes).lines().map(|l| l.to_string()));
+                    }
+                    return lines;
+                }
+                Some(Err(e)) => {
+                    lines.push(format!("── Decode failed: {} ──", e));
+                }
+                None => {}
+            }
+        }
+        if let Some(layout) = &self.record_layout {
+            // Gain/offset and decimation apply to the plotted member only; the table shows raw members
+            let body = self.scaling.skip_header(bytes);
+            let scaled = !SampleScaling { byte_offset: 0, ..self.scaling.clone() }.is_identity();
+            lines.push(format!(
+                "── Records {} x{} ({}{}) ──",
+                layout.summary(),
+                layout.record_count(body),
+                self.endianness,
+                if scaled { ", unscaled" } else { "" }
+            ));
+            lines.extend(format_records(body, layout, self.endianness));
+            lines.push(String::new());
+            lines.push(format!("── Hex dump ({} bytes) ──", bytes.len()));
+            for l in crate::data::format_hex(bytes).lines() {
+                lines.push(l.to_string());
+            }
+        } else if is_binary(bytes) {
+            // Show decoded values using current data type
+            lines.push(format!("── Decoded as {} ({}) ──", self.data_type, self.endianness));
+            if !self.scaling.is_identity() {
+                lines.push(self.scaling_summary());
+            }
+            let decoded = crate::data::format_blob(bytes, self.data_type, self.endianness, &self.scaling);
+            for l in decoded.lines() {
+                lines.push(l.to_string());
+            }
+            lines.push(String::new());
+            lines.push(format!("── Hex dump ({} bytes) ──", bytes.len()));
+            for l in crate::data::format_hex(bytes).lines() {
+                lines.push(l.to_string());
+            }
+        } else {
+            let s = String::from_utf8_lossy(bytes).to_string();
+            // Try to pretty-print JSON
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&s) {
+                if let Ok(pretty) = serde_json::to_string_pretty(&json) {
+                    lines.extend(pretty.lines().map(|l| l.to_string()));
+                    return lines;
+                }
+            }
+            lines.extend(s.lines().map(|l| l.to_string()));
+        }
+        lines
+    }
+
+    // ─── Collection elements ─────────────────────────────────
+
+    /// True if the loaded value is shown as a table of selectable elements
+    pub fn is_element_view(&self) -> bool {
+        match &self.current_value {
+            Some(RedisValue::ZSet(_)) => self.geo_points.is_none(),
+            Some(RedisValue::List(_) | RedisValue::Set(_) | RedisValue::Hash(_) | RedisValue::Stream(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn element_count(&self) -> usize {
+        match &self.current_value {
+            Some(RedisValue::List(items) | RedisValue::Set(items)) => items.len(),
+            Some(RedisValue::ZSet(pairs)) => pairs.len(),
+            Some(RedisValue::Hash(pairs)) => pairs.len(),
+            Some(RedisValue::Stream(entries)) => entries.len(),
+            _ => 0,
+        }
+    }
+
+    fn clamp_element_cursor(&mut self) {
+        self.element_cursor = self.element_cursor.min(self.element_count().saturating_sub(1));
+    }
+
+    pub fn move_element_cursor(&mut self, delta: i64) {
+        let last = self.element_count().saturating_sub(1) as i64;
+        self.element_cursor = (self.element_cursor as i64 + delta).clamp(0, last) as usize;
+        self.detail_scroll = 0;
+    }
+
+    pub fn scroll_element_detail(&mut self, delta: i32) {
+        self.detail_scroll = (self.detail_scroll as i32 + delta).max(0) as u16;
+    }
+
+    pub fn toggle_element_detail(&mut self) {
+        self.element_detail = !self.element_detail;
+        self.detail_scroll = 0;
+    }
+
+    /// The stream entry at a table row (streams are listed newest first)
+    fn stream_entry(&self, row: usize) -> Option<&StreamEntry> {
+        match &self.current_value {
+            Some(RedisValue::Stream(entries)) => entries.len().checked_sub(row + 1).map(|i| &entries[i]),
+            _ => None,
+        }
+    }
+
+    /// One-line summary of an element value for the table
+    fn element_summary(&self, bytes: &[u8], field: Option<&str>) -> String {
+        if let Some(vector) = detect_embedding(bytes) {
+            return format!(
+                "float32[{}] norm {:.4} {}",
+                vector.len(),
+                vector_norm(&vector),
+                sparkline(&vector, 16)
+            );
+        }
+        if let Some(Ok(rendered)) = self.formats.render(bytes, field) {
+            let first = rendered.lines.first().map(|l| l.trim()).unwrap_or("");
+            return format!("({}) {}", rendered.label, first);
+        }
+        if is_binary(bytes) {
+            let values = decode_blob(self.scaling.skip_header(bytes), self.data_type, self.endianness);
+            let preview: Vec<String> = values.iter().take(6).map(|v| format!("{}", v)).collect();
+            let more = if values.len() > 6 { ", …" } else { "" };
+            return format!("<{} bytes> {} x{}: {}{}", bytes.len(), self.data_type, values.len(), preview.join(", "), more);
+        }
+        String::from_utf8_lossy(bytes).replace('\n', "⏎")
+    }
+
+    /// Column names and cells of the element table
+    pub fn element_table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        match &self.current_value {
+            Some(RedisValue::Hash(pairs)) => (
+                names(&["field", "value"]),
+                pairs
+                    .iter()
+                    .map(|(f, v)| vec![f.clone(), self.element_summary(v, Some(f))])
+                    .collect(),
+            ),
+            Some(RedisValue::List(items)) => (
+                names(&["index", "value"]),
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| vec![i.to_string(), self.element_summary(v, Some(&i.to_string()))])
+                    .collect(),
+            ),
+            Some(RedisValue::Set(items)) => (
+                names(&["member"]),
+                items.iter().map(|m| vec![self.element_summary(m, None)]).collect(),
+            ),
+            Some(RedisValue::ZSet(pairs)) => (
+                names(&["score", "member"]),
+                pairs
+                    .iter()
+                    .map(|(m, score)| vec![score.to_string(), self.element_summary(m, None)])
+                    .collect(),
+            ),
+            Some(RedisValue::Stream(entries)) => (
+                names(&["id", "time", "fields"]),
+                entries
+                    .iter()
+                    .rev()
+                    .map(|e| {
+                        let fields: Vec<String> = e
+                            .fields
+                            .iter()
+                            .map(|(f, v)| format!("{}={}", f, self.element_summary(v, Some(f))))
+                            .collect();
+                        vec![e.id.clone(), format_stream_id(&e.id), fields.join("  ")]
+                    })
+                    .collect(),
+            ),
+            _ => (Vec::new(), Vec::new()),
+        }
+    }
+
+    fn format_elements(&self) -> Vec<String> {
+        let (columns, rows) = self.element_table();
+        if rows.is_empty() {
+            return vec!["(empty)".to_string()];
+        }
+        let mut lines = format_table(&columns, &rows, ELEMENT_COLUMN_WIDTH);
+        let rule = "─".repeat(lines[0].chars().count().min(120));
+        lines.insert(1, rule);
+        lines
+    }
+
+    /// Full decoding of the selected element for the detail pane, kept like
+    /// the lines of `format_value`
+    pub fn element_detail_lines(&mut self) -> Rc<Vec<String>> {
+        let key = self.render_key();
+        if let Some((k, cursor, lines)) = &self.detail_lines_cache {
+            if *k == key && *cursor == self.element_cursor {
+                return lines.clone();
+            }
+        }
+        let lines = Rc::new(self.render_element_detail());
+        self.detail_lines_cache = Some((key, self.element_cursor, lines.clone()));
+        lines
+    }
+
+    fn render_element_detail(&self) -> Vec<String> {
+        let idx = self.element_cursor;
+        let (title, bytes, field) = match &self.current_value {
+            Some(RedisValue::Hash(pairs)) => match pairs.get(idx) {
+                Some((f, v)) => (format!("field '{}'", f), v, Some(f.clone())),
+                None => return Vec::new(),
+            },
+            Some(RedisValue::List(items)) => match items.get(idx) {
+                Some(v) => (format!("index {}", idx), v, Some(idx.to_string())),
+                None => return Vec::new(),
+            },
+            Some(RedisValue::Set(items)) => match items.get(idx) {
+                Some(v) => ("member".to_string(), v, None),
+                None => return Vec::new(),
+            },
+            Some(RedisValue::ZSet(pairs)) => match pairs.get(idx) {
+                Some((m, score)) => (format!("member with score {}", score), m, None),
+                None => return Vec::new(),
+            },
+            Some(RedisValue::Stream(_)) => {
+                return match self.stream_entry(idx) {
+                    Some(entry) => format_stream_entries(
+                        std::slice::from_ref(entry),
+                        self.data_type,
+                        self.endianness,
+                        &self.scaling,
+                        &self.formats,
+                    ),
+                    None => Vec::new(),
+                };
+            }
+            _ => return Vec::new(),
+        };
+        let mut lines = vec![format!("── {} ({} bytes) ──", title, bytes.len())];
+        if let Some(vector) = detect_embedding(bytes) {
+            lines.extend(format_embedding("embedding", &vector));
+            lines.push(String::new());
+        }
+        lines.extend(self.format_bytes(bytes, field.as_deref()));
+        lines
+    }
+
+    /// Open the edit popup pre-filled with the selected element. Renaming a
+    /// hash field or changing a set/zset member replaces the original.
+    pub fn start_element_edit(&mut self) {
+        let Some(key) = self.selected_key_name().map(|k| k.to_string()) else {
+            return;
+        };
+        let idx = self.element_cursor;
+        // Binary values are edited as numbers of the current data type
+        let editable = |bytes: &[u8]| -> (String, bool) {
+            if is_binary(bytes) && self.data_type.byte_size().is_some() {
+                let values = decode_blob(bytes, self.data_type, self.endianness);
+                (values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", "), true)
+            } else {
+                (String::from_utf8_lossy(bytes).to_string(), false)
+            }
+        };
+        let (operation, fields, original, binary) = match &self.current_value {
+            Some(RedisValue::Hash(pairs)) => {
+                let Some((f, v)) = pairs.get(idx) else { return };
+                let (text, binary) = editable(v);
+                let fields = vec![("Field".to_string(), f.clone()), ("Value".to_string(), text)];
+                (EditOperation::HSet, fields, Some(f.as_bytes().to_vec()), binary)
+            }
+            Some(RedisValue::List(items)) => {
+                let Some(v) = items.get(idx) else { return };
+                let (text, binary) = editable(v);
+                let fields = vec![("Index".to_string(), idx.to_string()), ("Value".to_string(), text)];
+                (EditOperation::LSet, fields, None, binary)
+            }
+            Some(RedisValue::Set(items)) => {
+                let Some(m) = items.get(idx) else { return };
+                let (text, binary) = editable(m);
+                (EditOperation::SAdd, vec![("Member".to_string(), text)], Some(m.clone()), binary)
+            }
+            Some(RedisValue::ZSet(pairs)) => {
+                let Some((m, score)) = pairs.get(idx) else { return };
+                let (text, binary) = editable(m);
+                let fields = vec![("Score".to_string(), score.to_string()), ("Member".to_string(), text)];
+                (EditOperation::ZAdd, fields, Some(m.clone()), binary)
+            }
+            // Stream entries cannot be changed; start a new one from the selected entry
+            Some(RedisValue::Stream(_)) => {
+                let fields: Vec<(String, String, bool)> = match self.stream_entry(idx) {
+                    Some(entry) => entry
+                        .fields
+                        .iter()
+                        .map(|(f, v)| {
+                            let (text, binary) = editable(v);
+                            (f.clone(), text, binary)
+                        })
+                        .collect(),
+                    None => Vec::new(),
+                };
+                self.start_edit();
+                self.edit_fields.truncate(XADD_HEADER_FIELDS);
+                self.xadd_encodings.clear();
+                for (field, text, binary) in fields {
+                    let n = self.xadd_encodings.len() + 1;
+                    self.edit_fields.push((format!("Field {}", n), field));
+                    self.edit_fields.push((format!("Value {}", n), text));
+                    self.xadd_encodings.push(binary.then_some(self.data_type));
+                }
+                self.xadd_relabel();
+                self.xadd_grow();
+                return;
+            }
+            _ => return self.start_edit(),
+        };
+        self.edit_key = key;
+        self.edit_operation = Some(operation);
+        self.edit_fields = fields;
+        self.edit_focus = self.edit_fields.len() - 1;
+        self.edit_text = TextArea::default();
+        self.edit_multi_count = 0;
+        self.edit_element = original;
+        self.edit_binary_mode = binary;
+        if binary {
+            self.edit_binary_dtype_idx =
+                DataType::all().iter().position(|t| *t == self.data_type).unwrap_or(self.edit_binary_dtype_idx);
+        }
+        self.input_mode = InputMode::Edit;
+    }
+
+    /// Ask for confirmation before deleting the selected element
+    pub fn start_element_delete(&mut self) {
+        let Some(key) = self.selected_key_name().map(|k| k.to_string()) else {
+            return;
+        };
+        let idx = self.element_cursor;
+        let what = match &self.current_value {
+            Some(RedisValue::Hash(pairs)) => pairs.get(idx).map(|(f, _)| format!("HDEL field '{}'", f)),
+            Some(RedisValue::List(items)) => items.get(idx).map(|_| format!("list element [{}]", idx)),
+            Some(RedisValue::Set(items)) => {
+                items.get(idx).map(|m| format!("SREM member '{}'", String::from_utf8_lossy(m)))
+            }
+            Some(RedisValue::ZSet(pairs)) => {
+                pairs.get(idx).map(|(m, _)| format!("ZREM member '{}'", String::from_utf8_lossy(m)))
+            }
+            Some(RedisValue::Stream(_)) => self.stream_entry(idx).map(|e| format!("XDEL entry {}", e.id)),
+            _ => None,
+        };
+        if let Some(what) = what {
+            self.confirm_action = Some(format!("Delete {} from '{}'", what, key));
+            self.confirm_element = Some(idx);
+            self.input_mode = InputMode::Confirm;
+        }
+    }
+
+    /// Delete the element at a table row; returns the status message
+    pub fn delete_element(&mut self, client: &mut RedisClient, idx: usize) -> Result<String, String> {
+        let key = self.selected_key_name().ok_or("No key selected")?.to_string();
+        let action = match &self.current_value {
+            Some(RedisValue::Hash(_)) => "HDEL",
+            Some(RedisValue::List(_)) => "LREM",
+            Some(RedisValue::Set(_)) => "SREM",
+            Some(RedisValue::ZSet(_)) => "ZREM",
+            Some(RedisValue::Stream(_)) => "XDEL",
+            _ => return Err("Value has no elements".to_string()),
+        };
+        self.snapshot_key(client, &key, action)?;
+        let result = match &self.current_value {
+            Some(RedisValue::Hash(pairs)) => {
+                let (field, _) = pairs.get(idx).ok_or("No such field")?;
+                client.hdel(&key, field).map(|_| format!("HDEL {} {}", key, field))
+            }
+            Some(RedisValue::List(items)) => {
+                let item = items.get(idx).ok_or("No such element")?;
+                client.ldel_index(&key, idx as i64, item).map(|_| format!("Removed [{}] from {}", idx, key))
+            }
+            Some(RedisValue::Set(items)) => {
+                let member = items.get(idx).ok_or("No such member")?;
+                client.srem(&key, member).map(|_| format!("SREM {} {}", key, String::from_utf8_lossy(member)))
+            }
+            Some(RedisValue::ZSet(pairs)) => {
+                let (member, _) = pairs.get(idx).ok_or("No such member")?;
+                client.zrem(&key, member).map(|_| format!("ZREM {} {}", key, String::from_utf8_lossy(member)))
+            }
+            Some(RedisValue::Stream(_)) => {
+                let id = self.stream_entry(idx).ok_or("No such entry")?.id.clone();
+                client.xdel(&key, &id).map(|_| format!("XDEL {} {}", key, id))
+            }
+            _ => return Err("Value has no elements".to_string()),
+        };
+        let msg = result.map_err(|e| {
+            self.undo_stack.pop();
+            e.to_string()
+        })?;
+        self.load_selected_value(client);
+        Ok(msg)
+    }
+
+    // ─── RedisJSON tree ──────────────────────────────────────
+
+    pub fn is_viewing_json(&self) -> bool {
+        matches!(self.current_value, Some(RedisValue::Json(_)))
+    }
+
+    /// Visible rows of the JSON tree (empty unless a JSON value is loaded),
+    /// kept until the value changes or a container is expanded or collapsed
+    pub fn json_rows(&mut self) -> Rc<Vec<JsonRow>> {
+        if let Some((version, rows)) = &self.json_rows_cache {
+            if *version == self.value_version {
+                return rows.clone();
+            }
+        }
+        let rows = Rc::new(match &self.current_value {
+            Some(RedisValue::Json(root)) => json_rows(root, &self.json_collapsed),
+            _ => Vec::new(),
+        });
+        self.json_rows_cache = Some((self.value_version, rows.clone()));
+        rows
+    }
+
+    pub fn selected_json_row(&mut self) -> Option<JsonRow> {
+        self.json_rows().get(self.json_cursor).cloned()
+    }
+
+    /// Drop the rendered tree after `json_collapsed` changed
+    fn json_tree_changed(&mut self) {
+        self.json_rows_cache = None;
+        self.value_lines_cache = None;
+    }
+
+    fn clamp_json_cursor(&mut self) {
+        let count = self.json_rows().len();
+        self.json_cursor = self.json_cursor.min(count.saturating_sub(1));
+    }
+
+    pub fn json_cursor_down(&mut self) {
+        if self.json_cursor + 1 < self.json_rows().len() {
+            self.json_cursor += 1;
+        }
+    }
+
+    pub fn json_cursor_up(&mut self) {
+        self.json_cursor = self.json_cursor.saturating_sub(1);
+    }
+
+    /// Expand or collapse the selected container
+    pub fn json_toggle(&mut self) {
+        if let Some(row) = self.selected_json_row().filter(|r| r.container) {
+            let path = row.json_path();
+            if !self.json_collapsed.remove(&path) {
+                self.json_collapsed.insert(path);
+            }
+            self.json_tree_changed();
+        }
+    }
+
+    pub fn json_expand(&mut self) {
+        if let Some(row) = self.selected_json_row().filter(|r| r.container) {
+            if self.json_collapsed.remove(&row.json_path()) {
+                self.json_tree_changed();
+            }
+        }
+    }
+
+    /// Collapse the selected container, or jump to the parent row if it
+    /// is a leaf or already collapsed.
+    pub fn json_collapse(&mut self) {
+        let Some(row) = self.selected_json_row() else {
+            return;
+        };
+        let path = row.json_path();
+        if row.container && !self.json_collapsed.contains(&path) {
+            self.json_collapsed.insert(path);
+            self.json_tree_changed();
+        } else if let Some(parent) = row.path.split_last().map(|(_, p)| p) {
+            if let Some(pos) = self.json_rows().iter().position(|r| r.path == parent) {
+                self.json_cursor = pos;
+            }
+        }
+    }
+
+    // ─── RediSearch ──────────────────────────────────────────
+
+    /// Open the index browser with the indexes from FT._LIST
+    pub fn open_search_browser(&mut self, client: &mut RedisClient) {
+        match client.ft_list() {
+            Ok(indexes) => {
+                self.search_indexes = indexes;
+                self.search_cursor = self.search_cursor.min(self.search_indexes.len().saturating_sub(1));
+                self.load_search_info(client);
+                self.input_mode = InputMode::SearchIndexes;
+                self.status_message = format!("{} RediSearch indexes", self.search_indexes.len());
+            }
+            Err(e) => self.status_message = format!("RediSearch not available: {}", e),
+        }
+    }
+
+    fn load_search_info(&mut self, client: &mut RedisClient) {
+        self.search_info = None;
+        if let Some(index) = self.search_indexes.get(self.search_cursor).cloned() {
+            match client.ft_info(&index) {
+                Ok(info) => self.search_info = Some(info),
+                Err(e) => self.status_message = format!("Error: {}", e),
+            }
+        }
+    }
+
+    pub fn move_search_cursor(&mut self, client: &mut RedisClient, delta: i64) {
+        let target = self.search_cursor as i64 + delta;
+        if (0..self.search_indexes.len() as i64).contains(&target) {
+            self.search_cursor = target as usize;
+            self.load_search_info(client);
+        }
+    }
+
+    pub fn start_search_query(&mut self) {
+        if self.search_info.is_none() {
+            self.status_message = "No index selected".to_string();
+            return;
+        }
+        if self.search_form.is_empty() {
+            self.search_form = vec![
+                ("Command".to_string(), "FT.SEARCH".to_string()),
+                ("Query".to_string(), "*".to_string()),
+                ("Arguments".to_string(), "LIMIT 0 100".to_string()),
+            ];
+        }
+        self.edit_fields = self.search_form.clone();
+        self.edit_focus = 1;
+        self.input_mode = InputMode::SearchQuery;
+    }
+
+    /// Switch the query form between FT.SEARCH and FT.AGGREGATE
+    pub fn toggle_search_command(&mut self) {
+        let command = &mut self.edit_fields[0].1;
+        *command = if command == "FT.SEARCH" { "FT.AGGREGATE" } else { "FT.SEARCH" }.to_string();
+    }
+
+    pub fn run_search(&mut self, client: &mut RedisClient) -> Result<String, String> {
+        let index = self.search_info.as_ref().ok_or("No index selected")?.name.clone();
+        let command = self.edit_fields[0].1.clone();
+        let query = self.edit_fields[1].1.trim();
+        let query = if query.is_empty() { "*" } else { query };
+        let args = split_args(&self.edit_fields[2].1)?;
+        let results = client
+            .ft_query(&command, &index, query, &args)
+            .map_err(|e| e.to_string())?;
+        self.search_form = self.edit_fields.clone();
+        let msg = format!("{} {}: {} total, {} shown", command, index, results.total, results.hits.len());
+        self.search_results = Some(results);
+        self.search_result_cursor = 0;
+        self.input_mode = InputMode::SearchResults;
+        Ok(msg)
+    }
+
+    pub fn move_search_result_cursor(&mut self, delta: i64) {
+        let last = self.search_results.as_ref().map_or(0, |r| r.hits.len().saturating_sub(1)) as i64;
+        self.search_result_cursor = (self.search_result_cursor as i64 + delta).clamp(0, last) as usize;
+    }
+
+    /// Column names and cell text of the results table. Vector fields of the
+    /// index are decoded instead of shown as raw bytes.
+    pub fn search_table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let Some(results) = &self.search_results else {
+            return (Vec::new(), Vec::new());
+        };
+        let has_id = results.hits.iter().any(|h| h.id.is_some());
+        let has_score = results.hits.iter().any(|h| h.score.is_some());
+        let mut fields: Vec<&str> = Vec::new();
+        for hit in &results.hits {
+            for (name, _) in &hit.fields {
+                if !fields.contains(&name.as_str()) {
+                    fields.push(name);
+                }
+            }
+        }
+
+        let mut columns = Vec::new();
+        if has_id {
+            columns.push("key".to_string());
+        }
+        if has_score {
+            columns.push("score".to_string());
+        }
+        columns.extend(fields.iter().map(|f| f.to_string()));
+
+        let rows = results
+            .hits
+            .iter()
+            .map(|hit| {
+                let mut row = Vec::new();
+                if has_id {
+                    row.push(hit.id.clone().unwrap_or_default());
+                }
+                if has_score {
+                    row.push(hit.score.clone().unwrap_or_default());
+                }
+                for field in &fields {
+                    let cell = match hit.fields.iter().find(|(n, _)| n == field) {
+                        None => String::new(),
+                        Some((name, bytes)) => {
+                            match self.search_info.as_ref().and_then(|i| i.vector_field(name)) {
+                                // JSON indexes return vectors as JSON arrays
+                                Some(vector) if !bytes.starts_with(b"[") => format_vector(bytes, vector),
+                                _ if is_binary(bytes) => format!("<{} bytes>", bytes.len()),
+                                _ => String::from_utf8_lossy(bytes).to_string(),
+                            }
+                        }
+                    };
+                    row.push(cell);
+                }
+                row
+            })
+            .collect();
+        (columns, rows)
+    }
+
+    /// Open the key of the selected result row in the value view: the
+    /// document id, or a `__key` field loaded by FT.AGGREGATE
+    pub fn open_search_result(&mut self, client: &mut RedisClient) {
+        let Some(hit) = self
+            .search_results
+            .as_ref()
+            .and_then(|r| r.hits.get(self.search_result_cursor))
+        else {
+            return;
+        };
+        let key = hit.id.clone().or_else(|| {
+            hit.fields
+                .iter()
+                .find(|(n, _)| n == "__key")
+                .map(|(_, v)| String::from_utf8_lossy(v).to_string())
+        });
+        let Some(key) = key else {
+            self.status_message = "Row has no key (FT.AGGREGATE needs LOAD 1 @__key)".to_string();
+            return;
+        };
+        if !self.keys.contains(&key) {
+            // Narrow the key list to the document so it can be selected
+            self.filter_text = escape_glob(&key);
+            self.apply_filter();
+            self.refresh_keys(client);
+        }
+        match self.keys.iter().position(|k| *k == key) {
+            Some(idx) => {
+                self.key_list_state.select(Some(idx));
+                self.load_selected_value(client);
+                self.input_mode = InputMode::Normal;
+                self.active_panel = Panel::ValueView;
+                self.status_message = format!("Opened '{}'", key);
+            }
+            None => self.status_message = format!("Key '{}' not found", key),
+        }
+    }
+
+    // ─── Vector similarity ───────────────────────────────────
+
+    /// Open the comparison popup with the current key (and its first
+    /// embedding field) as vector A
+    pub fn start_vector_compare(&mut self) {
+        let key = self.selected_key_name().unwrap_or_default().to_string();
+        let field = match &self.current_value {
+            Some(RedisValue::Hash(pairs)) => pairs
+                .iter()
+                .find(|(_, v)| detect_embedding(v).is_some())
+                .map(|(f, _)| f.clone())
+                .unwrap_or_default(),
+            _ => String::new(),
+        };
+        let last_b = |i: usize| self.vector_form.get(i).map(|f| f.1.clone()).unwrap_or_default();
+        self.edit_fields = vec![
+            ("Key A".to_string(), key),
+            ("Field A (blank = string key)".to_string(), field),
+            ("Key B".to_string(), last_b(2)),
+            ("Field B (blank = Field A)".to_string(), last_b(3)),
+        ];
+        self.edit_focus = 2;
+        self.vector_result.clear();
+        self.input_mode = InputMode::VectorCompare;
+    }
+
+    /// Compare the two vectors; the popup stays open to try other pairs
+    pub fn run_vector_compare(&mut self, client: &mut RedisClient) -> Result<String, String> {
+        let value = |i: usize| self.edit_fields[i].1.trim().to_string();
+        let (key_a, field_a, key_b) = (value(0), value(1), value(2));
+        let field_b = if value(3).is_empty() { field_a.clone() } else { value(3) };
+        if key_a.is_empty() || key_b.is_empty() {
+            return Err("Both keys are required".to_string());
+        }
+        let a = load_vector(client, &key_a, &field_a)?;
+        let b = load_vector(client, &key_b, &field_b)?;
+        if a.len() != b.len() {
+            return Err(format!("Dimensions differ: {} vs {}", a.len(), b.len()));
+        }
+        let cosine = cosine_similarity(&a, &b)
+            .ok_or_else(|| "Cosine similarity is undefined for a zero vector".to_string())?;
+        let dot: f64 = a.iter().zip(&b).map(|(x, y)| x * y).sum();
+        let l2 = a.iter().zip(&b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt();
+        self.vector_result = vec![
+            format!("A: dim {}  norm {:.4}  {}", a.len(), vector_norm(&a), sparkline(&a, 40)),
+            format!("B: dim {}  norm {:.4}  {}", b.len(), vector_norm(&b), sparkline(&b, 40)),
+            format!("Cosine similarity {:.6}  (distance {:.6})", cosine, 1.0 - cosine),
+            format!("Inner product {:.6}  L2 distance {:.6}", dot, l2),
+        ];
+        self.vector_form = self.edit_fields.clone();
+        Ok(format!("Cosine similarity {:.4}", cosine))
+    }
+
+    // ─── Bitmap view ─────────────────────────────────────────
+
+    pub fn is_bitmap_view(&self) -> bool {
+        self.bitmap_view && matches!(self.current_value, Some(RedisValue::String(_)))
+    }
+
+    pub fn toggle_bitmap_view(&mut self) {
+        if !matches!(self.current_value, Some(RedisValue::String(_))) {
+            self.status_message = "Bitmap view is only available for strings".to_string();
+            return;
+        }
+        self.bitmap_view = !self.bitmap_view;
+        self.value_scroll = 0;
+        self.recompute_plot();
+        self.status_message = if self.bitmap_view {
+            "Bitmap view: arrows select a bit, [Enter] toggles it, [Q] BITFIELD".to_string()
+        } else {
+            "Bitmap view: OFF".to_string()
+        };
+    }
+
+    fn bitmap_len(&self) -> u64 {
+        match &self.current_value {
+            Some(RedisValue::String(bytes)) => bytes.len() as u64 * 8,
+            _ => 0,
+        }
+    }
+
+    fn clamp_bit_cursor(&mut self) {
+        self.bit_cursor = self.bit_cursor.min(self.bitmap_len().saturating_sub(1));
+    }
+
+    /// Move the bit cursor by `delta` bits, staying inside the string
+    pub fn move_bit_cursor(&mut self, delta: i64) {
+        self.bit_cursor = self.bit_cursor.saturating_add_signed(delta);
+        self.clamp_bit_cursor();
+    }
+
+    pub fn move_bit_cursor_rows(&mut self, rows: i64) {
+        let target = self.bit_cursor as i64 + rows * BITMAP_ROW_BITS as i64;
+        if (0..self.bitmap_len() as i64).contains(&target) {
+            self.bit_cursor = target as u64;
+        }
+    }
+
+    /// Flip the selected bit with SETBIT and reload the value
+    pub fn toggle_selected_bit(&mut self, client: &mut RedisClient) {
+        let Some(RedisValue::String(bytes)) = &self.current_value else {
+            return;
+        };
+        if bytes.is_empty() {
+            self.status_message = "Empty string: nothing to toggle".to_string();
+            return;
+        }
+        let Some(key) = self.selected_key_name().map(|k| k.to_string()) else {
+            return;
+        };
+        let offset = self.bit_cursor;
+        let value = !get_bit(bytes, offset);
+        match client.setbit(&key, offset, value) {
+            Ok(_) => {
+                self.load_selected_value(client);
+                self.status_message = format!("SETBIT {} {} {}", key, offset, value as u8);
+            }
+            Err(e) => self.status_message = format!("Error: {}", e),
+        }
+    }
+
+    fn format_bitmap(&self, bytes: &[u8]) -> Vec<String> {
+        let bits = bytes.len() as u64 * 8;
+        let first_one = bit_pos(bytes, true).map_or(-1, |p| p as i64);
+        // Like BITPOS without a range, a string of all ones reports the bit past the end
+        let first_zero = bit_pos(bytes, false).unwrap_or(bits);
+        let mut lines = vec![
+            format!("── Bitmap ({} bytes, {} bits) ──", bytes.len(), bits),
+            format!("BITCOUNT {}   BITPOS 1: {}   BITPOS 0: {}", bit_count(bytes), first_one, first_zero),
+            if bytes.is_empty() {
+                String::new()
+            } else {
+                let offset = self.bit_cursor;
+                let bit = get_bit(bytes, offset) as u8;
+                format!("Bit {} (byte {}, bit {}) = {}", offset, offset / 8, offset % 8, bit)
+            },
+            String::new(),
+        ];
+        lines.extend(format_bit_grid(bytes));
+        if !self.bitfields.is_empty() {
+            lines.push(String::new());
+            lines.push(format!("── BITFIELD {} ──", self.bitfield_spec));
+            for field in &self.bitfields {
+                let values: Vec<String> = field.decode(bytes).iter().map(|v| v.to_string()).collect();
+                let repeat = if field.count > 1 { format!(" x{}", field.count) } else { String::new() };
+                let shown = if values.is_empty() { "(past the end of the string)".to_string() } else { values.join(", ") };
+                lines.push(format!("{} @{}{}: {}", field.type_name(), field.offset, repeat, shown));
+            }
+        }
+        lines
+    }
+
+    /// Row and character column of the cursor in `format_value` output:
+    /// the selected JSON node, or the selected cell of the bitmap grid
+    pub fn value_cursor(&self) -> Option<(usize, Option<usize>)> {
+        if self.is_viewing_json() {
+            Some((self.json_cursor, None))
+        } else if self.is_element_view() && self.element_count() > 0 {
+            Some((ELEMENT_HEADER_LINES + self.element_cursor, None))
+        } else if self.is_bitmap_view() && self.bitmap_len() > 0 {
+            let row = BITMAP_HEADER_LINES + (self.bit_cursor / BITMAP_ROW_BITS as u64) as usize;
+            Some((row, Some(bit_grid_column(self.bit_cursor))))
+        } else {
+            None
+        }
+    }
+
+    fn start_bitfield_query(&mut self) {
+        self.edit_fields = vec![("Fields (type offset [xN]; ...)".to_string(), self.bitfield_spec.clone())];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::Query;
+    }
+
+    fn apply_bitfields(&mut self) -> Result<String, String> {
+        let spec = self.edit_fields[0].1.trim().to_string();
+        self.bitfields = BitField::parse_list(&spec)?;
+        self.bitfield_spec = spec;
+        self.recompute_plot();
+        self.set_auto_limits();
+        Ok(if self.bitfields.is_empty() {
+            "BITFIELD inspector cleared".to_string()
+        } else {
+            let gets: Vec<String> =
+                self.bitfields.iter().map(|f| format!("GET {} {}", f.type_name(), f.offset)).collect();
+            format!("BITFIELD {}", gets.join(" "))
+        })
+    }
+
+    // ─── Edit operations ─────────────────────────────────────
+
+    pub fn start_edit(&mut self) {
+        let key_type = self
+            .current_key_info
+            .as_ref()
+            .map(|i| i.key_type.as_str())
+            .unwrap_or("");
+        let key = match self.selected_key_name() {
+            Some(k) => k.to_string(),
+            None => return,
+        };
+        self.edit_key = key.clone();
+        self.edit_focus = 0;
+        self.edit_text = TextArea::default();
+        self.edit_multi_count = 0;
+        self.edit_element = None;
+
+        // HyperLogLogs are strings, so check the loaded value first
+        if let Some(kind) = self.prob_kind() {
+            let label = if kind == ProbKind::TDigest { "Values" } else { "Items" };
+            self.edit_operation = Some(EditOperation::ProbAdd);
+            self.edit_fields = vec![(format!("{} (space separated)", label), String::new())];
+            self.input_mode = InputMode::Edit;
+            return;
+        }
+
+        match key_type {
+            "string" => {
+                let current = match &self.current_value {
+                    Some(RedisValue::String(b)) => String::from_utf8_lossy(b).to_string(),
+                    _ => String::new(),
+                };
+                self.edit_operation = Some(EditOperation::SetString);
+                self.edit_fields = vec![("Value".to_string(), current)];
+            }
+            "hash" => {
+                self.edit_operation = Some(EditOperation::HSet);
+                self.edit_fields = vec![
+                    ("Field".to_string(), String::new()),
+                    ("Value".to_string(), String::new()),
+                ];
+            }
+            "list" => {
+                self.edit_operation = Some(EditOperation::RPush);
+                self.edit_fields = vec![("Value (appended)".to_string(), String::new())];
+            }
+            "set" => {
+                self.edit_operation = Some(EditOperation::SAdd);
+                self.edit_fields = vec![("Member".to_string(), String::new())];
+            }
+            "zset" => {
+                self.edit_operation = Some(EditOperation::ZAdd);
+                self.edit_fields = vec![
+                    ("Score".to_string(), "0".to_string()),
+                    ("Member".to_string(), String::new()),
+                ];
+            }
+            "stream" => {
+                self.edit_operation = Some(EditOperation::XAdd);
+                self.edit_fields = vec![
+                    ("ID (*, ms, ms-seq or ms-*)".to_string(), "*".to_string()),
+                    ("Trim (MAXLEN|MINID [~|=] threshold [LIMIT n])".to_string(), String::new()),
+                    ("NOMKSTREAM (y/n)".to_string(), "n".to_string()),
+                ];
+                self.xadd_encodings.clear();
+                self.xadd_grow();
+                self.edit_focus = XADD_HEADER_FIELDS;
+            }
+            "TSDB-TYPE" => {
+                self.edit_operation = Some(EditOperation::TsAdd);
+                self.edit_fields = vec![
+                    ("Timestamp (ms, * = now)".to_string(), "*".to_string()),
+                    ("Value".to_string(), String::new()),
+                ];
+            }
+            "ReJSON-RL" => {
+                let row = self.selected_json_row();
+                let (path, current) = match (&self.current_value, row) {
+                    (Some(RedisValue::Json(root)), Some(row)) => (
+                        row.json_path(),
+                        value_at(root, &row.path).map(|v| v.to_string()).unwrap_or_default(),
+                    ),
+                    _ => ("$".to_string(), String::new()),
+                };
+                self.edit_fields = vec![
+                    ("Path".to_string(), path),
+                    ("Value (JSON)".to_string(), current),
+                ];
+                self.edit_operation = Some(EditOperation::JsonSet);
+            }
+            _ => return,
+        }
+        self.input_mode = InputMode::Edit;
+    }
+
+    pub fn start_set_ttl(&mut self) {
+        let key = match self.selected_key_name() {
+            Some(k) => k.to_string(),
+            None => return,
+        };
+        let current_ttl = self
+            .current_key_info
+            .as_ref()
+            .map(|i| {
+                if i.ttl < 0 {
+                    String::new()
+                } else {
+                    i.ttl.to_string()
+                }
+            })
+            .unwrap_or_default();
+        self.edit_key = key;
+        self.edit_operation = Some(EditOperation::SetTTL);
+        self.edit_fields = vec![("TTL (seconds, empty=persist)".to_string(), current_ttl)];
+        self.edit_focus = 0;
+        self.edit_text = TextArea::default();
+        self.input_mode = InputMode::Edit;
+    }
+
+    pub fn start_rename(&mut self) {
+        let key = match self.selected_key_name() {
+            Some(k) => k.to_string(),
+            None => return,
+        };
+        self.edit_operation = Some(EditOperation::RenameKey);
+        self.edit_fields = vec![("New name".to_string(), key.clone())];
+        self.edit_key = key;
+        self.edit_focus = 0;
+        self.edit_text = TextArea::default();
+        self.input_mode = InputMode::Edit;
+    }
+
+    pub fn start_new_key(&mut self) {
+        self.edit_operation = Some(EditOperation::NewKey);
+        self.new_key_type_idx = 0;
+        self.edit_multi_count = 0;
+        self.edit_fields = vec![
+            ("Key".to_string(), String::new()),
+            ("Value".to_string(), String::new()),
+        ];
+        self.edit_key.clear();
+        self.edit_focus = 0;
+        self.edit_text = TextArea::default();
+        self.input_mode = InputMode::Edit;
+    }
+
+    // ─── Element removal and trims ───────────────────────────
+
+    /// Open the edit popup with the removal/trim commands of the key's type,
+    /// pre-filled from the selected element where one applies
+    pub fn start_remove(&mut self) {
+        let Some(key) = self.selected_key_name().map(|k| k.to_string()) else {
+            return;
+        };
+        let key_type = self.current_key_info.as_ref().map(|i| i.key_type.as_str()).unwrap_or("");
+        let Some(op) = remove_ops(key_type).first().cloned() else {
+            self.status_message = format!("No element removal for type '{}'", key_type);
+            return;
+        };
+        self.edit_key = key;
+        self.edit_multi_count = 0;
+        self.edit_element = None;
+        self.edit_binary_mode = false;
+        self.edit_fields = self.remove_fields(&op);
+        self.edit_focus = self.edit_fields.len() - 1;
+        self.edit_text = TextArea::default();
+        self.edit_operation = Some(op);
+        self.input_mode = InputMode::Edit;
+    }
+
+    pub fn is_remove_edit(&self) -> bool {
+        matches!(
+            self.edit_operation,
+            Some(
+                EditOperation::HDel
+                    | EditOperation::LRem
+                    | EditOperation::LPop
+                    | EditOperation::RPop
+                    | EditOperation::LTrim
+                    | EditOperation::SRem
+                    | EditOperation::ZRem
+                    | EditOperation::ZRemRangeByScore
+                    | EditOperation::XDel
+                    | EditOperation::XTrim
+            )
+        )
+    }
+
+    /// Switch the removal popup to the next/previous command for the key type
+    pub fn cycle_remove_op(&mut self, forward: bool) {
+        let key_type = self.current_key_info.as_ref().map(|i| i.key_type.as_str()).unwrap_or("");
+        let ops = remove_ops(key_type);
+        let Some(idx) = self.edit_operation.as_ref().and_then(|op| ops.iter().position(|o| o == op)) else {
+            return;
+        };
+        let n = ops.len();
+        let next = if forward { (idx + 1) % n } else { (idx + n - 1) % n };
+        let op = ops[next].clone();
+        self.edit_fields = self.remove_fields(&op);
+        self.edit_focus = self.edit_fields.len() - 1;
+        if !self.edit_supports_binary_op(&op) {
+            self.edit_binary_mode = false;
+        }
+        self.edit_operation = Some(op);
+    }
+
+    fn remove_fields(&self, op: &EditOperation) -> Vec<(String, String)> {
+        let selected = self.selected_element_text().unwrap_or_default();
+        let field = |label: &str, value: &str| (label.to_string(), value.to_string());
+        match op {
+            EditOperation::HDel => vec![field("Fields (one per line)", &selected)],
+            EditOperation::LRem => vec![field("Count (0 = all, <0 from tail)", "0"), field("Value", &selected)],
+            EditOperation::LPop | EditOperation::RPop => vec![field("Count", "1")],
+            EditOperation::LTrim => vec![field("Keep from index", "0"), field("Keep to index", "-1")],
+            EditOperation::SRem | EditOperation::ZRem => vec![field("Member", &selected)],
+            EditOperation::ZRemRangeByScore => vec![field("Min score", "-inf"), field("Max score", "+inf")],
+            EditOperation::XDel => vec![field("IDs (one per line)", &selected)],
+            EditOperation::XTrim => vec![
+                field("Strategy (MAXLEN/MINID)", "MAXLEN"),
+                field("Approximate ~ (y/n)", "y"),
+                field("Limit (with ~, blank = default)", ""),
+                field("Threshold", ""),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    /// The selected element as text a removal can target: its field, value,
+    /// member or ID. Binary elements have no text form.
+    fn selected_element_text(&self) -> Option<String> {
+        if !self.is_element_view() {
+            return None;
+        }
+        let idx = self.element_cursor;
+        let bytes: &[u8] = match &self.current_value {
+            Some(RedisValue::Hash(pairs)) => return pairs.get(idx).map(|(f, _)| f.clone()),
+            Some(RedisValue::List(items) | RedisValue::Set(items)) => items.get(idx)?,
+            Some(RedisValue::ZSet(pairs)) => &pairs.get(idx)?.0,
+            Some(RedisValue::Stream(_)) => return self.stream_entry(idx).map(|e| e.id.clone()),
+            _ => return None,
+        };
+        (!is_binary(bytes)).then(|| String::from_utf8_lossy(bytes).to_string())
+    }
+
+    /// An edit field as bytes, encoded when binary mode is on
+    fn edit_field_bytes(&self, idx: usize) -> Result<Vec<u8>, String> {
+        let text = &self.edit_fields[idx].1;
+        if self.edit_binary_mode {
+            encode_values(text, DataType::all()[self.edit_binary_dtype_idx], self.endianness)
+        } else {
+            Ok(text.as_bytes().to_vec())
+        }
+    }
+
+    /// Non-empty lines of a field, kept as typed: hash fields may contain spaces
+    fn edit_field_lines(&self, idx: usize, what: &str) -> Result<Vec<String>, String> {
+        let lines: Vec<String> = self.edit_fields[idx]
+            .1
+            .split('\n')
+            .map(|l| l.strip_suffix('\r').unwrap_or(l))
+            .filter(|l| !l.is_empty())
+            .map(|l| l.to_string())
+            .collect();
+        if lines.is_empty() {
+            return Err(format!("Enter at least one {}", what));
+        }
+        Ok(lines)
+    }
+
+    fn edit_field_number<T: std::str::FromStr>(&self, idx: usize) -> Result<T, String> {
+        let (label, text) = &self.edit_fields[idx];
+        text.trim().parse().map_err(|_| format!("Invalid {}", label.to_lowercase()))
+    }
+
+    /// The XTRIM arguments entered in the removal popup
+    fn edit_stream_trim(&self) -> Result<StreamTrim, String> {
+        let min_id = match self.edit_fields[0].1.trim().to_uppercase().as_str() {
+            "MAXLEN" => false,
+            "MINID" => true,
+            _ => return Err("Strategy must be MAXLEN or MINID".to_string()),
+        };
+        let approximate = self.edit_fields[1].1.trim().eq_ignore_ascii_case("y");
+        let limit = match self.edit_fields[2].1.trim() {
+            "" => None,
+            _ => Some(self.edit_field_number(2)?),
+        };
+        let threshold = self.edit_fields[3].1.trim().to_string();
+        if threshold.is_empty() {
+            return Err("Threshold is required".to_string());
+        }
+        if !min_id && threshold.parse::<u64>().is_err() {
+            return Err("MAXLEN threshold must be a length".to_string());
+        }
+        Ok(StreamTrim { min_id, approximate, threshold, limit })
+    }
+
+    /// Count what the pending removal would take out, ask for confirmation
+    pub fn confirm_remove(&mut self, client: &mut RedisClient) -> Result<(), String> {
+        let preview = self.remove_preview(client)?;
+        self.confirm_action = Some(format!("{} on '{}': {}", self.edit_op_label(), self.edit_key, preview));
+        self.confirm_edit = true;
+        self.input_mode = InputMode::Confirm;
+        Ok(())
+    }
+
+    fn remove_preview(&self, client: &mut RedisClient) -> Result<String, String> {
+        let key = self.edit_key.as_str();
+        let preview = match &self.edit_operation {
+            Some(EditOperation::HDel) => {
+                let fields = self.edit_field_lines(0, "field")?;
+                let found = client.hexists_count(key, &fields).map_err(|e| e.to_string())?;
+                format!("removes {} of {} field(s)", found, fields.len())
+            }
+            Some(EditOperation::LRem) => {
+                let count: i64 = self.edit_field_number(0)?;
+                let value = self.edit_field_bytes(1)?;
+                let found = client.lpos_count(key, &value, count).map_err(|e| e.to_string())?;
+                format!("removes {} element(s)", found)
+            }
+            Some(EditOperation::LPop | EditOperation::RPop) => {
+                let count: usize = self.edit_field_number(0)?;
+                if count == 0 {
+                    return Err("Count must be at least 1".to_string());
+                }
+                let len = client.llen(key).map_err(|e| e.to_string())? as usize;
+                format!("removes {} of {} element(s)", count.min(len), len)
+            }
+            Some(EditOperation::LTrim) => {
+                let start: i64 = self.edit_field_number(0)?;
+                let stop: i64 = self.edit_field_number(1)?;
+                let len = client.llen(key).map_err(|e| e.to_string())?;
+                let kept = range_len(len, start, stop);
+                format!("keeps {} and removes {} of {} element(s)", kept, len - kept, len)
+            }
+            Some(EditOperation::SRem) => {
+                let member = self.edit_field_bytes(0)?;
+                let found = client.sismember(key, &member).map_err(|e| e.to_string())?;
+                format!("removes {} member", usize::from(found))
+            }
+            Some(EditOperation::ZRem) => {
+                let member = self.edit_field_bytes(0)?;
+                match client.zscore(key, &member).map_err(|e| e.to_string())? {
+                    Some(score) => format!("removes 1 member (score {})", score),
+                    None => "removes 0 members (not found)".to_string(),
+                }
+            }
+            Some(EditOperation::ZRemRangeByScore) => {
+                let (min, max) = (self.edit_fields[0].1.trim(), self.edit_fields[1].1.trim());
+                let found = client.zcount(key, min, max).map_err(|e| e.to_string())?;
+                format!("removes {} member(s) with scores in [{}, {}]", found, min, max)
+            }
+            Some(EditOperation::XDel) => {
+                let ids = self.edit_field_lines(0, "ID")?;
+                let found = client.xexists_count(key, &ids).map_err(|e| e.to_string())?;
+                format!("removes {} of {} entries", found, ids.len())
+            }
+            Some(EditOperation::XTrim) => {
+                let trim = self.edit_stream_trim()?;
+                let len = client.xlen(key).map_err(|e| e.to_string())? as usize;
+                let mut removed = if trim.min_id {
+                    client.xcount_before(key, &trim.threshold, TRIM_PREVIEW_CAP).map_err(|e| e.to_string())?
+                } else {
+                    len.saturating_sub(trim.threshold.parse().unwrap_or(0))
+                };
+                if let (true, Some(limit)) = (trim.approximate, trim.limit) {
+                    removed = removed.min(limit as usize);
+                }
+                let count = if removed > TRIM_PREVIEW_CAP {
+                    format!("more than {}", TRIM_PREVIEW_CAP)
+                } else {
+                    removed.to_string()
+                };
+                if trim.approximate {
+                    // `~` only drops whole radix tree nodes, so fewer may go
+                    format!("{} removes up to {} of {} entries (~ removes whole nodes only)", trim.args().join(" "), count, len)
+                } else {
+                    format!("{} removes {} of {} entries", trim.args().join(" "), count, len)
+                }
+            }
+            _ => return Err("Not a removal".to_string()),
+        };
+        Ok(preview)
+    }
+
+    // ─── Edit field text ─────────────────────────────────────
+
+    /// The focused edit field with its editor state
+    pub fn focused_text(&mut self) -> Option<(&mut TextArea, &mut String)> {
+        let focus = self.edit_focus;
+        let (_, text) = self.edit_fields.get_mut(focus)?;
+        self.edit_text.bind(focus, text);
+        Some((&mut self.edit_text, text))
+    }
+
+    /// Values of text encodings and one-per-line lists may span lines
+    /// (Alt+Enter, or pasted)
+    pub fn edit_field_multiline(&self, idx: usize) -> bool {
+        self.edit_fields.get(idx).is_some_and(|(label, _)| {
+            label.ends_with("(one per line)")
+                || label.starts_with("Value") && !label.contains('[') && !self.edit_binary_mode
+        })
+    }
+
+    /// Insert pasted text into the focused field; single-line fields get
+    /// line breaks as spaces
+    pub fn edit_paste(&mut self, pasted: &str) {
+        let mut pasted = pasted.replace("\r\n", "\n").replace('\r', "\n");
+        if !self.edit_field_multiline(self.edit_focus) {
+            pasted = pasted.trim_end_matches('\n').replace('\n', " ");
+        }
+        if let Some((area, text)) = self.focused_text() {
+            area.insert(text, &pasted);
+        }
+        self.xadd_grow();
+    }
+
+    pub fn edit_prev_field(&mut self) {
+        if !self.edit_fields.is_empty() {
+            self.edit_focus = (self.edit_focus + self.edit_fields.len() - 1) % self.edit_fields.len();
+        }
+    }
+
+    // ─── External editor ─────────────────────────────────────
+
+    /// Check the selected value out to a temp file for `$EDITOR`: a string or
+    /// JSON document, or the selected hash field or list element
+    pub fn start_external_edit(&mut self, client: &mut RedisClient) -> Result<ExternalEdit, String> {
+        let key = self.selected_key_name().ok_or("No key selected")?.to_string();
+        let key_type = self.current_key_info.as_ref().map(|i| i.key_type.as_str()).unwrap_or("");
+        let target = match (key_type, &self.current_value) {
+            ("ReJSON-RL", Some(RedisValue::Json(_))) => EditTarget::JsonDoc,
+            ("string", Some(RedisValue::String(_))) => EditTarget::String,
+            ("hash", Some(RedisValue::Hash(pairs))) => {
+                let (field, _) = pairs.get(self.element_cursor).ok_or("No field selected")?;
+                EditTarget::HashField(field.clone())
+            }
+            ("list", Some(RedisValue::List(items))) if self.element_cursor < items.len() => {
+                EditTarget::ListIndex(self.element_cursor as i64)
+            }
+            _ => return Err("$EDITOR works on strings, JSON documents, hash fields and list elements".to_string()),
+        };
+        let original = target
+            .read(client, &key)
+            .map_err(|e| e.to_string())?
+            .ok_or("The value no longer exists")?;
+        ExternalEdit::checkout(&key, target, original, self.data_type, self.endianness).map_err(|e| e.to_string())
+    }
+
+    /// Write the edited file back, unless the value changed on the server
+    /// while it was open; then ask before overwriting
+    pub fn finish_external_edit(&mut self, client: &mut RedisClient, mut edit: ExternalEdit) -> Result<String, String> {
+        let kept = |e: anyhow::Error| format!("{}; the edit is kept in {}", e, edit.path.display());
+        let Some(new_value) = edit.encode_file().map_err(kept)? else {
+            edit.remove_file();
+            return Ok("No changes".to_string());
+        };
+        let current = edit.target.read(client, &edit.key).map_err(|e| e.to_string())?;
+        edit.new_value = Some(new_value);
+        if current.as_deref() != Some(edit.original.as_slice()) {
+            self.confirm_action = Some(format!(
+                "{} changed on the server while it was being edited. Overwrite",
+                edit.target.describe(&edit.key)
+            ));
+            self.external_edit = Some(edit);
+            self.input_mode = InputMode::Confirm;
+            return Ok("The value changed on the server".to_string());
+        }
+        self.save_external_edit(client, edit)
+    }
+
+    pub fn save_external_edit(&mut self, client: &mut RedisClient, edit: ExternalEdit) -> Result<String, String> {
+        let value = edit.new_value.as_deref().ok_or("Nothing to save")?;
+        self.snapshot_key(client, &edit.key, "$EDITOR save")?;
+        edit.target
+            .write(client, &edit.key, value)
+            .map_err(|e| format!("{}; the edit is kept in {}", e, edit.path.display()))?;
+        edit.remove_file();
+        self.load_selected_value(client);
+        Ok(format!("Saved {} ({})", edit.target.describe(&edit.key), edit.format.label()))
+    }
+
+    // ─── Concurrent changes ──────────────────────────────────
+
+    /// Edits that overwrite or remove what was loaded, as opposed to
+    /// appends and increments, which cannot clobber anything
+    fn edit_guarded(&self) -> bool {
+        match &self.edit_operation {
+            Some(EditOperation::SAdd) => self.edit_element.is_some(),
+            Some(op) => matches!(
+                op,
+                EditOperation::SetString
+                    | EditOperation::HSet
+                    | EditOperation::LSet
+                    | EditOperation::ZAdd
+                    | EditOperation::JsonSet
+                    | EditOperation::JsonDel
+            ) || self.is_remove_edit() && !matches!(op, EditOperation::XDel | EditOperation::XTrim),
+            None => false,
+        }
+    }
+
+    /// Before a guarded write, make sure the value on the server is still
+    /// the one that was loaded; otherwise open the conflict popup
+    fn check_conflict(&mut self, client: &mut RedisClient) -> Result<(), String> {
+        if !self.edit_guarded() {
+            return Ok(());
+        }
+        let Some(loaded) = &self.current_value else {
+            return Ok(());
+        };
+        // WATCHed until the write's EXEC, which fails if the key changes in between
+        client.watch(&self.edit_key).map_err(|e| e.to_string())?;
+        let server = match self.fetch_value(client, &self.edit_key) {
+            Ok(server) => server,
+            Err(e) => {
+                let _ = client.unwatch();
+                return Err(e.to_string());
+            }
+        };
+        if same_value(loaded, &server) {
+            return Ok(());
+        }
+        let _ = client.unwatch();
+        self.open_conflict(server)
+    }
+
+    /// Show how the server's value differs from the loaded one
+    fn open_conflict(&mut self, server: RedisValue) -> Result<(), String> {
+        let loaded = self.current_value.as_ref().ok_or("No loaded value")?;
+        let diff = diff_lines(&self.value_lines(loaded), &self.value_lines(&server));
+        self.edit_conflict = Some(EditConflict { server, diff: condense(&diff, 2), scroll: 0 });
+        self.input_mode = InputMode::Conflict;
+        Err(format!("'{}' changed on the server since it was loaded", self.edit_key))
+    }
+
+    /// A value as lines to diff: text by line, elements one per line
+    fn value_lines(&self, value: &RedisValue) -> Vec<String> {
+        match value {
+            RedisValue::String(bytes) if is_binary(bytes) => vec![self.element_summary(bytes, None)],
+            RedisValue::String(bytes) => String::from_utf8_lossy(bytes).split('\n').map(|l| l.to_string()).collect(),
+            RedisValue::Hash(pairs) => pairs
+                .iter()
+                .map(|(f, v)| format!("{} = {}", f, self.element_summary(v, Some(f))))
+                .collect(),
+            RedisValue::List(items) => items.iter().map(|v| self.element_summary(v, None)).collect(),
+            RedisValue::Set(items) => {
+                let mut members: Vec<String> = items.iter().map(|m| self.element_summary(m, None)).collect();
+                members.sort();
+                members
+            }
+            RedisValue::ZSet(pairs) => pairs
+                .iter()
+                .map(|(m, score)| format!("{} ({})", self.element_summary(m, None), score))
+                .collect(),
+            RedisValue::Json(root) => serde_json::to_string_pretty(root)
+                .unwrap_or_default()
+                .lines()
+                .map(|l| l.to_string())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The elements an element edit writes, as they stand in `value`: the
+    /// field, index or member being set and the original one it replaces
+    fn touched_elements(&self, value: &RedisValue) -> Vec<Option<Vec<u8>>> {
+        let mut names: Vec<Vec<u8>> = self.edit_element.iter().cloned().collect();
+        match (&self.edit_operation, value) {
+            (Some(EditOperation::HSet), RedisValue::Hash(pairs)) => {
+                names.push(self.edit_fields[0].1.as_bytes().to_vec());
+                names
+                    .iter()
+                    .map(|n| pairs.iter().find(|(f, _)| f.as_bytes() == n.as_slice()).map(|(_, v)| v.clone()))
+                    .collect()
+            }
+            (Some(EditOperation::LSet), RedisValue::List(items)) => {
+                let index: i64 = self.edit_fields[0].1.trim().parse().unwrap_or(0);
+                let index = if index < 0 { index + items.len() as i64 } else { index };
+                vec![usize::try_from(index).ok().and_then(|i| items.get(i)).cloned()]
+            }
+            (Some(EditOperation::SAdd), RedisValue::Set(items)) => {
+                names.extend(self.edit_field_bytes(0).ok());
+                names.iter().map(|n| items.contains(n).then(Vec::new)).collect()
+            }
+            (Some(EditOperation::ZAdd), RedisValue::ZSet(pairs)) => {
+                names.extend(self.edit_field_bytes(1).ok());
+                names
+                    .iter()
+                    .map(|n| pairs.iter().find(|(m, _)| m == n).map(|(_, score)| score.to_string().into_bytes()))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Take the server's version as the loaded one, so the edit is written
+    /// over it
+    pub fn overwrite_conflict(&mut self) {
+        if let Some(conflict) = self.edit_conflict.take() {
+            self.current_value = Some(conflict.server);
+            self.value_version += 1;
+        }
+        self.input_mode = InputMode::Edit;
+    }
+
+    /// Combine the edit with the server's changes. Text is merged line by
+    /// line; element edits go through when the server left that element
+    /// alone. Returns how many conflicts are left marked in the value.
+    pub fn merge_conflict(&mut self) -> Result<usize, String> {
+        let server = self.edit_conflict.as_ref().ok_or("Nothing to merge")?.server.clone();
+        let loaded = self.current_value.as_ref().ok_or("No loaded value")?;
+        let pretty = |v: &serde_json::Value| serde_json::to_string_pretty(v).unwrap_or_default();
+
+        let merged = match (&self.edit_operation, loaded, &server) {
+            (_, _, RedisValue::Unknown(_)) => {
+                return Err("The key is gone from the server: overwrite to recreate it, or reload".to_string());
+            }
+            (Some(EditOperation::SetString), RedisValue::String(base), RedisValue::String(theirs)) => {
+                if self.edit_binary_mode || is_binary(base) || is_binary(theirs) {
+                    return Err("Binary values cannot be merged: overwrite or reload".to_string());
+                }
+                let base = String::from_utf8_lossy(base);
+                Some((0, merge3(&base, &self.edit_fields[0].1, &String::from_utf8_lossy(theirs))))
+            }
+            // Whole documents merge as pretty-printed text; other paths apply
+            // to whatever the server has there
+            (Some(EditOperation::JsonSet), RedisValue::Json(base), RedisValue::Json(theirs))
+                if self.edit_fields[0].1.trim() == "$" =>
+            {
+                let ours: serde_json::Value = serde_json::from_str(&self.edit_fields[1].1)
+                    .map_err(|e| format!("Invalid JSON: {}", e))?;
+                Some((1, merge3(&pretty(base), &pretty(&ours), &pretty(theirs))))
+            }
+            (Some(EditOperation::JsonSet | EditOperation::JsonDel), _, _) => None,
+            _ if self.is_remove_edit() => None,
+            _ => {
+                if self.touched_elements(loaded) != self.touched_elements(&server) {
+                    return Err("The edited element itself changed on the server: overwrite or reload".to_string());
+                }
+                None
+            }
+        };
+
+        let conflicts = match merged {
+            Some((idx, (text, conflicts))) => {
+                self.edit_fields[idx].1 = text;
+                self.edit_focus = idx;
+                conflicts
+            }
+            None => 0,
+        };
+        self.edit_conflict = None;
+        self.current_value = Some(server);
+        self.value_version += 1;
+        self.input_mode = InputMode::Edit;
+        Ok(conflicts)
+    }
+
+    pub fn scroll_conflict(&mut self, delta: isize) {
+        if let Some(conflict) = &mut self.edit_conflict {
+            let max = conflict.diff.len().saturating_sub(1);
+            conflict.scroll = conflict.scroll.saturating_add_signed(delta).min(max);
+        }
+    }
+
+    // ─── Undo history ────────────────────────────────────────
+
+    /// Edits that can lose data: overwrites, removals and renames, and new
+    /// keys that may replace an existing one
+    fn edit_destructive(&self) -> bool {
+        self.edit_guarded()
+            || self.is_remove_edit()
+            || matches!(self.edit_operation, Some(EditOperation::RenameKey | EditOperation::NewKey))
+    }
+
+    fn push_undo(&mut self, entry: UndoEntry) {
+        self.undo_stack.push(entry);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// DUMP `key` into the undo history before `action` changes it
+    pub fn snapshot_key(&mut self, client: &mut RedisClient, key: &str, action: &str) -> Result<(), String> {
+        let entry = undo_snapshot(client, key, action)?;
+        self.push_undo(entry);
+        Ok(())
+    }
+
+    /// DEL a key, keeping it in the undo history
+    pub fn delete_key(&mut self, client: &mut RedisClient, key: &str) -> Result<(), String> {
+        self.snapshot_key(client, key, "DEL")?;
+        client.delete_key(key).map_err(|e| {
+            self.undo_stack.pop();
+            e.to_string()
+        })
+    }
+
+    /// Snapshot what the current edit will change; a rename also keeps the
+    /// key it replaces. Returns how many snapshots were taken.
+    fn snapshot_edit(&mut self, client: &mut RedisClient) -> Result<usize, String> {
+        let action = self.edit_op_label().to_string();
+        match self.edit_operation {
+            Some(EditOperation::RenameKey) => {
+                let to = self.edit_fields[0].1.clone();
+                let replaced = undo_snapshot(client, &to, "replaced by RENAME")?;
+                let taken = usize::from(replaced.payload.is_some());
+                if taken == 1 {
+                    self.push_undo(replaced);
+                }
+                let mut entry = undo_snapshot(client, &self.edit_key, &action)?;
+                entry.renamed_to = Some(to);
+                self.push_undo(entry);
+                Ok(taken + 1)
+            }
+            Some(EditOperation::NewKey) => {
+                let key = self.edit_fields[0].1.clone();
+                self.snapshot_key(client, &key, &action).map(|_| 1)
+            }
+            _ => {
+                let key = self.edit_key.clone();
+                self.snapshot_key(client, &key, &action).map(|_| 1)
+            }
+        }
+    }
+
+    /// Put back the key of history entry `idx` (oldest first) as it was
+    pub fn undo(&mut self, client: &mut RedisClient, idx: usize) -> Result<String, String> {
+        let entry = self.undo_stack.get(idx).cloned().ok_or("Nothing to undo")?;
+        let current_db = client.db;
+        if entry.db != current_db {
+            client.select_db(entry.db).map_err(|e| e.to_string())?;
+        }
+        // What the undo overwrites or deletes goes back into the history, so
+        // the undo can be undone in turn
+        let action = format!("undo of {}", entry.action);
+        let redo = |client: &mut RedisClient| -> Result<Vec<UndoEntry>, String> {
+            let mut redo = Vec::new();
+            let mut current = undo_snapshot(client, &entry.key, &action)?;
+            if let Some(to) = &entry.renamed_to {
+                let mut moved = undo_snapshot(client, to, &action)?;
+                if moved.payload.is_some() {
+                    if current.payload.is_some() {
+                        redo.push(current);
+                    }
+                    moved.renamed_to = Some(entry.key.clone());
+                    current = moved;
+                }
+            }
+            redo.push(current);
+            Ok(redo)
+        };
+        let restore = |client: &mut RedisClient| -> anyhow::Result<()> {
+            if let Some(to) = &entry.renamed_to {
+                client.delete_key(to)?;
+            }
+            match &entry.payload {
+                Some(payload) => client.restore(&entry.key, payload, entry.expires_at),
+                None => client.delete_key(&entry.key),
+            }
+        };
+        let result = redo(client).and_then(|redo| restore(client).map(|_| redo).map_err(|e| e.to_string()));
+        if entry.db != current_db {
+            client.select_db(current_db).map_err(|e| e.to_string())?;
+        }
+        let redo = result?;
+
+        self.undo_stack.remove(idx);
+        for entry in redo {
+            self.push_undo(entry);
+        }
+        self.undo_cursor = 0;
+        self.refresh_keys(client);
+        self.load_selected_value(client);
+        let what = if entry.payload.is_some() { "Restored" } else { "Removed" };
+        Ok(format!("{} '{}' (undid {} in db {})", what, entry.key, entry.action, entry.db))
+    }
+
+    /// The history entry under the popup cursor, which lists newest first
+    pub fn undo_selected(&self) -> Option<usize> {
+        self.undo_stack.len().checked_sub(self.undo_cursor + 1)
+    }
+
+    pub fn move_undo_cursor(&mut self, down: bool) {
+        if down {
+            self.undo_cursor = (self.undo_cursor + 1).min(self.undo_stack.len().saturating_sub(1));
+        } else {
+            self.undo_cursor = self.undo_cursor.saturating_sub(1);
+        }
+    }
+
+    /// Rows of the history popup, newest first
+    pub fn undo_table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let columns = ["time (UTC)", "db", "key", "before", "size"].iter().map(|s| s.to_string()).collect();
+        let rows = self
+            .undo_stack
+            .iter()
+            .rev()
+            .map(|e| {
+                let time = format_epoch_millis(e.at_ms);
+                let mut key = e.key.clone();
+                if let Some(to) = &e.renamed_to {
+                    key = format!("{} → {}", key, to);
+                }
+                let size = match &e.payload {
+                    Some(payload) => format!("{} B", payload.len()),
+                    None => "(new)".to_string(),
+                };
+                vec![time[11..19].to_string(), e.db.to_string(), key, e.action.clone(), size]
+            })
+            .collect();
+        (columns, rows)
+    }
+
+    // ─── Bulk actions ────────────────────────────────────────
+
+    /// Mark or unmark the selected key and move on to the next one
+    pub fn toggle_mark(&mut self) {
+        let Some(key) = self.selected_key_name().map(|s| s.to_string()) else {
+            return;
+        };
+        if !self.marked_keys.remove(&key) {
+            self.marked_keys.insert(key);
+        }
+        if self.key_list_state.selected().is_some_and(|i| i + 1 < self.keys.len()) {
+            self.select_next_key();
+        }
+    }
+
+    /// Mark every key the current filter lists
+    pub fn mark_all(&mut self) {
+        self.marked_keys.extend(self.keys.iter().cloned());
+    }
+
+    /// Flip the marks of the listed keys
+    pub fn invert_marks(&mut self) {
+        for key in &self.keys {
+            if !self.marked_keys.remove(key) {
+                self.marked_keys.insert(key.clone());
+            }
+        }
+    }
+
+    /// What a bulk action works on: the marked keys, else the selected one
+    pub fn bulk_targets(&self) -> Vec<String> {
+        if self.marked_keys.is_empty() {
+            return self.selected_key_name().map(|k| vec![k.to_string()]).unwrap_or_default();
+        }
+        let mut keys: Vec<String> = self.marked_keys.iter().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    pub fn bulk_action(&self) -> BulkAction {
+        BULK_ACTIONS[self.bulk_action_idx]
+    }
+
+    pub fn start_bulk(&mut self) {
+        if self.bulk_targets().is_empty() {
+            self.status_message = "No keys to act on (Space marks keys)".to_string();
+            return;
+        }
+        self.reset_bulk_fields();
+        self.input_mode = InputMode::Bulk;
+    }
+
+    pub fn cycle_bulk_action(&mut self, forward: bool) {
+        let n = BULK_ACTIONS.len();
+        self.bulk_action_idx = if forward { (self.bulk_action_idx + 1) % n } else { (self.bulk_action_idx + n - 1) % n };
+        self.reset_bulk_fields();
+    }
+
+    fn reset_bulk_fields(&mut self) {
+        let field = |label: &str, value: String| (label.to_string(), value);
+        self.edit_fields = match self.bulk_action() {
+            BulkAction::Delete | BulkAction::ClearTtl | BulkAction::Import | BulkAction::CopyTo | BulkAction::MoveTo => {
+                Vec::new()
+            }
+            BulkAction::SetTtl => vec![field("TTL (seconds)", String::new())],
+            BulkAction::MoveDb => vec![field("Target db", String::new())],
+            BulkAction::RenamePrefix => vec![
+                field("Old prefix", common_prefix(&self.bulk_targets())),
+                field("New prefix", String::new()),
+            ],
+            BulkAction::Export => vec![field("File", "redis-export.ndjson".to_string())],
+        };
+        self.edit_focus = 0;
+    }
+
+    /// Check the bulk form and show what it would do in the confirm popup
+    pub fn preview_bulk(&mut self) -> Result<(), String> {
+        let keys = self.bulk_targets();
+        let arg = |i: usize| self.edit_fields[i].1.clone();
+        let mut lines = Vec::new();
+        let mut affected = keys.clone();
+        match self.bulk_action() {
+            BulkAction::Delete => lines.push(format!("Delete {} keys with UNLINK", keys.len())),
+            BulkAction::SetTtl => {
+                let secs: u64 = arg(0).trim().parse().map_err(|_| "TTL must be a whole number of seconds")?;
+                if secs == 0 {
+                    return Err("TTL must be at least 1 second".to_string());
+                }
+                lines.push(format!("Expire {} keys in {}s", keys.len(), secs));
+            }
+            BulkAction::ClearTtl => lines.push(format!("Remove the TTL of {} keys", keys.len())),
+            BulkAction::MoveDb => {
+                let db: i64 = arg(0).trim().parse().map_err(|_| "Target db must be a number")?;
+                if db < 0 || db == self.db {
+                    return Err(format!("Target db must differ from db {}", self.db));
+                }
+                lines.push(format!("Move {} keys from db {} to db {}", keys.len(), self.db, db));
+                lines.push("Keys that already exist there are left alone".to_string());
+            }
+            BulkAction::RenamePrefix => {
+                let (old, new) = (arg(0), arg(1));
+                if old == new {
+                    return Err("Old and new prefix are the same".to_string());
+                }
+                affected.retain(|k| k.starts_with(&old));
+                if affected.is_empty() {
+                    return Err(format!("No key starts with '{}'", old));
+                }
+                lines.push(format!("Rename {} keys from '{}…' to '{}…'", affected.len(), old, new));
+                if affected.len() < keys.len() {
+                    lines.push(format!("{} keys without the prefix are skipped", keys.len() - affected.len()));
+                }
+                lines.push("Names that are already taken are left alone (RENAMENX)".to_string());
+                let samples: Vec<String> = affected
+                    .iter()
+                    .take(3)
+                    .map(|k| format!("  {} → {}{}", k, new, &k[old.len()..]))
+                    .collect();
+                lines.extend(samples);
+            }
+            BulkAction::Export => {
+                let path = arg(0).trim().to_string();
+                let format = ExportFormat::from_path(&path).map_err(|e| e.to_string())?;
+                let replace = if std::path::Path::new(&path).exists() { " (replacing it)" } else { "" };
+                lines.push(format!("Export {} keys as {} to {}{}", keys.len(), format.label(), path, replace));
+            }
+            BulkAction::Import => return Err("Import runs from its own popup (O)".to_string()),
+            BulkAction::CopyTo | BulkAction::MoveTo => return Err("Copy runs from its own popup (C)".to_string()),
+        }
+        if self.bulk_action() != BulkAction::RenamePrefix {
+            let mut sample = affected.iter().take(5).cloned().collect::<Vec<_>>().join(", ");
+            if affected.len() > 5 {
+                sample.push_str(&format!(" (+{} more)", affected.len() - 5));
+            }
+            lines.push(format!("  {}", sample));
+        }
+        let prefixes: Vec<String> = prefix_counts(&affected)
+            .iter()
+            .take(5)
+            .map(|(prefix, n)| format!("{} ×{}", prefix, n))
+            .collect();
+        lines.push(format!("Prefixes: {}", prefixes.join(", ")));
+        if self.bulk_action() != BulkAction::Export {
+            lines.push("Bulk actions are not kept in the undo history".to_string());
+        }
+        self.confirm_action = Some(lines.join("\n"));
+        self.confirm_bulk = true;
+        self.input_mode = InputMode::Confirm;
+        Ok(())
+    }
+
+    /// Start the confirmed bulk action; the event loop runs it a batch at a time
+    pub fn start_bulk_job(&mut self) -> Result<(), String> {
+        let action = self.bulk_action();
+        let args: Vec<String> = self.edit_fields.iter().map(|(_, v)| v.clone()).collect();
+        let mut keys = self.bulk_targets();
+        let mut exporter = None;
+        match action {
+            BulkAction::RenamePrefix => keys.retain(|k| k.starts_with(&args[0])),
+            BulkAction::Export => {
+                let file = Exporter::create(args[0].trim(), self.data_type, self.endianness);
+                exporter = Some(file.map_err(|e| format!("{:#}", e))?);
+            }
+            _ => {}
+        }
+        self.run_bulk_job(action, keys, args, exporter);
+        Ok(())
+    }
+
+    fn run_bulk_job(&mut self, action: BulkAction, keys: Vec<String>, args: Vec<String>, exporter: Option<Exporter>) {
+        self.bulk_job = Some(BulkJob {
+            action,
+            keys,
+            args,
+            done: 0,
+            changed: 0,
+            failed: 0,
+            last_error: None,
+            exporter,
+            importer: None,
+            target: None,
+        });
+        self.input_mode = InputMode::BulkProgress;
+    }
+
+    /// Run the next batch of the bulk job, finishing it after the last one
+    pub fn bulk_step(&mut self, client: &mut RedisClient) {
+        let Some(job) = &mut self.bulk_job else {
+            return;
+        };
+        let end = (job.done + BULK_BATCH).min(job.keys.len());
+        let batch = &job.keys[job.done..end];
+        let applied = |replies: Vec<i64>| replies.iter().filter(|r| **r == 1).count();
+        let result = match job.action {
+            // One small UNLINK per key lets other clients in between
+            BulkAction::Delete => client.per_key("UNLINK", batch, &[]).map(applied),
+            BulkAction::SetTtl => client.per_key("EXPIRE", batch, &job.args[..1]).map(applied),
+            BulkAction::ClearTtl => client.per_key("PERSIST", batch, &[]).map(applied),
+            BulkAction::MoveDb => client.per_key("MOVE", batch, &job.args[..1]).map(applied),
+            BulkAction::RenamePrefix => {
+                let (old, new) = (&job.args[0], &job.args[1]);
+                let pairs: Vec<(String, String)> =
+                    batch.iter().map(|k| (k.clone(), format!("{}{}", new, &k[old.len()..]))).collect();
+                client.renamenx_many(&pairs).map(applied)
+            }
+            BulkAction::Export => {
+                let mut written = 0;
+                for key in batch {
+                    let Some(exporter) = job.exporter.as_mut() else {
+                        break;
+                    };
+                    match exporter.write_key(client, key) {
+                        Ok(found) => written += usize::from(found),
+                        Err(e) => {
+                            job.failed += 1;
+                            job.last_error = Some(format!("'{}': {}", key, e));
+                        }
+                    }
+                }
+                Ok(written)
+            }
+            BulkAction::Import => match job.importer.as_mut() {
+                Some(importer) => importer.write(client, job.done..end),
+                None => Ok(0),
+            },
+            BulkAction::CopyTo | BulkAction::MoveTo => {
+                let moving = job.action == BulkAction::MoveTo;
+                let replace = job.args[1] == "replace";
+                match job.target.as_mut() {
+                    Some(target) => copy_to_server(client, target, batch, replace, moving).map(|(copied, errors)| {
+                        job.failed += errors.len();
+                        if let Some(e) = errors.last() {
+                            job.last_error = Some(e.clone());
+                        }
+                        copied
+                    }),
+                    None => {
+                        let db: i64 = job.args[0].parse().unwrap_or_default();
+                        copy_within_server(client, batch, db, replace, moving)
+                    }
+                }
+            }
+        };
+        match result {
+            Ok(n) => job.changed += n,
+            Err(e) => {
+                job.failed += batch.len();
+                job.last_error = Some(e.to_string());
+            }
+        }
+        job.done = end;
+        if job.done >= job.keys.len() {
+            self.finish_bulk(client, false);
+        }
+    }
+
+    /// End the bulk job after its last batch, or early when cancelled
+    pub fn finish_bulk(&mut self, client: &mut RedisClient, cancelled: bool) {
+        let Some(mut job) = self.bulk_job.take() else {
+            return;
+        };
+        let mut msg = format!("{} of {} keys {}", job.changed, job.keys.len(), job.action.done_label());
+        if let Some(exporter) = job.exporter.take() {
+            let (path, format) = (exporter.path.clone(), exporter.format);
+            match exporter.finish() {
+                Ok(()) => msg.push_str(&format!(" as {} to {}", format.label(), path)),
+                Err(e) => msg = format!("Error: {:#}", e),
+            }
+        }
+        if matches!(job.action, BulkAction::CopyTo | BulkAction::MoveTo) {
+            msg.push_str(&format!(" to {} db {}", job.args[2], job.args[0]));
+            let existing = job.done.saturating_sub(job.changed + job.failed);
+            if existing > 0 && job.args[1] != "replace" {
+                msg.push_str(&format!(", {} already there", existing));
+            }
+        }
+        if let Some(importer) = job.importer.take() {
+            let unit = importer.unit();
+            msg = format!("{} of {} {} imported from {}", job.changed, job.keys.len(), unit, importer.path);
+            let conflicts = importer.conflict_summary();
+            if !conflicts.is_empty() {
+                msg.push_str(&format!(", {}", conflicts));
+            }
+        }
+        if cancelled {
+            msg.push_str(&format!(", cancelled after {}", job.done));
+        }
+        if job.failed > 0 {
+            msg.push_str(&format!(", {} failed", job.failed));
+        }
+        if let Some(e) = &job.last_error {
+            msg.push_str(&format!(" (last error: {})", e));
+        }
+
+        if matches!(
+            job.action,
+            BulkAction::Delete | BulkAction::MoveDb | BulkAction::RenamePrefix | BulkAction::MoveTo
+        ) {
+            let handled: HashSet<&String> = job.keys[..job.done].iter().collect();
+            self.marked_keys.retain(|k| !handled.contains(k));
+            self.current_value = None;
+            self.current_key_info = None;
+            self.plot_data.clear();
+        }
+        self.input_mode = InputMode::Normal;
+        self.refresh_keys(client);
+        if self.current_key_info.is_some() {
+            self.load_selected_value(client);
+        }
+        self.status_message = msg;
+    }
+
+    // ─── Delete by pattern ───────────────────────────────────
+
+    pub fn start_pattern_delete(&mut self) {
+        let pattern = if self.filter_pattern == "*" { String::new() } else { self.filter_pattern.clone() };
+        self.edit_fields = vec![("Pattern".to_string(), pattern)];
+        self.edit_focus = 0;
+        self.pattern_delete = None;
+        self.input_mode = InputMode::PatternDelete;
+    }
+
+    /// SCAN the entered pattern and measure what it matches
+    pub fn preview_pattern_delete(&mut self, client: &mut RedisClient) -> Result<(), String> {
+        let pattern = self.edit_fields[0].1.clone();
+        if pattern.is_empty() {
+            return Err("Enter a glob pattern, e.g. tmp:*".to_string());
+        }
+        let keys = client.scan_keys_strict(&pattern).map_err(|e| e.to_string())?;
+        if keys.is_empty() {
+            return Err(format!("No keys match '{}' in db {}", pattern, self.db));
+        }
+        // Every nth key once there are too many to measure them all
+        let step = keys.len().div_ceil(MEMORY_SAMPLE_CAP);
+        let sample: Vec<String> = keys.iter().step_by(step).cloned().collect();
+        let mut measured = 0i64;
+        for chunk in sample.chunks(1000) {
+            let sizes = client.memory_usage(chunk).map_err(|e| e.to_string())?;
+            measured += sizes.iter().flatten().sum::<i64>();
+        }
+        let memory = (measured as f64 * keys.len() as f64 / sample.len() as f64) as i64;
+        self.pattern_delete = Some(PatternDelete {
+            pattern,
+            memory,
+            memory_estimated: step > 1,
+            keys,
+            typed: String::new(),
+        });
+        Ok(())
+    }
+
+    /// Start removing the previewed keys once their count has been typed
+    pub fn confirm_pattern_delete(&mut self) -> Result<(), String> {
+        let preview = self.pattern_delete.as_ref().ok_or("Nothing to delete")?;
+        if preview.typed.trim() != preview.keys.len().to_string() {
+            return Err(format!("Type {} to delete the keys", preview.keys.len()));
+        }
+        let keys = self.pattern_delete.take().map(|p| p.keys).unwrap_or_default();
+        self.run_bulk_job(BulkAction::Delete, keys, Vec::new(), None);
+        Ok(())
+    }
+
+    /// How many keys of the preview share each prefix, for its summary
+    pub fn pattern_delete_prefixes(&self) -> Vec<(String, usize)> {
+        self.pattern_delete.as_ref().map(|p| prefix_counts(&p.keys)).unwrap_or_default()
+    }
+
+    // ─── Export ──────────────────────────────────────────────
+
+    pub fn start_export(&mut self) {
+        self.edit_fields = vec![("File".to_string(), self.export_path.clone())];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::Export;
+    }
+
+    pub fn export_scope(&self) -> ExportScope {
+        EXPORT_SCOPES[self.export_scope_idx]
+    }
+
+    pub fn cycle_export_scope(&mut self, forward: bool) {
+        let n = EXPORT_SCOPES.len();
+        self.export_scope_idx = if forward { (self.export_scope_idx + 1) % n } else { (self.export_scope_idx + n - 1) % n };
+    }
+
+    pub fn export_scope_label(&self, scope: ExportScope) -> String {
+        match scope {
+            ExportScope::Keys if self.marked_keys.is_empty() => "Selected key".to_string(),
+            ExportScope::Keys => format!("{} marked keys", self.marked_keys.len()),
+            ExportScope::Filter => format!("Keys matching {}", self.filter_pattern),
+            ExportScope::Database => format!("All keys in db {}", self.db),
+        }
+    }
+
+    /// Start writing the chosen keys to the file; the format follows its extension
+    pub fn run_export(&mut self, client: &mut RedisClient) -> Result<(), String> {
+        let path = self.edit_fields[0].1.trim().to_string();
+        ExportFormat::from_path(&path).map_err(|e| e.to_string())?;
+        let keys = match self.export_scope() {
+            ExportScope::Keys => self.bulk_targets(),
+            ExportScope::Filter => client.scan_keys(&self.filter_pattern).map_err(|e| e.to_string())?,
+            ExportScope::Database => client.scan_keys("*").map_err(|e| e.to_string())?,
+        };
+        if keys.is_empty() {
+            return Err("No keys to export".to_string());
+        }
+        let exporter = Exporter::create(&path, self.data_type, self.endianness).map_err(|e| format!("{:#}", e))?;
+        self.export_path = path.clone();
+        self.run_bulk_job(BulkAction::Export, keys, vec![path], Some(exporter));
+        Ok(())
+    }
+
+    // ─── Import ──────────────────────────────────────────────
+
+    /// Fields of the import popup; the CSV ones only apply to .csv files
+    pub fn start_import(&mut self) {
+        let field = |label: &str, value: String| (label.to_string(), value);
+        let numeric = if self.data_type.byte_size().is_some() { self.data_type } else { DataType::Float32 };
+        self.edit_fields = vec![
+            field("File", self.import_path.clone()),
+            field("On conflict", ConflictPolicy::Skip.label().to_string()),
+            field("CSV key", String::new()),
+            field("CSV into", "stream".to_string()),
+            field("CSV type", numeric.to_string()),
+            field("Byte order", self.endianness.to_string()),
+        ];
+        self.edit_focus = 0;
+        self.input_mode = InputMode::Import;
+    }
+
+    /// Whether the focused import field is picked with Left/Right
+    pub fn import_field_is_choice(&self) -> bool {
+        matches!(self.edit_focus, 1 | 3 | 4 | 5)
+    }
+
+    pub fn cycle_import_choice(&mut self, forward: bool) {
+        let current = self.edit_fields[self.edit_focus].1.clone();
+        let choices: Vec<String> = match self.edit_focus {
+            1 => CONFLICT_POLICIES.iter().map(|p| p.label().to_string()).collect(),
+            3 => vec!["stream".to_string(), "blob".to_string()],
+            4 => DataType::all().iter().filter(|t| t.byte_size().is_some()).map(|t| t.to_string()).collect(),
+            5 => vec![Endianness::Little.to_string(), Endianness::Big.to_string()],
+            _ => return,
+        };
+        let n = choices.len();
+        let idx = choices.iter().position(|c| *c == current).unwrap_or(0);
+        let next = if forward { (idx + 1) % n } else { (idx + n - 1) % n };
+        self.edit_fields[self.edit_focus].1 = choices[next].clone();
+    }
+
+    /// Read the file and start writing it a batch at a time
+    pub fn run_import(&mut self) -> Result<(), String> {
+        let arg = |i: usize| self.edit_fields[i].1.trim().to_string();
+        let path = arg(0);
+        if path.is_empty() {
+            return Err("Enter the file to import".to_string());
+        }
+        let policy = ConflictPolicy::from_name(&arg(1)).unwrap_or(ConflictPolicy::Skip);
+        let csv = CsvOptions {
+            key: Some(arg(2)).filter(|k| !k.is_empty()),
+            into_stream: arg(3) == "stream",
+            data_type: DataType::from_name(&arg(4)).unwrap_or(DataType::Float32),
+            endianness: Endianness::from_name(&arg(5)).unwrap_or(Endianness::Little),
+        };
+        let importer = Importer::open(&path, policy, &csv).map_err(|e| format!("{:#}", e))?;
+        if importer.len() == 0 {
+            return Err(format!("Nothing to import in {}", path));
+        }
+        self.import_path = path.clone();
+        self.run_bulk_job(BulkAction::Import, importer.labels(), vec![path], None);
+        if let Some(job) = &mut self.bulk_job {
+            job.importer = Some(importer);
+        }
+        Ok(())
+    }
+
+    // ─── Copy between dbs and servers ────────────────────────
+
+    pub fn start_copy(&mut self) {
+        let field = |label: &str, value: String| (label.to_string(), value);
+        self.copy_servers = match ServerStore::load() {
+            Ok(store) => store.servers.into_iter().map(|s| s.name).collect(),
+            Err(_) => Vec::new(),
+        };
+        self.edit_fields = vec![
+            field("Keys", String::new()),
+            field("To server", THIS_SERVER.to_string()),
+            field("To db", self.db.to_string()),
+            field("Mode", "copy".to_string()),
+            field("Existing keys", "skip".to_string()),
+        ];
+        self.edit_focus = 0;
+        self.copy_plan = None;
+        self.input_mode = InputMode::Copy;
+    }
+
+    /// Fields picked with Left/Right; the server can be typed as well
+    pub fn copy_field_cycles(&self) -> bool {
+        matches!(self.edit_focus, 1 | 3 | 4)
+    }
+
+    pub fn copy_field_is_text(&self) -> bool {
+        matches!(self.edit_focus, 0..=2)
+    }
+
+    pub fn cycle_copy_choice(&mut self, forward: bool) {
+        let current = self.edit_fields[self.edit_focus].1.clone();
+        let choices: Vec<String> = match self.edit_focus {
+            1 => std::iter::once(THIS_SERVER.to_string()).chain(self.copy_servers.iter().cloned()).collect(),
+            3 => vec!["copy".to_string(), "move".to_string()],
+            4 => vec!["skip".to_string(), "replace".to_string()],
+            _ => return,
+        };
+        let n = choices.len();
+        let next = match choices.iter().position(|c| *c == current) {
+            Some(idx) if forward => (idx + 1) % n,
+            Some(idx) => (idx + n - 1) % n,
+            None => 0,
+        };
+        self.edit_fields[self.edit_focus].1 = choices[next].clone();
+    }
+
+    /// Name and URL of the server typed in the copy popup: this server, a
+    /// saved name, a redis:// URL, or `name=redis://...` to save one
+    fn resolve_copy_server(&self, input: &str, current_url: &str) -> Result<(String, String), String> {
+        if input.is_empty() || input == THIS_SERVER {
+            return Ok((THIS_SERVER.to_string(), current_url.to_string()));
+        }
+        let is_url = |s: &str| s.starts_with("redis://") || s.starts_with("rediss://");
+        let mut store = ServerStore::load().map_err(|e| format!("{:#}", e))?;
+        if let Some((name, url)) = input.split_once('=').filter(|(_, url)| is_url(url)) {
+            store.upsert(SavedServer { name: name.trim().to_string(), url: url.trim().to_string() });
+            store.save().map_err(|e| format!("{:#}", e))?;
+            return Ok((name.trim().to_string(), url.trim().to_string()));
+        }
+        if let Some(server) = store.find(input) {
+            return Ok((server.name.clone(), server.url.clone()));
+        }
+        if is_url(input) {
+            return Ok((server_label(input), input.to_string()));
+        }
+        Err(format!("Unknown server '{}': type a redis:// URL, or name=redis://... to save it", input))
+    }
+
+    /// Resolve the keys and the target, and count what is already there
+    pub fn preview_copy(&mut self, client: &mut RedisClient) -> Result<(), String> {
+        let arg = |i: usize| self.edit_fields[i].1.trim().to_string();
+        let keys = match arg(0) {
+            pattern if pattern.is_empty() => self.bulk_targets(),
+            pattern => client.scan_keys(&pattern).map_err(|e| e.to_string())?,
+        };
+        if keys.is_empty() {
+            return Err("No keys to copy".to_string());
+        }
+        let db: i64 = arg(2).parse().map_err(|_| "Target db must be a number")?;
+        let (server, url) = self.resolve_copy_server(&arg(1), &client.url)?;
+        let mut target = RedisClient::connect(&url).map_err(|e| format!("{:#}", e))?;
+        // By run_id, so another URL for the same server is not mistaken for a second one
+        let target_id = target.run_id().map_err(|e| format!("{:#}", e))?;
+        let same_server = client.run_id().is_ok_and(|id| id == target_id);
+        if same_server && db == self.db {
+            return Err(format!("Keys are already in db {} of this server", db));
+        }
+        target.select_db(db).map_err(|e| format!("{:#}", e))?;
+        let mut existing = 0;
+        for chunk in keys.chunks(1000) {
+            let found = target.per_key("EXISTS", chunk, &[]).map_err(|e| e.to_string())?;
+            existing += found.iter().filter(|n| **n > 0).count();
+        }
+        self.copy_plan = Some(CopyPlan {
+            keys,
+            server,
+            same_server,
+            db,
+            moving: arg(3) == "move",
+            replace: arg(4) == "replace",
+            existing,
+            target: (!same_server).then_some(target),
+        });
+        Ok(())
+    }
+
+    /// Start the previewed copy or move as a bulk job
+    pub fn confirm_copy(&mut self) {
+        let Some(plan) = self.copy_plan.take() else {
+            return;
+        };
+        let action = if plan.moving { BulkAction::MoveTo } else { BulkAction::CopyTo };
+        let replace = if plan.replace { "replace" } else { "skip" };
+        let args = vec![plan.db.to_string(), replace.to_string(), plan.server];
+        self.run_bulk_job(action, plan.keys, args, None);
+        if let Some(job) = &mut self.bulk_job {
+            job.target = plan.target;
+        }
+    }
+
+    /// How many keys of the copy preview share each prefix, for its summary
+    pub fn copy_prefixes(&self) -> Vec<(String, usize)> {
+        self.copy_plan.as_ref().map(|p| prefix_counts(&p.keys)).unwrap_or_default()
+    }
+
+    // ─── Stream XADD ─────────────────────────────────────────
+
+    /// Append an empty field/value pair once the last one has a field name
+    pub fn xadd_grow(&mut self) {
+        if self.edit_operation != Some(EditOperation::XAdd) {
+            return;
+        }
+        let last_named = self.edit_fields.len() == XADD_HEADER_FIELDS
+            || self.edit_fields.len() >= XADD_HEADER_FIELDS + 2
+                && !self.edit_fields[self.edit_fields.len() - 2].1.is_empty();
+        if last_named {
+            let n = self.xadd_encodings.len() + 1;
+            self.edit_fields.push((format!("Field {}", n), String::new()));
+            self.edit_fields.push((format!("Value {}", n), String::new()));
+            self.xadd_encodings.push(None);
+        }
+    }
+
+    /// The pair the focused XADD row belongs to
+    fn xadd_focused_pair(&self) -> Option<usize> {
+        if self.edit_operation != Some(EditOperation::XAdd) {
+            return None;
+        }
+        self.edit_focus.checked_sub(XADD_HEADER_FIELDS).map(|i| i / 2)
+    }
+
+    /// Ctrl+B in the XADD popup: binary-encode the focused value or not
+    pub fn xadd_toggle_binary(&mut self) {
+        let Some(pair) = self.xadd_focused_pair() else {
+            return;
+        };
+        let default = DataType::all()[self.edit_binary_dtype_idx];
+        if let Some(encoding) = self.xadd_encodings.get_mut(pair) {
+            *encoding = match encoding {
+                Some(_) => None,
+                None => Some(default),
+            };
+        }
+        self.xadd_relabel();
+    }
+
+    /// Ctrl+T in the XADD popup: next data type for the focused binary value
+    pub fn xadd_cycle_type(&mut self) {
+        let Some(pair) = self.xadd_focused_pair() else {
+            return;
+        };
+        let all = DataType::all();
+        // Skip String and Blob types (last two)
+        let max_idx = all.len() - 2;
+        if let Some(Some(dtype)) = self.xadd_encodings.get_mut(pair) {
+            let idx = all.iter().position(|t| t == dtype).unwrap_or(0);
+            *dtype = all[(idx + 1) % max_idx];
+            self.edit_binary_dtype_idx = (idx + 1) % max_idx;
+        }
+        self.xadd_relabel();
+    }
+
+    /// Show each value's encoding in its label
+    pub fn xadd_relabel(&mut self) {
+        for (pair, encoding) in self.xadd_encodings.iter().enumerate() {
+            if let Some((label, _)) = self.edit_fields.get_mut(XADD_HEADER_FIELDS + pair * 2 + 1) {
+                *label = match encoding {
+                    Some(dtype) => format!("Value {} [{} {}]", pair + 1, dtype, self.endianness),
+                    None => format!("Value {}", pair + 1),
+                };
+            }
+        }
     }
 
-    pub fn start_rename(&mut self) {
-        let key = match self.selected_key_name() {
-            Some(k) => k.to_string(),
-            None => return,
-        };
-        self.edit_operation = Some(EditOperation::RenameKey);
-        self.edit_fields = vec![("New name".to_string(), key.clone())];
-        self.edit_key = key;
-        self.edit_focus = 0;
-        self.input_mode = InputMode::Edit;
+    pub fn is_xadd_edit(&self) -> bool {
+        self.edit_operation == Some(EditOperation::XAdd)
     }
 
-    pub fn start_new_key(&mut self) {
-        self.edit_operation = Some(EditOperation::NewKey);
-        self.new_key_type_idx = 0;
-        self.edit_multi_count = 0;
-        self.edit_fields = vec![
-            ("Key".to_string(), String::new()),
-            ("Value".to_string(), String::new()),
-        ];
-        self.edit_key.clear();
-        self.edit_focus = 0;
-        self.input_mode = InputMode::Edit;
+    pub fn is_json_edit(&self) -> bool {
+        self.edit_operation.as_ref().is_some_and(|op| JSON_OPS.contains(op))
+    }
+
+    /// Whether Ctrl+B binary encoding applies to the current edit
+    pub fn edit_supports_binary(&self) -> bool {
+        self.edit_operation.as_ref().is_some_and(|op| self.edit_supports_binary_op(op))
+    }
+
+    fn edit_supports_binary_op(&self, op: &EditOperation) -> bool {
+        !JSON_OPS.contains(op)
+            && !matches!(
+                op,
+                EditOperation::TsAdd
+                    | EditOperation::ProbAdd
+                    | EditOperation::XAdd // encoded per value instead
+                    | EditOperation::HDel
+                    | EditOperation::LPop
+                    | EditOperation::RPop
+                    | EditOperation::LTrim
+                    | EditOperation::ZRemRangeByScore
+                    | EditOperation::XDel
+                    | EditOperation::XTrim
+            )
+    }
+
+    /// Switch the JSON edit popup to the next/previous JSON.* command,
+    /// keeping the path and, where it still applies, the value.
+    pub fn cycle_json_op(&mut self, forward: bool) {
+        let Some(idx) = self.edit_operation.as_ref().and_then(|op| JSON_OPS.iter().position(|o| o == op)) else {
+            return;
+        };
+        let n = JSON_OPS.len();
+        let next = if forward { (idx + 1) % n } else { (idx + n - 1) % n };
+        let op = JSON_OPS[next].clone();
+
+        let path = self.edit_fields.first().map(|f| f.1.clone()).unwrap_or_else(|| "$".to_string());
+        let value = self
+            .edit_fields
+            .iter()
+            .find(|f| f.0 == "Value (JSON)")
+            .map(|f| f.1.clone())
+            .unwrap_or_default();
+        let mut fields = vec![("Path".to_string(), path)];
+        match op {
+            EditOperation::JsonSet | EditOperation::JsonArrAppend => {
+                fields.push(("Value (JSON)".to_string(), value));
+            }
+            EditOperation::JsonNumIncrBy => fields.push(("Increment".to_string(), "1".to_string())),
+            _ => {}
+        }
+        self.edit_fields = fields;
+        self.edit_focus = self.edit_focus.min(self.edit_fields.len() - 1);
+        self.edit_operation = Some(op);
     }
 
     pub fn execute_edit(&mut self, client: &mut RedisClient) -> Result<(), String> {
@@ -955,34 +4545,57 @@ impl App {
             Some(op) => op.clone(),
             None => return Err("No operation".to_string()),
         };
+        self.check_conflict(client)?;
+
+        // Keep what is about to be overwritten or removed, for undo
+        let snapshots = if self.edit_destructive() {
+            match self.snapshot_edit(client) {
+                Ok(n) => n,
+                Err(e) => {
+                    let _ = client.unwatch();
+                    return Err(e);
+                }
+            }
+        } else {
+            0
+        };
+        let result = self.write_edit(client, op);
+        if result.is_err() {
+            self.undo_stack.truncate(self.undo_stack.len() - snapshots);
+            // A write that failed before its EXEC leaves the WATCH behind
+            let _ = client.unwatch();
+        }
+        result
+    }
 
+    fn write_edit(&mut self, client: &mut RedisClient, op: EditOperation) -> Result<(), String> {
         // Helper: encode value to binary if binary mode is on
         let bin_dtype = DataType::all()[self.edit_binary_dtype_idx];
         let bin_endian = self.endianness;
         let binary_mode = self.edit_binary_mode;
+        let encode = |text: &str| -> Result<Vec<u8>, String> {
+            if binary_mode {
+                encode_values(text, bin_dtype, bin_endian)
+            } else {
+                Ok(text.as_bytes().to_vec())
+            }
+        };
+        // Writes that may replace what was loaded go in one MULTI/EXEC, so
+        // the WATCH from check_conflict covers them
+        let mut writes = Vec::new();
 
         let result = match op {
             EditOperation::SetString => {
-                let value = &self.edit_fields[0].1;
-                if binary_mode {
-                    let bytes = encode_values(value, bin_dtype, bin_endian)?;
-                    client.set_bytes(&self.edit_key, &bytes).map_err(|e| e.to_string())
-                } else {
-                    client.set_string(&self.edit_key, value).map_err(|e| e.to_string())
-                }
+                writes.push(KeyWrite::Set(encode(&self.edit_fields[0].1)?));
+                Ok(())
             }
             EditOperation::HSet => {
                 let field = &self.edit_fields[0].1;
-                let value = &self.edit_fields[1].1;
                 if field.is_empty() {
                     return Err("Field name is required".to_string());
                 }
-                if binary_mode {
-                    let bytes = encode_values(value, bin_dtype, bin_endian)?;
-                    client.hset_bytes(&self.edit_key, field, &bytes).map_err(|e| e.to_string())
-                } else {
-                    client.hset(&self.edit_key, field, value).map_err(|e| e.to_string())
-                }
+                writes.push(KeyWrite::HSet(field.clone(), encode(&self.edit_fields[1].1)?));
+                Ok(())
             }
             EditOperation::RPush => {
                 let value = &self.edit_fields[0].1;
@@ -998,47 +4611,51 @@ impl App {
                     .1
                     .parse()
                     .map_err(|_| "Invalid index".to_string())?;
-                let value = &self.edit_fields[1].1;
-                if binary_mode {
-                    let bytes = encode_values(value, bin_dtype, bin_endian)?;
-                    client.lset_bytes(&self.edit_key, index, &bytes).map_err(|e| e.to_string())
-                } else {
-                    client.lset(&self.edit_key, index, value).map_err(|e| e.to_string())
-                }
+                writes.push(KeyWrite::LSet(index, encode(&self.edit_fields[1].1)?));
+                Ok(())
             }
             EditOperation::SAdd => {
-                let member = &self.edit_fields[0].1;
-                if binary_mode {
-                    let bytes = encode_values(member, bin_dtype, bin_endian)?;
-                    client.sadd_bytes(&self.edit_key, &bytes).map_err(|e| e.to_string())
-                } else {
-                    client.sadd(&self.edit_key, member).map_err(|e| e.to_string())
-                }
+                writes.push(KeyWrite::SAdd(encode(&self.edit_fields[0].1)?));
+                Ok(())
             }
             EditOperation::ZAdd => {
                 let score: f64 = self.edit_fields[0]
                     .1
                     .parse()
                     .map_err(|_| "Invalid score (must be a number)".to_string())?;
-                let member = &self.edit_fields[1].1;
-                if binary_mode {
-                    let bytes = encode_values(member, bin_dtype, bin_endian)?;
-                    client.zadd_bytes(&self.edit_key, score, &bytes).map_err(|e| e.to_string())
-                } else {
-                    client.zadd(&self.edit_key, score, member).map_err(|e| e.to_string())
-                }
+                writes.push(KeyWrite::ZAdd(score, encode(&self.edit_fields[1].1)?));
+                Ok(())
             }
             EditOperation::XAdd => {
-                let field = &self.edit_fields[0].1;
-                let value = &self.edit_fields[1].1;
-                if field.is_empty() {
-                    return Err("Field name is required".to_string());
+                let id = self.edit_fields[0].1.trim();
+                let id = if id.is_empty() { "*" } else { id };
+                if !is_stream_id_spec(id) {
+                    return Err("ID must be *, <ms>, <ms>-<seq> or <ms>-*".to_string());
                 }
-                if binary_mode {
-                    let bytes = encode_values(value, bin_dtype, bin_endian)?;
-                    client.xadd_binary(&self.edit_key, field, &bytes).map_err(|e| e.to_string())
-                } else {
-                    client.xadd(&self.edit_key, field, value).map_err(|e| e.to_string())
+                let trim = parse_stream_trim(&self.edit_fields[1].1)?;
+                let nomkstream = self.edit_fields[2].1.trim().eq_ignore_ascii_case("y");
+                let mut fields = Vec::new();
+                for (pair, encoding) in self.edit_fields[XADD_HEADER_FIELDS..].chunks(2).zip(&self.xadd_encodings) {
+                    let (name, value) = (&pair[0].1, &pair[1].1);
+                    if name.is_empty() {
+                        if !value.is_empty() {
+                            return Err(format!("{} has no field name", pair[1].0));
+                        }
+                        continue;
+                    }
+                    let bytes = match encoding {
+                        Some(dtype) => encode_values(value, *dtype, bin_endian)?,
+                        None => value.as_bytes().to_vec(),
+                    };
+                    fields.push((name.clone(), bytes));
+                }
+                if fields.is_empty() {
+                    return Err("At least one field is required".to_string());
+                }
+                match client.xadd_entry(&self.edit_key, id, trim.as_ref(), nomkstream, &fields) {
+                    Ok(Some(_)) => Ok(()),
+                    Ok(None) => Err("Stream does not exist (NOMKSTREAM)".to_string()),
+                    Err(e) => Err(e.to_string()),
                 }
             }
             EditOperation::SetTTL => {
@@ -1093,13 +4710,139 @@ impl App {
                     }
                 }
             }
+            EditOperation::JsonSet => {
+                let path = self.edit_fields[0].1.trim().to_string();
+                writes.push(KeyWrite::JsonSet(path, parse_json_field(&self.edit_fields[1].1)?));
+                Ok(())
+            }
+            EditOperation::JsonDel => {
+                writes.push(KeyWrite::JsonDel(self.edit_fields[0].1.trim().to_string()));
+                Ok(())
+            }
+            EditOperation::JsonArrAppend => {
+                let path = self.edit_fields[0].1.trim();
+                let json = parse_json_field(&self.edit_fields[1].1)?;
+                match client.json_arrappend(&self.edit_key, path, &json) {
+                    Ok(lengths) if lengths.iter().any(|l| l.is_some()) => Ok(()),
+                    Ok(_) => Err(format!("No array at {}", path)),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            EditOperation::JsonNumIncrBy => {
+                let path = self.edit_fields[0].1.trim();
+                let by = self.edit_fields[1].1.trim();
+                by.parse::<f64>()
+                    .map_err(|_| "Invalid increment (must be a number)".to_string())?;
+                match client.json_numincrby(&self.edit_key, path, by) {
+                    Ok(values) if values.trim() == "[null]" || values.trim() == "[]" => {
+                        Err(format!("No number at {}", path))
+                    }
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(e.to_string()),
+                }
+            }
+            EditOperation::TsAdd => {
+                let ts = match self.edit_fields[0].1.trim() {
+                    "" => "*",
+                    ts => ts,
+                };
+                if ts != "*" && ts.parse::<u64>().is_err() {
+                    return Err("Invalid timestamp (ms since epoch, or *)".to_string());
+                }
+                let value = self.edit_fields[1].1.trim();
+                value
+                    .parse::<f64>()
+                    .map_err(|_| "Invalid value (must be a number)".to_string())?;
+                client.ts_add(&self.edit_key, ts, value).map(|_| ()).map_err(|e| e.to_string())
+            }
+            EditOperation::ProbAdd => {
+                let kind = self.prob_kind().ok_or("Value is not a probabilistic structure")?;
+                let items: Vec<String> =
+                    self.edit_fields[0].1.split_whitespace().map(|s| s.to_string()).collect();
+                if items.is_empty() {
+                    return Err("Enter at least one item (space separated)".to_string());
+                }
+                if kind == ProbKind::TDigest && items.iter().any(|v| v.parse::<f64>().is_err()) {
+                    return Err("t-digest values must be numbers".to_string());
+                }
+                client.prob_add(&self.edit_key, kind, &items).map_err(|e| e.to_string())
+            }
+            EditOperation::HDel => {
+                let fields = self.edit_field_lines(0, "field")?;
+                writes.extend(fields.into_iter().map(KeyWrite::HDel));
+                Ok(())
+            }
+            EditOperation::LRem => {
+                let count: i64 = self.edit_field_number(0)?;
+                writes.push(KeyWrite::LRem(count, self.edit_field_bytes(1)?));
+                Ok(())
+            }
+            EditOperation::LPop | EditOperation::RPop => {
+                let count: usize = self.edit_field_number(0)?;
+                writes.push(KeyWrite::Pop { from_tail: op == EditOperation::RPop, count });
+                Ok(())
+            }
+            EditOperation::LTrim => {
+                let start: i64 = self.edit_field_number(0)?;
+                let stop: i64 = self.edit_field_number(1)?;
+                writes.push(KeyWrite::LTrim(start, stop));
+                Ok(())
+            }
+            EditOperation::SRem => {
+                writes.push(KeyWrite::SRem(self.edit_field_bytes(0)?));
+                Ok(())
+            }
+            EditOperation::ZRem => {
+                writes.push(KeyWrite::ZRem(self.edit_field_bytes(0)?));
+                Ok(())
+            }
+            EditOperation::ZRemRangeByScore => {
+                let (min, max) = (self.edit_fields[0].1.trim(), self.edit_fields[1].1.trim());
+                writes.push(KeyWrite::ZRemRangeByScore(min.to_string(), max.to_string()));
+                Ok(())
+            }
+            EditOperation::XDel => {
+                let ids = self.edit_field_lines(0, "ID")?;
+                ids.iter()
+                    .try_for_each(|id| client.xdel(&self.edit_key, id).map(|_| ()))
+                    .map_err(|e| e.to_string())
+            }
+            EditOperation::XTrim => {
+                let trim = self.edit_stream_trim()?;
+                client.xtrim(&self.edit_key, &trim).map(|_| ()).map_err(|e| e.to_string())
+            }
         };
+        result?;
 
-        result
+        // An in-place element edit that renamed the field or changed the
+        // member leaves the original behind, so remove it
+        if let Some(original) = self.edit_element.clone() {
+            let replaced = match &self.edit_operation {
+                Some(EditOperation::HSet) if self.edit_fields[0].1.as_bytes() != original => {
+                    Some(KeyWrite::HDel(String::from_utf8_lossy(&original).to_string()))
+                }
+                Some(EditOperation::SAdd) if encode(&self.edit_fields[0].1)? != original => {
+                    Some(KeyWrite::SRem(original))
+                }
+                Some(EditOperation::ZAdd) if encode(&self.edit_fields[1].1)? != original => {
+                    Some(KeyWrite::ZRem(original))
+                }
+                _ => None,
+            };
+            writes.extend(replaced);
+        }
+
+        if !writes.is_empty() && !client.exec_watched(&self.edit_key, &writes).map_err(|e| e.to_string())? {
+            // EXEC came back nil: the key changed after check_conflict looked
+            let server = self.fetch_value(client, &self.edit_key).map_err(|e| e.to_string())?;
+            return self.open_conflict(server);
+        }
+        Ok(())
     }
 
     pub fn cancel_edit(&mut self) {
         self.edit_operation = None;
+        self.edit_element = None;
         self.edit_fields.clear();
         self.edit_focus = 0;
         self.edit_binary_mode = false;
@@ -1124,24 +4867,53 @@ impl App {
             Some(EditOperation::SetTTL) => "EXPIRE",
             Some(EditOperation::RenameKey) => "RENAME",
             Some(EditOperation::NewKey) => "NEW KEY",
+            Some(EditOperation::JsonSet) => "JSON.SET",
+            Some(EditOperation::JsonDel) => "JSON.DEL",
+            Some(EditOperation::JsonArrAppend) => "JSON.ARRAPPEND",
+            Some(EditOperation::JsonNumIncrBy) => "JSON.NUMINCRBY",
+            Some(EditOperation::TsAdd) => "TS.ADD",
+            Some(EditOperation::ProbAdd) => self.prob_kind().map_or("ADD", |k| k.add_command()),
+            Some(EditOperation::HDel) => "HDEL",
+            Some(EditOperation::LRem) => "LREM",
+            Some(EditOperation::LPop) => "LPOP",
+            Some(EditOperation::RPop) => "RPOP",
+            Some(EditOperation::LTrim) => "LTRIM",
+            Some(EditOperation::SRem) => "SREM",
+            Some(EditOperation::ZRem) => "ZREM",
+            Some(EditOperation::ZRemRangeByScore) => "ZREMRANGEBYSCORE",
+            Some(EditOperation::XDel) => "XDEL",
+            Some(EditOperation::XTrim) => "XTRIM",
             None => "",
         }
     }
 
     /// Returns true if the current edit operation supports adding multiple entries
     pub fn is_multi_entry_edit(&self) -> bool {
-        matches!(
-            &self.edit_operation,
-            Some(EditOperation::HSet)
-                | Some(EditOperation::RPush)
-                | Some(EditOperation::SAdd)
-                | Some(EditOperation::ZAdd)
-                | Some(EditOperation::XAdd)
-        )
+        self.edit_element.is_none()
+            && matches!(
+                &self.edit_operation,
+                Some(EditOperation::HSet)
+                    | Some(EditOperation::RPush)
+                    | Some(EditOperation::SAdd)
+                    | Some(EditOperation::ZAdd)
+                    | Some(EditOperation::XAdd)
+                    | Some(EditOperation::TsAdd)
+                    | Some(EditOperation::ProbAdd)
+            )
     }
 
     /// Reset input fields for the next entry (keep labels, clear values)
     pub fn reset_edit_fields_for_next(&mut self) {
+        if self.is_xadd_edit() {
+            // Keep the trim options and field names for entries of the same shape
+            self.edit_fields[0].1 = "*".to_string();
+            for pair in self.edit_fields[XADD_HEADER_FIELDS..].chunks_mut(2) {
+                pair[1].1.clear();
+            }
+            self.edit_focus = XADD_HEADER_FIELDS + 1;
+            self.edit_multi_count += 1;
+            return;
+        }
         for (_label, value) in &mut self.edit_fields {
             value.clear();
         }
@@ -1262,7 +5034,13 @@ pub fn generate_wave_blob(config: &SignalGenConfig, time_offset: f64) -> Vec<u8>
     blob
 }
 
-fn format_stream_entries(entries: &[StreamEntry], data_type: DataType, endianness: Endianness) -> Vec<String> {
+fn format_stream_entries(
+    entries: &[StreamEntry],
+    data_type: DataType,
+    endianness: Endianness,
+    scaling: &SampleScaling,
+    formats: &FormatSettings,
+) -> Vec<String> {
     let mut lines = Vec::new();
     let total = entries.len();
     // Show only last 5 entries (newest first)
@@ -1275,48 +5053,362 @@ fn format_stream_entries(entries: &[StreamEntry], data_type: DataType, endiannes
         let time_str = format_stream_id(&entry.id);
         lines.push(format!("--- {} ({}) ---", entry.id, time_str));
         for (fname, fval) in &entry.fields {
-            if fname.starts_with('_') && is_binary(fval) {
-                // Binary data field - show decoded values + hex summary
-                let decoded = decode_blob(fval, data_type, endianness);
-                if !decoded.is_empty() {
-                    let preview: Vec<String> = decoded.iter().take(8).map(|v| {
-                        match data_type {
-                            DataType::Float32 | DataType::Float64 => format!("{:.4}", v),
-                            _ => format!("{}", *v as i64),
-                        }
-                    }).collect();
-                    let suffix = if decoded.len() > 8 {
-                        format!(" ..({} vals)", decoded.len())
-                    } else {
-                        String::new()
-                    };
-                    lines.push(format!("  {} [{}]: [{}]{}",
-                        fname, data_type, preview.join(", "), suffix));
+            let plain = stream_field_lines(fname, fval, data_type, endianness, scaling);
+            push_element(&mut lines, &format!("  {}:", fname), formats.render(fval, Some(fname)), plain);
+        }
+    }
+    lines
+}
+
+/// Lines for one stream field without a formatter: `_`-prefixed binary
+/// fields are decoded as samples with a hex summary, others shown as text
+fn stream_field_lines(
+    fname: &str,
+    fval: &[u8],
+    data_type: DataType,
+    endianness: Endianness,
+    scaling: &SampleScaling,
+) -> Vec<String> {
+    let mut lines = Vec::new();
+    if fname.starts_with('_') && is_binary(fval) {
+        // Binary data field - show decoded values + hex summary
+        let decoded = scaling.apply(decode_blob(scaling.skip_header(fval), data_type, endianness));
+        if !decoded.is_empty() {
+            let preview: Vec<String> = decoded.iter().take(8).map(|v| {
+                match data_type {
+                    _ if scaling.is_scaled() => format!("{:.4}", v),
+                    DataType::Float32 | DataType::Float64 => format!("{:.4}", v),
+                    _ => format!("{}", *v as i64),
                 }
-                // Hex summary
-                let hex: String = fval
-                    .iter()
-                    .take(24)
-                    .map(|b| format!("{:02x}", b))
-                    .collect::<Vec<_>>()
-                    .join(" ");
-                let suffix = if fval.len() > 24 { "..." } else { "" };
-                lines.push(format!(
-                    "  {} [hex, {} bytes]: {}{}",
-                    fname,
-                    fval.len(),
-                    hex,
-                    suffix
-                ));
+            }).collect();
+            let suffix = if decoded.len() > 8 {
+                format!(" ..({} vals)", decoded.len())
             } else {
-                let s = String::from_utf8_lossy(fval);
-                lines.push(format!("  {}: {}", fname, s));
+                String::new()
+            };
+            lines.push(format!("  {} [{}]: [{}]{}",
+                fname, data_type, preview.join(", "), suffix));
+        }
+        // Hex summary
+        let hex: String = fval
+            .iter()
+            .take(24)
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let suffix = if fval.len() > 24 { "..." } else { "" };
+        lines.push(format!(
+            "  {} [hex, {} bytes]: {}{}",
+            fname,
+            fval.len(),
+            hex,
+            suffix
+        ));
+    } else {
+        let s = String::from_utf8_lossy(fval);
+        lines.push(format!("  {}: {}", fname, s));
+    }
+    lines
+}
+
+/// Append a collection element: its formatted tree indented under `label`,
+/// or the `plain` lines when no formatter applies (plus the error, if the
+/// selected format failed)
+fn push_element(
+    lines: &mut Vec<String>,
+    label: &str,
+    rendered: Option<Result<Rendered, String>>,
+    plain: Vec<String>,
+) {
+    let indent = " ".repeat(label.len() - label.trim_start().len() + 4);
+    match rendered {
+        Some(Ok(r)) => {
+            lines.push(format!("{} ({})", label, r.label));
+            lines.extend(r.lines.into_iter().map(|l| format!("{}{}", indent, l)));
+        }
+        Some(Err(e)) => {
+            lines.extend(plain);
+            lines.push(format!("{}(decode failed: {})", indent, e));
+        }
+        None => lines.extend(plain),
+    }
+}
+
+/// TS.INFO fields, labels, compaction rules and the queried samples
+fn format_timeseries(series: &TimeSeries) -> Vec<String> {
+    let mut lines = vec!["── TS.INFO ──".to_string()];
+    for (name, value) in &series.info {
+        let is_time = matches!(name.as_str(), "firstTimestamp" | "lastTimestamp");
+        match value.parse::<i64>() {
+            Ok(ms) if is_time && ms > 0 => {
+                lines.push(format!("{}: {} ({})", name, value, format_epoch_millis(ms)));
             }
+            Ok(ms) if name == "retentionTime" => {
+                let retention = if ms == 0 { "forever".to_string() } else { format_duration_ms(ms) };
+                lines.push(format!("{}: {} ({})", name, value, retention));
+            }
+            _ => lines.push(format!("{}: {}", name, value)),
+        }
+    }
+
+    lines.push(String::new());
+    lines.push(format!("── Labels ({}) ──", series.labels.len()));
+    for (name, value) in &series.labels {
+        lines.push(format!("{} = {}", name, value));
+    }
+
+    lines.push(String::new());
+    lines.push(format!("── Compaction rules ({}) ──", series.rules.len()));
+    for rule in &series.rules {
+        lines.push(format!(
+            "→ {}  {} over {}",
+            rule.dest_key,
+            rule.aggregation,
+            format_duration_ms(rule.bucket_ms)
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(format!("── {} ({} samples) ──", series.query.summary(), series.samples.len()));
+    for (ts, value) in &series.samples {
+        lines.push(format!("{}  {}  {}", format_epoch_millis(*ts), ts, value));
+    }
+    lines
+}
+
+/// Whether a sorted set looks like a geo set: every score is a 52-bit
+/// integer geohash, and they are not all small integers (ranks, counters)
+fn looks_like_geo(pairs: &[(Vec<u8>, f64)]) -> bool {
+    const GEOHASH_MAX: f64 = (1u64 << 52) as f64;
+    !pairs.is_empty()
+        && pairs.iter().all(|(_, s)| s.fract() == 0.0 && (0.0..GEOHASH_MAX).contains(s))
+        && pairs.iter().any(|(_, s)| *s >= (1u64 << 32) as f64)
+}
+
+/// INFO fields of a probabilistic structure, its Top-K list or estimated
+/// distribution, and the results of the last query
+fn format_probabilistic(prob: &ProbStructure, query: &str, results: &[(String, String)]) -> Vec<String> {
+    let mut lines = vec![format!("── {} ──", prob.kind.name())];
+    lines.extend(prob.info.iter().map(|(name, value)| format!("{}: {}", name, value)));
+
+    if prob.kind == ProbKind::TopK {
+        lines.push(String::new());
+        lines.push(format!("── Top {} items (TOPK.LIST) ──", prob.top_items.len()));
+        let width = prob.top_items.iter().map(|(item, _)| item.chars().count()).max().unwrap_or(0);
+        for (i, (item, count)) in prob.top_items.iter().enumerate() {
+            lines.push(format!("{:>3}. {:<width$}  {}", i + 1, item, count, width = width));
         }
     }
+
+    if !prob.histogram.is_empty() {
+        let max = prob.histogram.iter().map(|b| b.1).fold(0.0, f64::max);
+        let half = prob.histogram.get(1).map_or(0.0, |b| b.0 - prob.histogram[0].0) / 2.0;
+        lines.push(String::new());
+        lines.push(format!("── Distribution ({} bins, estimated from CDF) ──", prob.histogram.len()));
+        for (center, count) in &prob.histogram {
+            let bar = if max > 0.0 { (count / max * 30.0).round() as usize } else { 0 };
+            lines.push(format!(
+                "{:>12.4} .. {:<12.4} {:>10.1} {}",
+                center - half,
+                center + half,
+                count,
+                "█".repeat(bar)
+            ));
+        }
+    }
+
+    if !query.is_empty() {
+        lines.push(String::new());
+        lines.push(format!("── {} ──", query));
+        lines.extend(results.iter().map(|(item, reply)| format!("{}: {}", item, reply)));
+    }
     lines
 }
 
+/// Milliseconds as a short duration, e.g. `90s` or `1h`
+fn format_duration_ms(ms: i64) -> String {
+    const UNITS: &[(i64, &str)] = &[(86_400_000, "d"), (3_600_000, "h"), (60_000, "m"), (1000, "s")];
+    UNITS
+        .iter()
+        .find(|(unit, _)| ms >= *unit && ms % unit == 0)
+        .map(|(unit, suffix)| format!("{}{}", ms / unit, suffix))
+        .unwrap_or_else(|| format!("{}ms", ms))
+}
+
+/// Whether `id` is an XADD ID: `*`, `<ms>`, `<ms>-<seq>` or `<ms>-*`
+fn is_stream_id_spec(id: &str) -> bool {
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match id.split_once('-') {
+        _ if id == "*" => true,
+        None => digits(id),
+        Some((ms, seq)) => digits(ms) && (seq == "*" || digits(seq)),
+    }
+}
+
+/// Parse XADD trimming such as `MAXLEN ~ 1000`, `MINID 1700000000000-0` or
+/// `MAXLEN ~ 1000 LIMIT 100`. Blank means no trimming.
+fn parse_stream_trim(text: &str) -> Result<Option<StreamTrim>, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let Some(strategy) = words.first() else {
+        return Ok(None);
+    };
+    let min_id = match strategy.to_uppercase().as_str() {
+        "MAXLEN" => false,
+        "MINID" => true,
+        _ => return Err("Trim must start with MAXLEN or MINID".to_string()),
+    };
+    let mut rest = &words[1..];
+    let approximate = rest.first() == Some(&"~");
+    if matches!(rest.first(), Some(&"~") | Some(&"=")) {
+        rest = &rest[1..];
+    }
+    let (threshold, limit) = match rest {
+        [threshold] => (threshold.to_string(), None),
+        [threshold, kw, n] if kw.eq_ignore_ascii_case("LIMIT") => {
+            let n = n.parse().map_err(|_| "LIMIT must be a number".to_string())?;
+            (threshold.to_string(), Some(n))
+        }
+        _ => return Err("Trim is MAXLEN|MINID [~|=] <threshold> [LIMIT <n>]".to_string()),
+    };
+    if limit.is_some() && !approximate {
+        return Err("LIMIT needs approximate trimming (~)".to_string());
+    }
+    if !min_id && threshold.parse::<u64>().is_err() {
+        return Err("MAXLEN threshold must be a length".to_string());
+    }
+    if min_id && (!is_stream_id_spec(&threshold) || threshold.ends_with('*')) {
+        return Err("MINID threshold must be a stream ID".to_string());
+    }
+    Ok(Some(StreamTrim { min_id, approximate, threshold, limit }))
+}
+
+/// How many elements LTRIM keeps of a list of `len` (LRANGE index rules)
+fn range_len(len: i64, start: i64, stop: i64) -> i64 {
+    let start = if start < 0 { (len + start).max(0) } else { start };
+    let stop = if stop < 0 { len + stop } else { stop.min(len - 1) };
+    if start > stop || start >= len {
+        0
+    } else {
+        stop - start + 1
+    }
+}
+
+fn undo_snapshot(client: &mut RedisClient, key: &str, action: &str) -> Result<UndoEntry, String> {
+    let snapshot = client
+        .dump(key)
+        .map_err(|e| format!("Cannot keep an undo snapshot of '{}', so nothing was changed: {}", key, e))?;
+    let (payload, expires_at) = match snapshot {
+        Some((payload, expires_at)) => (Some(payload), expires_at),
+        None => (None, None),
+    };
+    Ok(UndoEntry {
+        at_ms: now_epoch_millis(),
+        db: client.db,
+        key: key.to_string(),
+        action: action.to_string(),
+        payload,
+        expires_at,
+        renamed_to: None,
+    })
+}
+
+/// Longest prefix shared by all keys, cut back to its last ':' if it has one
+fn common_prefix(keys: &[String]) -> String {
+    let Some(first) = keys.first() else {
+        return String::new();
+    };
+    let mut len = first.len();
+    for key in &keys[1..] {
+        len = first.bytes().zip(key.bytes()).take(len).take_while(|(a, b)| a == b).count();
+    }
+    while !first.is_char_boundary(len) {
+        len -= 1;
+    }
+    let prefix = &first[..len];
+    match prefix.rfind(':') {
+        Some(i) => prefix[..=i].to_string(),
+        None => prefix.to_string(),
+    }
+}
+
+/// How many keys share each first segment ("user:"), most common first
+fn prefix_counts(keys: &[String]) -> Vec<(String, usize)> {
+    let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+    for key in keys {
+        let prefix = match key.find(':') {
+            Some(i) => key[..=i].to_string(),
+            None => "(no prefix)".to_string(),
+        };
+        *counts.entry(prefix).or_default() += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// Whether two loads of a key hold the same content; set members may come
+/// back in any order
+fn same_value(a: &RedisValue, b: &RedisValue) -> bool {
+    match (a, b) {
+        (RedisValue::String(a), RedisValue::String(b)) => a == b,
+        (RedisValue::List(a), RedisValue::List(b)) => a == b,
+        (RedisValue::Set(a), RedisValue::Set(b)) => {
+            let (mut a, mut b) = (a.clone(), b.clone());
+            a.sort();
+            b.sort();
+            a == b
+        }
+        (RedisValue::ZSet(a), RedisValue::ZSet(b)) => a == b,
+        (RedisValue::Hash(a), RedisValue::Hash(b)) => a == b,
+        (RedisValue::Json(a), RedisValue::Json(b)) => a == b,
+        (RedisValue::Probabilistic(a), RedisValue::Probabilistic(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Validate a JSON value typed into the edit popup and return it compacted
+fn parse_json_field(text: &str) -> Result<String, String> {
+    serde_json::from_str::<serde_json::Value>(text.trim())
+        .map(|v| v.to_string())
+        .map_err(|e| format!("Invalid JSON (strings need quotes): {}", e))
+}
+
+/// Lay out a table as padded text: the header line followed by one line per
+/// row. Columns are as wide as their widest cell, capped at `max_width`.
+pub fn format_table(columns: &[String], rows: &[Vec<String>], max_width: usize) -> Vec<String> {
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(c, name)| {
+            rows.iter()
+                .map(|r| r[c].chars().count())
+                .chain(std::iter::once(name.chars().count()))
+                .max()
+                .unwrap_or(0)
+                .min(max_width)
+        })
+        .collect();
+    let cell = |text: &str, width: usize| -> String {
+        let text = text.replace('\n', " ");
+        if text.chars().count() > width {
+            format!("{}…", text.chars().take(width.saturating_sub(1)).collect::<String>())
+        } else {
+            format!("{:<width$}", text, width = width)
+        }
+    };
+    std::iter::once(columns)
+        .chain(rows.iter().map(|r| r.as_slice()))
+        .map(|cells| {
+            cells
+                .iter()
+                .zip(&widths)
+                .map(|(text, w)| cell(text, *w))
+                .collect::<Vec<_>>()
+                .join("  ")
+        })
+        .collect()
+}
+
 /// Convert a Redis stream ID (unix_ms-seq) to a human-readable time string.
 /// Format: HH:MM:SS.mmm:seq
 fn format_stream_id(id: &str) -> String {
@@ -1339,20 +5431,14 @@ fn format_stream_id(id: &str) -> String {
     format!("{:02}:{:02}:{:02}.{:03}:{}", hrs, mins, secs, millis, seq)
 }
 
-fn extract_stream_plot_data(
-    entries: &[StreamEntry],
-    data_type: DataType,
-    endianness: Endianness,
-) -> Vec<f64> {
+fn extract_stream_plot_blob(entries: &[StreamEntry]) -> Option<&[u8]> {
     // Only plot the newest (last) entry's waveform
-    if let Some(entry) = entries.last() {
-        for (fname, fval) in &entry.fields {
-            if fname.starts_with('_') {
-                return decode_blob(fval, data_type, endianness);
-            }
-        }
-    }
-    Vec::new()
+    entries
+        .last()?
+        .fields
+        .iter()
+        .find(|(fname, _)| fname.starts_with('_'))
+        .map(|(_, fval)| fval.as_slice())
 }
 
 /// Zoom a range [lo, hi] by factor centered at frac (0..1).
@@ -1420,3 +5506,140 @@ fn compute_fft_magnitude(data: &[f64]) -> Vec<f64> {
         .map(|c| c.norm() * inv_n)
         .collect()
 }
+
+/// A hash field holding an embedding: dimension, norm and a sparkline
+fn format_embedding(field: &str, vector: &[f64]) -> Vec<String> {
+    let min = vector.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = vector.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    vec![
+        format!(
+            "{}  =>  float32[{}]  norm {:.4}  min {:.4}  max {:.4}",
+            field,
+            vector.len(),
+            vector_norm(vector),
+            min,
+            max
+        ),
+        format!("    {}", sparkline(vector, 64)),
+    ]
+}
+
+/// Read a vector for comparison: a FLOAT32 blob, or a JSON array of numbers
+fn load_vector(client: &mut RedisClient, key: &str, field: &str) -> Result<Vec<f64>, String> {
+    let field = (!field.is_empty()).then_some(field);
+    let bytes = client
+        .get_bytes(key, field)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| match field {
+            Some(f) => format!("'{}' has no field '{}'", key, f),
+            None => format!("'{}' not found", key),
+        })?;
+    if is_binary(&bytes) {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(format!("{} bytes is not a FLOAT32 vector", bytes.len()));
+        }
+        return Ok(decode_blob(&bytes, DataType::Float32, Endianness::Little));
+    }
+    serde_json::from_slice::<Vec<f64>>(&bytes)
+        .map_err(|_| format!("'{}' is neither a FLOAT32 blob nor a JSON array", key))
+}
+
+/// Summary of a vector blob: dimension and the first components
+pub fn format_vector(bytes: &[u8], vector: &VectorInfo) -> String {
+    let data_type = match vector.data_type.as_str() {
+        "FLOAT32" => DataType::Float32,
+        "FLOAT64" => DataType::Float64,
+        other => return format!("<{} {} bytes>", other, bytes.len()),
+    };
+    let values = decode_blob(bytes, data_type, Endianness::Little);
+    let head: Vec<String> = values.iter().take(4).map(|v| format!("{:.3}", v)).collect();
+    let more = if values.len() > 4 { ", …" } else { "" };
+    format!("vec[{}] {}{}", values.len(), head.join(", "), more)
+}
+
+/// Split command arguments on whitespace; double quotes group words
+fn split_args(input: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    args.push(std::mem::take(&mut current));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if in_quotes {
+        return Err("Unclosed '\"' in arguments".to_string());
+    }
+    if has_token {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// COPY or MOVE a batch to another db of this server; returns how many
+/// keys arrived
+fn copy_within_server(client: &mut RedisClient, keys: &[String], db: i64, replace: bool, moving: bool) -> anyhow::Result<usize> {
+    if moving && !replace {
+        let moved = client.per_key("MOVE", keys, &[db.to_string()])?;
+        return Ok(moved.iter().filter(|r| **r == 1).count());
+    }
+    let replies = client.copy_many(keys, db, replace)?;
+    let copied: Vec<String> = keys.iter().zip(&replies).filter(|(_, r)| **r == 1).map(|(k, _)| k.clone()).collect();
+    if moving && !copied.is_empty() {
+        client.per_key("UNLINK", &copied, &[])?;
+    }
+    Ok(copied.len())
+}
+
+/// DUMP a batch and RESTORE it on another server with the remaining TTLs;
+/// returns how many keys arrived and the errors other than BUSYKEY
+fn copy_to_server(
+    client: &mut RedisClient,
+    target: &mut RedisClient,
+    keys: &[String],
+    replace: bool,
+    moving: bool,
+) -> anyhow::Result<(usize, Vec<String>)> {
+    let dumps = client.dump_many(keys)?;
+    let results = target.restore_many(&dumps, replace)?;
+    let mut restored = Vec::new();
+    let mut errors = Vec::new();
+    for (dump, result) in dumps.into_iter().zip(results) {
+        match result {
+            Ok(()) => restored.push(dump.key),
+            // Already there and not replacing: left alone
+            Err(e) if e.contains("BUSYKEY") => {}
+            Err(e) => errors.push(format!("'{}': {}", dump.key, e)),
+        }
+    }
+    if moving && !restored.is_empty() {
+        client.per_key("UNLINK", &restored, &[])?;
+    }
+    Ok((restored.len(), errors))
+}
+
+/// Escape glob metacharacters so a key name matches only itself in SCAN
+fn escape_glob(key: &str) -> String {
+    let mut out = String::new();
+    for c in key.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
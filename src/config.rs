@@ -0,0 +1,561 @@
+use crate::data::{DataType, Endianness, SampleScaling};
+use crate::formats::ValueFormat;
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// Directory holding redis-tui's config files.
+/// `$REDIS_TUI_CONFIG_DIR`, else `$XDG_CONFIG_HOME/redis-tui`, else `~/.config/redis-tui`.
+pub fn config_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("REDIS_TUI_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(dir) = std::env::var("XDG_CONFIG_HOME") {
+        if !dir.is_empty() {
+            return PathBuf::from(dir).join("redis-tui");
+        }
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".config").join("redis-tui")
+}
+
+/// Record layout settings stored in a profile
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutProfile {
+    pub definition: String,
+    pub header_size: usize,
+    pub packed: bool,
+    /// Member to plot, by name
+    pub field: String,
+}
+
+/// Decode settings applied automatically to keys matching `pattern`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodeProfile {
+    /// Exact key name or glob pattern (`*`, `?`, `[abc]`)
+    pub pattern: String,
+    pub data_type: DataType,
+    pub endianness: Endianness,
+    pub layout: Option<LayoutProfile>,
+    pub scaling: SampleScaling,
+    /// Manual Y limits for the signal plot (None = auto)
+    pub plot_y_limits: Option<(f64, f64)>,
+    pub fft_enabled: bool,
+    pub fft_log_scale: bool,
+    /// Value formatter and per-field overrides
+    pub value_format: ValueFormat,
+    pub field_formats: Vec<(String, ValueFormat)>,
+    /// Protobuf schema path and message type (empty = none)
+    pub proto_schema: String,
+    pub proto_message: String,
+}
+
+impl DecodeProfile {
+    pub fn matches(&self, key: &str) -> bool {
+        self.pattern == key || glob_match(&self.pattern, key)
+    }
+
+    /// One-line description for the profile manager popup
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{} {}", self.data_type, self.endianness)];
+        if let Some(layout) = &self.layout {
+            parts.push(format!("struct {{{}}}", layout.definition));
+            if !layout.field.is_empty() {
+                parts.push(format!("plot {}", layout.field));
+            }
+        }
+        if !self.scaling.is_identity() {
+            parts.push("scaled".to_string());
+        }
+        if let Some((lo, hi)) = self.plot_y_limits {
+            parts.push(format!("y {}..{}", lo, hi));
+        }
+        if self.fft_enabled {
+            parts.push(if self.fft_log_scale { "FFT log" } else { "FFT" }.to_string());
+        }
+        if self.value_format != ValueFormat::Auto || !self.field_formats.is_empty() {
+            let mut formats = vec![self.value_format.to_string()];
+            formats.extend(self.field_formats.iter().map(|(f, fmt)| format!("{}={}", f, fmt)));
+            parts.push(format!("format {}", formats.join(" ")));
+        }
+        parts.join(", ")
+    }
+
+    fn to_json(&self) -> Value {
+        let sc = &self.scaling;
+        let mut v = json!({
+            "pattern": self.pattern,
+            "data_type": self.data_type.to_string(),
+            "endianness": self.endianness.to_string(),
+            "scaling": {
+                "byte_offset": sc.byte_offset,
+                "max_samples": sc.max_samples,
+                "decimation": sc.decimation,
+                "channels": sc.channels,
+                "channel": sc.channel,
+                "gain": sc.gain,
+                "offset": sc.offset,
+                "unit": sc.unit,
+            },
+            "fft": self.fft_enabled,
+            "fft_log": self.fft_log_scale,
+        });
+        if let Some(layout) = &self.layout {
+            v["layout"] = json!({
+                "definition": layout.definition,
+                "header_size": layout.header_size,
+                "packed": layout.packed,
+                "field": layout.field,
+            });
+        }
+        if let Some((lo, hi)) = self.plot_y_limits {
+            v["plot_y_limits"] = json!([lo, hi]);
+        }
+        if self.value_format != ValueFormat::Auto {
+            v["format"] = json!(self.value_format.to_string());
+        }
+        if !self.field_formats.is_empty() {
+            v["field_formats"] = Value::Array(
+                self.field_formats
+                    .iter()
+                    .map(|(field, fmt)| json!({"field": field, "format": fmt.to_string()}))
+                    .collect(),
+            );
+        }
+        if !self.proto_schema.is_empty() {
+            v["proto_schema"] = json!(self.proto_schema);
+        }
+        if !self.proto_message.is_empty() {
+            v["proto_message"] = json!(self.proto_message);
+        }
+        v
+    }
+
+    /// Parse a stored profile; the error names what could not be read
+    fn from_json(v: &Value) -> Result<DecodeProfile, String> {
+        let pattern = v
+            .get("pattern")
+            .and_then(|p| p.as_str())
+            .ok_or("missing \"pattern\"")?
+            .to_string();
+        let err = |what: String| format!("'{}': {}", pattern, what);
+        let type_name = v
+            .get("data_type")
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| err("missing \"data_type\"".to_string()))?;
+        let data_type =
+            DataType::from_name(type_name).ok_or_else(|| err(format!("unknown data type '{}'", type_name)))?;
+        let endianness = match v.get("endianness").and_then(|e| e.as_str()) {
+            Some(name) => Endianness::from_name(name).ok_or_else(|| err(format!("unknown endianness '{}'", name)))?,
+            None => Endianness::Little,
+        };
+        let format = |name: Option<&str>| -> Result<ValueFormat, String> {
+            match name {
+                Some(name) => ValueFormat::from_name(name).ok_or_else(|| err(format!("unknown format '{}'", name))),
+                None => Ok(ValueFormat::Auto),
+            }
+        };
+        let layout = match v.get("layout") {
+            Some(l) => Some(LayoutProfile {
+                definition: l
+                    .get("definition")
+                    .and_then(|d| d.as_str())
+                    .ok_or_else(|| err("layout without a \"definition\"".to_string()))?
+                    .to_string(),
+                header_size: l.get("header_size").and_then(|h| h.as_u64()).unwrap_or(0) as usize,
+                packed: l.get("packed").and_then(|p| p.as_bool()).unwrap_or(false),
+                field: l.get("field").and_then(|f| f.as_str()).unwrap_or("").to_string(),
+            }),
+            None => None,
+        };
+        let mut scaling = SampleScaling::default();
+        if let Some(sc) = v.get("scaling") {
+            let get_usize = |k: &str| sc.get(k).and_then(|x| x.as_u64()).map(|x| x as usize);
+            let get_f64 = |k: &str| sc.get(k).and_then(|x| x.as_f64());
+            scaling.byte_offset = get_usize("byte_offset").unwrap_or(0);
+            scaling.max_samples = get_usize("max_samples").unwrap_or(0);
+            scaling.decimation = get_usize("decimation").unwrap_or(1).max(1);
+            scaling.channels = get_usize("channels").unwrap_or(1).max(1);
+            scaling.channel = get_usize("channel").unwrap_or(0);
+            scaling.gain = get_f64("gain").unwrap_or(1.0);
+            scaling.offset = get_f64("offset").unwrap_or(0.0);
+            scaling.unit = sc.get("unit").and_then(|u| u.as_str()).unwrap_or("").to_string();
+            if scaling.channel >= scaling.channels {
+                return Err(err(format!("channel {} of {}", scaling.channel, scaling.channels)));
+            }
+        }
+        let plot_y_limits = v.get("plot_y_limits").and_then(|l| {
+            let arr = l.as_array()?;
+            Some((arr.first()?.as_f64()?, arr.get(1)?.as_f64()?))
+        });
+        let mut field_formats = Vec::new();
+        for e in v.get("field_formats").and_then(|f| f.as_array()).into_iter().flatten() {
+            let field = e
+                .get("field")
+                .and_then(|f| f.as_str())
+                .ok_or_else(|| err("field format without a \"field\"".to_string()))?;
+            field_formats.push((field.to_string(), format(e.get("format").and_then(|f| f.as_str()))?));
+        }
+        let get_str = |k: &str| v.get(k).and_then(|s| s.as_str()).unwrap_or("").to_string();
+        Ok(DecodeProfile {
+            data_type,
+            endianness,
+            layout,
+            scaling,
+            plot_y_limits,
+            fft_enabled: v.get("fft").and_then(|f| f.as_bool()).unwrap_or(false),
+            fft_log_scale: v.get("fft_log").and_then(|f| f.as_bool()).unwrap_or(false),
+            value_format: format(v.get("format").and_then(|f| f.as_str()))?,
+            field_formats,
+            proto_schema: get_str("proto_schema"),
+            proto_message: get_str("proto_message"),
+            pattern,
+        })
+    }
+}
+
+/// Decode profiles persisted as JSON in the config directory
+pub struct ProfileStore {
+    pub path: PathBuf,
+    pub profiles: Vec<DecodeProfile>,
+    /// Stored entries that could not be read, with the reason; written back as-is
+    pub unreadable: Vec<(Value, String)>,
+}
+
+impl ProfileStore {
+    /// A store with no profiles, saved to the default location
+    pub fn empty() -> Self {
+        Self {
+            path: config_dir().join("profiles.json"),
+            profiles: Vec::new(),
+            unreadable: Vec::new(),
+        }
+    }
+
+    /// Load profiles from `profiles.json`; a missing file gives an empty store
+    pub fn load() -> Result<Self> {
+        let path = config_dir().join("profiles.json");
+        let mut profiles = Vec::new();
+        let mut unreadable = Vec::new();
+        if path.exists() {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let root: Value = serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            if let Some(list) = root.get("profiles").and_then(|p| p.as_array()) {
+                for entry in list {
+                    match DecodeProfile::from_json(entry) {
+                        Ok(profile) => profiles.push(profile),
+                        Err(e) => unreadable.push((entry.clone(), e)),
+                    }
+                }
+            }
+        }
+        Ok(Self { path, profiles, unreadable })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let root = json!({
+            "profiles": self
+                .profiles
+                .iter()
+                .map(|p| p.to_json())
+                .chain(self.unreadable.iter().map(|(v, _)| v.clone()))
+                .collect::<Vec<_>>(),
+        });
+        let text = serde_json::to_string_pretty(&root)?;
+        std::fs::write(&self.path, text)
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Find the profile for a key: an exact match wins, then the first matching glob
+    pub fn find(&self, key: &str) -> Option<&DecodeProfile> {
+        self.profiles
+            .iter()
+            .find(|p| p.pattern == key)
+            .or_else(|| self.profiles.iter().find(|p| p.matches(key)))
+    }
+
+    /// Insert a profile, replacing any existing one with the same pattern
+    pub fn upsert(&mut self, profile: DecodeProfile) {
+        match self.profiles.iter_mut().find(|p| p.pattern == profile.pattern) {
+            Some(existing) => *existing = profile,
+            None => self.profiles.push(profile),
+        }
+    }
+}
+
+/// A server keys can be copied to, by name
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedServer {
+    pub name: String,
+    pub url: String,
+}
+
+/// Servers persisted as JSON in the config directory
+pub struct ServerStore {
+    pub path: PathBuf,
+    pub servers: Vec<SavedServer>,
+}
+
+impl ServerStore {
+    /// Load servers from `servers.json`; a missing file gives an empty store
+    pub fn load() -> Result<Self> {
+        let path = config_dir().join("servers.json");
+        let mut servers = Vec::new();
+        if path.exists() {
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let root: Value = serde_json::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            if let Some(list) = root.get("servers").and_then(|s| s.as_array()) {
+                servers = list
+                    .iter()
+                    .filter_map(|s| {
+                        Some(SavedServer {
+                            name: s.get("name")?.as_str()?.to_string(),
+                            url: s.get("url")?.as_str()?.to_string(),
+                        })
+                    })
+                    .collect();
+            }
+        }
+        Ok(Self { path, servers })
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let root = json!({
+            "servers": self.servers.iter().map(|s| json!({"name": s.name, "url": s.url})).collect::<Vec<_>>(),
+        });
+        let text = serde_json::to_string_pretty(&root)?;
+        // URLs may hold passwords, so only the user can read the file
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let write = || -> std::io::Result<()> {
+            let mut file = options.open(&self.path)?;
+            #[cfg(unix)]
+            file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+            file.write_all(text.as_bytes())
+        };
+        write().with_context(|| format!("Failed to write {}", self.path.display()))?;
+        Ok(())
+    }
+
+    pub fn find(&self, name: &str) -> Option<&SavedServer> {
+        self.servers.iter().find(|s| s.name == name)
+    }
+
+    /// Insert a server, replacing any existing one with the same name
+    pub fn upsert(&mut self, server: SavedServer) {
+        match self.servers.iter_mut().find(|s| s.name == server.name) {
+            Some(existing) => *existing = server,
+            None => self.servers.push(server),
+        }
+    }
+}
+
+/// `host:port` of a Redis URL, without scheme, password or db
+pub fn server_label(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+    rest.split('/').next().unwrap_or(rest).to_string()
+}
+
+/// Match a Redis-style glob pattern: `*`, `?`, `[abc]`, `[^a-z]` and `\` escapes
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    glob_match_at(&p, &t)
+}
+
+fn glob_match_at(p: &[char], t: &[char]) -> bool {
+    let (mut pi, mut ti) = (0, 0);
+    // Backtracking point for the last `*`
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() {
+            match p[pi] {
+                '*' => {
+                    star = Some((pi, ti));
+                    pi += 1;
+                    continue;
+                }
+                '?' => {
+                    pi += 1;
+                    ti += 1;
+                    continue;
+                }
+                '[' => {
+                    if let Some((matched, next)) = match_class(p, pi, t[ti]) {
+                        if matched {
+                            pi = next;
+                            ti += 1;
+                            continue;
+                        }
+                    }
+                }
+                '\\' if pi + 1 < p.len() => {
+                    if p[pi + 1] == t[ti] {
+                        pi += 2;
+                        ti += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == t[ti] {
+                        pi += 1;
+                        ti += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        match star {
+            Some((sp, st)) => {
+                pi = sp + 1;
+                ti = st + 1;
+                star = Some((sp, st + 1));
+            }
+            None => return false,
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Match `c` against the `[...]` class starting at `p[start]`.
+/// Returns (matched, index after the class), or None if the class is unclosed.
+fn match_class(p: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negate = i < p.len() && (p[i] == '^' || p[i] == '!');
+    if negate {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    while i < p.len() && (p[i] != ']' || first) {
+        first = false;
+        if i + 2 < p.len() && p[i + 1] == '-' && p[i + 2] != ']' {
+            if p[i] <= c && c <= p[i + 2] {
+                matched = true;
+            }
+            i += 3;
+        } else {
+            if p[i] == c {
+                matched = true;
+            }
+            i += 1;
+        }
+    }
+    if i >= p.len() {
+        return None;
+    }
+    Some((matched != negate, i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("blob:*", "blob:float32_1k"));
+        assert!(glob_match("*_1k", "blob:float32_1k"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(!glob_match("a*b*c", "axxbyy"));
+        assert!(glob_match("h?llo", "hello"));
+        assert!(!glob_match("h?llo", "hllo"));
+        assert!(glob_match("h[ae]llo", "hallo"));
+        assert!(!glob_match("h[ae]llo", "hillo"));
+        assert!(glob_match("h[^e]llo", "hallo"));
+        assert!(!glob_match("h[^e]llo", "hello"));
+        assert!(glob_match("key[0-9]", "key7"));
+        assert!(!glob_match("key[0-9]", "keyx"));
+        assert!(glob_match("a\\*", "a*"));
+        assert!(!glob_match("a\\*", "ab"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+        // An unclosed class matches nothing rather than panicking
+        assert!(!glob_match("a[bc", "ab"));
+    }
+
+    #[test]
+    fn profile_round_trip() {
+        let profile = DecodeProfile {
+            pattern: "blob:*".to_string(),
+            data_type: DataType::Int16,
+            endianness: Endianness::Big,
+            layout: Some(LayoutProfile {
+                definition: "u32 ts; i16 x".to_string(),
+                header_size: 8,
+                packed: true,
+                field: "x".to_string(),
+            }),
+            scaling: SampleScaling {
+                channels: 4,
+                channel: 2,
+                gain: 0.5,
+                unit: "mV".to_string(),
+                ..SampleScaling::default()
+            },
+            plot_y_limits: Some((-1.0, 1.0)),
+            fft_enabled: true,
+            fft_log_scale: true,
+            value_format: ValueFormat::Auto,
+            field_formats: Vec::new(),
+            proto_schema: String::new(),
+            proto_message: String::new(),
+        };
+        assert_eq!(DecodeProfile::from_json(&profile.to_json()), Ok(profile));
+    }
+
+    #[test]
+    fn unreadable_profiles_are_reported_and_kept() {
+        let bad = json!({"pattern": "k", "data_type": "int24"});
+        assert_eq!(DecodeProfile::from_json(&bad).unwrap_err(), "'k': unknown data type 'int24'");
+        let channel = json!({"pattern": "k", "data_type": "uint8", "scaling": {"channels": 2, "channel": 2}});
+        assert!(DecodeProfile::from_json(&channel).is_err());
+        assert!(DecodeProfile::from_json(&json!({"data_type": "uint8"})).is_err());
+
+        let dir = std::env::temp_dir().join(format!("redis-tui-profiles-{}", std::process::id()));
+        let store = ProfileStore {
+            path: dir.join("profiles.json"),
+            profiles: Vec::new(),
+            unreadable: vec![(bad.clone(), String::new())],
+        };
+        store.save().unwrap();
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&store.path).unwrap()).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(saved["profiles"], json!([bad]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn servers_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("redis-tui-servers-{}", std::process::id()));
+        let path = dir.join("servers.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let server = SavedServer { name: "prod".to_string(), url: "redis://:secret@db:6379".to_string() };
+        let store = ServerStore { path: path.clone(), servers: vec![server] };
+        store.save().unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(saved.contains("redis://:secret@db:6379"));
+    }
+}
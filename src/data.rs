@@ -15,7 +15,6 @@ pub enum DataType {
 }
 
 impl DataType {
-    #[allow(dead_code)]
     pub fn byte_size(&self) -> Option<usize> {
         match self {
             DataType::Int8 | DataType::UInt8 => Some(1),
@@ -56,6 +55,11 @@ impl DataType {
             all[idx - 1]
         }
     }
+
+    /// Parse a data type from its display name (e.g. `float32`)
+    pub fn from_name(name: &str) -> Option<DataType> {
+        DataType::all().iter().copied().find(|t| t.to_string() == name)
+    }
 }
 
 impl fmt::Display for DataType {
@@ -88,6 +92,15 @@ impl Endianness {
             Endianness::Big => Endianness::Little,
         }
     }
+
+    /// Parse `LE`/`BE` as produced by Display
+    pub fn from_name(name: &str) -> Option<Endianness> {
+        match name {
+            "LE" | "le" | "little" => Some(Endianness::Little),
+            "BE" | "be" | "big" => Some(Endianness::Big),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Endianness {
@@ -160,14 +173,79 @@ fn decode_chunks(bytes: &[u8], chunk_size: usize, f: impl Fn(&[u8]) -> f64) -> V
         .collect()
 }
 
+/// Sample selection and linear scaling applied around blob decoding.
+/// Raw counts become physical units via `value * gain + offset`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleScaling {
+    /// Bytes to skip at the start of the blob (e.g. a header)
+    pub byte_offset: usize,
+    /// Maximum number of decoded samples to use (0 = all)
+    pub max_samples: usize,
+    /// Keep every Nth sample (1 = all)
+    pub decimation: usize,
+    /// Number of interleaved channels in the decoded samples (1 = one channel)
+    pub channels: usize,
+    /// Channel to keep when `channels > 1`, counted from 0
+    pub channel: usize,
+    pub gain: f64,
+    pub offset: f64,
+    /// Unit label shown on the plot axis and next to decoded values
+    pub unit: String,
+}
+
+impl Default for SampleScaling {
+    fn default() -> Self {
+        Self {
+            byte_offset: 0,
+            max_samples: 0,
+            decimation: 1,
+            channels: 1,
+            channel: 0,
+            gain: 1.0,
+            offset: 0.0,
+            unit: String::new(),
+        }
+    }
+}
+
+impl SampleScaling {
+    pub fn is_identity(&self) -> bool {
+        *self == SampleScaling::default()
+    }
+
+    /// True if gain/offset change the decoded values
+    pub fn is_scaled(&self) -> bool {
+        self.gain != 1.0 || self.offset != 0.0
+    }
+
+    /// The part of the blob after the configured byte offset
+    pub fn skip_header<'a>(&self, bytes: &'a [u8]) -> &'a [u8] {
+        bytes.get(self.byte_offset..).unwrap_or(&[])
+    }
+
+    /// Pick one channel out of interleaved samples, then limit, decimate and
+    /// scale them. `max_samples` counts samples of the selected channel.
+    pub fn apply(&self, values: Vec<f64>) -> Vec<f64> {
+        let limit = if self.max_samples > 0 { self.max_samples } else { usize::MAX };
+        values
+            .into_iter()
+            .skip(self.channel)
+            .step_by(self.channels.max(1))
+            .take(limit)
+            .step_by(self.decimation.max(1))
+            .map(|v| v * self.gain + self.offset)
+            .collect()
+    }
+}
+
 /// Format raw bytes as a human-readable string according to data type
-#[allow(dead_code)]
-pub fn format_blob(bytes: &[u8], data_type: DataType, endianness: Endianness) -> String {
+pub fn format_blob(bytes: &[u8], data_type: DataType, endianness: Endianness, scaling: &SampleScaling) -> String {
+    let bytes = scaling.skip_header(bytes);
     match data_type {
         DataType::Blob => format_hex(bytes),
         DataType::String => String::from_utf8_lossy(bytes).to_string(),
         _ => {
-            let values = decode_blob(bytes, data_type, endianness);
+            let values = scaling.apply(decode_blob(bytes, data_type, endianness));
             if values.is_empty() {
                 return "(no complete values)".to_string();
             }
@@ -175,12 +253,17 @@ pub fn format_blob(bytes: &[u8], data_type: DataType, endianness: Endianness) ->
                 .iter()
                 .map(|v| {
                     match data_type {
+                        _ if scaling.is_scaled() => format!("{:.6}", v),
                         DataType::Float32 | DataType::Float64 => format!("{:.6}", v),
                         _ => format!("{}", *v as i64),
                     }
                 })
                 .collect();
-            formatted.join(", ")
+            if scaling.unit.is_empty() {
+                formatted.join(", ")
+            } else {
+                format!("{} ({})", formatted.join(", "), scaling.unit)
+            }
         }
     }
 }
@@ -220,6 +303,200 @@ pub fn format_hex(bytes: &[u8]) -> String {
     result
 }
 
+/// Bits shown per row of the bitmap grid
+pub const BITMAP_ROW_BITS: usize = 32;
+
+/// Format bytes as a grid of bits, most significant bit first like `SETBIT`/`GETBIT`.
+/// Each row starts with the bit offset and ends with the hex bytes it covers.
+pub fn format_bit_grid(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(BITMAP_ROW_BITS / 8)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let mut line = format!("{:>8}  ", row * BITMAP_ROW_BITS);
+            for (i, byte) in chunk.iter().enumerate() {
+                if i > 0 {
+                    line.push(' ');
+                }
+                for bit in (0..8).rev() {
+                    line.push(if byte >> bit & 1 == 1 { '█' } else { '·' });
+                }
+            }
+            let missing = BITMAP_ROW_BITS / 8 - chunk.len();
+            line.push_str(&" ".repeat(missing * 9));
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            line.push_str(&format!("  {}", hex.join(" ")));
+            line
+        })
+        .collect()
+}
+
+/// Character column of a bit inside its `format_bit_grid` row
+pub fn bit_grid_column(offset: u64) -> usize {
+    let bit = offset as usize % BITMAP_ROW_BITS;
+    10 + bit + bit / 8
+}
+
+/// Value of the bit at `offset` (0 past the end of the string, like `GETBIT`)
+pub fn get_bit(bytes: &[u8], offset: u64) -> bool {
+    bytes
+        .get((offset / 8) as usize)
+        .is_some_and(|b| b >> (7 - offset % 8) & 1 == 1)
+}
+
+/// Number of set bits, as returned by `BITCOUNT`
+pub fn bit_count(bytes: &[u8]) -> u64 {
+    bytes.iter().map(|b| b.count_ones() as u64).sum()
+}
+
+/// Offset of the first bit equal to `bit`, as returned by `BITPOS` without a range
+pub fn bit_pos(bytes: &[u8], bit: bool) -> Option<u64> {
+    bytes
+        .iter()
+        .position(|&b| if bit { b != 0 } else { b != 0xff })
+        .map(|i| {
+            let b = if bit { bytes[i] } else { !bytes[i] };
+            i as u64 * 8 + b.leading_zeros() as u64
+        })
+}
+
+/// A `BITFIELD GET` style integer: `i5`, `u16`, ... at a bit offset
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitField {
+    pub signed: bool,
+    pub width: u32,
+    /// Bit offset of the first value
+    pub offset: u64,
+    /// Number of consecutive values
+    pub count: usize,
+}
+
+impl BitField {
+    /// Parse a list of fields such as `u8 0; i5 100; u12 #2 x16`.
+    ///
+    /// Fields are separated by `;` or commas. Types follow `BITFIELD`: `i1`..`i64`
+    /// and `u1`..`u63`. Offsets prefixed with `#` are multiplied by the width, and
+    /// an `xN` suffix reads N consecutive values.
+    pub fn parse_list(spec: &str) -> Result<Vec<BitField>, String> {
+        spec.split([';', ','])
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(BitField::parse)
+            .collect()
+    }
+
+    fn parse(spec: &str) -> Result<BitField, String> {
+        let tokens: Vec<&str> = spec.split_whitespace().collect();
+        let (ty, offset, count) = match tokens.as_slice() {
+            [ty, offset] => (*ty, *offset, "x1"),
+            [ty, offset, count] => (*ty, *offset, *count),
+            _ => return Err(format!("Expected '<type> <offset> [xN]' in '{}'", spec)),
+        };
+        let signed = match ty.chars().next() {
+            Some('i') | Some('I') => true,
+            Some('u') | Some('U') => false,
+            _ => return Err(format!("Unknown bitfield type '{}'", ty)),
+        };
+        let width: u32 = ty[1..].parse().map_err(|_| format!("Unknown bitfield type '{}'", ty))?;
+        let max = if signed { 64 } else { 63 };
+        if width == 0 || width > max {
+            return Err(format!("'{}' must be 1..{} bits wide", ty, max));
+        }
+        let offset = match offset.strip_prefix('#') {
+            Some(n) => n.parse::<u64>().ok().and_then(|n| n.checked_mul(width as u64)),
+            None => offset.parse::<u64>().ok(),
+        }
+        .ok_or_else(|| format!("Invalid bit offset '{}'", offset))?;
+        let count = count
+            .strip_prefix(['x', 'X'])
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|&n| n > 0)
+            .ok_or_else(|| format!("Invalid repeat count '{}'", count))?;
+        Ok(BitField { signed, width, offset, count })
+    }
+
+    /// Type name as written in `BITFIELD`, e.g. `i5`
+    pub fn type_name(&self) -> String {
+        format!("{}{}", if self.signed { 'i' } else { 'u' }, self.width)
+    }
+
+    /// Decode the values of this field that start within the string
+    pub fn decode(&self, bytes: &[u8]) -> Vec<i64> {
+        let bits = bytes.len() as u64 * 8;
+        let available = bits.saturating_sub(self.offset).div_ceil(self.width as u64);
+        (0..(self.count as u64).min(available))
+            .map(|i| read_bits(bytes, self.offset + i * self.width as u64, self.width, self.signed))
+            .collect()
+    }
+}
+
+/// Read a `width`-bit integer at a bit offset, most significant bit first.
+/// Bits past the end of the string read as 0, like `BITFIELD GET`.
+pub fn read_bits(bytes: &[u8], offset: u64, width: u32, signed: bool) -> i64 {
+    let raw = (0..width as u64).fold(0u64, |acc, i| acc << 1 | get_bit(bytes, offset + i) as u64);
+    if signed && width < 64 && raw >> (width - 1) & 1 == 1 {
+        (raw | (u64::MAX << width)) as i64
+    } else {
+        raw as i64
+    }
+}
+
+/// Smallest dimension treated as an embedding
+const MIN_EMBEDDING_DIM: usize = 8;
+
+/// Decode a binary blob as a little-endian FLOAT32 embedding (the layout used by
+/// RediSearch vector fields) if it plausibly is one: every component finite and
+/// of moderate magnitude, which random or integer data almost never is.
+pub fn detect_embedding(bytes: &[u8]) -> Option<Vec<f64>> {
+    if !is_binary(bytes) || !bytes.len().is_multiple_of(4) || bytes.len() / 4 < MIN_EMBEDDING_DIM {
+        return None;
+    }
+    let values = decode_blob(bytes, DataType::Float32, Endianness::Little);
+    let plausible = values
+        .iter()
+        .filter(|v| **v == 0.0 || (1e-6..=1e4).contains(&v.abs()))
+        .count();
+    let all_finite = values.iter().all(|v| v.is_finite());
+    let any_nonzero = values.iter().any(|v| *v != 0.0);
+    (all_finite && any_nonzero && plausible * 10 >= values.len() * 9).then_some(values)
+}
+
+/// Euclidean (L2) norm of a vector
+pub fn vector_norm(values: &[f64]) -> f64 {
+    values.iter().map(|v| v * v).sum::<f64>().sqrt()
+}
+
+/// Cosine similarity of two vectors of the same dimension
+pub fn cosine_similarity(a: &[f64], b: &[f64]) -> Option<f64> {
+    let denom = vector_norm(a) * vector_norm(b);
+    if a.len() != b.len() || denom == 0.0 {
+        return None;
+    }
+    Some(a.iter().zip(b).map(|(x, y)| x * y).sum::<f64>() / denom)
+}
+
+/// One-line sparkline of at most `width` cells; longer inputs are averaged per cell
+pub fn sparkline(values: &[f64], width: usize) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let cells = values.len().min(width);
+    let means: Vec<f64> = (0..cells)
+        .map(|i| {
+            let chunk = &values[i * values.len() / cells..(i + 1) * values.len() / cells];
+            chunk.iter().sum::<f64>() / chunk.len() as f64
+        })
+        .collect();
+    let min = means.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = means.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let span = if max > min { max - min } else { 1.0 };
+    means
+        .iter()
+        .map(|m| LEVELS[(((m - min) / span) * 7.0).round() as usize])
+        .collect()
+}
+
 /// Encode a string of comma/space-separated numeric values into binary bytes.
 /// Supports ints and floats depending on the target DataType.
 pub fn encode_values(input: &str, data_type: DataType, endianness: Endianness) -> Result<Vec<u8>, String> {
@@ -300,3 +577,553 @@ pub fn encode_values(input: &str, data_type: DataType, endianness: Endianness) -
 pub fn is_binary(bytes: &[u8]) -> bool {
     bytes.iter().any(|&b| b < 0x20 && b != b'\n' && b != b'\r' && b != b'\t')
 }
+
+/// A single member of a record layout
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordField {
+    pub name: String,
+    pub data_type: DataType,
+    /// Byte offset of the member inside one record
+    pub offset: usize,
+    /// Size of the member in bytes (fixed-size char arrays use DataType::String)
+    pub size: usize,
+}
+
+impl RecordField {
+    /// True if the member holds a numeric value that can be plotted
+    pub fn is_numeric(&self) -> bool {
+        self.data_type.byte_size().is_some()
+    }
+}
+
+/// Layout of an array of fixed-size C structs, optionally preceded by a header
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordLayout {
+    pub fields: Vec<RecordField>,
+    /// Bytes to skip before the first record
+    pub header_size: usize,
+    /// Size of one record including trailing padding
+    pub record_size: usize,
+    /// Packed layouts ignore natural alignment
+    pub packed: bool,
+}
+
+/// Most members a layout may expand to (arrays count once per element)
+const MAX_RECORD_MEMBERS: usize = 4096;
+
+/// Largest record a layout may describe, padding included
+const MAX_RECORD_SIZE: usize = 1 << 20;
+
+impl RecordLayout {
+    /// Parse a layout definition such as `u32 ts; i16 x; i16 y; f32 v`.
+    ///
+    /// Members are separated by `;` or newlines and written as `<type> <name>`
+    /// with an optional `[N]` array suffix. C type names (`uint16_t`, `float`,
+    /// `char name[16]`, ...) are accepted, as is a surrounding `struct { ... }`.
+    /// `pad N` inserts explicit padding, and the directives `header N` and
+    /// `packed` override the arguments. Without `packed`, members are aligned
+    /// to their natural size like a C compiler would.
+    pub fn parse(definition: &str, header_size: usize, packed: bool) -> Result<RecordLayout, String> {
+        let mut header_size = header_size;
+        let mut packed = packed;
+        let mut members: Vec<(String, DataType, usize)> = Vec::new(); // (name, type, size)
+
+        // Strip comments and a surrounding `struct name { ... }` wrapper
+        let cleaned: String = definition
+            .lines()
+            .map(|l| l.split("//").next().unwrap_or("").split('#').next().unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join(";");
+        let body = match (cleaned.find('{'), cleaned.rfind('}')) {
+            (Some(open), Some(close)) if open < close => cleaned[open + 1..close].to_string(),
+            _ => cleaned,
+        };
+
+        for stmt in body.split(';').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+            let tokens: Vec<&str> = stmt.split_whitespace().collect();
+            match tokens.as_slice() {
+                ["packed"] => packed = true,
+                ["aligned"] => packed = false,
+                ["header", n] => {
+                    header_size = n.parse().map_err(|_| format!("Invalid header size '{}'", n))?;
+                }
+                ["pad", n] => {
+                    let n: usize = n.parse().map_err(|_| format!("Invalid pad size '{}'", n))?;
+                    members.push((String::new(), DataType::Blob, n));
+                }
+                _ => {
+                    // Allow `header=16` without spaces
+                    if let Some(n) = stmt.strip_prefix("header=") {
+                        header_size = n.trim().parse().map_err(|_| format!("Invalid header size '{}'", n))?;
+                        continue;
+                    }
+                    let (type_tokens, decl) = tokens.split_at(tokens.len().saturating_sub(1));
+                    if type_tokens.is_empty() {
+                        return Err(format!("Expected '<type> <name>' in '{}'", stmt));
+                    }
+                    let type_name = type_tokens.join(" ");
+                    let decl = decl[0];
+                    let (name, count) = match decl.find('[') {
+                        Some(open) => {
+                            let close = decl.rfind(']').ok_or_else(|| format!("Unclosed '[' in '{}'", decl))?;
+                            let n: usize = decl[open + 1..close]
+                                .parse()
+                                .map_err(|_| format!("Invalid array length in '{}'", decl))?;
+                            (&decl[..open], n.max(1))
+                        }
+                        None => (decl, 1),
+                    };
+                    let data_type = parse_c_type(&type_name)
+                        .ok_or_else(|| format!("Unknown type '{}'", type_name))?;
+                    if data_type == DataType::String {
+                        // char arrays are a single fixed-size text member
+                        members.push((name.to_string(), DataType::String, count));
+                    } else if count == 1 {
+                        let size = data_type.byte_size().unwrap_or(1);
+                        members.push((name.to_string(), data_type, size));
+                    } else {
+                        if members.len().saturating_add(count) > MAX_RECORD_MEMBERS {
+                            return Err(format!("'{}' makes more than {} members", decl, MAX_RECORD_MEMBERS));
+                        }
+                        let size = data_type.byte_size().unwrap_or(1);
+                        for i in 0..count {
+                            members.push((format!("{}[{}]", name, i), data_type, size));
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut fields = Vec::new();
+        let mut offset = 0usize;
+        let mut max_align = 1usize;
+        let too_large = || format!("Records are larger than {} bytes", MAX_RECORD_SIZE);
+        for (name, data_type, size) in members {
+            let end = |start: usize| start.checked_add(size).filter(|end| *end <= MAX_RECORD_SIZE).ok_or_else(too_large);
+            if data_type == DataType::Blob {
+                // explicit padding
+                offset = end(offset)?;
+                continue;
+            }
+            let align = if packed { 1 } else { data_type.byte_size().unwrap_or(1) };
+            max_align = max_align.max(align);
+            offset = offset.div_ceil(align) * align;
+            fields.push(RecordField { name, data_type, offset, size });
+            offset = end(offset)?;
+        }
+        if fields.is_empty() {
+            return Err("Layout has no members".to_string());
+        }
+        let record_size = offset.div_ceil(max_align) * max_align;
+
+        Ok(RecordLayout {
+            fields,
+            header_size,
+            record_size,
+            packed,
+        })
+    }
+
+    /// Index of the first numeric member at or after `idx` (wrapping)
+    pub fn next_numeric_field(&self, idx: usize, forward: bool) -> usize {
+        let n = self.fields.len();
+        for step in 1..=n {
+            let i = if forward {
+                (idx + step) % n
+            } else {
+                (idx + n * step - step) % n
+            };
+            if self.fields[i].is_numeric() {
+                return i;
+            }
+        }
+        idx
+    }
+
+    /// Number of complete records in a blob
+    pub fn record_count(&self, bytes: &[u8]) -> usize {
+        bytes.len().saturating_sub(self.header_size) / self.record_size.max(1)
+    }
+
+    /// Short description for titles, e.g. `{u32 ts; f32 v} 8B`
+    pub fn summary(&self) -> String {
+        let members: Vec<String> = self
+            .fields
+            .iter()
+            .map(|f| format!("{} {}", short_type_name(f.data_type, f.size), f.name))
+            .collect();
+        format!("{{{}}} {}B", members.join("; "), self.record_size)
+    }
+}
+
+/// Map a C or short type name to a DataType (`char` maps to String)
+fn parse_c_type(name: &str) -> Option<DataType> {
+    let name = name.trim().trim_start_matches("const ").trim();
+    Some(match name {
+        "i8" | "int8" | "int8_t" | "signed char" => DataType::Int8,
+        "u8" | "uint8" | "uint8_t" | "unsigned char" | "byte" => DataType::UInt8,
+        "i16" | "int16" | "int16_t" | "short" => DataType::Int16,
+        "u16" | "uint16" | "uint16_t" | "unsigned short" => DataType::UInt16,
+        "i32" | "int32" | "int32_t" | "int" => DataType::Int32,
+        "u32" | "uint32" | "uint32_t" | "unsigned int" | "unsigned" => DataType::UInt32,
+        "f32" | "float32" | "float" => DataType::Float32,
+        "f64" | "float64" | "double" => DataType::Float64,
+        "char" => DataType::String,
+        _ => return None,
+    })
+}
+
+fn short_type_name(data_type: DataType, size: usize) -> String {
+    match data_type {
+        DataType::Int8 => "i8".to_string(),
+        DataType::Int16 => "i16".to_string(),
+        DataType::Int32 => "i32".to_string(),
+        DataType::UInt8 => "u8".to_string(),
+        DataType::UInt16 => "u16".to_string(),
+        DataType::UInt32 => "u32".to_string(),
+        DataType::Float32 => "f32".to_string(),
+        DataType::Float64 => "f64".to_string(),
+        DataType::String | DataType::Blob => format!("char[{}]", size),
+    }
+}
+
+/// Decode one member of every record in the blob for plotting
+pub fn decode_record_field(bytes: &[u8], layout: &RecordLayout, field_idx: usize, endianness: Endianness) -> Vec<f64> {
+    let field = match layout.fields.get(field_idx) {
+        Some(f) if f.is_numeric() => f,
+        _ => return Vec::new(),
+    };
+    let body = bytes.get(layout.header_size..).unwrap_or(&[]);
+    body.chunks_exact(layout.record_size.max(1))
+        .filter_map(|rec| rec.get(field.offset..field.offset + field.size))
+        .filter_map(|raw| decode_blob(raw, field.data_type, endianness).first().copied())
+        .collect()
+}
+
+/// Format a blob as a table with one row per record and one column per member
+pub fn format_records(bytes: &[u8], layout: &RecordLayout, endianness: Endianness) -> Vec<String> {
+    let body = bytes.get(layout.header_size..).unwrap_or(&[]);
+    let record_size = layout.record_size.max(1);
+
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    for rec in body.chunks_exact(record_size) {
+        let mut row = Vec::with_capacity(layout.fields.len());
+        for field in &layout.fields {
+            let raw = &rec[field.offset..field.offset + field.size];
+            let cell = match field.data_type {
+                DataType::String | DataType::Blob => {
+                    let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+                    String::from_utf8_lossy(&raw[..end]).to_string()
+                }
+                DataType::Float32 | DataType::Float64 => {
+                    format!("{:.6}", decode_blob(raw, field.data_type, endianness)[0])
+                }
+                _ => format!("{}", decode_blob(raw, field.data_type, endianness)[0] as i64),
+            };
+            row.push(cell);
+        }
+        rows.push(row);
+    }
+
+    // Column widths: fit the header and every cell
+    let index_width = rows.len().saturating_sub(1).to_string().len().max(1) + 2;
+    let widths: Vec<usize> = layout
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(c, f)| {
+            rows.iter()
+                .map(|r| r[c].len())
+                .chain(std::iter::once(f.name.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut lines = Vec::new();
+    if layout.header_size > 0 {
+        let header = &bytes[..layout.header_size.min(bytes.len())];
+        let hex: Vec<String> = header.iter().take(32).map(|b| format!("{:02x}", b)).collect();
+        let suffix = if header.len() > 32 { " ..." } else { "" };
+        lines.push(format!("Header ({} bytes): {}{}", layout.header_size, hex.join(" "), suffix));
+    }
+    let mut head = format!("{:>w$}", "#", w = index_width);
+    for (f, w) in layout.fields.iter().zip(&widths) {
+        head.push_str(&format!("  {:>w$}", f.name, w = *w));
+    }
+    lines.push(head);
+    for (i, row) in rows.iter().enumerate() {
+        let mut line = format!("{:>w$}", format!("[{}]", i), w = index_width);
+        for (cell, w) in row.iter().zip(&widths) {
+            line.push_str(&format!("  {:>w$}", cell, w = *w));
+        }
+        lines.push(line);
+    }
+    let trailing = body.len() % record_size;
+    if trailing > 0 {
+        lines.push(format!("({} trailing bytes not decoded)", trailing));
+    }
+    lines
+}
+
+/// A scored interpretation of a binary blob
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    pub data_type: DataType,
+    pub endianness: Endianness,
+    /// Plausibility of this interpretation (0..1)
+    pub score: f64,
+    /// How clearly it beats the best rival of a different width/byte order (0..1)
+    pub confidence: f64,
+}
+
+/// Bytes examined per candidate; longer blobs are judged on their prefix
+const DETECT_SAMPLE_BYTES: usize = 64 * 1024;
+
+/// Score every numeric DataType/Endianness combination for a blob and return
+/// them best first.
+///
+/// Each candidate is judged on length divisibility, the share of implausible
+/// values (NaN, infinities, denormals and huge magnitudes for floats) and
+/// smoothness: the mean step between neighbours relative to the value range,
+/// which is small for real signals and large for misread bytes. Two readings
+/// are smooth for almost any smooth signal and are discounted: integers whose
+/// bytes also form plausible floats of the same width, and wide types whose
+/// bytes are equally well explained by a narrower type.
+pub fn detect_data_type(bytes: &[u8]) -> Vec<Detection> {
+    let mut candidates: Vec<(DataType, Endianness)> = Vec::new();
+    for &dt in DataType::all() {
+        match dt.byte_size() {
+            None => continue,
+            Some(1) => candidates.push((dt, Endianness::Little)),
+            Some(_) => {
+                candidates.push((dt, Endianness::Little));
+                candidates.push((dt, Endianness::Big));
+            }
+        }
+    }
+
+    // (data_type, endianness, score, share of plausible values, value range)
+    let raw: Vec<(DataType, Endianness, f64, f64, f64)> = candidates
+        .into_iter()
+        .map(|(dt, end)| {
+            let (score, valid, range) = score_interpretation(bytes, dt, end);
+            (dt, end, score, valid, range)
+        })
+        .collect();
+
+    let width = |dt: DataType| dt.byte_size().unwrap_or(1);
+    let is_float = |dt: DataType| matches!(dt, DataType::Float32 | DataType::Float64);
+    let mut scored: Vec<Detection> = raw
+        .iter()
+        .map(|&(data_type, endianness, score, _, range)| {
+            let mut adjusted = score;
+            // Integers that are also fully plausible floats are most likely floats
+            let float_twin = raw.iter().any(|&(dt, end, s, valid, _)| {
+                is_float(dt) && !is_float(data_type) && width(dt) == width(data_type)
+                    && end == endianness && valid >= 0.99 && s >= 0.9 * score
+            });
+            // A wide type is smooth whenever a narrower type is; prefer the narrower
+            let narrower_twin = raw.iter().any(|&(dt, end, s, _, _)| {
+                width(dt) < width(data_type)
+                    && (end == endianness || width(dt) == 1)
+                    && s >= 0.9 * score
+            });
+            // Values wrapping around zero span the whole range when read with the
+            // wrong signedness; prefer the signed/unsigned twin with the tighter range
+            let sign_twin = raw.iter().any(|&(dt, end, _, _, r)| {
+                dt != data_type && !is_float(dt) && !is_float(data_type)
+                    && width(dt) == width(data_type) && end == endianness
+                    && r < 0.5 * range
+            });
+            if float_twin {
+                adjusted *= 0.6;
+            }
+            if narrower_twin {
+                adjusted *= 0.6;
+            }
+            if sign_twin {
+                adjusted *= 0.95;
+            }
+            Detection {
+                data_type,
+                endianness,
+                score: adjusted,
+                confidence: 0.0,
+            }
+        })
+        .collect();
+    // Stable sort keeps DataType::all() order for ties (e.g. all-zero blobs)
+    scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Confidence: margin over the best candidate that reads the bytes differently.
+    // Signed/unsigned variants of the same width are not rivals.
+    if let Some(best) = scored.first().copied() {
+        let rival = scored
+            .iter()
+            .skip(1)
+            .find(|d| width(d.data_type) != width(best.data_type) || d.endianness != best.endianness)
+            .map(|d| d.score)
+            .unwrap_or(0.0);
+        let margin = if best.score > 0.0 { (best.score - rival) / best.score } else { 0.0 };
+        scored[0].confidence = (best.score * (margin * 2.5).min(1.0)).clamp(0.0, 1.0);
+    }
+    scored
+}
+
+/// Returns (score, share of plausible values, value range) for one interpretation
+fn score_interpretation(
+    bytes: &[u8],
+    data_type: DataType,
+    endianness: Endianness,
+) -> (f64, f64, f64) {
+    let size = match data_type.byte_size() {
+        Some(s) => s,
+        None => return (0.0, 0.0, 0.0),
+    };
+    let sample = &bytes[..bytes.len().min(DETECT_SAMPLE_BYTES)];
+    let values = decode_blob(sample, data_type, endianness);
+    if values.len() < 2 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let is_float = matches!(data_type, DataType::Float32 | DataType::Float64);
+    let implausible = values
+        .iter()
+        .filter(|v| {
+            !v.is_finite()
+                || (is_float && **v != 0.0 && (v.abs() < 1.0e-20 || v.abs() > 1.0e10))
+        })
+        .count();
+    let valid_frac = 1.0 - implausible as f64 / values.len() as f64;
+    let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    if finite.len() < 2 {
+        return (0.0, valid_frac, 0.0);
+    }
+
+    let min = finite.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = finite.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = max - min;
+    let smoothness = if range == 0.0 {
+        1.0
+    } else {
+        let steps: f64 = finite.windows(2).map(|w| (w[1] - w[0]).abs()).sum();
+        let roughness = steps / (finite.len() - 1) as f64 / range;
+        // Uniform noise has a mean step of ~1/3 of the range
+        (1.0 - roughness * 3.0).clamp(0.0, 1.0)
+    };
+
+    let divisible = if bytes.len().is_multiple_of(size) { 1.0 } else { 0.7 };
+    (valid_frac.powi(4) * (0.2 + 0.8 * smoothness) * divisible, valid_frac, range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(layout: &RecordLayout) -> Vec<(&str, usize, usize)> {
+        layout.fields.iter().map(|f| (f.name.as_str(), f.offset, f.size)).collect()
+    }
+
+    #[test]
+    fn scaling_selects_channel_then_limits() {
+        let samples: Vec<f64> = (0..12).map(f64::from).collect();
+        let scaling = SampleScaling { channels: 3, channel: 1, max_samples: 3, ..SampleScaling::default() };
+        assert_eq!(scaling.apply(samples.clone()), [1.0, 4.0, 7.0]);
+        let scaling = SampleScaling { decimation: 2, gain: 2.0, offset: 1.0, ..scaling };
+        assert_eq!(scaling.apply(samples), [3.0, 15.0]);
+    }
+
+    fn best(bytes: &[u8]) -> Detection {
+        detect_data_type(bytes)[0]
+    }
+
+    fn score_of(bytes: &[u8], data_type: DataType, endianness: Endianness) -> f64 {
+        let all = detect_data_type(bytes);
+        all.iter().find(|d| d.data_type == data_type && d.endianness == endianness).unwrap().score
+    }
+
+    #[test]
+    fn detects_smooth_floats() {
+        let bytes: Vec<u8> = (0..256).flat_map(|i| ((i as f32 / 20.0).sin()).to_le_bytes()).collect();
+        let d = best(&bytes);
+        assert_eq!((d.data_type, d.endianness), (DataType::Float32, Endianness::Little));
+        assert!(d.confidence >= 0.5, "confidence {}", d.confidence);
+
+        let bytes: Vec<u8> = (0..256).flat_map(|i| ((i as f64 / 20.0).cos()).to_be_bytes()).collect();
+        let d = best(&bytes);
+        assert_eq!((d.data_type, d.endianness), (DataType::Float64, Endianness::Big));
+    }
+
+    #[test]
+    fn float_magnitude_limits() {
+        // Values beyond 1e10 or below 1e-20 are implausible floats
+        let huge: Vec<u8> = (0..64).flat_map(|i| (2.0e10f32 + i as f32 * 1.0e8).to_le_bytes()).collect();
+        assert_eq!(score_of(&huge, DataType::Float32, Endianness::Little), 0.0);
+        let tiny: Vec<u8> = (1..65).flat_map(|i| (i as f32 * 1.0e-22).to_le_bytes()).collect();
+        assert_eq!(score_of(&tiny, DataType::Float32, Endianness::Little), 0.0);
+        let inside: Vec<u8> = (1..65).flat_map(|i| (i as f32 * 1.0e9 / 64.0).to_le_bytes()).collect();
+        assert!(score_of(&inside, DataType::Float32, Endianness::Little) > 0.9);
+    }
+
+    #[test]
+    fn prefers_narrow_and_signed_twins() {
+        // A u8 ramp also reads as a smooth u16 ramp; the 0.6 penalty picks the narrower type
+        let ramp: Vec<u8> = (0..=255).collect();
+        assert_eq!(best(&ramp).data_type.byte_size(), Some(1));
+
+        // Around zero, the unsigned reading spans the whole range and loses by 0.95
+        let bytes: Vec<u8> = (-100i16..100).flat_map(|v| v.to_le_bytes()).collect();
+        let d = best(&bytes);
+        assert_eq!((d.data_type, d.endianness), (DataType::Int16, Endianness::Little));
+        let signed = score_of(&bytes, DataType::Int16, Endianness::Little);
+        let unsigned = score_of(&bytes, DataType::UInt16, Endianness::Little);
+        assert!(unsigned < signed && unsigned > 0.0);
+    }
+
+    #[test]
+    fn confidence_is_margin_over_rival() {
+        // The margin over the best rival of another width or byte order,
+        // scaled by 2.5 and capped at the best score
+        let bytes: Vec<u8> = (0..256).flat_map(|i| ((i as f32 / 20.0).sin()).to_le_bytes()).collect();
+        let all = detect_data_type(&bytes);
+        let top = all[0];
+        let width = |d: &Detection| d.data_type.byte_size();
+        let rival = all[1..]
+            .iter()
+            .find(|d| width(d) != width(&top) || d.endianness != top.endianness)
+            .unwrap();
+        let margin = (top.score - rival.score) / top.score;
+        assert!((top.confidence - top.score * (margin * 2.5).min(1.0)).abs() < 1e-12);
+        assert!(all[1..].iter().all(|d| d.confidence == 0.0));
+    }
+
+    #[test]
+    fn layout_aligns_members() {
+        let layout = RecordLayout::parse("u8 flag; u32 ts; i16 x; f64 v", 0, false).unwrap();
+        assert_eq!(offsets(&layout), [("flag", 0, 1), ("ts", 4, 4), ("x", 8, 2), ("v", 16, 8)]);
+        assert_eq!(layout.record_size, 24);
+
+        let packed = RecordLayout::parse("u8 flag; u32 ts; packed", 0, false).unwrap();
+        assert_eq!(offsets(&packed), [("flag", 0, 1), ("ts", 1, 4)]);
+        assert_eq!(packed.record_size, 5);
+    }
+
+    #[test]
+    fn layout_arrays_pad_and_header() {
+        let layout = RecordLayout::parse("struct s { header 16; char name[6]; pad 2; int16_t xy[2]; }", 0, false).unwrap();
+        assert_eq!(offsets(&layout), [("name", 0, 6), ("xy[0]", 8, 2), ("xy[1]", 10, 2)]);
+        assert_eq!(layout.header_size, 16);
+        assert_eq!(layout.record_count(&[0; 16 + 12 * 3 + 5]), 3);
+    }
+
+    #[test]
+    fn layout_rejects_bad_and_huge_definitions() {
+        assert!(RecordLayout::parse("", 0, false).is_err());
+        assert!(RecordLayout::parse("u24 x", 0, false).is_err());
+        assert!(RecordLayout::parse("f32 v[3", 0, false).is_err());
+        assert!(RecordLayout::parse("f32 v[1000000000]", 0, false).is_err());
+        assert!(RecordLayout::parse("char s[1000000000]", 0, false).is_err());
+        assert!(RecordLayout::parse(&format!("u8 a; pad {}; pad {}", usize::MAX, usize::MAX), 0, false).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+/// Largest LCS table (old lines × new lines) before falling back to
+/// "everything removed, everything added"
+const LCS_LIMIT: usize = 4_000_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+    /// Unchanged lines left out of a condensed diff
+    Skipped(usize),
+}
+
+/// Lines of `old` replaced by `lines`: `old[start..end]`
+#[derive(Debug)]
+struct Hunk {
+    start: usize,
+    end: usize,
+    lines: Vec<String>,
+}
+
+pub fn diff_lines(old: &[String], new: &[String]) -> Vec<DiffLine> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut out: Vec<DiffLine> = old[..prefix].iter().cloned().map(DiffLine::Same).collect();
+    if a.len() * b.len() > LCS_LIMIT {
+        out.extend(a.iter().cloned().map(DiffLine::Removed));
+        out.extend(b.iter().cloned().map(DiffLine::Added));
+    } else {
+        // lcs[i][j]: longest common subsequence of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = if a[i] == b[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                out.push(DiffLine::Same(a[i].clone()));
+                i += 1;
+                j += 1;
+            } else if i < a.len() && (j == b.len() || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+                out.push(DiffLine::Removed(a[i].clone()));
+                i += 1;
+            } else {
+                out.push(DiffLine::Added(b[j].clone()));
+                j += 1;
+            }
+        }
+    }
+    out.extend(old[old.len() - suffix..].iter().cloned().map(DiffLine::Same));
+    out
+}
+
+/// Keep `context` unchanged lines around each change and count the rest
+pub fn condense(diff: &[DiffLine], context: usize) -> Vec<DiffLine> {
+    let changed: Vec<bool> = diff.iter().map(|l| !matches!(l, DiffLine::Same(_))).collect();
+    let mut out = Vec::new();
+    let mut skipped = 0;
+    for (i, line) in diff.iter().enumerate() {
+        let lo = i.saturating_sub(context);
+        let hi = (i + context + 1).min(diff.len());
+        if changed[lo..hi].iter().any(|c| *c) {
+            if skipped > 0 {
+                out.push(DiffLine::Skipped(skipped));
+                skipped = 0;
+            }
+            out.push(line.clone());
+        } else {
+            skipped += 1;
+        }
+    }
+    if skipped > 0 {
+        out.push(DiffLine::Skipped(skipped));
+    }
+    out
+}
+
+fn hunks(base: &[String], side: &[String]) -> Vec<Hunk> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut pos = 0;
+    let mut open = false;
+    for line in diff_lines(base, side) {
+        match line {
+            DiffLine::Same(_) | DiffLine::Skipped(_) => {
+                pos += 1;
+                open = false;
+            }
+            DiffLine::Removed(_) | DiffLine::Added(_) => {
+                if !open {
+                    hunks.push(Hunk { start: pos, end: pos, lines: Vec::new() });
+                    open = true;
+                }
+                let hunk = hunks.last_mut().expect("hunk was just opened");
+                match line {
+                    DiffLine::Removed(_) => {
+                        pos += 1;
+                        hunk.end = pos;
+                    }
+                    DiffLine::Added(text) => hunk.lines.push(text),
+                    _ => {}
+                }
+            }
+        }
+    }
+    hunks
+}
+
+/// `base[start..end]` with one side's hunks inside that range applied
+fn apply(base: &[String], hunks: &[&Hunk], start: usize, end: usize) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut pos = start;
+    for hunk in hunks {
+        out.extend_from_slice(&base[pos..hunk.start]);
+        out.extend(hunk.lines.iter().cloned());
+        pos = hunk.end;
+    }
+    out.extend_from_slice(&base[pos..end]);
+    out
+}
+
+/// Line-based three-way merge of our edit and the server's version of
+/// `base`. Changes that touch the same lines are kept as a conflict block
+/// between `<<<<<<<` and `>>>>>>>` markers. Returns the text and the
+/// number of conflicts.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> (String, usize) {
+    let split = |s: &str| s.split('\n').map(|l| l.to_string()).collect::<Vec<_>>();
+    let (base, ours, theirs) = (split(base), split(ours), split(theirs));
+    let (a, b) = (hunks(&base, &ours), hunks(&base, &theirs));
+    let (mut i, mut j) = (0, 0);
+    let mut pos = 0;
+    let mut out: Vec<String> = Vec::new();
+    let mut conflicts = 0;
+
+    while i < a.len() || j < b.len() {
+        // Gather every hunk from either side that overlaps or touches the first
+        let first = match (a.get(i), b.get(j)) {
+            (Some(x), Some(y)) => x.start.min(y.start),
+            (Some(x), None) => x.start,
+            (None, Some(y)) => y.start,
+            (None, None) => break,
+        };
+        let (mut end, mut ours_in, mut theirs_in) = (first, Vec::new(), Vec::new());
+        loop {
+            if let Some(h) = a.get(i).filter(|h| h.start <= end) {
+                end = end.max(h.end);
+                ours_in.push(h);
+                i += 1;
+            } else if let Some(h) = b.get(j).filter(|h| h.start <= end) {
+                end = end.max(h.end);
+                theirs_in.push(h);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+
+        out.extend_from_slice(&base[pos..first]);
+        let mine = apply(&base, &ours_in, first, end);
+        let server = apply(&base, &theirs_in, first, end);
+        if ours_in.is_empty() || mine == server {
+            out.extend(server);
+        } else if theirs_in.is_empty() {
+            out.extend(mine);
+        } else {
+            conflicts += 1;
+            out.push("<<<<<<< yours".to_string());
+            out.extend(mine);
+            out.push("=======".to_string());
+            out.extend(server);
+            out.push(">>>>>>> server".to_string());
+        }
+        pos = end;
+    }
+    out.extend_from_slice(&base[pos..]);
+    (out.join("\n"), conflicts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.split('\n').map(|l| l.to_string()).collect()
+    }
+
+    fn render(diff: &[DiffLine]) -> String {
+        diff.iter()
+            .map(|l| match l {
+                DiffLine::Same(t) => format!(" {}", t),
+                DiffLine::Removed(t) => format!("-{}", t),
+                DiffLine::Added(t) => format!("+{}", t),
+                DiffLine::Skipped(n) => format!("~{}", n),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    #[test]
+    fn diff_edits() {
+        let diff = |a: &str, b: &str| render(&diff_lines(&lines(a), &lines(b)));
+        assert_eq!(diff("a\nb\nc", "a\nb\nc"), " a, b, c");
+        assert_eq!(diff("a\nb\nc", "a\nB\nc"), " a,-b,+B, c");
+        // Inserts and removals at either end
+        assert_eq!(diff("b\nc", "a\nb\nc"), "+a, b, c");
+        assert_eq!(diff("a\nb", "a\nb\nc"), " a, b,+c");
+        assert_eq!(diff("a\nb\nc", "b"), "-a, b,-c");
+        // Adjacent changes stay separate where a common line is kept
+        assert_eq!(diff("a\nb\nc\nd", "A\nb\nC\nd"), "-a,+A, b,-c,+C, d");
+    }
+
+    #[test]
+    fn condense_keeps_context() {
+        let old = lines("1\n2\n3\n4\n5\n6\n7\n8\n9");
+        let mut new = old.clone();
+        new[4] = "five".to_string();
+        assert_eq!(render(&condense(&diff_lines(&old, &new), 1)), "~3, 4,-5,+five, 6,~3");
+        // Changes at both ends, and a gap too small to skip
+        new[0] = "one".to_string();
+        new[8] = "nine".to_string();
+        assert_eq!(
+            render(&condense(&diff_lines(&old, &new), 2)),
+            "-1,+one, 2, 3, 4,-5,+five, 6, 7, 8,-9,+nine"
+        );
+        assert_eq!(render(&condense(&diff_lines(&old, &old), 2)), "~9");
+    }
+
+    #[test]
+    fn merge_clean() {
+        let base = "a\nb\nc\nd\ne";
+        // Separate changes on both sides
+        assert_eq!(merge3(base, "A\nb\nc\nd\ne", "a\nb\nc\nd\nE"), ("A\nb\nc\nd\nE".to_string(), 0));
+        // Inserts at the start and the end
+        assert_eq!(merge3(base, "x\na\nb\nc\nd\ne", "a\nb\nc\nd\ne\ny"), ("x\na\nb\nc\nd\ne\ny".to_string(), 0));
+        // The same change on both sides is taken once
+        assert_eq!(merge3(base, "a\nB\nc\nd\ne", "a\nB\nc\nd\ne"), ("a\nB\nc\nd\ne".to_string(), 0));
+        assert_eq!(merge3(base, "a\nb\nc\nd\ne\nz", "a\nb\nc\nd\ne\nz"), ("a\nb\nc\nd\ne\nz".to_string(), 0));
+        // Only one side changed
+        assert_eq!(merge3(base, base, "a\nc\nd\ne"), ("a\nc\nd\ne".to_string(), 0));
+        assert_eq!(merge3(base, "a\nc\nd\ne", base), ("a\nc\nd\ne".to_string(), 0));
+    }
+
+    #[test]
+    fn merge_conflicts() {
+        let base = "a\nb\nc\nd\ne";
+        let (text, conflicts) = merge3(base, "a\nmine\nc\nd\ne", "a\ntheirs\nc\nd\ne");
+        assert_eq!(conflicts, 1);
+        assert_eq!(text, "a\n<<<<<<< yours\nmine\n=======\ntheirs\n>>>>>>> server\nc\nd\ne");
+
+        // Changes to neighbouring lines touch, so they conflict as one block
+        let (text, conflicts) = merge3(base, "a\nB\nc\nd\ne", "a\nb\nC\nd\ne");
+        assert_eq!(conflicts, 1);
+        assert_eq!(text, "a\n<<<<<<< yours\nB\nc\n=======\nb\nC\n>>>>>>> server\nd\ne");
+
+        // Different inserts at the same place, and two separate conflicts
+        let (_, conflicts) = merge3(base, "x\na\nb\nc\nd\ne", "y\na\nb\nc\nd\ne");
+        assert_eq!(conflicts, 1);
+        let (_, conflicts) = merge3(base, "A\nb\nc\nd\nE", "1\nb\nc\nd\n5");
+        assert_eq!(conflicts, 2);
+    }
+}
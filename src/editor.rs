@@ -0,0 +1,238 @@
+use crate::data::{decode_blob, encode_values, is_binary, DataType, Endianness};
+use crate::redis_client::RedisClient;
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// What an external edit reads and writes back
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditTarget {
+    String,
+    HashField(String),
+    ListIndex(i64),
+    /// A whole RedisJSON document (JSON.GET/JSON.SET at `$`)
+    JsonDoc,
+}
+
+/// How the value is laid out in the temp file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileFormat {
+    Text,
+    /// Pretty-printed JSON; `compact` writes it back on one line
+    Json { compact: bool },
+    /// One decoded number per line
+    Numbers(DataType, Endianness),
+}
+
+impl FileFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Text => "txt",
+            FileFormat::Json { .. } => "json",
+            FileFormat::Numbers(..) => "csv",
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            FileFormat::Text => "text".to_string(),
+            FileFormat::Json { .. } => "JSON".to_string(),
+            FileFormat::Numbers(dtype, endian) => format!("{} {} values", dtype, endian),
+        }
+    }
+}
+
+/// A value checked out to a temp file for `$EDITOR`
+#[derive(Debug, Clone)]
+pub struct ExternalEdit {
+    pub key: String,
+    pub target: EditTarget,
+    pub format: FileFormat,
+    /// The value on the server when the file was written
+    pub original: Vec<u8>,
+    pub path: PathBuf,
+    /// File contents before editing, to tell whether anything changed
+    pub initial_text: String,
+    /// Encoded result, kept while an overwrite is being confirmed
+    pub new_value: Option<Vec<u8>>,
+}
+
+impl EditTarget {
+    pub fn describe(&self, key: &str) -> String {
+        match self {
+            EditTarget::String | EditTarget::JsonDoc => format!("'{}'", key),
+            EditTarget::HashField(field) => format!("'{}' field '{}'", key, field),
+            EditTarget::ListIndex(index) => format!("'{}' [{}]", key, index),
+        }
+    }
+
+    /// The current value on the server, None if it is gone
+    pub fn read(&self, client: &mut RedisClient, key: &str) -> Result<Option<Vec<u8>>> {
+        match self {
+            EditTarget::String => client.get_bytes(key, None),
+            EditTarget::HashField(field) => client.get_bytes(key, Some(field)),
+            EditTarget::ListIndex(index) => client.lindex(key, *index),
+            EditTarget::JsonDoc => Ok(client.json_get(key)?.map(String::into_bytes)),
+        }
+    }
+
+    pub fn write(&self, client: &mut RedisClient, key: &str, value: &[u8]) -> Result<()> {
+        match self {
+            EditTarget::String => client.set_bytes(key, value),
+            EditTarget::HashField(field) => client.hset_bytes(key, field, value),
+            EditTarget::ListIndex(index) => client.lset_bytes(key, *index, value),
+            EditTarget::JsonDoc => client.json_set(key, "$", &String::from_utf8_lossy(value)),
+        }
+    }
+}
+
+impl ExternalEdit {
+    /// Write `original` to a temp file in a form fit for editing. Binary values
+    /// are decoded as `data_type` numbers, so that type must be numeric.
+    pub fn checkout(
+        key: &str,
+        target: EditTarget,
+        original: Vec<u8>,
+        data_type: DataType,
+        endianness: Endianness,
+    ) -> Result<Self> {
+        let (format, text) = if target == EditTarget::JsonDoc {
+            let value: serde_json::Value = serde_json::from_slice(&original).context("Invalid JSON from JSON.GET")?;
+            // A `$` path returns the matches wrapped in an array
+            let value = match value {
+                serde_json::Value::Array(mut matches) if matches.len() == 1 => matches.remove(0),
+                other => other,
+            };
+            (FileFormat::Json { compact: false }, serde_json::to_string_pretty(&value)?)
+        } else if is_binary(&original) {
+            let Some(size) = data_type.byte_size() else {
+                bail!("Binary value: pick a numeric data type with 't' first");
+            };
+            if !original.len().is_multiple_of(size) {
+                bail!("{} bytes is not a whole number of {} values", original.len(), data_type);
+            }
+            let lines: Vec<String> = decode_blob(&original, data_type, endianness)
+                .iter()
+                .map(|v| exact_number(*v, data_type))
+                .collect();
+            (FileFormat::Numbers(data_type, endianness), lines.join("\n") + "\n")
+        } else {
+            let text = String::from_utf8_lossy(&original).to_string();
+            let trimmed = text.trim_start();
+            match serde_json::from_str::<serde_json::Value>(&text) {
+                Ok(value) if trimmed.starts_with('{') || trimmed.starts_with('[') => {
+                    let compact = !text.contains('\n');
+                    (FileFormat::Json { compact }, serde_json::to_string_pretty(&value)?)
+                }
+                _ => (FileFormat::Text, text),
+            }
+        };
+
+        let safe_key: String = key
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .take(40)
+            .collect();
+        let path = create_private_file(&format!("{}.{}", safe_key, format.extension()), &text)?;
+
+        Ok(Self {
+            key: key.to_string(),
+            target,
+            format,
+            original,
+            path,
+            initial_text: text,
+            new_value: None,
+        })
+    }
+
+    /// The edited file re-encoded for the server, None when it is unchanged
+    pub fn encode_file(&self) -> Result<Option<Vec<u8>>> {
+        let text = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path.display()))?;
+        if text == self.initial_text {
+            return Ok(None);
+        }
+        let bytes = match self.format {
+            FileFormat::Text => text.into_bytes(),
+            FileFormat::Json { compact } => {
+                let value: serde_json::Value =
+                    serde_json::from_str(&text).map_err(|e| anyhow::anyhow!("Invalid JSON: {}", e))?;
+                if compact || self.target == EditTarget::JsonDoc {
+                    value.to_string().into_bytes()
+                } else {
+                    text.into_bytes()
+                }
+            }
+            FileFormat::Numbers(dtype, endian) => {
+                // One value per line; commas and blank lines are fine too
+                encode_values(&text.replace('\n', ","), dtype, endian).map_err(anyhow::Error::msg)?
+            }
+        };
+        Ok(Some(bytes))
+    }
+
+    pub fn remove_file(&self) {
+        let _ = std::fs::remove_file(&self.path);
+        if let Some(dir) = self.path.parent() {
+            // Editors may leave swap or backup files behind
+            let _ = std::fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Write `text` to a new file called `name` in a fresh temp directory that
+/// only the user can open. Nothing already in the shared temp dir (such as a
+/// planted symlink) is followed or reused.
+fn create_private_file(name: &str, text: &str) -> Result<PathBuf> {
+    use std::io::Write;
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!("redis-tui-{}-{}", std::process::id(), nanos));
+    let mut builder = std::fs::DirBuilder::new();
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let path = dir.join(name);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(&path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Print a decoded value so that encoding it again gives the same bytes
+pub fn exact_number(value: f64, data_type: DataType) -> String {
+    match data_type {
+        // Shortest text that parses back to the same f32
+        DataType::Float32 => format!("{}", value as f32),
+        DataType::Float64 => format!("{}", value),
+        _ => format!("{}", value as i64),
+    }
+}
+
+/// Run `$VISUAL` or `$EDITOR` (default `vi`) on a file and wait for it.
+/// The variable may hold arguments too, e.g. `code --wait`.
+pub fn run_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("redis-tui")
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to run editor '{}'", editor))?;
+    if !status.success() {
+        bail!("Editor '{}' exited with {}", editor, status);
+    }
+    Ok(())
+}
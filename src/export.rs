@@ -0,0 +1,407 @@
+use crate::data::{decode_blob, is_binary, DataType, Endianness};
+use crate::editor::exact_number;
+use crate::redis_client::{RedisClient, RedisValue, StreamEntry, TsQuery};
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Map, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Stream entries read per XRANGE while exporting
+const XRANGE_PAGE: usize = 1000;
+
+/// Most elements or samples per command in a RESP file
+const RESP_CHUNK: usize = 500;
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Export file formats, picked by the file extension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// One JSON object per key and line (.ndjson, .jsonl)
+    Ndjson,
+    /// The same objects as one JSON array (.json)
+    Json,
+    /// `key,type,id,field,value` rows with binary values decoded (.csv)
+    Csv,
+    /// Commands that recreate the keys, for `redis-cli --pipe` (.resp, .redis)
+    Resp,
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &str) -> Result<Self> {
+        let ext = Path::new(path).extension().and_then(|e| e.to_str()).unwrap_or("").to_ascii_lowercase();
+        Ok(match ext.as_str() {
+            "ndjson" | "jsonl" => ExportFormat::Ndjson,
+            "json" => ExportFormat::Json,
+            "csv" => ExportFormat::Csv,
+            "resp" | "redis" => ExportFormat::Resp,
+            _ => bail!("Unknown export format '.{}': use .ndjson, .json, .csv or .resp", ext),
+        })
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "NDJSON",
+            ExportFormat::Json => "JSON array",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Resp => "RESP commands",
+        }
+    }
+}
+
+/// Everything stored under a key, read in full (the views cap long values)
+#[derive(Debug)]
+pub struct KeyDump {
+    pub key_type: String,
+    pub value: RedisValue,
+    pub ttl_ms: Option<i64>,
+}
+
+pub fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Padded base64 as written by `base64_encode`; whitespace is ignored, but
+/// missing or misplaced padding and nonzero unused bits are errors, so each
+/// value has exactly one encoding
+pub fn base64_decode(text: &str) -> Result<Vec<u8>> {
+    let text: Vec<u8> = text.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+    if !text.len().is_multiple_of(4) {
+        bail!("Invalid base64: length {} is not a multiple of 4", text.len());
+    }
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let quads = text.chunks(4).count();
+    for (i, quad) in text.chunks(4).enumerate() {
+        let pad = quad.iter().rev().take_while(|c| **c == b'=').count();
+        if pad > 2 || (pad > 0 && i + 1 < quads) {
+            bail!("Invalid base64: '=' before the end");
+        }
+        let mut n = 0u32;
+        for c in &quad[..4 - pad] {
+            let Some(v) = BASE64_ALPHABET.iter().position(|a| a == c) else {
+                bail!("Invalid base64 character '{}'", *c as char);
+            };
+            n = n << 6 | v as u32;
+        }
+        n <<= 6 * pad;
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        if bytes[3 - pad..].iter().any(|b| *b != 0) {
+            bail!("Invalid base64: nonzero bits after the last byte");
+        }
+        out.extend_from_slice(&bytes[..3 - pad]);
+    }
+    Ok(out)
+}
+
+/// Text as a JSON string, binary as `{"base64": ...}`
+pub fn bytes_value(bytes: &[u8]) -> Value {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !is_binary(bytes) => Value::String(text.to_string()),
+        _ => json!({ "base64": base64_encode(bytes) }),
+    }
+}
+
+/// A sorted set score; JSON has no infinities, so those become "inf"/"-inf"
+fn score_value(score: f64) -> Value {
+    if score.is_finite() {
+        json!(score)
+    } else {
+        Value::String(score.to_string())
+    }
+}
+
+/// Every entry of a stream, a page at a time
+pub fn all_stream_entries(client: &mut RedisClient, key: &str) -> Result<Vec<StreamEntry>> {
+    let mut entries = client.xrange(key, "-", XRANGE_PAGE)?;
+    let mut page = entries.len();
+    while page == XRANGE_PAGE {
+        let after = format!("({}", entries[entries.len() - 1].id);
+        let more = client.xrange(key, &after, XRANGE_PAGE)?;
+        page = more.len();
+        entries.extend(more);
+    }
+    Ok(entries)
+}
+
+/// Read a key for export; None if it is gone
+pub fn read_key(client: &mut RedisClient, key: &str) -> Result<Option<KeyDump>> {
+    let key_type = client.key_type(key)?;
+    let value = match key_type.as_str() {
+        "none" => return Ok(None),
+        // Raw bytes, so HyperLogLogs come back as they were
+        "string" => match client.get_bytes(key, None)? {
+            Some(bytes) => RedisValue::String(bytes),
+            None => return Ok(None),
+        },
+        "stream" => RedisValue::Stream(all_stream_entries(client, key)?),
+        "TSDB-TYPE" => {
+            let query = TsQuery { count: 0, reverse: false, ..TsQuery::default() };
+            RedisValue::TimeSeries(client.get_timeseries(key, &query)?)
+        }
+        _ => match client.get_value(key)? {
+            value @ (RedisValue::Hash(_)
+            | RedisValue::List(_)
+            | RedisValue::Set(_)
+            | RedisValue::ZSet(_)
+            | RedisValue::Json(_)) => value,
+            _ => bail!("{} keys cannot be exported", key_type),
+        },
+    };
+    let pttl = client.pttl(key)?;
+    Ok(Some(KeyDump { key_type, value, ttl_ms: (pttl > 0).then_some(pttl) }))
+}
+
+/// One key as an export record: `{"key", "type", "ttl_ms", "value"}`, with
+/// `ttl_ms` null for keys that do not expire
+pub fn json_record(key: &str, dump: &KeyDump) -> Value {
+    let value = match &dump.value {
+        RedisValue::String(bytes) => bytes_value(bytes),
+        RedisValue::Hash(pairs) => Value::Object(pairs.iter().map(|(f, v)| (f.clone(), bytes_value(v))).collect()),
+        RedisValue::List(items) | RedisValue::Set(items) => items.iter().map(|v| bytes_value(v)).collect(),
+        RedisValue::ZSet(pairs) => pairs.iter().map(|(m, score)| json!([bytes_value(m), score_value(*score)])).collect(),
+        // Field pairs rather than an object: stream field names may repeat
+        RedisValue::Stream(entries) => entries
+            .iter()
+            .map(|e| {
+                let fields: Vec<Value> = e.fields.iter().map(|(f, v)| json!([f, bytes_value(v)])).collect();
+                json!({ "id": e.id, "fields": fields })
+            })
+            .collect(),
+        RedisValue::Json(doc) => doc.clone(),
+        RedisValue::TimeSeries(series) => {
+            let labels: Map<String, Value> =
+                series.labels.iter().map(|(k, v)| (k.clone(), Value::String(v.clone()))).collect();
+            let samples: Vec<Value> = series.samples.iter().map(|(t, v)| json!([t, v])).collect();
+            json!({ "labels": labels, "samples": samples })
+        }
+        RedisValue::Probabilistic(_) | RedisValue::Unknown(_) => Value::Null,
+    };
+    json!({
+        "key": key,
+        "type": dump.key_type,
+        "ttl_ms": dump.ttl_ms,
+        "value": value,
+    })
+}
+
+fn csv_cell(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// CSV rows of one key. Binary values that are a whole number of
+/// `data_type` values become one row per number, with `[i]` after the field.
+pub fn csv_rows(key: &str, dump: &KeyDump, data_type: DataType, endianness: Endianness) -> Vec<String> {
+    let mut rows = Vec::new();
+    let mut push = |id: &str, field: &str, bytes: &[u8]| {
+        let row = |field: &str, value: &str| {
+            [key, &dump.key_type, id, field, value].iter().map(|c| csv_cell(c)).collect::<Vec<_>>().join(",")
+        };
+        match data_type.byte_size() {
+            Some(size) if is_binary(bytes) && bytes.len().is_multiple_of(size) => {
+                for (i, v) in decode_blob(bytes, data_type, endianness).iter().enumerate() {
+                    rows.push(row(&format!("{}[{}]", field, i), &exact_number(*v, data_type)));
+                }
+            }
+            _ if is_binary(bytes) => rows.push(row(field, &format!("base64:{}", base64_encode(bytes)))),
+            _ => rows.push(row(field, &String::from_utf8_lossy(bytes))),
+        }
+    };
+    match &dump.value {
+        RedisValue::String(bytes) => push("", "", bytes),
+        RedisValue::Hash(pairs) => pairs.iter().for_each(|(f, v)| push("", f, v)),
+        RedisValue::List(items) => items.iter().enumerate().for_each(|(i, v)| push(&i.to_string(), "", v)),
+        RedisValue::Set(items) => items.iter().for_each(|v| push("", "", v)),
+        RedisValue::ZSet(pairs) => {
+            for (member, score) in pairs {
+                push("", &String::from_utf8_lossy(member), score.to_string().as_bytes());
+            }
+        }
+        RedisValue::Stream(entries) => {
+            for entry in entries {
+                entry.fields.iter().for_each(|(f, v)| push(&entry.id, f, v));
+            }
+        }
+        RedisValue::Json(doc) => push("", "", doc.to_string().as_bytes()),
+        RedisValue::TimeSeries(series) => {
+            for (t, v) in &series.samples {
+                push(&t.to_string(), "", v.to_string().as_bytes());
+            }
+        }
+        RedisValue::Probabilistic(_) | RedisValue::Unknown(_) => {}
+    }
+    rows
+}
+
+/// One command in the RESP wire format
+pub fn resp_encode(out: &mut Vec<u8>, args: &[Vec<u8>]) {
+    out.extend_from_slice(format!("*{}\r\n", args.len()).as_bytes());
+    for arg in args {
+        out.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        out.extend_from_slice(arg);
+        out.extend_from_slice(b"\r\n");
+    }
+}
+
+/// Commands that recreate a key from scratch: DEL, the writes, then PEXPIRE
+pub fn restore_commands(key: &str, dump: &KeyDump) -> Vec<Vec<Vec<u8>>> {
+    let mut out = Vec::new();
+    let arg = |s: &str| s.as_bytes().to_vec();
+    let mut cmd = |name: &str, rest: Vec<Vec<u8>>| {
+        let mut args = vec![arg(name), arg(key)];
+        args.extend(rest);
+        out.push(args);
+    };
+    cmd("DEL", Vec::new());
+    match &dump.value {
+        RedisValue::String(bytes) => cmd("SET", vec![bytes.clone()]),
+        RedisValue::Hash(pairs) => {
+            for chunk in pairs.chunks(RESP_CHUNK) {
+                cmd("HSET", chunk.iter().flat_map(|(f, v)| [arg(f), v.clone()]).collect());
+            }
+        }
+        RedisValue::List(items) => items.chunks(RESP_CHUNK).for_each(|c| cmd("RPUSH", c.to_vec())),
+        RedisValue::Set(items) => items.chunks(RESP_CHUNK).for_each(|c| cmd("SADD", c.to_vec())),
+        RedisValue::ZSet(pairs) => {
+            for chunk in pairs.chunks(RESP_CHUNK) {
+                cmd("ZADD", chunk.iter().flat_map(|(m, score)| [arg(&score.to_string()), m.clone()]).collect());
+            }
+        }
+        RedisValue::Stream(entries) => {
+            for entry in entries {
+                let mut args = vec![arg(&entry.id)];
+                args.extend(entry.fields.iter().flat_map(|(f, v)| [arg(f), v.clone()]));
+                cmd("XADD", args);
+            }
+        }
+        RedisValue::Json(doc) => cmd("JSON.SET", vec![arg("$"), arg(&doc.to_string())]),
+        RedisValue::TimeSeries(series) => {
+            let mut args = Vec::new();
+            if !series.labels.is_empty() {
+                args.push(arg("LABELS"));
+                args.extend(series.labels.iter().flat_map(|(k, v)| [arg(k), arg(v)]));
+            }
+            cmd("TS.CREATE", args);
+            for chunk in series.samples.chunks(RESP_CHUNK) {
+                // TS.MADD repeats the key before every sample but the first
+                let mut args = Vec::new();
+                for (i, (t, v)) in chunk.iter().enumerate() {
+                    if i > 0 {
+                        args.push(arg(key));
+                    }
+                    args.extend([arg(&t.to_string()), arg(&v.to_string())]);
+                }
+                cmd("TS.MADD", args);
+            }
+        }
+        RedisValue::Probabilistic(_) | RedisValue::Unknown(_) => {}
+    }
+    if let Some(ttl) = dump.ttl_ms {
+        cmd("PEXPIRE", vec![arg(&ttl.to_string())]);
+    }
+    out
+}
+
+/// `restore_commands` as a RESP file fragment
+pub fn resp_commands(key: &str, dump: &KeyDump) -> Vec<u8> {
+    let mut out = Vec::new();
+    for command in restore_commands(key, dump) {
+        resp_encode(&mut out, &command);
+    }
+    out
+}
+
+/// An export file being written a key at a time
+#[derive(Debug)]
+pub struct Exporter {
+    pub format: ExportFormat,
+    pub path: String,
+    writer: BufWriter<File>,
+    data_type: DataType,
+    endianness: Endianness,
+    written: usize,
+}
+
+impl Exporter {
+    /// Create `path`; CSV blobs are decoded as `data_type` values
+    pub fn create(path: &str, data_type: DataType, endianness: Endianness) -> Result<Self> {
+        let format = ExportFormat::from_path(path)?;
+        let file = File::create(path).with_context(|| format!("Cannot create {}", path))?;
+        let mut writer = BufWriter::new(file);
+        match format {
+            ExportFormat::Json => writer.write_all(b"[")?,
+            ExportFormat::Csv => writer.write_all(b"key,type,id,field,value\n")?,
+            _ => {}
+        }
+        Ok(Self { format, path: path.to_string(), writer, data_type, endianness, written: 0 })
+    }
+
+    /// Append `key`; false if it no longer exists
+    pub fn write_key(&mut self, client: &mut RedisClient, key: &str) -> Result<bool> {
+        let Some(dump) = read_key(client, key)? else {
+            return Ok(false);
+        };
+        match self.format {
+            ExportFormat::Ndjson => writeln!(self.writer, "{}", json_record(key, &dump))?,
+            ExportFormat::Json => {
+                let sep = if self.written == 0 { "\n" } else { ",\n" };
+                write!(self.writer, "{}{}", sep, json_record(key, &dump))?;
+            }
+            ExportFormat::Csv => {
+                for row in csv_rows(key, &dump, self.data_type, self.endianness) {
+                    writeln!(self.writer, "{}", row)?;
+                }
+            }
+            ExportFormat::Resp => self.writer.write_all(&resp_commands(key, &dump))?,
+        }
+        self.written += 1;
+        Ok(true)
+    }
+
+    /// Close the JSON array and flush the file
+    pub fn finish(mut self) -> Result<()> {
+        if self.format == ExportFormat::Json {
+            self.writer.write_all(b"\n]\n")?;
+        }
+        self.writer.flush().with_context(|| format!("Failed to write {}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_round_trip() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for len in 0..=bytes.len() {
+            let encoded = base64_encode(&bytes[..len]);
+            assert_eq!(base64_decode(&encoded).unwrap(), &bytes[..len], "{}", encoded);
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_decode("Zm9v\nYmE=").unwrap(), b"fooba");
+    }
+
+    #[test]
+    fn base64_rejects_non_canonical() {
+        for bad in ["Zm8", "Zm9=", "Zg=A", "Zg==Zg==", "Z===", "Zm9-", "Zh=="] {
+            assert!(base64_decode(bad).is_err(), "{}", bad);
+        }
+    }
+}
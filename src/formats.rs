@@ -0,0 +1,2280 @@
+use crate::data::{format_hex, is_binary};
+use std::fmt;
+use std::io::Read;
+
+/// Largest decompressed payload we are willing to render
+const MAX_DECOMPRESSED: u64 = 64 * 1024 * 1024;
+
+/// Nesting limit for the recursive decoders
+const MAX_DEPTH: usize = 64;
+
+/// Number of bytes shown in hex for binary leaves of a tree
+const BYTES_PREVIEW: usize = 32;
+
+/// How a value (or a hash/stream field, or list item) is rendered in the value view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueFormat {
+    /// Detect compressed and binary serialization formats by their content
+    Auto,
+    /// No formatter: text, JSON or decoded numeric samples as before
+    Raw,
+    Json,
+    MessagePack,
+    Cbor,
+    Protobuf,
+    Bson,
+    JavaSerialized,
+    /// gzip or zlib
+    Gzip,
+    Zstd,
+    Lz4,
+    Snappy,
+}
+
+impl ValueFormat {
+    pub fn all() -> &'static [ValueFormat] {
+        &[
+            ValueFormat::Auto,
+            ValueFormat::Raw,
+            ValueFormat::Json,
+            ValueFormat::MessagePack,
+            ValueFormat::Cbor,
+            ValueFormat::Protobuf,
+            ValueFormat::Bson,
+            ValueFormat::JavaSerialized,
+            ValueFormat::Gzip,
+            ValueFormat::Zstd,
+            ValueFormat::Lz4,
+            ValueFormat::Snappy,
+        ]
+    }
+
+    /// Parse a format name as shown by Display (case-insensitive, a few aliases)
+    pub fn from_name(name: &str) -> Option<ValueFormat> {
+        let name = name.trim().to_ascii_lowercase();
+        match name.as_str() {
+            "messagepack" | "mpk" => return Some(ValueFormat::MessagePack),
+            "proto" | "pb" => return Some(ValueFormat::Protobuf),
+            "javaserialized" | "jser" => return Some(ValueFormat::JavaSerialized),
+            "zlib" | "deflate" | "gz" => return Some(ValueFormat::Gzip),
+            "zst" => return Some(ValueFormat::Zstd),
+            "none" | "text" => return Some(ValueFormat::Raw),
+            _ => {}
+        }
+        Self::all().iter().copied().find(|f| f.to_string() == name)
+    }
+}
+
+impl fmt::Display for ValueFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueFormat::Auto => write!(f, "auto"),
+            ValueFormat::Raw => write!(f, "raw"),
+            ValueFormat::Json => write!(f, "json"),
+            ValueFormat::MessagePack => write!(f, "msgpack"),
+            ValueFormat::Cbor => write!(f, "cbor"),
+            ValueFormat::Protobuf => write!(f, "protobuf"),
+            ValueFormat::Bson => write!(f, "bson"),
+            ValueFormat::JavaSerialized => write!(f, "java"),
+            ValueFormat::Gzip => write!(f, "gzip"),
+            ValueFormat::Zstd => write!(f, "zstd"),
+            ValueFormat::Lz4 => write!(f, "lz4"),
+            ValueFormat::Snappy => write!(f, "snappy"),
+        }
+    }
+}
+
+/// A decoded value, common to all structured formats
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    Array(Vec<Node>),
+    Map(Vec<(String, Node)>),
+    /// A typed value: CBOR tags, BSON ObjectIds and dates, Java objects, ...
+    Tagged(String, Box<Node>),
+}
+
+/// Render a node as an indented tree, one line per leaf or container
+pub fn render_tree(node: &Node) -> Vec<String> {
+    let mut lines = Vec::new();
+    render_node(node, 0, None, &mut lines);
+    lines
+}
+
+fn render_node(node: &Node, depth: usize, label: Option<&str>, out: &mut Vec<String>) {
+    let mut prefix = "  ".repeat(depth);
+    if let Some(label) = label {
+        prefix.push_str(label);
+        prefix.push_str(": ");
+    }
+    let (tag, inner) = match node {
+        Node::Tagged(tag, inner) => (Some(tag.as_str()), inner.as_ref()),
+        _ => (None, node),
+    };
+    let tag_prefix = tag.map(|t| format!("{} ", t)).unwrap_or_default();
+    match inner {
+        Node::Array(items) => {
+            out.push(format!("{}{}[{} items]", prefix, tag_prefix, items.len()));
+            for (i, item) in items.iter().enumerate() {
+                render_node(item, depth + 1, Some(&format!("[{}]", i)), out);
+            }
+        }
+        Node::Map(entries) => {
+            out.push(format!("{}{}{{{} fields}}", prefix, tag_prefix, entries.len()));
+            for (k, v) in entries {
+                render_node(v, depth + 1, Some(k), out);
+            }
+        }
+        Node::Tagged(..) => {
+            out.push(format!("{}{}", prefix, tag.unwrap_or("")));
+            render_node(inner, depth + 1, None, out);
+        }
+        leaf => out.push(format!("{}{}{}", prefix, tag_prefix, scalar_text(leaf))),
+    }
+}
+
+/// Single-line text for a leaf node (containers are summarized)
+fn scalar_text(node: &Node) -> String {
+    match node {
+        Node::Null => "null".to_string(),
+        Node::Bool(b) => b.to_string(),
+        Node::Int(i) => i.to_string(),
+        Node::UInt(u) => u.to_string(),
+        Node::Float(f) => f.to_string(),
+        Node::Str(s) => format!("{:?}", s),
+        Node::Bytes(b) => match std::str::from_utf8(b) {
+            Ok(s) if !b.is_empty() && !is_binary(b) => format!("b{:?}", s),
+            _ => {
+                let hex: Vec<String> =
+                    b.iter().take(BYTES_PREVIEW).map(|x| format!("{:02x}", x)).collect();
+                let more = if b.len() > BYTES_PREVIEW { " .." } else { "" };
+                format!("<{} bytes> {}{}", b.len(), hex.join(" "), more)
+            }
+        },
+        Node::Array(items) => format!("[{} items]", items.len()),
+        Node::Map(entries) => format!("{{{} fields}}", entries.len()),
+        Node::Tagged(tag, inner) => format!("{} {}", tag, scalar_text(inner)),
+    }
+}
+
+/// Text used for a map key of any type
+fn key_text(node: &Node) -> String {
+    match node {
+        Node::Str(s) => s.clone(),
+        other => scalar_text(other),
+    }
+}
+
+/// A successfully formatted value
+pub struct Rendered {
+    /// What was decoded, e.g. `msgpack` or `gzip → cbor`
+    pub label: String,
+    pub lines: Vec<String>,
+}
+
+/// Formatter settings for the value view
+pub struct FormatSettings {
+    /// Format for the whole value and every element without an override
+    pub format: ValueFormat,
+    /// Per hash/stream field name (or list index) overrides
+    pub field_formats: Vec<(String, ValueFormat)>,
+    /// Schema file for Protobuf (`.proto` or descriptor set), as entered
+    pub proto_path: String,
+    pub proto: Option<ProtoSchema>,
+    /// Message type used to decode Protobuf values
+    pub proto_message: String,
+}
+
+impl Default for FormatSettings {
+    fn default() -> Self {
+        Self {
+            format: ValueFormat::Auto,
+            field_formats: Vec::new(),
+            proto_path: String::new(),
+            proto: None,
+            proto_message: String::new(),
+        }
+    }
+}
+
+impl FormatSettings {
+    /// Format selected for a field (None = the value itself)
+    pub fn format_for(&self, field: Option<&str>) -> ValueFormat {
+        field
+            .and_then(|name| self.field_formats.iter().find(|(f, _)| f == name))
+            .map(|(_, fmt)| *fmt)
+            .unwrap_or(self.format)
+    }
+
+    /// Set (or with Auto, clear) the override for one field
+    pub fn set_field_format(&mut self, field: &str, format: ValueFormat) {
+        self.field_formats.retain(|(f, _)| f != field);
+        if format != self.format {
+            self.field_formats.push((field.to_string(), format));
+        }
+    }
+
+    /// Short description for titles, e.g. `msgpack` or `auto +2 fields`
+    pub fn summary(&self) -> String {
+        let mut s = self.format.to_string();
+        if self.format == ValueFormat::Protobuf && !self.proto_message.is_empty() {
+            s.push_str(&format!(" {}", self.proto_message));
+        }
+        if !self.field_formats.is_empty() {
+            s.push_str(&format!(" +{} fields", self.field_formats.len()));
+        }
+        s
+    }
+
+    /// Decode `bytes` with the format selected for `field`.
+    /// None means no formatter applies and the value should be shown as-is,
+    /// which includes a detected format that then fails to decode;
+    /// Some(Err) means an explicitly selected format failed to decode.
+    pub fn render(&self, bytes: &[u8], field: Option<&str>) -> Option<Result<Rendered, String>> {
+        match self.format_for(field) {
+            ValueFormat::Raw => None,
+            ValueFormat::Auto => self.decode(bytes, detect_format(bytes)?, 0).ok().map(Ok),
+            explicit => Some(self.decode(bytes, explicit, 0).map_err(|e| format!("{}: {}", explicit, e))),
+        }
+    }
+
+    fn decode(&self, bytes: &[u8], format: ValueFormat, depth: usize) -> Result<Rendered, String> {
+        let tree = |label: &str, node: Node| Rendered {
+            label: label.to_string(),
+            lines: render_tree(&node),
+        };
+        match format {
+            ValueFormat::Auto | ValueFormat::Raw => Ok(plain_text(bytes)),
+            ValueFormat::Json => {
+                let text = std::str::from_utf8(bytes).map_err(|_| "not UTF-8 text".to_string())?;
+                let json: serde_json::Value =
+                    serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))?;
+                let pretty = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+                Ok(Rendered {
+                    label: "json".to_string(),
+                    lines: pretty.lines().map(|l| l.to_string()).collect(),
+                })
+            }
+            ValueFormat::MessagePack => Ok(tree("msgpack", decode_msgpack(bytes)?)),
+            ValueFormat::Cbor => Ok(tree("cbor", decode_cbor(bytes)?)),
+            ValueFormat::Bson => Ok(tree("bson", decode_bson(bytes)?)),
+            ValueFormat::JavaSerialized => Ok(tree("java", decode_java(bytes)?)),
+            ValueFormat::Protobuf => match (&self.proto, self.proto_message.trim()) {
+                (Some(schema), name) => {
+                    let message = schema.resolve_message(name)?;
+                    let node = schema.decode(bytes, &message.full_name)?;
+                    Ok(tree(&format!("protobuf {}", message.full_name), node))
+                }
+                (None, _) => Ok(tree("protobuf (no schema)", decode_protobuf_raw(bytes)?)),
+            },
+            ValueFormat::Gzip | ValueFormat::Zstd | ValueFormat::Lz4 | ValueFormat::Snappy => {
+                let inner = decompress(bytes, format)?;
+                let inner_format = detect_format(&inner).or_else(|| {
+                    (self.format == ValueFormat::Protobuf || self.proto.is_some())
+                        .then_some(ValueFormat::Protobuf)
+                });
+                let rendered = match inner_format {
+                    Some(f) if depth < 4 => self.decode(&inner, f, depth + 1).unwrap_or_else(|_| plain_text(&inner)),
+                    _ => plain_text(&inner),
+                };
+                Ok(Rendered {
+                    label: format!("{} {}→{} {}", format, bytes.len(), inner.len(), rendered.label),
+                    lines: rendered.lines,
+                })
+            }
+        }
+    }
+}
+
+/// Undecoded payload: pretty JSON, text lines or a hex dump
+fn plain_text(bytes: &[u8]) -> Rendered {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !is_binary(bytes) => {
+            if let Ok(json) = serde_json::from_str::<serde_json::Value>(text) {
+                if let Ok(pretty) = serde_json::to_string_pretty(&json) {
+                    return Rendered {
+                        label: "json".to_string(),
+                        lines: pretty.lines().map(|l| l.to_string()).collect(),
+                    };
+                }
+            }
+            Rendered {
+                label: "text".to_string(),
+                lines: text.lines().map(|l| l.to_string()).collect(),
+            }
+        }
+        _ => Rendered {
+            label: "hex".to_string(),
+            lines: format_hex(bytes).lines().map(|l| l.to_string()).collect(),
+        },
+    }
+}
+
+/// Guess the format of a value from magic bytes or a complete, structured parse.
+/// Text is never claimed here (JSON pretty-printing is handled by the caller),
+/// and Protobuf has no reliable signature so it must be selected explicitly.
+pub fn detect_format(bytes: &[u8]) -> Option<ValueFormat> {
+    if bytes.len() < 2 {
+        return None;
+    }
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        return Some(ValueFormat::Gzip);
+    }
+    if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        return Some(ValueFormat::Zstd);
+    }
+    if bytes.starts_with(&[0x04, 0x22, 0x4d, 0x18]) {
+        return Some(ValueFormat::Lz4);
+    }
+    if bytes.starts_with(SNAPPY_STREAM_MAGIC) {
+        return Some(ValueFormat::Snappy);
+    }
+    if bytes.starts_with(&[0xac, 0xed, 0x00, 0x05]) {
+        return Some(ValueFormat::JavaSerialized);
+    }
+    // Text (including JSON) is left to the caller
+    if std::str::from_utf8(bytes).is_ok() && !is_binary(bytes) {
+        return None;
+    }
+    // zlib: deflate method, 32K window and a valid header checksum
+    if bytes[0] == 0x78 && ((u16::from(bytes[0]) << 8) | u16::from(bytes[1])).is_multiple_of(31) {
+        return Some(ValueFormat::Gzip);
+    }
+    if bytes.len() >= 5
+        && i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize == bytes.len()
+        && bytes[bytes.len() - 1] == 0
+        && decode_bson(bytes).is_ok()
+    {
+        return Some(ValueFormat::Bson);
+    }
+    // MessagePack and CBOR: the whole value must parse into a map or array
+    let is_container = |node: &Node| match node {
+        Node::Array(_) | Node::Map(_) => true,
+        Node::Tagged(_, inner) => matches!(inner.as_ref(), Node::Array(_) | Node::Map(_)),
+        _ => false,
+    };
+    if decode_msgpack(bytes).map(|n| is_container(&n)).unwrap_or(false) {
+        return Some(ValueFormat::MessagePack);
+    }
+    if decode_cbor(bytes).map(|n| is_container(&n)).unwrap_or(false) {
+        return Some(ValueFormat::Cbor);
+    }
+    None
+}
+
+// ─── Byte reader ─────────────────────────────────────────────
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if n > self.remaining() {
+            return Err(format!("truncated at byte {} (need {} more)", self.pos, n));
+        }
+        let slice = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn peek(&self) -> Result<u8, String> {
+        self.buf.get(self.pos).copied().ok_or_else(|| "unexpected end of data".to_string())
+    }
+
+    fn be_u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn be_u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn be_u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    /// Length prefix that must fit in the remaining data (guards huge allocations)
+    fn count(&mut self, n: u64, min_item_size: usize) -> Result<usize, String> {
+        let n = usize::try_from(n).map_err(|_| "length overflow".to_string())?;
+        if n.saturating_mul(min_item_size) > self.remaining() {
+            return Err(format!("length {} exceeds remaining {} bytes", n, self.remaining()));
+        }
+        Ok(n)
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let b = self.u8()?;
+            value |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("varint too long".to_string())
+    }
+
+    fn cstring(&mut self) -> Result<String, String> {
+        let rest = &self.buf[self.pos..];
+        let end = rest.iter().position(|&b| b == 0).ok_or("unterminated string")?;
+        let s = String::from_utf8_lossy(&rest[..end]).to_string();
+        self.pos += end + 1;
+        Ok(s)
+    }
+}
+
+fn check_depth(depth: usize) -> Result<(), String> {
+    if depth > MAX_DEPTH {
+        Err(format!("nesting deeper than {}", MAX_DEPTH))
+    } else {
+        Ok(())
+    }
+}
+
+fn expect_end(r: &Reader) -> Result<(), String> {
+    if r.at_end() {
+        Ok(())
+    } else {
+        Err(format!("{} trailing bytes", r.remaining()))
+    }
+}
+
+// ─── MessagePack ─────────────────────────────────────────────
+
+pub fn decode_msgpack(bytes: &[u8]) -> Result<Node, String> {
+    let mut r = Reader::new(bytes);
+    let node = msgpack_value(&mut r, 0)?;
+    expect_end(&r)?;
+    Ok(node)
+}
+
+fn msgpack_value(r: &mut Reader, depth: usize) -> Result<Node, String> {
+    check_depth(depth)?;
+    let b = r.u8()?;
+    let node = match b {
+        0x00..=0x7f => Node::UInt(u64::from(b)),
+        0x80..=0x8f => msgpack_map(r, u64::from(b & 0x0f), depth)?,
+        0x90..=0x9f => msgpack_array(r, u64::from(b & 0x0f), depth)?,
+        0xa0..=0xbf => msgpack_str(r, u64::from(b & 0x1f))?,
+        0xc0 => Node::Null,
+        0xc2 => Node::Bool(false),
+        0xc3 => Node::Bool(true),
+        0xc4 => { let n = r.u8()?; Node::Bytes(r.take(n as usize)?.to_vec()) }
+        0xc5 => { let n = r.be_u16()?; Node::Bytes(r.take(n as usize)?.to_vec()) }
+        0xc6 => { let n = r.be_u32()?; Node::Bytes(r.take(n as usize)?.to_vec()) }
+        0xc7 => { let n = r.u8()? as usize; msgpack_ext(r, n)? }
+        0xc8 => { let n = r.be_u16()? as usize; msgpack_ext(r, n)? }
+        0xc9 => { let n = r.be_u32()? as usize; msgpack_ext(r, n)? }
+        0xca => Node::Float(f64::from(f32::from_bits(r.be_u32()?))),
+        0xcb => Node::Float(f64::from_bits(r.be_u64()?)),
+        0xcc => Node::UInt(u64::from(r.u8()?)),
+        0xcd => Node::UInt(u64::from(r.be_u16()?)),
+        0xce => Node::UInt(u64::from(r.be_u32()?)),
+        0xcf => Node::UInt(r.be_u64()?),
+        0xd0 => Node::Int(i64::from(r.u8()? as i8)),
+        0xd1 => Node::Int(i64::from(r.be_u16()? as i16)),
+        0xd2 => Node::Int(i64::from(r.be_u32()? as i32)),
+        0xd3 => Node::Int(r.be_u64()? as i64),
+        0xd4 => msgpack_ext(r, 1)?,
+        0xd5 => msgpack_ext(r, 2)?,
+        0xd6 => msgpack_ext(r, 4)?,
+        0xd7 => msgpack_ext(r, 8)?,
+        0xd8 => msgpack_ext(r, 16)?,
+        0xd9 => { let n = r.u8()?; msgpack_str(r, u64::from(n))? }
+        0xda => { let n = r.be_u16()?; msgpack_str(r, u64::from(n))? }
+        0xdb => { let n = r.be_u32()?; msgpack_str(r, u64::from(n))? }
+        0xdc => { let n = r.be_u16()?; msgpack_array(r, u64::from(n), depth)? }
+        0xdd => { let n = r.be_u32()?; msgpack_array(r, u64::from(n), depth)? }
+        0xde => { let n = r.be_u16()?; msgpack_map(r, u64::from(n), depth)? }
+        0xdf => { let n = r.be_u32()?; msgpack_map(r, u64::from(n), depth)? }
+        0xe0..=0xff => Node::Int(i64::from(b as i8)),
+        0xc1 => return Err("invalid MessagePack byte 0xc1".to_string()),
+    };
+    Ok(node)
+}
+
+fn msgpack_str(r: &mut Reader, n: u64) -> Result<Node, String> {
+    let n = r.count(n, 1)?;
+    let bytes = r.take(n)?;
+    std::str::from_utf8(bytes)
+        .map(|s| Node::Str(s.to_string()))
+        .map_err(|_| "invalid UTF-8 in string".to_string())
+}
+
+fn msgpack_array(r: &mut Reader, n: u64, depth: usize) -> Result<Node, String> {
+    let n = r.count(n, 1)?;
+    let items = (0..n).map(|_| msgpack_value(r, depth + 1)).collect::<Result<_, _>>()?;
+    Ok(Node::Array(items))
+}
+
+fn msgpack_map(r: &mut Reader, n: u64, depth: usize) -> Result<Node, String> {
+    let n = r.count(n, 2)?;
+    let mut entries = Vec::with_capacity(n);
+    for _ in 0..n {
+        let key = msgpack_value(r, depth + 1)?;
+        let value = msgpack_value(r, depth + 1)?;
+        entries.push((key_text(&key), value));
+    }
+    Ok(Node::Map(entries))
+}
+
+fn msgpack_ext(r: &mut Reader, n: usize) -> Result<Node, String> {
+    let ext_type = r.u8()? as i8;
+    let data = r.take(n)?;
+    // Type -1 is the standard timestamp extension
+    if ext_type == -1 {
+        let (secs, nanos) = match data.len() {
+            4 => (i64::from(u32::from_be_bytes([data[0], data[1], data[2], data[3]])), 0),
+            8 => {
+                let v = u64::from_be_bytes(data.try_into().unwrap_or_default());
+                ((v & 0x3_ffff_ffff) as i64, (v >> 34) as u32)
+            }
+            12 => (
+                i64::from_be_bytes(data[4..12].try_into().unwrap_or_default()),
+                u32::from_be_bytes([data[0], data[1], data[2], data[3]]),
+            ),
+            _ => return Err("invalid timestamp extension".to_string()),
+        };
+        let millis = secs.saturating_mul(1000).saturating_add(i64::from(nanos / 1_000_000));
+        return Ok(Node::Tagged("timestamp".to_string(), Box::new(Node::Str(format_epoch_millis(millis)))));
+    }
+    Ok(Node::Tagged(format!("ext({})", ext_type), Box::new(Node::Bytes(data.to_vec()))))
+}
+
+// ─── CBOR ────────────────────────────────────────────────────
+
+pub fn decode_cbor(bytes: &[u8]) -> Result<Node, String> {
+    let mut r = Reader::new(bytes);
+    let node = cbor_value(&mut r, 0)?;
+    expect_end(&r)?;
+    Ok(node)
+}
+
+/// Read the argument of a CBOR initial byte; None for indefinite length
+fn cbor_arg(r: &mut Reader, info: u8) -> Result<Option<u64>, String> {
+    Ok(Some(match info {
+        0..=23 => u64::from(info),
+        24 => u64::from(r.u8()?),
+        25 => u64::from(r.be_u16()?),
+        26 => u64::from(r.be_u32()?),
+        27 => r.be_u64()?,
+        31 => return Ok(None),
+        _ => return Err(format!("reserved CBOR additional info {}", info)),
+    }))
+}
+
+fn cbor_value(r: &mut Reader, depth: usize) -> Result<Node, String> {
+    check_depth(depth)?;
+    let b = r.u8()?;
+    let (major, info) = (b >> 5, b & 0x1f);
+    if major == 7 {
+        return match info {
+            20 => Ok(Node::Bool(false)),
+            21 => Ok(Node::Bool(true)),
+            22 | 23 => Ok(Node::Null),
+            24 => Ok(Node::Tagged("simple".to_string(), Box::new(Node::UInt(u64::from(r.u8()?))))),
+            25 => Ok(Node::Float(f16_to_f64(r.be_u16()?))),
+            26 => Ok(Node::Float(f64::from(f32::from_bits(r.be_u32()?)))),
+            27 => Ok(Node::Float(f64::from_bits(r.be_u64()?))),
+            0..=19 => Ok(Node::Tagged("simple".to_string(), Box::new(Node::UInt(u64::from(info))))),
+            _ => Err("unexpected CBOR break".to_string()),
+        };
+    }
+    let arg = cbor_arg(r, info)?;
+    match (major, arg) {
+        (0, Some(n)) => Ok(Node::UInt(n)),
+        (1, Some(n)) => Ok(match i64::try_from(n) {
+            Ok(n) => Node::Int(-1 - n),
+            Err(_) => Node::Str(format!("-{}", u128::from(n) + 1)),
+        }),
+        (2, _) | (3, _) => {
+            let bytes = match arg {
+                Some(n) => {
+                    let n = r.count(n, 1)?;
+                    r.take(n)?.to_vec()
+                }
+                None => {
+                    // Indefinite length: definite-length chunks until break
+                    let mut out = Vec::new();
+                    while r.peek()? != 0xff {
+                        match cbor_value(r, depth + 1)? {
+                            Node::Bytes(chunk) => out.extend(chunk),
+                            Node::Str(chunk) => out.extend(chunk.into_bytes()),
+                            _ => return Err("invalid chunk in indefinite string".to_string()),
+                        }
+                    }
+                    r.u8()?;
+                    out
+                }
+            };
+            if major == 2 {
+                Ok(Node::Bytes(bytes))
+            } else {
+                String::from_utf8(bytes)
+                    .map(Node::Str)
+                    .map_err(|_| "invalid UTF-8 in text string".to_string())
+            }
+        }
+        (4, _) => {
+            let mut items = Vec::new();
+            match arg {
+                Some(n) => {
+                    for _ in 0..r.count(n, 1)? {
+                        items.push(cbor_value(r, depth + 1)?);
+                    }
+                }
+                None => {
+                    while r.peek()? != 0xff {
+                        items.push(cbor_value(r, depth + 1)?);
+                    }
+                    r.u8()?;
+                }
+            }
+            Ok(Node::Array(items))
+        }
+        (5, _) => {
+            let mut entries = Vec::new();
+            let mut entry = |r: &mut Reader| -> Result<(), String> {
+                let key = cbor_value(r, depth + 1)?;
+                let value = cbor_value(r, depth + 1)?;
+                entries.push((key_text(&key), value));
+                Ok(())
+            };
+            match arg {
+                Some(n) => {
+                    for _ in 0..r.count(n, 2)? {
+                        entry(r)?;
+                    }
+                }
+                None => {
+                    while r.peek()? != 0xff {
+                        entry(r)?;
+                    }
+                    r.u8()?;
+                }
+            }
+            Ok(Node::Map(entries))
+        }
+        (6, Some(tag)) => {
+            let inner = cbor_value(r, depth + 1)?;
+            Ok(match (tag, inner) {
+                // Self-described CBOR marker
+                (55799, inner) => inner,
+                (1, Node::UInt(secs)) => Node::Tagged(
+                    "epoch".to_string(),
+                    Box::new(Node::Str(format_epoch_millis((secs as i64).saturating_mul(1000)))),
+                ),
+                (0, inner) => Node::Tagged("datetime".to_string(), Box::new(inner)),
+                (2, inner) => Node::Tagged("bignum".to_string(), Box::new(inner)),
+                (3, inner) => Node::Tagged("-bignum".to_string(), Box::new(inner)),
+                (37, inner) => Node::Tagged("uuid".to_string(), Box::new(inner)),
+                (tag, inner) => Node::Tagged(format!("tag({})", tag), Box::new(inner)),
+            })
+        }
+        _ => Err(format!("invalid CBOR initial byte 0x{:02x}", b)),
+    }
+}
+
+/// IEEE 754 half precision to f64
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = i32::from((bits >> 10) & 0x1f);
+    let mant = f64::from(bits & 0x3ff);
+    sign * match exp {
+        0 => mant * 2f64.powi(-24),
+        31 if mant == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + mant / 1024.0) * 2f64.powi(exp - 15),
+    }
+}
+
+// ─── BSON ────────────────────────────────────────────────────
+
+pub fn decode_bson(bytes: &[u8]) -> Result<Node, String> {
+    let mut r = Reader::new(bytes);
+    let node = bson_document(&mut r, false, 0)?;
+    expect_end(&r)?;
+    Ok(node)
+}
+
+fn bson_i32(r: &mut Reader) -> Result<i32, String> {
+    Ok(i32::from_le_bytes(r.array()?))
+}
+
+fn bson_string(r: &mut Reader) -> Result<String, String> {
+    let len = bson_i32(r)?;
+    if len < 1 {
+        return Err("invalid BSON string length".to_string());
+    }
+    let bytes = r.take(len as usize)?;
+    Ok(String::from_utf8_lossy(&bytes[..bytes.len() - 1]).to_string())
+}
+
+fn bson_document(r: &mut Reader, is_array: bool, depth: usize) -> Result<Node, String> {
+    check_depth(depth)?;
+    let start = r.pos;
+    let len = bson_i32(r)?;
+    if len < 5 || start + len as usize > r.buf.len() {
+        return Err("invalid BSON document length".to_string());
+    }
+    let end = start + len as usize;
+    let mut entries = Vec::new();
+    loop {
+        let t = r.u8()?;
+        if t == 0 {
+            break;
+        }
+        let name = r.cstring()?;
+        let value = match t {
+            0x01 => Node::Float(f64::from_le_bytes(r.array()?)),
+            0x02 => Node::Str(bson_string(r)?),
+            0x03 => bson_document(r, false, depth + 1)?,
+            0x04 => bson_document(r, true, depth + 1)?,
+            0x05 => {
+                let n = bson_i32(r)?;
+                let subtype = r.u8()?;
+                let data = r.take(usize::try_from(n).map_err(|_| "invalid binary length")?)?;
+                match subtype {
+                    0x04 if data.len() == 16 => Node::Tagged("uuid".to_string(), Box::new(Node::Str(hex_string(data)))),
+                    0x00 => Node::Bytes(data.to_vec()),
+                    s => Node::Tagged(format!("binary({})", s), Box::new(Node::Bytes(data.to_vec()))),
+                }
+            }
+            0x06 => Node::Tagged("undefined".to_string(), Box::new(Node::Null)),
+            0x07 => Node::Tagged("ObjectId".to_string(), Box::new(Node::Str(hex_string(r.take(12)?)))),
+            0x08 => Node::Bool(r.u8()? != 0),
+            0x09 => Node::Tagged(
+                "date".to_string(),
+                Box::new(Node::Str(format_epoch_millis(i64::from_le_bytes(r.array()?)))),
+            ),
+            0x0a => Node::Null,
+            0x0b => {
+                let pattern = r.cstring()?;
+                let options = r.cstring()?;
+                Node::Tagged("regex".to_string(), Box::new(Node::Str(format!("/{}/{}", pattern, options))))
+            }
+            0x0c => {
+                let ns = bson_string(r)?;
+                let id = hex_string(r.take(12)?);
+                Node::Tagged("DBPointer".to_string(), Box::new(Node::Str(format!("{} {}", ns, id))))
+            }
+            0x0d => Node::Tagged("code".to_string(), Box::new(Node::Str(bson_string(r)?))),
+            0x0e => Node::Tagged("symbol".to_string(), Box::new(Node::Str(bson_string(r)?))),
+            0x0f => {
+                bson_i32(r)?;
+                let code = bson_string(r)?;
+                let scope = bson_document(r, false, depth + 1)?;
+                Node::Tagged(
+                    "code_w_scope".to_string(),
+                    Box::new(Node::Map(vec![("code".to_string(), Node::Str(code)), ("scope".to_string(), scope)])),
+                )
+            }
+            0x10 => Node::Int(i64::from(bson_i32(r)?)),
+            0x11 => {
+                let v = u64::from_le_bytes(r.array()?);
+                Node::Tagged("timestamp".to_string(), Box::new(Node::Str(format!("{}:{}", v >> 32, v & 0xffff_ffff))))
+            }
+            0x12 => Node::Int(i64::from_le_bytes(r.array()?)),
+            0x13 => Node::Tagged("decimal128".to_string(), Box::new(Node::Bytes(r.take(16)?.to_vec()))),
+            0x7f => Node::Tagged("MaxKey".to_string(), Box::new(Node::Null)),
+            0xff => Node::Tagged("MinKey".to_string(), Box::new(Node::Null)),
+            t => return Err(format!("unknown BSON element type 0x{:02x}", t)),
+        };
+        entries.push((name, value));
+    }
+    if r.pos != end {
+        return Err("BSON document length mismatch".to_string());
+    }
+    Ok(if is_array {
+        Node::Array(entries.into_iter().map(|(_, v)| v).collect())
+    } else {
+        Node::Map(entries)
+    })
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The current time in milliseconds since the Unix epoch
+pub fn now_epoch_millis() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as i64)
+}
+
+/// Milliseconds since the Unix epoch as an ISO-8601 UTC timestamp
+pub fn format_epoch_millis(ms: i64) -> String {
+    let days = ms.div_euclid(86_400_000);
+    let rem = ms.rem_euclid(86_400_000);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        rem / 3_600_000,
+        rem / 60_000 % 60,
+        rem / 1000 % 60,
+        rem % 1000
+    )
+}
+
+// ─── Protobuf ────────────────────────────────────────────────
+
+/// A field value read from the protobuf wire format
+enum Wire<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+    Group(&'a [u8]),
+}
+
+/// Read one field: (field number, value)
+fn proto_field<'a>(r: &mut Reader<'a>) -> Result<(u64, Wire<'a>), String> {
+    proto_field_at(r, 0)
+}
+
+/// Read one field, `depth` groups deep
+fn proto_field_at<'a>(r: &mut Reader<'a>, depth: usize) -> Result<(u64, Wire<'a>), String> {
+    check_depth(depth)?;
+    let key = r.varint()?;
+    let (number, wire_type) = (key >> 3, key & 7);
+    if number == 0 || number > 536_870_911 {
+        return Err(format!("invalid field number {}", number));
+    }
+    let value = match wire_type {
+        0 => Wire::Varint(r.varint()?),
+        1 => Wire::Fixed64(u64::from_le_bytes(r.array()?)),
+        2 => {
+            let n = r.varint()?;
+            let n = r.count(n, 1)?;
+            Wire::Bytes(r.take(n)?)
+        }
+        3 => {
+            // Group: nested fields up to the matching end-group tag
+            let start = r.pos;
+            loop {
+                let before = r.pos;
+                let inner_key = r.varint()?;
+                if inner_key & 7 == 4 {
+                    if inner_key >> 3 != number {
+                        return Err("mismatched end-group tag".to_string());
+                    }
+                    break Wire::Group(&r.buf[start..before]);
+                }
+                r.pos = before;
+                proto_field_at(r, depth + 1)?;
+            }
+        }
+        4 => return Err("unexpected end-group tag".to_string()),
+        5 => Wire::Fixed32(u32::from_le_bytes(r.array()?)),
+        t => return Err(format!("invalid wire type {}", t)),
+    };
+    Ok((number, value))
+}
+
+/// Decode a protobuf message without a schema: field numbers as keys,
+/// length-delimited fields shown as text, nested messages or bytes.
+pub fn decode_protobuf_raw(bytes: &[u8]) -> Result<Node, String> {
+    proto_raw_message(bytes, 0)
+}
+
+fn proto_raw_message(bytes: &[u8], depth: usize) -> Result<Node, String> {
+    check_depth(depth)?;
+    let mut r = Reader::new(bytes);
+    let mut entries = Vec::new();
+    while !r.at_end() {
+        let (number, wire) = proto_field(&mut r)?;
+        let value = match wire {
+            Wire::Varint(v) => Node::UInt(v),
+            Wire::Fixed64(v) => {
+                let f = f64::from_bits(v);
+                if f.is_finite() && f != 0.0 && (1e-9..1e15).contains(&f.abs()) {
+                    Node::Float(f)
+                } else {
+                    Node::UInt(v)
+                }
+            }
+            Wire::Fixed32(v) => {
+                let f = f32::from_bits(v);
+                if f.is_finite() && f != 0.0 && (1e-9..1e15).contains(&f.abs()) {
+                    Node::Float(f64::from(f))
+                } else {
+                    Node::UInt(u64::from(v))
+                }
+            }
+            Wire::Bytes(b) => proto_raw_bytes(b, depth),
+            Wire::Group(b) => proto_raw_message(b, depth + 1)?,
+        };
+        entries.push((number.to_string(), value));
+    }
+    Ok(Node::Map(entries))
+}
+
+/// A length-delimited field of unknown type: printable text first, then a
+/// nested message, else raw bytes
+fn proto_raw_bytes(bytes: &[u8], depth: usize) -> Node {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        if !bytes.is_empty() && !is_binary(bytes) {
+            return Node::Str(s.to_string());
+        }
+    }
+    match proto_raw_message(bytes, depth + 1) {
+        Ok(node) if !bytes.is_empty() => node,
+        _ => Node::Bytes(bytes.to_vec()),
+    }
+}
+
+/// Scalar/compound type of a protobuf field
+#[derive(Debug, Clone, PartialEq)]
+enum ProtoKind {
+    Double,
+    Float,
+    Int64,
+    UInt64,
+    Int32,
+    Fixed64,
+    Fixed32,
+    Bool,
+    String,
+    Bytes,
+    UInt32,
+    SFixed32,
+    SFixed64,
+    SInt32,
+    SInt64,
+    /// Message or enum by (possibly unresolved) name
+    Named(String),
+    Message(String),
+    Enum(String),
+}
+
+impl ProtoKind {
+    fn from_scalar_name(name: &str) -> Option<ProtoKind> {
+        Some(match name {
+            "double" => ProtoKind::Double,
+            "float" => ProtoKind::Float,
+            "int64" => ProtoKind::Int64,
+            "uint64" => ProtoKind::UInt64,
+            "int32" => ProtoKind::Int32,
+            "fixed64" => ProtoKind::Fixed64,
+            "fixed32" => ProtoKind::Fixed32,
+            "bool" => ProtoKind::Bool,
+            "string" => ProtoKind::String,
+            "bytes" => ProtoKind::Bytes,
+            "uint32" => ProtoKind::UInt32,
+            "sfixed32" => ProtoKind::SFixed32,
+            "sfixed64" => ProtoKind::SFixed64,
+            "sint32" => ProtoKind::SInt32,
+            "sint64" => ProtoKind::SInt64,
+            _ => return None,
+        })
+    }
+
+    /// FieldDescriptorProto.Type numbers
+    fn from_descriptor(type_id: u64, type_name: &str) -> ProtoKind {
+        match type_id {
+            1 => ProtoKind::Double,
+            2 => ProtoKind::Float,
+            3 => ProtoKind::Int64,
+            4 => ProtoKind::UInt64,
+            5 => ProtoKind::Int32,
+            6 => ProtoKind::Fixed64,
+            7 => ProtoKind::Fixed32,
+            8 => ProtoKind::Bool,
+            9 => ProtoKind::String,
+            10 | 11 => ProtoKind::Message(type_name.trim_start_matches('.').to_string()),
+            12 => ProtoKind::Bytes,
+            13 => ProtoKind::UInt32,
+            14 => ProtoKind::Enum(type_name.trim_start_matches('.').to_string()),
+            15 => ProtoKind::SFixed32,
+            16 => ProtoKind::SFixed64,
+            17 => ProtoKind::SInt32,
+            18 => ProtoKind::SInt64,
+            _ => ProtoKind::Named(type_name.trim_start_matches('.').to_string()),
+        }
+    }
+
+    /// Numeric kinds may be packed into a single length-delimited field
+    fn is_packable(&self) -> bool {
+        !matches!(
+            self,
+            ProtoKind::String | ProtoKind::Bytes | ProtoKind::Message(_) | ProtoKind::Named(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ProtoField {
+    number: u64,
+    name: String,
+    kind: ProtoKind,
+    repeated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProtoMessage {
+    /// Fully-qualified name without the leading dot, e.g. `pkg.Outer.Inner`
+    pub full_name: String,
+    fields: Vec<ProtoField>,
+    /// Synthetic `map<K, V>` entry type
+    map_entry: bool,
+}
+
+#[derive(Debug, Clone)]
+struct ProtoEnum {
+    full_name: String,
+    values: Vec<(i64, String)>,
+}
+
+/// Message and enum definitions from a `.proto` file or a compiled descriptor set
+#[derive(Debug, Clone, Default)]
+pub struct ProtoSchema {
+    pub messages: Vec<ProtoMessage>,
+    enums: Vec<ProtoEnum>,
+}
+
+impl ProtoSchema {
+    /// Load a `.proto` source file (imports resolved next to it) or a
+    /// `FileDescriptorSet` written by `protoc --descriptor_set_out`
+    pub fn load(path: &str) -> Result<ProtoSchema, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Cannot read '{}': {}", path, e))?;
+        let mut schema = ProtoSchema::default();
+        if path.ends_with(".proto") {
+            let mut seen = Vec::new();
+            schema.parse_proto_file(std::path::Path::new(path), &mut seen)?;
+        } else {
+            schema.parse_descriptor_set(&bytes)?;
+        }
+        schema.resolve_names();
+        if schema.messages.is_empty() {
+            return Err(format!("No message types in '{}'", path));
+        }
+        Ok(schema)
+    }
+
+    /// Find a message by full name or unique suffix; an empty name picks the
+    /// only top-level message, if there is exactly one
+    pub fn resolve_message(&self, name: &str) -> Result<&ProtoMessage, String> {
+        let name = name.trim().trim_start_matches('.');
+        if name.is_empty() {
+            let top: Vec<&ProtoMessage> = self
+                .messages
+                .iter()
+                .filter(|m| !m.map_entry && !self.messages.iter().any(|o| m.full_name.starts_with(&format!("{}.", o.full_name))))
+                .collect();
+            return match top.as_slice() {
+                [only] => Ok(only),
+                _ => Err(format!("Set a message type ({})", self.message_names().join(", "))),
+            };
+        }
+        if let Some(m) = self.messages.iter().find(|m| m.full_name == name) {
+            return Ok(m);
+        }
+        let suffix = format!(".{}", name);
+        let matches: Vec<&ProtoMessage> =
+            self.messages.iter().filter(|m| m.full_name.ends_with(&suffix)).collect();
+        match matches.as_slice() {
+            [only] => Ok(only),
+            [] => Err(format!("Unknown message type '{}'", name)),
+            _ => Err(format!("Ambiguous message type '{}'", name)),
+        }
+    }
+
+    /// Names of the message types that can be selected
+    pub fn message_names(&self) -> Vec<&str> {
+        self.messages
+            .iter()
+            .filter(|m| !m.map_entry)
+            .map(|m| m.full_name.as_str())
+            .collect()
+    }
+
+    fn find_message(&self, full_name: &str) -> Option<&ProtoMessage> {
+        self.messages.iter().find(|m| m.full_name == full_name)
+    }
+
+    fn find_enum(&self, full_name: &str) -> Option<&ProtoEnum> {
+        self.enums.iter().find(|e| e.full_name == full_name)
+    }
+
+    /// Decode a message of the given type
+    pub fn decode(&self, bytes: &[u8], message: &str) -> Result<Node, String> {
+        self.decode_message(bytes, message, 0)
+    }
+
+    fn decode_message(&self, bytes: &[u8], message: &str, depth: usize) -> Result<Node, String> {
+        check_depth(depth)?;
+        let msg = match self.find_message(message) {
+            Some(m) => m,
+            None => return proto_raw_message(bytes, depth),
+        };
+        let mut r = Reader::new(bytes);
+        let mut entries: Vec<(String, Node)> = Vec::new();
+        while !r.at_end() {
+            let (number, wire) = proto_field(&mut r)?;
+            let field = match msg.fields.iter().find(|f| f.number == number) {
+                Some(f) => f,
+                None => {
+                    // Unknown field: keep it under its number
+                    let value = match wire {
+                        Wire::Varint(v) | Wire::Fixed64(v) => Node::UInt(v),
+                        Wire::Fixed32(v) => Node::UInt(u64::from(v)),
+                        Wire::Bytes(b) => proto_raw_bytes(b, depth),
+                        Wire::Group(b) => proto_raw_message(b, depth + 1)?,
+                    };
+                    entries.push((number.to_string(), value));
+                    continue;
+                }
+            };
+            let values = match wire {
+                Wire::Bytes(b) if field.kind.is_packable() => {
+                    // Packed repeated scalars
+                    let mut pr = Reader::new(b);
+                    let mut out = Vec::new();
+                    while !pr.at_end() {
+                        let w = match field.kind {
+                            ProtoKind::Double | ProtoKind::Fixed64 | ProtoKind::SFixed64 => {
+                                Wire::Fixed64(u64::from_le_bytes(pr.array()?))
+                            }
+                            ProtoKind::Float | ProtoKind::Fixed32 | ProtoKind::SFixed32 => {
+                                Wire::Fixed32(u32::from_le_bytes(pr.array()?))
+                            }
+                            _ => Wire::Varint(pr.varint()?),
+                        };
+                        out.push(self.proto_scalar(&field.kind, w));
+                    }
+                    out
+                }
+                Wire::Bytes(b) => vec![match &field.kind {
+                    ProtoKind::String => Node::Str(String::from_utf8_lossy(b).to_string()),
+                    ProtoKind::Message(name) => self.decode_message(b, name, depth + 1)?,
+                    ProtoKind::Named(_) => proto_raw_bytes(b, depth),
+                    _ => Node::Bytes(b.to_vec()),
+                }],
+                Wire::Group(b) => vec![match &field.kind {
+                    ProtoKind::Message(name) => self.decode_message(b, name, depth + 1)?,
+                    _ => proto_raw_message(b, depth + 1)?,
+                }],
+                w => vec![self.proto_scalar(&field.kind, w)],
+            };
+            let map_entry = match &field.kind {
+                ProtoKind::Message(name) => self.find_message(name).map(|m| m.map_entry).unwrap_or(false),
+                _ => false,
+            };
+            match entries.iter_mut().find(|(k, _)| *k == field.name) {
+                Some((_, existing)) if field.repeated => match existing {
+                    Node::Array(items) => items.extend(values),
+                    Node::Map(pairs) if map_entry => pairs.extend(values.into_iter().filter_map(map_pair)),
+                    _ => {}
+                },
+                Some((_, existing)) => {
+                    if let Some(v) = values.into_iter().next_back() {
+                        *existing = v;
+                    }
+                }
+                None if map_entry => {
+                    entries.push((field.name.clone(), Node::Map(values.into_iter().filter_map(map_pair).collect())));
+                }
+                None if field.repeated => entries.push((field.name.clone(), Node::Array(values))),
+                None => {
+                    if let Some(v) = values.into_iter().next_back() {
+                        entries.push((field.name.clone(), v));
+                    }
+                }
+            }
+        }
+        Ok(Node::Map(entries))
+    }
+
+    fn proto_scalar(&self, kind: &ProtoKind, wire: Wire) -> Node {
+        let raw = match wire {
+            Wire::Varint(v) | Wire::Fixed64(v) => v,
+            Wire::Fixed32(v) => u64::from(v),
+            Wire::Bytes(b) | Wire::Group(b) => return Node::Bytes(b.to_vec()),
+        };
+        match kind {
+            ProtoKind::Double => Node::Float(f64::from_bits(raw)),
+            ProtoKind::Float => Node::Float(f64::from(f32::from_bits(raw as u32))),
+            ProtoKind::Int64 | ProtoKind::SFixed64 => Node::Int(raw as i64),
+            ProtoKind::Int32 | ProtoKind::SFixed32 => Node::Int(i64::from(raw as i32)),
+            ProtoKind::SInt32 | ProtoKind::SInt64 => Node::Int((raw >> 1) as i64 ^ -((raw & 1) as i64)),
+            ProtoKind::Bool => Node::Bool(raw != 0),
+            ProtoKind::Enum(name) => {
+                let value = i64::from(raw as i32);
+                match self
+                    .find_enum(name)
+                    .and_then(|e| e.values.iter().find(|(n, _)| *n == value))
+                {
+                    Some((_, label)) => Node::Str(label.clone()),
+                    None => Node::Int(value),
+                }
+            }
+            _ => Node::UInt(raw),
+        }
+    }
+
+    /// Turn field type names into resolved message/enum references using
+    /// protobuf scoping: innermost enclosing scope first
+    fn resolve_names(&mut self) {
+        let message_names: Vec<String> = self.messages.iter().map(|m| m.full_name.clone()).collect();
+        let enum_names: Vec<String> = self.enums.iter().map(|e| e.full_name.clone()).collect();
+        for msg in &mut self.messages {
+            for field in &mut msg.fields {
+                let name = match &field.kind {
+                    ProtoKind::Named(n) => n.clone(),
+                    _ => continue,
+                };
+                let mut scope = msg.full_name.clone();
+                let resolved = loop {
+                    let candidate = if scope.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", scope, name)
+                    };
+                    if message_names.contains(&candidate) {
+                        break Some(ProtoKind::Message(candidate));
+                    }
+                    if enum_names.contains(&candidate) {
+                        break Some(ProtoKind::Enum(candidate));
+                    }
+                    if scope.is_empty() {
+                        break None;
+                    }
+                    scope = scope.rsplit_once('.').map(|(s, _)| s.to_string()).unwrap_or_default();
+                };
+                if let Some(kind) = resolved {
+                    field.kind = kind;
+                }
+            }
+        }
+    }
+
+    // ── Descriptor sets ──
+
+    fn parse_descriptor_set(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let mut r = Reader::new(bytes);
+        let mut files = 0;
+        while !r.at_end() {
+            if let (1, Wire::Bytes(file)) = proto_field(&mut r)? {
+                self.parse_file_descriptor(file)?;
+                files += 1;
+            }
+        }
+        if files == 0 {
+            return Err("Not a descriptor set (FileDescriptorSet)".to_string());
+        }
+        Ok(())
+    }
+
+    fn parse_file_descriptor(&mut self, bytes: &[u8]) -> Result<(), String> {
+        let mut package = String::new();
+        let mut messages = Vec::new();
+        let mut enums = Vec::new();
+        let mut r = Reader::new(bytes);
+        while !r.at_end() {
+            match proto_field(&mut r)? {
+                (2, Wire::Bytes(b)) => package = String::from_utf8_lossy(b).to_string(),
+                (4, Wire::Bytes(b)) => messages.push(b),
+                (5, Wire::Bytes(b)) => enums.push(b),
+                _ => {}
+            }
+        }
+        for b in enums {
+            self.parse_enum_descriptor(b, &package)?;
+        }
+        for b in messages {
+            self.parse_message_descriptor(b, &package)?;
+        }
+        Ok(())
+    }
+
+    fn parse_message_descriptor(&mut self, bytes: &[u8], scope: &str) -> Result<(), String> {
+        let mut name = String::new();
+        let mut fields = Vec::new();
+        let mut nested = Vec::new();
+        let mut enums = Vec::new();
+        let mut map_entry = false;
+        let mut r = Reader::new(bytes);
+        while !r.at_end() {
+            match proto_field(&mut r)? {
+                (1, Wire::Bytes(b)) => name = String::from_utf8_lossy(b).to_string(),
+                (2, Wire::Bytes(b)) => fields.push(parse_field_descriptor(b)?),
+                (3, Wire::Bytes(b)) => nested.push(b),
+                (4, Wire::Bytes(b)) => enums.push(b),
+                (7, Wire::Bytes(b)) => {
+                    // MessageOptions.map_entry = 7
+                    let mut o = Reader::new(b);
+                    while !o.at_end() {
+                        if let (7, Wire::Varint(v)) = proto_field(&mut o)? {
+                            map_entry = v != 0;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        let full_name = qualify(scope, &name);
+        for b in enums {
+            self.parse_enum_descriptor(b, &full_name)?;
+        }
+        for b in nested {
+            self.parse_message_descriptor(b, &full_name)?;
+        }
+        self.messages.push(ProtoMessage { full_name, fields, map_entry });
+        Ok(())
+    }
+
+    fn parse_enum_descriptor(&mut self, bytes: &[u8], scope: &str) -> Result<(), String> {
+        let mut name = String::new();
+        let mut values = Vec::new();
+        let mut r = Reader::new(bytes);
+        while !r.at_end() {
+            match proto_field(&mut r)? {
+                (1, Wire::Bytes(b)) => name = String::from_utf8_lossy(b).to_string(),
+                (2, Wire::Bytes(b)) => {
+                    let mut v = Reader::new(b);
+                    let (mut label, mut number) = (String::new(), 0i64);
+                    while !v.at_end() {
+                        match proto_field(&mut v)? {
+                            (1, Wire::Bytes(s)) => label = String::from_utf8_lossy(s).to_string(),
+                            (2, Wire::Varint(n)) => number = i64::from(n as i32),
+                            _ => {}
+                        }
+                    }
+                    values.push((number, label));
+                }
+                _ => {}
+            }
+        }
+        self.enums.push(ProtoEnum { full_name: qualify(scope, &name), values });
+        Ok(())
+    }
+
+    // ── .proto sources ──
+
+    fn parse_proto_file(&mut self, path: &std::path::Path, seen: &mut Vec<std::path::PathBuf>) -> Result<(), String> {
+        if seen.iter().any(|p| p == path) {
+            return Ok(());
+        }
+        seen.push(path.to_path_buf());
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read '{}': {}", path.display(), e))?;
+        let tokens = tokenize_proto(&text);
+        let mut p = ProtoParser { tokens: &tokens, pos: 0 };
+        let mut package = String::new();
+        while let Some(tok) = p.next() {
+            match tok {
+                "syntax" | "edition" | "option" => p.skip_statement(),
+                "package" => {
+                    package = p.next().unwrap_or("").to_string();
+                    p.skip_statement();
+                }
+                "import" => {
+                    let mut target = p.next().unwrap_or("");
+                    if target == "public" || target == "weak" {
+                        target = p.next().unwrap_or("");
+                    }
+                    p.skip_statement();
+                    // Imports are resolved next to the importing file; missing ones are skipped
+                    let import = path.parent().unwrap_or(std::path::Path::new(".")).join(target.trim_matches('"'));
+                    if import.exists() {
+                        self.parse_proto_file(&import, seen)?;
+                    }
+                }
+                "message" => self.parse_proto_message(&mut p, &package)?,
+                "enum" => self.parse_proto_enum(&mut p, &package)?,
+                "service" | "extend" => p.skip_block(),
+                ";" => {}
+                other => return Err(format!("{}: unexpected '{}'", path.display(), other)),
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_proto_message(&mut self, p: &mut ProtoParser, scope: &str) -> Result<(), String> {
+        let name = p.next().ok_or("expected message name")?.to_string();
+        let full_name = qualify(scope, &name);
+        p.expect("{")?;
+        let mut fields = Vec::new();
+        let mut oneof_depth = 0;
+        loop {
+            let tok = p.next().ok_or_else(|| format!("unterminated message {}", name))?;
+            match tok {
+                "}" if oneof_depth > 0 => oneof_depth -= 1,
+                "}" => break,
+                ";" => {}
+                "message" => self.parse_proto_message(p, &full_name)?,
+                "enum" => self.parse_proto_enum(p, &full_name)?,
+                "option" | "reserved" | "extensions" => p.skip_statement(),
+                "extend" => p.skip_block(),
+                "oneof" => {
+                    p.next();
+                    p.expect("{")?;
+                    oneof_depth += 1;
+                }
+                "map" => {
+                    // map<K, V> name = N;  →  repeated NameEntry { K key = 1; V value = 2; }
+                    p.expect("<")?;
+                    let key_type = p.next().ok_or("expected map key type")?.to_string();
+                    p.expect(",")?;
+                    let value_type = p.next().ok_or("expected map value type")?.to_string();
+                    p.expect(">")?;
+                    let field_name = p.next().ok_or("expected map field name")?.to_string();
+                    p.expect("=")?;
+                    let number = p.number()?;
+                    p.skip_statement();
+                    let entry_name = format!("{}Entry", upper_camel(&field_name));
+                    self.messages.push(ProtoMessage {
+                        full_name: qualify(&full_name, &entry_name),
+                        fields: vec![
+                            ProtoField { number: 1, name: "key".to_string(), kind: proto_kind(&key_type), repeated: false },
+                            ProtoField { number: 2, name: "value".to_string(), kind: proto_kind(&value_type), repeated: false },
+                        ],
+                        map_entry: true,
+                    });
+                    fields.push(ProtoField { number, name: field_name, kind: ProtoKind::Named(entry_name), repeated: true });
+                }
+                _ => {
+                    let (repeated, type_name) = match tok {
+                        "repeated" => (true, p.next().ok_or("expected field type")?),
+                        "optional" | "required" => (false, p.next().ok_or("expected field type")?),
+                        t => (false, t),
+                    };
+                    if type_name == "group" {
+                        return Err("proto2 groups are not supported".to_string());
+                    }
+                    let field_name = p.next().ok_or("expected field name")?.to_string();
+                    p.expect("=")?;
+                    let number = p.number()?;
+                    p.skip_statement();
+                    fields.push(ProtoField { number, name: field_name, kind: proto_kind(type_name), repeated });
+                }
+            }
+        }
+        self.messages.push(ProtoMessage { full_name, fields, map_entry: false });
+        Ok(())
+    }
+
+    fn parse_proto_enum(&mut self, p: &mut ProtoParser, scope: &str) -> Result<(), String> {
+        let name = p.next().ok_or("expected enum name")?.to_string();
+        p.expect("{")?;
+        let mut values = Vec::new();
+        loop {
+            let tok = p.next().ok_or_else(|| format!("unterminated enum {}", name))?;
+            match tok {
+                "}" => break,
+                ";" => {}
+                "option" | "reserved" => p.skip_statement(),
+                label => {
+                    let label = label.to_string();
+                    p.expect("=")?;
+                    let neg = p.peek() == Some("-");
+                    if neg {
+                        p.next();
+                    }
+                    let n = p.number()? as i64;
+                    values.push((if neg { -n } else { n }, label));
+                    p.skip_statement();
+                }
+            }
+        }
+        self.enums.push(ProtoEnum { full_name: qualify(scope, &name), values });
+        Ok(())
+    }
+}
+
+fn parse_field_descriptor(bytes: &[u8]) -> Result<ProtoField, String> {
+    let (mut name, mut number, mut label, mut type_id, mut type_name) = (String::new(), 0, 1, 0, String::new());
+    let mut r = Reader::new(bytes);
+    while !r.at_end() {
+        match proto_field(&mut r)? {
+            (1, Wire::Bytes(b)) => name = String::from_utf8_lossy(b).to_string(),
+            (3, Wire::Varint(v)) => number = v,
+            (4, Wire::Varint(v)) => label = v,
+            (5, Wire::Varint(v)) => type_id = v,
+            (6, Wire::Bytes(b)) => type_name = String::from_utf8_lossy(b).to_string(),
+            _ => {}
+        }
+    }
+    Ok(ProtoField {
+        number,
+        name,
+        kind: ProtoKind::from_descriptor(type_id, &type_name),
+        // LABEL_REPEATED = 3
+        repeated: label == 3,
+    })
+}
+
+/// Map entry message → (key text, value)
+fn map_pair(entry: Node) -> Option<(String, Node)> {
+    match entry {
+        Node::Map(mut fields) => {
+            let value = fields.iter().position(|(k, _)| k == "value").map(|i| fields.remove(i).1);
+            let key = fields.iter().find(|(k, _)| k == "key").map(|(_, v)| key_text(v));
+            Some((key.unwrap_or_default(), value.unwrap_or(Node::Null)))
+        }
+        _ => None,
+    }
+}
+
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+fn proto_kind(type_name: &str) -> ProtoKind {
+    ProtoKind::from_scalar_name(type_name)
+        .unwrap_or_else(|| ProtoKind::Named(type_name.trim_start_matches('.').to_string()))
+}
+
+/// `foo_bar` → `FooBar`, as protoc names map entry types
+fn upper_camel(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(c) => c.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Split `.proto` source into identifiers, numbers, strings and symbols
+fn tokenize_proto(text: &str) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '/' && chars.get(i + 1) == Some(&'/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '"' || c == '\'' {
+            let start = i;
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                if chars[i] == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(chars[start..i.min(chars.len())].iter().collect());
+        } else if c.is_alphanumeric() || c == '_' || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(chars[start..i].iter().collect());
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+    tokens
+}
+
+struct ProtoParser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'a> ProtoParser<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        let tok = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(tok.as_str())
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(|t| t.as_str())
+    }
+
+    fn expect(&mut self, want: &str) -> Result<(), String> {
+        match self.next() {
+            Some(tok) if tok == want => Ok(()),
+            Some(tok) => Err(format!("expected '{}', found '{}'", want, tok)),
+            None => Err(format!("expected '{}'", want)),
+        }
+    }
+
+    fn number(&mut self) -> Result<u64, String> {
+        let tok = self.next().ok_or("expected number")?;
+        let parsed = match tok.strip_prefix("0x").or_else(|| tok.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => tok.parse(),
+        };
+        parsed.map_err(|_| format!("invalid number '{}'", tok))
+    }
+
+    /// Skip to the end of the current statement (`;`), stepping over `[...]`/`{...}`
+    fn skip_statement(&mut self) {
+        let mut depth = 0;
+        while let Some(tok) = self.next() {
+            match tok {
+                "[" | "{" | "(" => depth += 1,
+                "]" | ")" => depth -= 1,
+                "}" => {
+                    depth -= 1;
+                    if depth <= 0 {
+                        return;
+                    }
+                }
+                ";" if depth <= 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    /// Skip a `name { ... }` block
+    fn skip_block(&mut self) {
+        while let Some(tok) = self.next() {
+            if tok == "{" {
+                let mut depth = 1;
+                while depth > 0 {
+                    match self.next() {
+                        Some("{") => depth += 1,
+                        Some("}") => depth -= 1,
+                        None => return,
+                        _ => {}
+                    }
+                }
+                return;
+            }
+        }
+    }
+}
+
+// ─── Java serialization ──────────────────────────────────────
+
+const TC_NULL: u8 = 0x70;
+const TC_REFERENCE: u8 = 0x71;
+const TC_CLASSDESC: u8 = 0x72;
+const TC_OBJECT: u8 = 0x73;
+const TC_STRING: u8 = 0x74;
+const TC_ARRAY: u8 = 0x75;
+const TC_CLASS: u8 = 0x76;
+const TC_BLOCKDATA: u8 = 0x77;
+const TC_ENDBLOCKDATA: u8 = 0x78;
+const TC_RESET: u8 = 0x79;
+const TC_BLOCKDATALONG: u8 = 0x7a;
+const TC_EXCEPTION: u8 = 0x7b;
+const TC_LONGSTRING: u8 = 0x7c;
+const TC_PROXYCLASSDESC: u8 = 0x7d;
+const TC_ENUM: u8 = 0x7e;
+const BASE_WIRE_HANDLE: u32 = 0x7e_0000;
+
+const SC_WRITE_METHOD: u8 = 0x01;
+const SC_SERIALIZABLE: u8 = 0x02;
+const SC_EXTERNALIZABLE: u8 = 0x04;
+const SC_BLOCK_DATA: u8 = 0x08;
+
+struct JavaClassDesc {
+    name: String,
+    flags: u8,
+    /// (type code, field name)
+    fields: Vec<(u8, String)>,
+    super_class: Option<usize>,
+}
+
+/// Something a back-reference handle can point at
+enum JavaHandle {
+    Class(usize),
+    Value(String),
+}
+
+struct JavaParser<'a> {
+    r: Reader<'a>,
+    classes: Vec<JavaClassDesc>,
+    handles: Vec<JavaHandle>,
+}
+
+/// Decode a Java serialization stream (`ObjectOutputStream`). Objects become
+/// maps of their serialized fields tagged with the class name; data written
+/// by custom `writeObject` methods is shown as raw block data.
+pub fn decode_java(bytes: &[u8]) -> Result<Node, String> {
+    let mut p = JavaParser { r: Reader::new(bytes), classes: Vec::new(), handles: Vec::new() };
+    if p.r.be_u16()? != 0xaced {
+        return Err("not a Java serialization stream".to_string());
+    }
+    let version = p.r.be_u16()?;
+    if version != 5 {
+        return Err(format!("unsupported stream version {}", version));
+    }
+    let mut contents = Vec::new();
+    while !p.r.at_end() {
+        contents.push(p.content(0)?);
+    }
+    Ok(if contents.len() == 1 { contents.remove(0) } else { Node::Array(contents) })
+}
+
+impl JavaParser<'_> {
+    fn utf(&mut self) -> Result<String, String> {
+        let n = self.r.be_u16()?;
+        Ok(String::from_utf8_lossy(self.r.take(n as usize)?).to_string())
+    }
+
+    fn new_handle(&mut self, handle: JavaHandle) -> usize {
+        self.handles.push(handle);
+        self.handles.len() - 1
+    }
+
+    /// Read a back-reference and return its index into `handles`
+    fn handle(&mut self) -> Result<usize, String> {
+        let h = self.r.be_u32()?;
+        h.checked_sub(BASE_WIRE_HANDLE)
+            .map(|i| i as usize)
+            .filter(|&i| i < self.handles.len())
+            .ok_or_else(|| format!("invalid handle 0x{:x}", h))
+    }
+
+    fn content(&mut self, depth: usize) -> Result<Node, String> {
+        check_depth(depth)?;
+        let mut tc = self.r.u8()?;
+        // Resets only clear the handle table; any number may precede the content
+        while tc == TC_RESET {
+            self.handles.clear();
+            tc = self.r.u8()?;
+        }
+        match tc {
+            TC_NULL => Ok(Node::Null),
+            TC_REFERENCE => {
+                let handle = self.handle()?;
+                let label = match &self.handles[handle] {
+                    JavaHandle::Class(idx) => format!("class {}", self.classes[*idx].name),
+                    JavaHandle::Value(label) => label.clone(),
+                };
+                Ok(Node::Tagged("ref".to_string(), Box::new(Node::Str(label))))
+            }
+            TC_CLASSDESC | TC_PROXYCLASSDESC => {
+                let idx = self.class_desc_body(tc, depth)?;
+                Ok(Node::Tagged("classdesc".to_string(), Box::new(Node::Str(self.classes[idx].name.clone()))))
+            }
+            TC_STRING | TC_LONGSTRING => {
+                let s = if tc == TC_STRING {
+                    self.utf()?
+                } else {
+                    let n = self.r.be_u64()?;
+                    let n = self.r.count(n, 1)?;
+                    String::from_utf8_lossy(self.r.take(n)?).to_string()
+                };
+                let preview: String = s.chars().take(40).collect();
+                self.new_handle(JavaHandle::Value(format!("{:?}", preview)));
+                Ok(Node::Str(s))
+            }
+            TC_CLASS => {
+                let desc = self.class_desc(depth)?;
+                let name = desc.map(|i| self.classes[i].name.clone()).unwrap_or_default();
+                self.new_handle(JavaHandle::Value(format!("class {}", name)));
+                Ok(Node::Tagged("class".to_string(), Box::new(Node::Str(name))))
+            }
+            TC_ENUM => {
+                let desc = self.class_desc(depth)?;
+                let name = desc.map(|i| self.classes[i].name.clone()).unwrap_or_default();
+                let handle = self.new_handle(JavaHandle::Value(name.clone()));
+                let constant = match self.content(depth + 1)? {
+                    Node::Str(s) => s,
+                    other => scalar_text(&other),
+                };
+                self.handles[handle] = JavaHandle::Value(format!("{}.{}", name, constant));
+                Ok(Node::Tagged(name, Box::new(Node::Str(constant))))
+            }
+            TC_ARRAY => self.array(depth),
+            TC_OBJECT => self.object(depth),
+            TC_BLOCKDATA => {
+                let n = self.r.u8()?;
+                Ok(Node::Tagged("blockdata".to_string(), Box::new(Node::Bytes(self.r.take(n as usize)?.to_vec()))))
+            }
+            TC_BLOCKDATALONG => {
+                let n = self.r.be_u32()?;
+                Ok(Node::Tagged("blockdata".to_string(), Box::new(Node::Bytes(self.r.take(n as usize)?.to_vec()))))
+            }
+            TC_EXCEPTION => Err("stream contains a serialized exception".to_string()),
+            other => Err(format!("unexpected type code 0x{:02x} at byte {}", other, self.r.pos - 1)),
+        }
+    }
+
+    /// A class descriptor in any form: new, proxy, reference or null
+    fn class_desc(&mut self, depth: usize) -> Result<Option<usize>, String> {
+        check_depth(depth)?;
+        match self.r.u8()? {
+            TC_NULL => Ok(None),
+            TC_REFERENCE => match self.handle().map(|h| &self.handles[h])? {
+                JavaHandle::Class(idx) => Ok(Some(*idx)),
+                JavaHandle::Value(_) => Err("reference is not a class descriptor".to_string()),
+            },
+            tc @ (TC_CLASSDESC | TC_PROXYCLASSDESC) => self.class_desc_body(tc, depth).map(Some),
+            tc => Err(format!("expected class descriptor, found 0x{:02x}", tc)),
+        }
+    }
+
+    fn class_desc_body(&mut self, tc: u8, depth: usize) -> Result<usize, String> {
+        let idx = self.classes.len();
+        if tc == TC_PROXYCLASSDESC {
+            self.new_handle(JavaHandle::Class(idx));
+            let count = self.r.be_u32()?;
+            let interfaces = (0..self.r.count(u64::from(count), 2)?)
+                .map(|_| self.utf())
+                .collect::<Result<Vec<_>, _>>()?;
+            self.classes.push(JavaClassDesc {
+                name: format!("Proxy({})", interfaces.join(", ")),
+                flags: SC_SERIALIZABLE,
+                fields: Vec::new(),
+                super_class: None,
+            });
+        } else {
+            let name = self.utf()?;
+            self.r.be_u64()?; // serialVersionUID
+            self.new_handle(JavaHandle::Class(idx));
+            let flags = self.r.u8()?;
+            let count = self.r.be_u16()?;
+            let mut fields = Vec::with_capacity(count as usize);
+            for _ in 0..count {
+                let code = self.r.u8()?;
+                let field_name = self.utf()?;
+                if code == b'L' || code == b'[' {
+                    // Field class name (a string or a reference to one)
+                    self.content(depth + 1)?;
+                }
+                fields.push((code, field_name));
+            }
+            self.classes.push(JavaClassDesc { name, flags, fields, super_class: None });
+        }
+        self.annotation(depth)?;
+        let super_class = self.class_desc(depth + 1)?;
+        self.classes[idx].super_class = super_class;
+        Ok(idx)
+    }
+
+    /// Contents up to TC_ENDBLOCKDATA
+    fn annotation(&mut self, depth: usize) -> Result<Vec<Node>, String> {
+        let mut items = Vec::new();
+        while self.r.peek()? != TC_ENDBLOCKDATA {
+            items.push(self.content(depth + 1)?);
+        }
+        self.r.u8()?;
+        Ok(items)
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Node, String> {
+        let desc = self.class_desc(depth)?.ok_or("object without class descriptor")?;
+        let class_name = self.classes[desc].name.clone();
+        self.new_handle(JavaHandle::Value(format!("object {}", class_name)));
+        // Class data is written from the topmost superclass down
+        let mut chain = vec![desc];
+        while let Some(parent) = self.classes[*chain.last().unwrap_or(&desc)].super_class {
+            if chain.contains(&parent) {
+                break;
+            }
+            chain.push(parent);
+        }
+        let mut entries = Vec::new();
+        for &class in chain.iter().rev() {
+            let flags = self.classes[class].flags;
+            if flags & SC_EXTERNALIZABLE != 0 {
+                if flags & SC_BLOCK_DATA == 0 {
+                    return Err(format!("{}: old externalizable format", self.classes[class].name));
+                }
+                entries.push(("@external".to_string(), Node::Array(self.annotation(depth)?)));
+                continue;
+            }
+            if flags & SC_SERIALIZABLE == 0 {
+                continue;
+            }
+            let fields = self.classes[class].fields.iter().map(|(c, n)| (*c, n.clone())).collect::<Vec<_>>();
+            for (code, name) in fields {
+                let value = self.field_value(code, depth)?;
+                entries.push((name, value));
+            }
+            if flags & SC_WRITE_METHOD != 0 {
+                let extra = self.annotation(depth)?;
+                if !extra.is_empty() {
+                    let label = if chain.len() > 1 {
+                        format!("@{}", self.classes[class].name)
+                    } else {
+                        "@writeObject".to_string()
+                    };
+                    entries.push((label, Node::Array(extra)));
+                }
+            }
+        }
+        Ok(Node::Tagged(class_name, Box::new(Node::Map(entries))))
+    }
+
+    fn field_value(&mut self, code: u8, depth: usize) -> Result<Node, String> {
+        let r = &mut self.r;
+        Ok(match code {
+            b'B' => Node::Int(i64::from(r.u8()? as i8)),
+            b'C' => Node::Str(char::from_u32(u32::from(r.be_u16()?)).unwrap_or('?').to_string()),
+            b'D' => Node::Float(f64::from_bits(r.be_u64()?)),
+            b'F' => Node::Float(f64::from(f32::from_bits(r.be_u32()?))),
+            b'I' => Node::Int(i64::from(r.be_u32()? as i32)),
+            b'J' => Node::Int(r.be_u64()? as i64),
+            b'S' => Node::Int(i64::from(r.be_u16()? as i16)),
+            b'Z' => Node::Bool(r.u8()? != 0),
+            b'L' | b'[' => self.content(depth + 1)?,
+            other => return Err(format!("invalid field type code '{}'", other as char)),
+        })
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Node, String> {
+        let desc = self.class_desc(depth)?.ok_or("array without class descriptor")?;
+        let class_name = self.classes[desc].name.clone();
+        self.new_handle(JavaHandle::Value(format!("array {}", class_name)));
+        let size = self.r.be_u32()? as i32;
+        if size < 0 {
+            return Err("negative array size".to_string());
+        }
+        // Class name is `[` + component type code
+        let code = class_name.as_bytes().get(1).copied().unwrap_or(b'L');
+        if code == b'B' {
+            let n = self.r.count(size as u64, 1)?;
+            return Ok(Node::Bytes(self.r.take(n)?.to_vec()));
+        }
+        let n = self.r.count(size as u64, 1)?;
+        let items = (0..n).map(|_| self.field_value(code, depth)).collect::<Result<Vec<_>, _>>()?;
+        Ok(Node::Array(items))
+    }
+}
+
+// ─── Compression ─────────────────────────────────────────────
+
+const SNAPPY_STREAM_MAGIC: &[u8] = b"\xff\x06\x00\x00sNaPpY";
+
+fn read_limited<R: Read>(reader: R) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    reader
+        .take(MAX_DECOMPRESSED + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| e.to_string())?;
+    if out.len() as u64 > MAX_DECOMPRESSED {
+        return Err(format!("decompressed size exceeds {} MiB", MAX_DECOMPRESSED >> 20));
+    }
+    Ok(out)
+}
+
+/// Decompress a gzip/zlib, zstd, LZ4 (frame or size-prepended block) or
+/// Snappy (framed or raw) payload
+pub fn decompress(bytes: &[u8], format: ValueFormat) -> Result<Vec<u8>, String> {
+    let out = match format {
+        ValueFormat::Gzip if bytes.starts_with(&[0x1f, 0x8b]) => {
+            read_limited(flate2::read::MultiGzDecoder::new(bytes))
+        }
+        ValueFormat::Gzip => read_limited(flate2::read::ZlibDecoder::new(bytes)),
+        ValueFormat::Zstd => {
+            let decoder = ruzstd::decoding::StreamingDecoder::new(bytes).map_err(|e| e.to_string())?;
+            read_limited(decoder)
+        }
+        ValueFormat::Lz4 if bytes.starts_with(&[0x04, 0x22, 0x4d, 0x18]) => {
+            read_limited(lz4_flex::frame::FrameDecoder::new(bytes))
+        }
+        ValueFormat::Lz4 => {
+            let size = bytes
+                .get(..4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or("truncated LZ4 block")?;
+            if u64::from(size) > MAX_DECOMPRESSED {
+                return Err(format!("decompressed size exceeds {} MiB", MAX_DECOMPRESSED >> 20));
+            }
+            lz4_flex::block::decompress_size_prepended(bytes).map_err(|e| e.to_string())
+        }
+        ValueFormat::Snappy if bytes.starts_with(SNAPPY_STREAM_MAGIC) => {
+            read_limited(snap::read::FrameDecoder::new(bytes))
+        }
+        ValueFormat::Snappy => {
+            let size = snap::raw::decompress_len(bytes).map_err(|e| e.to_string())?;
+            if size as u64 > MAX_DECOMPRESSED {
+                return Err(format!("decompressed size exceeds {} MiB", MAX_DECOMPRESSED >> 20));
+            }
+            snap::raw::Decoder::new().decompress_vec(bytes).map_err(|e| e.to_string())
+        }
+        other => return Err(format!("{} is not a compression format", other)),
+    };
+    out.map_err(|e| format!("{} decompression failed: {}", format, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn map(entries: &[(&str, Node)]) -> Node {
+        Node::Map(entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+    }
+
+    /// Every strict prefix of a valid encoding, from `min` bytes on, must fail cleanly
+    fn assert_truncations_fail(bytes: &[u8], min: usize, decode: fn(&[u8]) -> Result<Node, String>) {
+        for len in min..bytes.len() {
+            assert!(decode(&bytes[..len]).is_err(), "prefix of {} bytes decoded", len);
+        }
+    }
+
+    #[test]
+    fn msgpack() {
+        // {"a": 1, "b": [true, nil, -1, 1.5]}
+        let bytes = [
+            0x82, 0xa1, b'a', 0x01, 0xa1, b'b', 0x94, 0xc3, 0xc0, 0xff, 0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0,
+        ];
+        let expected = map(&[
+            ("a", Node::UInt(1)),
+            ("b", Node::Array(vec![Node::Bool(true), Node::Null, Node::Int(-1), Node::Float(1.5)])),
+        ]);
+        assert_eq!(decode_msgpack(&bytes), Ok(expected));
+        assert_eq!(detect_format(&bytes), Some(ValueFormat::MessagePack));
+        assert_truncations_fail(&bytes, 1, decode_msgpack);
+        // Trailing garbage, a length past the end and unbounded nesting
+        assert!(decode_msgpack(&[0x90, 0x00]).is_err());
+        assert!(decode_msgpack(&[0xdb, 0xff, 0xff, 0xff, 0xff, b'x']).is_err());
+        assert!(decode_msgpack(&[0x91; 1000]).is_err());
+    }
+
+    #[test]
+    fn cbor() {
+        // {"a": 1, "b": [true, null, -1, 1.5 (half float)]}
+        let bytes = [0xa2, 0x61, b'a', 0x01, 0x61, b'b', 0x84, 0xf5, 0xf6, 0x20, 0xf9, 0x3e, 0x00];
+        let expected = map(&[
+            ("a", Node::UInt(1)),
+            ("b", Node::Array(vec![Node::Bool(true), Node::Null, Node::Int(-1), Node::Float(1.5)])),
+        ]);
+        assert_eq!(decode_cbor(&bytes), Ok(expected));
+        assert_eq!(detect_format(&bytes), Some(ValueFormat::Cbor));
+        assert_truncations_fail(&bytes, 1, decode_cbor);
+        assert!(decode_cbor(&[0x80, 0x00]).is_err());
+        assert!(decode_cbor(&[0x7b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err());
+        assert!(decode_cbor(&[0x81; 1000]).is_err());
+        // Reserved additional information
+        assert!(decode_cbor(&[0x1c]).is_err());
+    }
+
+    #[test]
+    fn bson() {
+        // {"a": int32 1, "s": "hi"}
+        let bytes = [
+            0x16, 0, 0, 0, // document length
+            0x10, b'a', 0, 1, 0, 0, 0, // int32
+            0x02, b's', 0, 3, 0, 0, 0, b'h', b'i', 0, // string
+            0,
+        ];
+        let expected = map(&[("a", Node::Int(1)), ("s", Node::Str("hi".to_string()))]);
+        assert_eq!(decode_bson(&bytes), Ok(expected));
+        assert_eq!(detect_format(&bytes), Some(ValueFormat::Bson));
+        assert_truncations_fail(&bytes, 1, decode_bson);
+        // Document length that disagrees with the data, and an unknown element type
+        let mut wrong_len = bytes;
+        wrong_len[0] = 0x40;
+        assert!(decode_bson(&wrong_len).is_err());
+        let mut bad_type = bytes;
+        bad_type[4] = 0x55;
+        assert!(decode_bson(&bad_type).is_err());
+    }
+
+    #[test]
+    fn protobuf_wire() {
+        // field 1 = varint 150, field 2 = "hi", field 3 = fixed32 7
+        let bytes = [0x08, 0x96, 0x01, 0x12, 0x02, b'h', b'i', 0x1d, 7, 0, 0, 0];
+        let node = decode_protobuf_raw(&bytes).unwrap();
+        let lines = render_tree(&node).join("\n");
+        assert!(lines.contains("1: 150"), "{}", lines);
+        assert!(lines.contains("hi"), "{}", lines);
+        assert!(lines.contains("3: 7"), "{}", lines);
+        // Field boundaries are valid message ends; cuts inside a field are not
+        for len in [1, 2, 4, 5, 6, 8, 9, 10, 11] {
+            assert!(decode_protobuf_raw(&bytes[..len]).is_err(), "prefix of {} bytes decoded", len);
+        }
+        // Wire types 6 and 7 do not exist, and a length past the end
+        assert!(decode_protobuf_raw(&[0x0e]).is_err());
+        assert!(decode_protobuf_raw(&[0x12, 0x7f, b'x']).is_err());
+        // Deeply nested groups are cut off
+        let groups: Vec<u8> = std::iter::repeat_n(0x0b, 1000).collect();
+        assert!(decode_protobuf_raw(&groups).is_err());
+    }
+
+    fn temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("redis-tui-formats-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::File::create(&path).unwrap().write_all(bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn protobuf_schemas() {
+        let text = "syntax = \"proto3\";\npackage t;\n\
+                    enum Kind { A = 0; B = 1; }\n\
+                    message M { int32 id = 1; string name = 2; repeated sint32 xs = 3; Kind kind = 4; map<string, int64> tags = 5; }\n";
+        let path = temp_file("m.proto", text.as_bytes());
+        let schema = ProtoSchema::load(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let schema = schema.unwrap();
+        assert_eq!(schema.resolve_message("").unwrap().full_name, "t.M");
+
+        // id=5, name="n", xs=[-1, 1] packed, kind=B, tags={"k": 2}
+        let bytes = [
+            0x08, 5, 0x12, 1, b'n', 0x1a, 2, 0x01, 0x02, 0x20, 1, 0x2a, 5, 0x0a, 1, b'k', 0x10, 2,
+        ];
+        let node = schema.decode(&bytes, "t.M").unwrap();
+        let lines = render_tree(&node).join("\n");
+        for want in ["id: 5", "name: \"n\"", "-1", "B", "k: 2"] {
+            assert!(lines.contains(want), "missing {} in\n{}", want, lines);
+        }
+        assert!(schema.decode(&bytes[..bytes.len() - 1], "t.M").is_err());
+
+        // The same message as a FileDescriptorSet: file { package "t", message M { field id int32 = 1 } }
+        let field = [0x0a, 2, b'i', b'd', 0x18, 1, 0x20, 1, 0x28, 5];
+        let mut message = vec![0x0a, 1, b'M', 0x12, field.len() as u8];
+        message.extend(field);
+        let mut file = vec![0x12, 1, b't', 0x22, message.len() as u8];
+        file.extend(&message);
+        let mut set = vec![0x0a, file.len() as u8];
+        set.extend(&file);
+        let path = temp_file("m.desc", &set);
+        let schema = ProtoSchema::load(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        let schema = schema.unwrap();
+        assert_eq!(schema.decode(&[0x08, 5], "t.M"), Ok(map(&[("id", Node::Int(5))])));
+
+        let path = temp_file("bad.desc", &set[..set.len() - 3]);
+        assert!(ProtoSchema::load(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn java_serialization() {
+        // An instance of `class P implements Serializable { int x = 7; String s = "hi"; }`
+        let bytes = [
+            0xac, 0xed, 0x00, 0x05, // magic, version
+            0x73, 0x72, 0x00, 0x01, b'P', // TC_OBJECT TC_CLASSDESC "P"
+            0, 0, 0, 0, 0, 0, 0, 1, // serialVersionUID
+            0x02, 0x00, 0x02, // SC_SERIALIZABLE, 2 fields
+            b'I', 0x00, 0x01, b'x', //
+            b'L', 0x00, 0x01, b's', 0x74, 0x00, 0x12, // L s, TC_STRING type name
+            b'L', b'j', b'a', b'v', b'a', b'/', b'l', b'a', b'n', b'g', b'/', b'S', b't', b'r', b'i', b'n', b'g', b';',
+            0x78, 0x70, // TC_ENDBLOCKDATA, no superclass
+            0, 0, 0, 7, // x
+            0x74, 0x00, 0x02, b'h', b'i', // s
+        ];
+        let node = decode_java(&bytes).unwrap();
+        let lines = render_tree(&node).join("\n");
+        assert!(lines.contains('P') && lines.contains("x: 7") && lines.contains("s: \"hi\""), "{}", lines);
+        assert_eq!(detect_format(&bytes), Some(ValueFormat::JavaSerialized));
+        // The bare header is an empty stream; any cut after it is an error
+        assert_truncations_fail(&bytes, 5, decode_java);
+        // Dangling back-reference and unknown type code
+        assert!(decode_java(&[0xac, 0xed, 0x00, 0x05, 0x71, 0x00, 0x7e, 0x00, 0x05]).is_err());
+        assert!(decode_java(&[0xac, 0xed, 0x00, 0x05, 0x42]).is_err());
+    }
+
+    #[test]
+    fn decompression_round_trips() {
+        let payload = b"{\"hello\": \"world\", \"n\": [1, 2, 3]}".repeat(20);
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&payload).unwrap();
+        let gz = gz.finish().unwrap();
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        zlib.write_all(&payload).unwrap();
+        let zlib = zlib.finish().unwrap();
+        let zstd = ruzstd::encoding::compress_to_vec(&payload[..], ruzstd::encoding::CompressionLevel::Fastest);
+        let lz4_frame = {
+            let mut enc = lz4_flex::frame::FrameEncoder::new(Vec::new());
+            enc.write_all(&payload).unwrap();
+            enc.finish().unwrap()
+        };
+        let lz4_block = lz4_flex::block::compress_prepend_size(&payload);
+        let snappy_frame = {
+            let mut enc = snap::write::FrameEncoder::new(Vec::new());
+            enc.write_all(&payload).unwrap();
+            enc.into_inner().unwrap()
+        };
+        let snappy_raw = snap::raw::Encoder::new().compress_vec(&payload).unwrap();
+
+        let cases = [
+            (ValueFormat::Gzip, gz, true),
+            (ValueFormat::Gzip, zlib, true),
+            (ValueFormat::Zstd, zstd, true),
+            (ValueFormat::Lz4, lz4_frame, true),
+            (ValueFormat::Lz4, lz4_block, false),
+            (ValueFormat::Snappy, snappy_frame, true),
+            (ValueFormat::Snappy, snappy_raw, false),
+        ];
+        for (format, compressed, detectable) in cases {
+            assert_eq!(decompress(&compressed, format).as_deref(), Ok(&payload[..]), "{}", format);
+            if detectable {
+                assert_eq!(detect_format(&compressed), Some(format));
+            }
+            let truncated = &compressed[..compressed.len() / 2];
+            assert!(decompress(truncated, format).is_err(), "{}", format);
+        }
+        // Size headers claiming more than the limit are refused up front
+        assert!(decompress(&[0xff, 0xff, 0xff, 0xff, 0x00], ValueFormat::Lz4).is_err());
+        assert!(decompress(&[0xff, 0xff, 0xff, 0xff, 0x0f], ValueFormat::Snappy).is_err());
+        assert!(decompress(b"not compressed", ValueFormat::Zstd).is_err());
+    }
+
+    #[test]
+    fn auto_detect_false_positive_falls_back() {
+        // 0x78 0x9c looks like a zlib header but is not followed by deflate data
+        let settings = FormatSettings::default();
+        let bytes = [0x78, 0x9c, 0xff, 0xff, 0xff, 0x00];
+        assert_eq!(detect_format(&bytes), Some(ValueFormat::Gzip));
+        assert!(settings.render(&bytes, None).is_none());
+
+        let explicit = FormatSettings { format: ValueFormat::Gzip, ..FormatSettings::default() };
+        assert!(matches!(explicit.render(&bytes, None), Some(Err(_))));
+    }
+}
@@ -0,0 +1,666 @@
+use crate::data::{encode_values, DataType, Endianness};
+use crate::export::{base64_decode, restore_commands, ExportFormat, KeyDump};
+use crate::redis_client::{RedisClient, RedisValue, StreamEntry, TimeSeries, TsQuery};
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+/// Items written per pipeline when importing without the TUI
+const BATCH: usize = 100;
+
+/// What to do when an imported key already exists
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    Skip,
+    Overwrite,
+    /// Import under `<key>:imported` (or `:imported:2`, ...) instead
+    Rename,
+}
+
+pub const CONFLICT_POLICIES: &[ConflictPolicy] =
+    &[ConflictPolicy::Skip, ConflictPolicy::Overwrite, ConflictPolicy::Rename];
+
+impl ConflictPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictPolicy::Skip => "skip",
+            ConflictPolicy::Overwrite => "overwrite",
+            ConflictPolicy::Rename => "rename",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        CONFLICT_POLICIES.iter().copied().find(|p| p.label() == name)
+    }
+}
+
+/// Where the numbers of a CSV file go
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub key: Option<String>, // default: the file name without its extension
+    pub into_stream: bool,   // an XADD per row, else one string blob
+    pub data_type: DataType,
+    pub endianness: Endianness,
+}
+
+/// One thing to write: a whole key, or a line of a command file
+#[derive(Debug)]
+pub enum ImportItem {
+    Key(String, Box<KeyDump>),
+    Command(Vec<Vec<u8>>),
+}
+
+impl ImportItem {
+    /// The (first) key it writes, for progress labels
+    pub fn key(&self) -> Option<String> {
+        match self {
+            ImportItem::Key(key, _) => Some(key.clone()),
+            ImportItem::Command(args) => match key_spec(args) {
+                Ok(KeySpec::None) | Err(_) => None,
+                Ok(_) => Some(String::from_utf8_lossy(&args[1]).to_string()),
+            },
+        }
+    }
+}
+
+/// Where a command of a command file names its keys
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KeySpec {
+    /// No key, e.g. PING
+    None,
+    /// One key, right after the command name
+    One,
+    /// A key at the start of every group of this many arguments (DEL, MSET, TS.MADD)
+    Groups(usize),
+}
+
+/// Writes to the key in their first argument
+const SINGLE_KEY_WRITES: &[&str] = &[
+    "SET", "SETNX", "SETEX", "PSETEX", "GETSET", "GETDEL", "APPEND", "SETRANGE", "SETBIT", "INCR", "INCRBY",
+    "INCRBYFLOAT", "DECR", "DECRBY", "HSET", "HSETNX", "HMSET", "HDEL", "HINCRBY", "HINCRBYFLOAT", "LPUSH",
+    "RPUSH", "LPUSHX", "RPUSHX", "LINSERT", "LSET", "LREM", "LTRIM", "LPOP", "RPOP", "SADD", "SREM", "SPOP",
+    "ZADD", "ZINCRBY", "ZREM", "ZREMRANGEBYSCORE", "ZREMRANGEBYRANK", "ZREMRANGEBYLEX", "XADD", "XDEL", "XTRIM",
+    "PFADD", "GEOADD", "EXPIRE", "PEXPIRE", "EXPIREAT", "PEXPIREAT", "PERSIST", "RESTORE", "JSON.SET", "JSON.DEL",
+    "JSON.MERGE", "JSON.ARRAPPEND", "TS.CREATE", "TS.ALTER", "TS.ADD", "TS.INCRBY", "TS.DECRBY", "BF.ADD",
+    "BF.MADD", "BF.RESERVE", "CF.ADD", "CF.ADDNX", "CF.RESERVE",
+];
+
+/// Commands that would change the connection, the database or keys other
+/// than the ones they name
+const REFUSED_COMMANDS: &[&str] =
+    &["SELECT", "SWAPDB", "MULTI", "EXEC", "DISCARD", "WATCH", "FLUSHDB", "FLUSHALL", "MOVE", "EVAL", "EVALSHA", "FCALL"];
+
+fn key_spec(args: &[Vec<u8>]) -> Result<KeySpec> {
+    let name = String::from_utf8_lossy(&args[0]).to_ascii_uppercase();
+    let spec = match name.as_str() {
+        "PING" | "ECHO" => return Ok(KeySpec::None),
+        "DEL" | "UNLINK" => KeySpec::Groups(1),
+        "MSET" | "MSETNX" => KeySpec::Groups(2),
+        "TS.MADD" => KeySpec::Groups(3),
+        _ if SINGLE_KEY_WRITES.contains(&name.as_str()) => KeySpec::One,
+        _ if REFUSED_COMMANDS.contains(&name.as_str()) => {
+            bail!("{} cannot be imported: only writes to the keys they name are", name)
+        }
+        _ => bail!("{} is not a supported write", name),
+    };
+    let complete = match spec {
+        KeySpec::Groups(n) => args.len() > 1 && (args.len() - 1).is_multiple_of(n),
+        _ => args.len() > 1,
+    };
+    if !complete {
+        bail!("Wrong number of arguments for {}", name);
+    }
+    Ok(spec)
+}
+
+/// A file read into memory, written a batch at a time
+#[derive(Debug)]
+pub struct Importer {
+    pub path: String,
+    pub format: ExportFormat,
+    items: Vec<ImportItem>,
+    policy: ConflictPolicy,
+    targets: HashMap<String, Option<String>>, // key in the file → key written, None = skipped
+    pub skipped: usize,
+    pub renamed: usize,
+    pub overwritten: usize,
+}
+
+impl Importer {
+    /// Read and parse `path`; the format follows the extension, as for export
+    pub fn open(path: &str, policy: ConflictPolicy, csv: &CsvOptions) -> Result<Self> {
+        let format = ExportFormat::from_path(path)?;
+        let bytes = std::fs::read(path).with_context(|| format!("Cannot read {}", path))?;
+        let text = || String::from_utf8(bytes.clone()).with_context(|| format!("{} is not UTF-8", path));
+        let items = match format {
+            ExportFormat::Ndjson => text()?
+                .lines()
+                .enumerate()
+                .filter(|(_, line)| !line.trim().is_empty())
+                .map(|(_, line)| {
+                    let record: Value = serde_json::from_str(line)?;
+                    parse_record(&record)
+                })
+                .collect::<Result<Vec<_>>>(),
+            ExportFormat::Json => match serde_json::from_str(&text()?)? {
+                Value::Array(records) => records.iter().map(parse_record).collect(),
+                _ => Err(anyhow!("Expected an array of key objects")),
+            },
+            ExportFormat::Csv => {
+                let key = match &csv.key {
+                    Some(key) if !key.is_empty() => key.clone(),
+                    _ => Path::new(path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                };
+                parse_csv(&text()?, &key, csv)
+            }
+            ExportFormat::Resp => parse_commands(&bytes),
+        }
+        .with_context(|| format!("Cannot import {}", path))?;
+        Ok(Self {
+            path: path.to_string(),
+            format,
+            items,
+            policy,
+            targets: HashMap::new(),
+            skipped: 0,
+            renamed: 0,
+            overwritten: 0,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Progress labels, one per item
+    pub fn labels(&self) -> Vec<String> {
+        self.items.iter().map(|item| item.key().unwrap_or_default()).collect()
+    }
+
+    /// "keys" or "commands"
+    pub fn unit(&self) -> &'static str {
+        if self.format == ExportFormat::Resp {
+            "commands"
+        } else {
+            "keys"
+        }
+    }
+
+    /// Where a key of the file goes under the conflict policy, decided the
+    /// first time it is seen; None to skip it
+    fn resolve(&mut self, client: &mut RedisClient, key: &str, commands: &mut Vec<Vec<Vec<u8>>>) -> Result<Option<String>> {
+        if let Some(target) = self.targets.get(key) {
+            return Ok(target.clone());
+        }
+        let target = if !client.exists(key)? {
+            Some(key.to_string())
+        } else {
+            match self.policy {
+                ConflictPolicy::Skip => {
+                    self.skipped += 1;
+                    None
+                }
+                ConflictPolicy::Overwrite => {
+                    self.overwritten += 1;
+                    commands.push(vec![b"DEL".to_vec(), key.as_bytes().to_vec()]);
+                    Some(key.to_string())
+                }
+                ConflictPolicy::Rename => {
+                    let mut n = 1;
+                    let mut name = format!("{}:imported", key);
+                    while client.exists(&name)? || self.targets.values().any(|t| t.as_deref() == Some(name.as_str())) {
+                        n += 1;
+                        name = format!("{}:imported:{}", key, n);
+                    }
+                    self.renamed += 1;
+                    Some(name)
+                }
+            }
+        };
+        self.targets.insert(key.to_string(), target.clone());
+        Ok(target)
+    }
+
+    /// Write `range` of the items in one pipeline; returns how many were written
+    pub fn write(&mut self, client: &mut RedisClient, range: Range<usize>) -> Result<usize> {
+        let mut commands = Vec::new();
+        let mut written = 0;
+        let items = std::mem::take(&mut self.items);
+        let result = (|| {
+            for item in &items[range] {
+                let args = match item {
+                    ImportItem::Key(key, dump) => {
+                        if let Some(target) = self.resolve(client, key, &mut commands)? {
+                            commands.extend(restore_commands(&target, dump));
+                            written += 1;
+                        }
+                        continue;
+                    }
+                    ImportItem::Command(args) => args,
+                };
+                let group = match key_spec(args)? {
+                    KeySpec::None => {
+                        commands.push(args.clone());
+                        written += 1;
+                        continue;
+                    }
+                    KeySpec::One => args.len() - 1,
+                    KeySpec::Groups(n) => n,
+                };
+                // Each key with the arguments that go with it; skipped keys
+                // are left out, and the command too once none are left
+                let mut command = vec![args[0].clone()];
+                for chunk in args[1..].chunks(group) {
+                    let key = String::from_utf8_lossy(&chunk[0]).to_string();
+                    if let Some(target) = self.resolve(client, &key, &mut commands)? {
+                        // Unchanged names keep their bytes, which need not be UTF-8
+                        command.push(if target == key { chunk[0].clone() } else { target.into_bytes() });
+                        command.extend_from_slice(&chunk[1..]);
+                    }
+                }
+                if command.len() > 1 {
+                    commands.push(command);
+                    written += 1;
+                }
+            }
+            client.run_commands(&commands)
+        })();
+        self.items = items;
+        result.map(|_| written)
+    }
+
+    /// Write everything, reporting (done, total) after each batch
+    pub fn run(&mut self, client: &mut RedisClient, mut progress: impl FnMut(usize, usize)) -> Result<usize> {
+        let total = self.len();
+        let mut written = 0;
+        for start in (0..total).step_by(BATCH) {
+            let end = (start + BATCH).min(total);
+            written += self.write(client, start..end)?;
+            progress(end, total);
+        }
+        Ok(written)
+    }
+
+    /// "12 skipped, 3 renamed" for whatever the conflict policy did
+    pub fn conflict_summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.skipped > 0 {
+            parts.push(format!("{} existing skipped", self.skipped));
+        }
+        if self.overwritten > 0 {
+            parts.push(format!("{} overwritten", self.overwritten));
+        }
+        if self.renamed > 0 {
+            parts.push(format!("{} renamed to :imported", self.renamed));
+        }
+        parts.join(", ")
+    }
+}
+
+/// A JSON string, `{"base64": ...}` or number as bytes
+fn record_bytes(value: &Value) -> Result<Vec<u8>> {
+    match value {
+        Value::String(text) => Ok(text.clone().into_bytes()),
+        Value::Number(n) => Ok(n.to_string().into_bytes()),
+        Value::Object(obj) => match obj.get("base64").and_then(Value::as_str) {
+            Some(encoded) => base64_decode(encoded),
+            None => bail!("Expected a string or {{\"base64\": ...}}"),
+        },
+        _ => bail!("Expected a string or {{\"base64\": ...}}, got {}", value),
+    }
+}
+
+fn record_array(value: &Value) -> Result<&Vec<Value>> {
+    value.as_array().ok_or_else(|| anyhow!("Expected an array, got {}", value))
+}
+
+fn record_f64(value: &Value) -> Result<f64> {
+    match value {
+        Value::Number(n) => n.as_f64().ok_or_else(|| anyhow!("Bad number {}", n)),
+        Value::String(s) => s.parse().map_err(|_| anyhow!("Bad number '{}'", s)),
+        _ => bail!("Expected a number, got {}", value),
+    }
+}
+
+/// One `{"key", "type", "ttl_ms", "value"}` object, as written by export
+fn parse_record(record: &Value) -> Result<ImportItem> {
+    let key = record["key"].as_str().ok_or_else(|| anyhow!("Record without a \"key\""))?;
+    let key_type = record["type"].as_str().ok_or_else(|| anyhow!("'{}' has no \"type\"", key))?;
+    let value = &record["value"];
+    let parsed = (|| {
+        Ok(match key_type {
+            "string" => RedisValue::String(record_bytes(value)?),
+            "hash" => {
+                let obj = value.as_object().ok_or_else(|| anyhow!("Expected an object"))?;
+                RedisValue::Hash(obj.iter().map(|(f, v)| Ok((f.clone(), record_bytes(v)?))).collect::<Result<_>>()?)
+            }
+            "list" => RedisValue::List(record_array(value)?.iter().map(record_bytes).collect::<Result<_>>()?),
+            "set" => RedisValue::Set(record_array(value)?.iter().map(record_bytes).collect::<Result<_>>()?),
+            "zset" => RedisValue::ZSet(
+                record_array(value)?
+                    .iter()
+                    .map(|pair| Ok((record_bytes(&pair[0])?, record_f64(&pair[1])?)))
+                    .collect::<Result<_>>()?,
+            ),
+            "stream" => RedisValue::Stream(
+                record_array(value)?
+                    .iter()
+                    .map(|entry| {
+                        let id = entry["id"].as_str().unwrap_or("*").to_string();
+                        let fields = record_array(&entry["fields"])?
+                            .iter()
+                            .map(|pair| {
+                                let field = pair[0].as_str().ok_or_else(|| anyhow!("Bad stream field {}", pair[0]))?;
+                                Ok((field.to_string(), record_bytes(&pair[1])?))
+                            })
+                            .collect::<Result<_>>()?;
+                        Ok(StreamEntry { id, fields })
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            "ReJSON-RL" => RedisValue::Json(value.clone()),
+            "TSDB-TYPE" => {
+                let labels = match value["labels"].as_object() {
+                    Some(obj) => obj.iter().map(|(k, v)| (k.clone(), v.as_str().unwrap_or_default().to_string())).collect(),
+                    None => Vec::new(),
+                };
+                let samples = record_array(&value["samples"])?
+                    .iter()
+                    .map(|s| Ok((s[0].as_i64().ok_or_else(|| anyhow!("Bad timestamp {}", s[0]))?, record_f64(&s[1])?)))
+                    .collect::<Result<_>>()?;
+                RedisValue::TimeSeries(TimeSeries {
+                    info: Vec::new(),
+                    labels,
+                    rules: Vec::new(),
+                    samples,
+                    query: TsQuery::default(),
+                })
+            }
+            other => bail!("type '{}' cannot be imported", other),
+        })
+    })()
+    .with_context(|| format!("Key '{}'", key))?;
+    let dump = KeyDump {
+        key_type: key_type.to_string(),
+        value: parsed,
+        ttl_ms: record["ttl_ms"].as_i64().filter(|ttl| *ttl > 0),
+    };
+    Ok(ImportItem::Key(key.to_string(), Box::new(dump)))
+}
+
+/// Split CSV text into rows of cells; quoted cells may hold commas, quotes
+/// ("") and line breaks
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(std::mem::take(&mut cell)),
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            '\r' if !quoted => {}
+            c => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    rows.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    rows
+}
+
+/// Numeric columns of a CSV file as a binary blob, or as one stream entry
+/// per row with the numbers in the `_` field and text columns as fields. A
+/// first row that is not all numbers is the header; an `id` column holds
+/// stream entry IDs.
+fn parse_csv(text: &str, key: &str, opts: &CsvOptions) -> Result<Vec<ImportItem>> {
+    if opts.data_type.byte_size().is_none() {
+        bail!("CSV numbers need a numeric data type, not {}", opts.data_type);
+    }
+    let mut rows = csv_records(text);
+    let is_number = |cell: &str| cell.trim().parse::<f64>().is_ok();
+    let header = match rows.first() {
+        Some(first) if !first.iter().all(|c| is_number(c)) => rows.remove(0),
+        Some(first) => (0..first.len()).map(|i| format!("c{}", i)).collect(),
+        None => bail!("No rows"),
+    };
+    let id_col = header.iter().position(|h| h.trim() == "id");
+    let numeric: Vec<bool> = (0..header.len())
+        .map(|j| Some(j) != id_col && rows.iter().all(|r| r.get(j).is_some_and(|c| is_number(c))))
+        .collect();
+    // Cells past the header's columns are kept as text
+    let is_numeric = |j: usize| numeric.get(j).copied().unwrap_or(false);
+    let encode = |row: &[String]| -> Result<Vec<u8>> {
+        let numbers: Vec<&str> = row.iter().enumerate().filter(|(j, _)| is_numeric(*j)).map(|(_, c)| c.trim()).collect();
+        encode_values(&numbers.join(","), opts.data_type, opts.endianness).map_err(|e| anyhow!(e))
+    };
+
+    let value = if opts.into_stream {
+        let entries = rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let id = id_col.and_then(|j| row.get(j)).map_or("*", |id| id.trim()).to_string();
+                let mut fields: Vec<(String, Vec<u8>)> = row
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| !is_numeric(*j) && Some(*j) != id_col)
+                    .map(|(j, cell)| (header.get(j).cloned().unwrap_or_else(|| format!("c{}", j)), cell.clone().into_bytes()))
+                    .collect();
+                if numeric.iter().any(|n| *n) {
+                    fields.push(("_".to_string(), encode(row).with_context(|| format!("Row {}", i + 1))?));
+                }
+                Ok(StreamEntry { id, fields })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        RedisValue::Stream(entries)
+    } else {
+        let blob = rows.iter().map(|row| encode(row)).collect::<Result<Vec<_>>>()?.concat();
+        RedisValue::String(blob)
+    };
+    let key_type = if opts.into_stream { "stream" } else { "string" };
+    let dump = KeyDump { key_type: key_type.to_string(), value, ttl_ms: None };
+    Ok(vec![ImportItem::Key(key.to_string(), Box::new(dump))])
+}
+
+/// Commands in RESP (`redis-cli --pipe` input) or as redis-cli lines, where
+/// double quotes allow `\n`, `\"` and `\xHH` escapes and `#` starts a comment
+fn parse_commands(bytes: &[u8]) -> Result<Vec<ImportItem>> {
+    let mut items = Vec::new();
+    let mut pos = 0;
+    let line_end = |from: usize| bytes[from..].iter().position(|b| *b == b'\n').map_or(bytes.len(), |i| from + i);
+    while pos < bytes.len() {
+        if bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        } else if bytes[pos] == b'*' {
+            let end = line_end(pos);
+            let count: usize = parse_len(&bytes[pos + 1..end])?;
+            pos = end + 1;
+            // The count comes from the file: every argument takes at least 4 bytes
+            let mut args = Vec::with_capacity(count.min(bytes.len() / 4));
+            for _ in 0..count {
+                if bytes.get(pos) != Some(&b'$') {
+                    bail!("Expected a bulk string at byte {}", pos);
+                }
+                let end = line_end(pos);
+                let len = parse_len(&bytes[pos + 1..end])?;
+                let start = end + 1;
+                let arg = start
+                    .checked_add(len)
+                    .and_then(|stop| bytes.get(start..stop))
+                    .ok_or_else(|| anyhow!("Truncated RESP at byte {}", start))?;
+                args.push(arg.to_vec());
+                pos = start + len + 2;
+            }
+            if !args.is_empty() {
+                key_spec(&args).with_context(|| format!("Command {}", items.len() + 1))?;
+                items.push(ImportItem::Command(args));
+            }
+        } else {
+            let end = line_end(pos);
+            let line = String::from_utf8_lossy(&bytes[pos..end]).to_string();
+            pos = end + 1;
+            if !line.trim_start().starts_with('#') {
+                let args = split_command(line.trim_end_matches('\r'))?;
+                if !args.is_empty() {
+                    key_spec(&args).with_context(|| format!("Command {}", items.len() + 1))?;
+                    items.push(ImportItem::Command(args));
+                }
+            }
+        }
+    }
+    Ok(items)
+}
+
+fn parse_len(digits: &[u8]) -> Result<usize> {
+    let text = String::from_utf8_lossy(digits);
+    text.trim().parse().map_err(|_| anyhow!("Bad RESP length '{}'", text.trim()))
+}
+
+/// Split a redis-cli command line into arguments
+fn split_command(line: &str) -> Result<Vec<Vec<u8>>> {
+    let mut args = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else {
+            return Ok(args);
+        };
+        let mut arg = Vec::new();
+        let push = |arg: &mut Vec<u8>, c: char| arg.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        match first {
+            '"' => loop {
+                match chars.next().ok_or_else(|| anyhow!("Unclosed '\"' in: {}", line))? {
+                    '"' => break,
+                    '\\' => match chars.next() {
+                        Some('n') => arg.push(b'\n'),
+                        Some('r') => arg.push(b'\r'),
+                        Some('t') => arg.push(b'\t'),
+                        Some('x') => {
+                            let hex: String = [chars.next(), chars.next()].iter().flatten().collect();
+                            let byte = u8::from_str_radix(&hex, 16).map_err(|_| anyhow!("Bad \\x{} in: {}", hex, line))?;
+                            arg.push(byte);
+                        }
+                        Some(c) => push(&mut arg, c),
+                        None => bail!("Dangling '\\' in: {}", line),
+                    },
+                    c => push(&mut arg, c),
+                }
+            },
+            '\'' => loop {
+                match chars.next().ok_or_else(|| anyhow!("Unclosed \"'\" in: {}", line))? {
+                    '\'' => break,
+                    '\\' if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        arg.push(b'\'');
+                    }
+                    c => push(&mut arg, c),
+                }
+            },
+            c => {
+                push(&mut arg, c);
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    push(&mut arg, c);
+                }
+            }
+        }
+        args.push(arg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{base64_decode, csv_rows, resp_commands};
+
+    fn hash_dump() -> KeyDump {
+        let pairs = vec![
+            ("plain".to_string(), b"text".to_vec()),
+            ("a,b".to_string(), b"say \"hi\", twice".to_vec()),
+            ("lines".to_string(), b"one\r\ntwo\n".to_vec()),
+            ("bin".to_string(), vec![0, 159, 146, 150, 255, b'\n']),
+        ];
+        KeyDump { key_type: "hash".to_string(), value: RedisValue::Hash(pairs), ttl_ms: Some(1500) }
+    }
+
+    #[test]
+    fn csv_rows_parse_back() {
+        let dump = hash_dump();
+        let RedisValue::Hash(pairs) = &dump.value else { unreachable!() };
+        let text = csv_rows("k,1", &dump, DataType::Blob, Endianness::Little).join("\n");
+        let rows = csv_records(&text);
+        assert_eq!(rows.len(), pairs.len());
+        for (row, (field, value)) in rows.iter().zip(pairs) {
+            assert_eq!(row[..4], ["k,1", "hash", "", field.as_str()]);
+            let parsed = match row[4].strip_prefix("base64:") {
+                Some(encoded) => base64_decode(encoded).unwrap(),
+                None => row[4].clone().into_bytes(),
+            };
+            assert_eq!(parsed, value.clone(), "{}", field);
+        }
+    }
+
+    #[test]
+    fn resp_commands_parse_back() {
+        let mut dumps = vec![hash_dump()];
+        let items = vec![b"$3\r\n".to_vec(), Vec::new()];
+        dumps.push(KeyDump { key_type: "list".to_string(), value: RedisValue::List(items), ttl_ms: None });
+        for dump in &dumps {
+            let key = "key \"with\"\r\nbreak";
+            let items = parse_commands(&resp_commands(key, dump)).unwrap();
+            let parsed: Vec<Vec<Vec<u8>>> = items
+                .into_iter()
+                .map(|item| match item {
+                    ImportItem::Command(args) => args,
+                    ImportItem::Key(..) => panic!("expected a command"),
+                })
+                .collect();
+            assert_eq!(parsed, restore_commands(key, dump));
+        }
+    }
+
+    #[test]
+    fn command_lines() {
+        let items = parse_commands(b"# comment\nSET k \"a\\x00\\\"b\"\r\n  HSET 'h k' f 'it\\'s'\n").unwrap();
+        let args: Vec<_> = items
+            .iter()
+            .map(|item| match item {
+                ImportItem::Command(args) => args.clone(),
+                ImportItem::Key(..) => Vec::new(),
+            })
+            .collect();
+        assert_eq!(args[0], [b"SET".to_vec(), b"k".to_vec(), b"a\0\"b".to_vec()]);
+        assert_eq!(args[1], [b"HSET".to_vec(), b"h k".to_vec(), b"f".to_vec(), b"it's".to_vec()]);
+        assert!(parse_commands(b"*2\r\n$3\r\nGET\r\n$10\r\nk\r\n").is_err());
+    }
+
+    #[test]
+    fn command_keys() {
+        let spec = |line: &str| key_spec(&split_command(line).unwrap());
+        assert_eq!(spec("set k v").unwrap(), KeySpec::One);
+        assert_eq!(spec("PING").unwrap(), KeySpec::None);
+        assert_eq!(spec("MSET a 1 b 2").unwrap(), KeySpec::Groups(2));
+        assert_eq!(spec("DEL a b c").unwrap(), KeySpec::Groups(1));
+        assert_eq!(spec("TS.MADD a 1 1.5 b 1 2.5").unwrap(), KeySpec::Groups(3));
+        for bad in ["SELECT 1", "MULTI", "EXEC", "FLUSHALL", "GET k", "MSET a 1 b", "SET", "TS.MADD a 1"] {
+            assert!(spec(bad).is_err(), "{}", bad);
+        }
+        let err = parse_commands(b"SET a 1\nSELECT 2\nSET a 2\n").unwrap_err();
+        assert!(format!("{:#}", err).starts_with("Command 2: SELECT cannot be imported"), "{:#}", err);
+        let items = parse_commands(b"MSET a 1 b 2\nPING\n").unwrap();
+        assert_eq!(items.iter().map(ImportItem::key).collect::<Vec<_>>(), [Some("a".to_string()), None]);
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// One step of a JSONPath: an object member or an array index
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSeg {
+    Key(String),
+    Index(usize),
+}
+
+/// A visible line of the RedisJSON tree view
+#[derive(Debug, Clone)]
+pub struct JsonRow {
+    pub path: Vec<PathSeg>,
+    pub text: String,
+    pub container: bool,
+}
+
+impl JsonRow {
+    /// JSONPath of this row, e.g. `$.sensors[2].name`
+    pub fn json_path(&self) -> String {
+        json_path(&self.path)
+    }
+}
+
+/// Format path segments as a JSONPath accepted by the JSON.* commands
+pub fn json_path(segs: &[PathSeg]) -> String {
+    let mut out = String::from("$");
+    for seg in segs {
+        match seg {
+            PathSeg::Key(k) if is_identifier(k) => {
+                out.push('.');
+                out.push_str(k);
+            }
+            PathSeg::Key(k) => {
+                out.push('[');
+                out.push_str(&Value::String(k.clone()).to_string());
+                out.push(']');
+            }
+            PathSeg::Index(i) => out.push_str(&format!("[{}]", i)),
+        }
+    }
+    out
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Flatten a JSON document into tree rows, skipping children of collapsed
+/// containers. Collapsed containers are identified by their JSONPath.
+pub fn json_rows(root: &Value, collapsed: &HashSet<String>) -> Vec<JsonRow> {
+    let mut rows = Vec::new();
+    let mut path = Vec::new();
+    push_rows(root, None, &mut path, collapsed, &mut rows);
+    rows
+}
+
+fn push_rows(
+    value: &Value,
+    label: Option<String>,
+    path: &mut Vec<PathSeg>,
+    collapsed: &HashSet<String>,
+    rows: &mut Vec<JsonRow>,
+) {
+    let indent = "  ".repeat(path.len());
+    let label = label.unwrap_or_else(|| "$".to_string());
+    let children: Vec<(PathSeg, String, &Value)> = match value {
+        Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (PathSeg::Key(k.clone()), k.clone(), v))
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (PathSeg::Index(i), format!("[{}]", i), v))
+            .collect(),
+        scalar => {
+            rows.push(JsonRow {
+                path: path.clone(),
+                text: format!("{}  {}: {}", indent, label, scalar),
+                container: false,
+            });
+            return;
+        }
+    };
+
+    let is_collapsed = collapsed.contains(&json_path(path));
+    let size = match value {
+        Value::Object(_) => format!("{{{}}}", children.len()),
+        _ => format!("[{}]", children.len()),
+    };
+    let marker = if is_collapsed { "▸" } else { "▾" };
+    rows.push(JsonRow {
+        path: path.clone(),
+        text: format!("{}{} {}  {}", indent, marker, label, size),
+        container: true,
+    });
+    if is_collapsed {
+        return;
+    }
+    for (seg, child_label, child) in children {
+        path.push(seg);
+        push_rows(child, Some(child_label), path, collapsed, rows);
+        path.pop();
+    }
+}
+
+/// Look up the value at a path, if it exists
+pub fn value_at<'a>(root: &'a Value, segs: &[PathSeg]) -> Option<&'a Value> {
+    segs.iter().try_fold(root, |v, seg| match seg {
+        PathSeg::Key(k) => v.get(k.as_str()),
+        PathSeg::Index(i) => v.get(*i),
+    })
+}
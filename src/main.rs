@@ -1,13 +1,27 @@
 mod app;
+mod config;
 mod data;
+mod diff;
+mod editor;
+mod export;
+mod formats;
+mod import;
+mod json_tree;
+mod rdb;
 mod redis_client;
+mod textarea;
 mod ui;
 
 use anyhow::{Context, Result};
 use app::{App, InputMode, Panel};
 use clap::Parser;
+use data::{DataType, Endianness};
+use import::{ConflictPolicy, CsvOptions, Importer};
 use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers, MouseEvent, MouseEventKind, EnableMouseCapture, DisableMouseCapture},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, KeyCode,
+        KeyModifiers, MouseEvent, MouseEventKind,
+    },
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
@@ -40,6 +54,38 @@ struct Args {
     /// Full Redis URL (overrides host/port/password/db)
     #[arg(short, long)]
     url: Option<String>,
+
+    /// Import a .ndjson/.json/.csv/.resp file before starting (repeatable)
+    #[arg(long, value_name = "FILE")]
+    import: Vec<String>,
+
+    /// What to do with imported keys that already exist: skip, overwrite or rename
+    #[arg(long, default_value = "skip")]
+    on_conflict: String,
+
+    /// Key for a CSV import (default: the file name without its extension)
+    #[arg(long)]
+    csv_key: Option<String>,
+
+    /// Write a CSV as a stream (one entry per row) or as one binary blob
+    #[arg(long, default_value = "stream")]
+    csv_into: String,
+
+    /// Numeric type of CSV values: int8, uint8, int16, uint16, int32, uint32, float32, float64
+    #[arg(long, default_value = "float32")]
+    csv_type: String,
+
+    /// Byte order of CSV values: LE or BE
+    #[arg(long, default_value = "LE")]
+    csv_endian: String,
+
+    /// Exit after --import instead of starting the TUI
+    #[arg(long)]
+    no_tui: bool,
+
+    /// Browse an RDB file (e.g. dump.rdb) read-only instead of connecting
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["import", "url"])]
+    rdb: Option<String>,
 }
 
 impl Args {
@@ -56,15 +102,57 @@ impl Args {
             auth, self.host, self.port, self.db
         )
     }
+
+    fn csv_options(&self) -> Result<CsvOptions> {
+        let data_type = DataType::from_name(&self.csv_type)
+            .filter(|t| t.byte_size().is_some())
+            .with_context(|| format!("--csv-type '{}' is not a numeric type", self.csv_type))?;
+        let endianness = Endianness::from_name(&self.csv_endian)
+            .with_context(|| format!("--csv-endian must be LE or BE, not '{}'", self.csv_endian))?;
+        let into_stream = match self.csv_into.as_str() {
+            "stream" => true,
+            "blob" => false,
+            other => anyhow::bail!("--csv-into must be stream or blob, not '{}'", other),
+        };
+        Ok(CsvOptions { key: self.csv_key.clone(), into_stream, data_type, endianness })
+    }
+}
+
+/// Run the --import files, reporting progress on stderr
+fn import_files(client: &mut RedisClient, args: &Args) -> Result<()> {
+    let policy = ConflictPolicy::from_name(&args.on_conflict)
+        .with_context(|| format!("--on-conflict must be skip, overwrite or rename, not '{}'", args.on_conflict))?;
+    let csv = args.csv_options()?;
+    for path in &args.import {
+        let mut importer = Importer::open(path, policy, &csv)?;
+        let unit = importer.unit();
+        let written = importer.run(client, |done, total| eprint!("\r{}: {}/{} {}", path, done, total, unit))?;
+        eprint!("\r{}: {} of {} {} imported", path, written, importer.len(), unit);
+        let conflicts = importer.conflict_summary();
+        if !conflicts.is_empty() {
+            eprint!(", {}", conflicts);
+        }
+        eprintln!();
+    }
+    Ok(())
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let url = args.redis_url();
-
-    // Connect to Redis
-    let mut client = RedisClient::connect(&url)
-        .with_context(|| format!("Failed to connect to Redis at {}", url))?;
+    // Connect to Redis, or read the RDB file
+    let mut client = match &args.rdb {
+        Some(path) => RedisClient::open_rdb(path)?,
+        None => {
+            let url = args.redis_url();
+            RedisClient::connect(&url).with_context(|| format!("Failed to connect to Redis at {}", url))?
+        }
+    };
+    // Background listeners of an RDB file get its path, which they cannot connect to
+    let url = client.url.clone();
+    import_files(&mut client, &args)?;
+    if args.no_tui {
+        return Ok(());
+    }
 
     // Set up terminal
     enable_raw_mode().context("Failed to enable raw mode")?;
@@ -74,6 +162,9 @@ fn main() -> Result<()> {
     io::stdout()
         .execute(EnableMouseCapture)
         .context("Failed to enable mouse capture")?;
+    io::stdout()
+        .execute(EnableBracketedPaste)
+        .context("Failed to enable bracketed paste")?;
     let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend).context("Failed to create terminal")?;
 
@@ -82,6 +173,9 @@ fn main() -> Result<()> {
 
     // Restore terminal
     disable_raw_mode().context("Failed to disable raw mode")?;
+    io::stdout()
+        .execute(DisableBracketedPaste)
+        .context("Failed to disable bracketed paste")?;
     io::stdout()
         .execute(DisableMouseCapture)
         .context("Failed to disable mouse capture")?;
@@ -223,6 +317,7 @@ fn run_app(
 ) -> Result<()> {
     let mut app = App::new();
     app.db = client.db;
+    app.rdb_path = client.rdb_path().map(str::to_string);
 
     // Initial key load
     app.refresh_keys(client);
@@ -234,8 +329,9 @@ fn run_app(
     loop {
         terminal.draw(|frame| ui::draw(frame, &mut app))?;
 
-        // Poll for events with short timeout
-        if event::poll(Duration::from_millis(50))? {
+        // Poll for events with short timeout; don't wait while a bulk job runs
+        let timeout = if app.bulk_job.is_some() { Duration::ZERO } else { Duration::from_millis(50) };
+        if event::poll(timeout)? {
             let ev = event::read()?;
 
             // Handle mouse events
@@ -243,6 +339,13 @@ fn run_app(
                 handle_mouse_event(&mut app, mouse);
             }
 
+            // Bracketed paste goes into the focused edit field
+            if let Event::Paste(text) = &ev {
+                if app.input_mode == InputMode::Edit {
+                    app.edit_paste(text);
+                }
+            }
+
             if let Event::Key(key) = ev {
                 // Stop stream listener on any navigation away
                 let prev_key = app.selected_key_name().map(|s| s.to_string());
@@ -265,9 +368,45 @@ fn run_app(
                     InputMode::Edit => {
                         handle_edit_input(&mut app, client, key.code, key.modifiers)
                     }
+                    InputMode::Conflict => handle_conflict_input(&mut app, client, key.code),
+                    InputMode::UndoHistory => handle_undo_input(&mut app, client, key.code),
+                    InputMode::Bulk => handle_bulk_input(&mut app, key.code),
+                    InputMode::PatternDelete => handle_pattern_delete_input(&mut app, client, key.code),
+                    InputMode::Export => handle_export_input(&mut app, client, key.code),
+                    InputMode::Import => handle_import_input(&mut app, key.code),
+                    InputMode::Copy => handle_copy_input(&mut app, client, key.code),
+                    InputMode::BulkProgress if key.code == KeyCode::Esc => app.finish_bulk(client, true),
+                    InputMode::BulkProgress => {}
                     InputMode::PlotLimit => {
                         handle_plot_limit_input(&mut app, key.code)
                     }
+                    InputMode::RecordLayout => {
+                        handle_record_layout_input(&mut app, key.code)
+                    }
+                    InputMode::Scaling => {
+                        handle_scaling_input(&mut app, key.code)
+                    }
+                    InputMode::Profiles => {
+                        handle_profiles_input(&mut app, key.code, key.modifiers)
+                    }
+                    InputMode::ValueFormat => {
+                        handle_value_format_input(&mut app, key.code)
+                    }
+                    InputMode::Query => {
+                        handle_query_input(&mut app, client, key.code)
+                    }
+                    InputMode::VectorCompare => {
+                        handle_vector_compare_input(&mut app, client, key.code)
+                    }
+                    InputMode::SearchIndexes => {
+                        handle_search_indexes_input(&mut app, client, key.code)
+                    }
+                    InputMode::SearchQuery => {
+                        handle_search_query_input(&mut app, client, key.code)
+                    }
+                    InputMode::SearchResults => {
+                        handle_search_results_input(&mut app, client, key.code)
+                    }
                     InputMode::SignalGen => {
                         handle_signal_gen_input(&mut app, key.code);
                         // Check if user pressed Enter to start the generator
@@ -297,6 +436,11 @@ fn run_app(
                     InputMode::Normal => {
                         handle_normal_input(&mut app, client, key.code, key.modifiers);
 
+                        // Edit the value in $EDITOR with 'E'
+                        if key.code == KeyCode::Char('E') && app.current_key_info.is_some() {
+                            edit_externally(terminal, &mut app, client)?;
+                        }
+
                         // Toggle plot visibility with 'p'
                         if key.code == KeyCode::Char('p') {
                             app.plot_visible = !app.plot_visible;
@@ -357,6 +501,11 @@ fn run_app(
             }
         }
 
+        // Next batch of a running bulk action
+        if app.bulk_job.is_some() {
+            app.bulk_step(client);
+        }
+
         // Check for completed background FFT
         app.poll_fft();
 
@@ -380,6 +529,57 @@ fn run_app(
     }
 }
 
+/// Suspend the TUI while the selected value is edited in `$EDITOR`, then
+/// write it back
+fn edit_externally(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    client: &mut RedisClient,
+) -> Result<()> {
+    let edit = match app.start_external_edit(client) {
+        Ok(edit) => edit,
+        Err(e) => {
+            app.status_message = format!("Error: {}", e);
+            return Ok(());
+        }
+    };
+
+    disable_raw_mode().context("Failed to disable raw mode")?;
+    io::stdout()
+        .execute(DisableBracketedPaste)
+        .context("Failed to disable bracketed paste")?;
+    io::stdout()
+        .execute(DisableMouseCapture)
+        .context("Failed to disable mouse capture")?;
+    io::stdout()
+        .execute(LeaveAlternateScreen)
+        .context("Failed to leave alternate screen")?;
+    let edited = editor::run_editor(&edit.path);
+    enable_raw_mode().context("Failed to enable raw mode")?;
+    io::stdout()
+        .execute(EnterAlternateScreen)
+        .context("Failed to enter alternate screen")?;
+    io::stdout()
+        .execute(EnableMouseCapture)
+        .context("Failed to enable mouse capture")?;
+    io::stdout()
+        .execute(EnableBracketedPaste)
+        .context("Failed to enable bracketed paste")?;
+    terminal.clear().context("Failed to clear terminal")?;
+
+    app.status_message = match edited {
+        Ok(()) => match app.finish_external_edit(client, edit) {
+            Ok(msg) => msg,
+            Err(e) => format!("Error: {}", e),
+        },
+        Err(e) => {
+            edit.remove_file();
+            format!("Error: {}", e)
+        }
+    };
+    Ok(())
+}
+
 fn handle_normal_input(
     app: &mut App,
     client: &mut RedisClient,
@@ -387,6 +587,10 @@ fn handle_normal_input(
     modifiers: KeyModifiers,
 ) {
     match code {
+        KeyCode::Esc if !app.marked_keys.is_empty() => {
+            app.marked_keys.clear();
+            app.status_message = "Marks cleared".to_string();
+        }
         KeyCode::Char('q') | KeyCode::Esc => {
             app.running = false;
         }
@@ -416,6 +620,84 @@ fn handle_normal_input(
             app.load_selected_value(client);
         }
 
+        // Key marks for bulk actions
+        KeyCode::Char(' ') if app.active_panel == Panel::KeyList => {
+            app.toggle_mark();
+        }
+        KeyCode::Char('*') if app.active_panel == Panel::KeyList => {
+            app.mark_all();
+            app.status_message = format!("{} keys marked", app.marked_keys.len());
+        }
+        KeyCode::Char('!') if app.active_panel == Panel::KeyList => {
+            app.invert_marks();
+            app.status_message = format!("{} keys marked", app.marked_keys.len());
+        }
+
+        // JSON tree navigation
+        KeyCode::Up if app.active_panel == Panel::ValueView && app.is_viewing_json() => {
+            app.json_cursor_up();
+        }
+        KeyCode::Down if app.active_panel == Panel::ValueView && app.is_viewing_json() => {
+            app.json_cursor_down();
+        }
+        KeyCode::Enter if app.active_panel == Panel::ValueView && app.is_viewing_json() => {
+            app.json_toggle();
+        }
+        KeyCode::Right if app.active_panel == Panel::ValueView && app.is_viewing_json() => {
+            app.json_expand();
+        }
+        KeyCode::Left if app.active_panel == Panel::ValueView && app.is_viewing_json() => {
+            app.json_collapse();
+        }
+
+        // Bitmap cell navigation
+        KeyCode::Up if app.active_panel == Panel::ValueView && app.is_bitmap_view() => {
+            app.move_bit_cursor_rows(-1);
+        }
+        KeyCode::Down if app.active_panel == Panel::ValueView && app.is_bitmap_view() => {
+            app.move_bit_cursor_rows(1);
+        }
+        KeyCode::Left if app.active_panel == Panel::ValueView && app.is_bitmap_view() => {
+            app.move_bit_cursor(-1);
+        }
+        KeyCode::Right if app.active_panel == Panel::ValueView && app.is_bitmap_view() => {
+            app.move_bit_cursor(1);
+        }
+        KeyCode::Enter | KeyCode::Char(' ') if app.active_panel == Panel::ValueView && app.is_bitmap_view() => {
+            app.toggle_selected_bit(client);
+        }
+
+        // Collection element table
+        KeyCode::Up if app.active_panel == Panel::ValueView && app.is_element_view() => {
+            app.move_element_cursor(-1);
+        }
+        KeyCode::Down if app.active_panel == Panel::ValueView && app.is_element_view() => {
+            app.move_element_cursor(1);
+        }
+        KeyCode::PageUp if app.active_panel == Panel::ValueView && app.is_element_view() => {
+            if app.element_detail {
+                app.scroll_element_detail(-10);
+            } else {
+                app.move_element_cursor(-10);
+            }
+        }
+        KeyCode::PageDown if app.active_panel == Panel::ValueView && app.is_element_view() => {
+            if app.element_detail {
+                app.scroll_element_detail(10);
+            } else {
+                app.move_element_cursor(10);
+            }
+        }
+        KeyCode::Enter if app.active_panel == Panel::ValueView && app.is_element_view() => {
+            app.toggle_element_detail();
+        }
+        KeyCode::Char('s') if app.active_panel == Panel::ValueView && app.is_element_view() => {
+            app.start_element_edit();
+        }
+        KeyCode::Char('d') if app.active_panel == Panel::ValueView && app.is_element_view() => {
+            app.start_element_delete();
+        }
+
         // Value view scrolling
         KeyCode::Up if app.active_panel == Panel::ValueView => {
             app.scroll_value_up();
@@ -439,14 +721,17 @@ fn handle_normal_input(
             } else {
                 app.data_type = app.data_type.next();
             }
+            app.mark_manual_type();
             app.recompute_plot();
         }
         KeyCode::Char('T') => {
             app.data_type = app.data_type.prev();
+            app.mark_manual_type();
             app.recompute_plot();
         }
         KeyCode::Char('e') => {
             app.endianness = app.endianness.toggle();
+            app.mark_manual_type();
             app.recompute_plot();
         }
         KeyCode::Char('a') => {
@@ -464,6 +749,50 @@ fn handle_normal_input(
             let state = if app.fft_enabled { "ON" } else { "OFF" };
             app.status_message = format!("FFT: {}", state);
         }
+        KeyCode::Char('L') => {
+            app.start_record_layout();
+        }
+        KeyCode::Char('o') => {
+            app.start_set_scaling();
+        }
+        KeyCode::Char('P') => {
+            app.start_profiles();
+        }
+        KeyCode::Char('F') => {
+            app.start_value_format();
+        }
+        KeyCode::Char('G') => {
+            app.toggle_geo_view(client);
+        }
+        KeyCode::Char('B') => {
+            app.toggle_bitmap_view();
+        }
+        KeyCode::Char('I') => {
+            app.open_search_browser(client);
+        }
+        KeyCode::Char('V') => {
+            app.start_vector_compare();
+        }
+        KeyCode::Char('Q') => {
+            app.start_query();
+        }
+        KeyCode::Char('A') => {
+            app.toggle_auto_detect();
+            app.status_message = match (app.auto_detect, app.detection_label()) {
+                (true, Some(label)) => format!("Auto-detect: ON ({})", label),
+                (true, None) => "Auto-detect: ON".to_string(),
+                (false, _) => "Auto-detect: OFF".to_string(),
+            };
+        }
+        KeyCode::Char('m') | KeyCode::Char('M') => {
+            if app.record_layout.is_some() {
+                app.cycle_record_field(code == KeyCode::Char('m'));
+                let name = app.record_field_name().unwrap_or("?").to_string();
+                app.status_message = format!("Plot member: {}", name);
+            } else {
+                app.status_message = "No record layout (press L to define one)".to_string();
+            }
+        }
         KeyCode::Char('g') => {
             app.fft_log_scale = !app.fft_log_scale;
             let state = if app.fft_log_scale { "log" } else { "linear" };
@@ -473,6 +802,7 @@ fn handle_normal_input(
         // Global data type and endianness (work from any panel)
         KeyCode::Char('t') if app.active_panel != Panel::DataPlot => {
             app.data_type = app.data_type.next();
+            app.mark_manual_type();
             app.recompute_plot();
         }
 
@@ -506,6 +836,9 @@ fn handle_normal_input(
                 app.start_rename();
             }
         }
+        KeyCode::Char('D') if app.current_key_info.is_some() => {
+            app.start_remove();
+        }
         KeyCode::Char('d') => {
             if let Some(key) = app.selected_key_name() {
                 app.confirm_action = Some(format!("Delete key '{}'", key));
@@ -513,15 +846,52 @@ fn handle_normal_input(
             }
         }
 
+        // Undo history
+        KeyCode::Char('u') => {
+            app.status_message = match app.undo_stack.len().checked_sub(1) {
+                Some(last) => match app.undo(client, last) {
+                    Ok(msg) => msg,
+                    Err(e) => format!("Error: {}", e),
+                },
+                None => "Nothing to undo".to_string(),
+            };
+        }
+        KeyCode::Char('U') => {
+            app.undo_cursor = 0;
+            app.input_mode = InputMode::UndoHistory;
+        }
+
+        // Bulk actions on the marked keys
+        KeyCode::Char('b') => {
+            app.start_bulk();
+        }
+        KeyCode::Char('X') => {
+            app.start_pattern_delete();
+        }
+        KeyCode::Char('W') => {
+            app.start_export();
+        }
+        KeyCode::Char('O') => {
+            app.start_import();
+        }
+        KeyCode::Char('C') => {
+            app.start_copy();
+        }
+
         // Database selection
         KeyCode::Char(c) if c.is_ascii_digit() => {
             let db = c.to_digit(10).unwrap() as i64;
             if let Err(e) = client.select_db(db) {
                 app.status_message = format!("Error: {}", e);
             } else {
+                // Marks name keys of the db they were made in
+                let unmarked = std::mem::take(&mut app.marked_keys).len();
                 app.db = db;
                 app.refresh_keys(client);
-                app.status_message = format!("Switched to DB {}", db);
+                app.status_message = match unmarked {
+                    0 => format!("Switched to DB {}", db),
+                    n => format!("Switched to DB {} ({} marks cleared)", db, n),
+                };
             }
         }
 
@@ -554,9 +924,50 @@ fn handle_confirm_input(app: &mut App, client: &mut RedisClient, code: KeyCode)
     match code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
             // Execute the confirmed action
-            if app.confirm_action.is_some() {
+            if let Some(edit) = app.external_edit.take() {
+                app.confirm_action = None;
+                app.input_mode = InputMode::Normal;
+                app.status_message = match app.save_external_edit(client, edit) {
+                    Ok(msg) => msg,
+                    Err(e) => format!("Error: {}", e),
+                };
+                return;
+            } else if app.confirm_edit {
+                app.confirm_edit = false;
+                app.confirm_action = None;
+                let op_label = app.edit_op_label().to_string();
+                let key = app.edit_key.clone();
+                match app.execute_edit(client) {
+                    Ok(_) => {
+                        app.cancel_edit();
+                        app.status_message = format!("{} on '{}' OK", op_label, key);
+                        app.refresh_keys(client);
+                        app.load_selected_value(client);
+                    }
+                    Err(e) => {
+                        app.status_message = format!("Error: {}", e);
+                        if app.edit_conflict.is_none() {
+                            app.input_mode = InputMode::Edit;
+                        }
+                    }
+                }
+                return;
+            } else if app.confirm_bulk {
+                app.confirm_bulk = false;
+                app.confirm_action = None;
+                if let Err(e) = app.start_bulk_job() {
+                    app.status_message = format!("Error: {}", e);
+                    app.input_mode = InputMode::Bulk;
+                }
+                return;
+            } else if let Some(idx) = app.confirm_element.take() {
+                app.status_message = match app.delete_element(client, idx) {
+                    Ok(msg) => msg,
+                    Err(e) => format!("Error deleting: {}", e),
+                };
+            } else if app.confirm_action.is_some() {
                 if let Some(key) = app.selected_key_name().map(|s| s.to_string()) {
-                    match client.delete_key(&key) {
+                    match app.delete_key(client, &key) {
                         Ok(_) => {
                             app.status_message = format!("Deleted '{}'", key);
                             app.current_value = None;
@@ -575,12 +986,126 @@ fn handle_confirm_input(app: &mut App, client: &mut RedisClient, code: KeyCode)
         }
         KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
             app.confirm_action = None;
+            app.confirm_element = None;
+            if let Some(edit) = app.external_edit.take() {
+                app.status_message = format!("Not saved; the edit is kept in {}", edit.path.display());
+            }
+            // Back to the removal or bulk popup to adjust it
+            app.input_mode = if app.confirm_edit {
+                InputMode::Edit
+            } else if app.confirm_bulk {
+                InputMode::Bulk
+            } else {
+                InputMode::Normal
+            };
+            app.confirm_edit = false;
+            app.confirm_bulk = false;
+        }
+        _ => {}
+    }
+}
+
+fn handle_bulk_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Left => app.cycle_bulk_action(false),
+        KeyCode::Right => app.cycle_bulk_action(true),
+        KeyCode::Down => app.edit_next_field(),
+        KeyCode::Up => app.edit_prev_field(),
+        KeyCode::Enter => {
+            if let Err(e) = app.preview_bulk() {
+                app.status_message = format!("Error: {}", e);
+            }
+        }
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_pattern_delete_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    let previewing = app.pattern_delete.is_some();
+    match code {
+        // Back from the preview to the pattern
+        KeyCode::Esc if previewing => app.pattern_delete = None,
+        KeyCode::Esc => {
             app.input_mode = InputMode::Normal;
         }
+        KeyCode::Enter if previewing => {
+            if let Err(e) = app.confirm_pattern_delete() {
+                app.status_message = e;
+            }
+        }
+        KeyCode::Enter => {
+            if let Err(e) = app.preview_pattern_delete(client) {
+                app.status_message = format!("Error: {}", e);
+            }
+        }
+        KeyCode::Backspace => {
+            if let Some(preview) = &mut app.pattern_delete {
+                preview.typed.pop();
+            } else {
+                app.edit_fields[0].1.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Some(preview) = &mut app.pattern_delete {
+                preview.typed.push(c);
+            } else {
+                app.edit_fields[0].1.push(c);
+            }
+        }
         _ => {}
     }
 }
 
+fn handle_copy_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    let previewing = app.copy_plan.is_some();
+    match code {
+        // Back from the preview to the form
+        KeyCode::Esc if previewing => app.copy_plan = None,
+        KeyCode::Enter if previewing => app.confirm_copy(),
+        KeyCode::Enter => {
+            if let Err(e) = app.preview_copy(client) {
+                app.status_message = format!("Error: {}", e);
+            }
+        }
+        _ if previewing => {}
+        KeyCode::Down => app.edit_next_field(),
+        KeyCode::Up => app.edit_prev_field(),
+        KeyCode::Left if app.copy_field_cycles() => app.cycle_copy_choice(false),
+        KeyCode::Right if app.copy_field_cycles() => app.cycle_copy_choice(true),
+        KeyCode::Backspace | KeyCode::Char(_) if !app.copy_field_is_text() => {}
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_export_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        KeyCode::Left => app.cycle_export_scope(false),
+        KeyCode::Right => app.cycle_export_scope(true),
+        KeyCode::Enter => {
+            if let Err(e) = app.run_export(client) {
+                app.status_message = format!("Error: {}", e);
+            }
+        }
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_import_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Down => app.edit_next_field(),
+        KeyCode::Up => app.edit_prev_field(),
+        KeyCode::Left if app.import_field_is_choice() => app.cycle_import_choice(false),
+        KeyCode::Right if app.import_field_is_choice() => app.cycle_import_choice(true),
+        KeyCode::Enter => {
+            if let Err(e) = app.run_import() {
+                app.status_message = format!("Error: {}", e);
+            }
+        }
+        KeyCode::Backspace | KeyCode::Char(_) if app.import_field_is_choice() => {}
+        _ => handle_form_input(app, code),
+    }
+}
+
 fn handle_edit_input(
     app: &mut App,
     client: &mut RedisClient,
@@ -589,8 +1114,21 @@ fn handle_edit_input(
 ) {
     let is_new_key = app.edit_operation == Some(app::EditOperation::NewKey);
 
+    // XADD encodes each value on its own: Ctrl+B/Ctrl+T act on the focused one
+    if app.is_xadd_edit() && modifiers.contains(KeyModifiers::CONTROL) {
+        match code {
+            KeyCode::Char('b') => app.xadd_toggle_binary(),
+            KeyCode::Char('t') => app.xadd_cycle_type(),
+            KeyCode::Char('e') => {
+                app.endianness = app.endianness.toggle();
+                app.xadd_relabel();
+            }
+            _ => handle_text_input(app, code, modifiers),
+        }
+        return;
+    }
     // Ctrl+B: toggle binary mode
-    if code == KeyCode::Char('b') && modifiers.contains(KeyModifiers::CONTROL) {
+    if code == KeyCode::Char('b') && modifiers.contains(KeyModifiers::CONTROL) && app.edit_supports_binary() {
         app.edit_binary_mode = !app.edit_binary_mode;
         let state = if app.edit_binary_mode { "ON" } else { "OFF" };
         app.status_message = format!("Binary encode: {}", state);
@@ -635,56 +1173,184 @@ fn handle_edit_input(
                 }
             }
         }
-        KeyCode::Enter => {
-            match app.execute_edit(client) {
-                Ok(_) => {
-                    let op_label = app.edit_op_label().to_string();
-                    let key = app.edit_key.clone();
-                    if app.is_multi_entry_edit() {
-                        // Stay open for next entry, clear fields
-                        app.reset_edit_fields_for_next();
-                        app.status_message = format!(
-                            "{} on '{}' OK ({} added so far)",
-                            op_label, key, app.edit_multi_count
-                        );
-                    } else {
-                        // Single-entry operation, close popup
-                        app.cancel_edit();
-                        app.status_message = format!("{} on '{}' OK", op_label, key);
-                        app.refresh_keys(client);
-                        app.load_selected_value(client);
-                    }
-                }
-                Err(e) => {
-                    app.status_message = format!("Error: {}", e);
-                }
+        // Alt+Enter breaks the line in multi-line values
+        KeyCode::Enter if modifiers.contains(KeyModifiers::ALT) => {
+            handle_text_input(app, code, modifiers);
+        }
+        // Removals and trims are previewed and confirmed first
+        KeyCode::Enter if app.is_remove_edit() => {
+            if let Err(e) = app.confirm_remove(client) {
+                app.status_message = format!("Error: {}", e);
             }
         }
-        // Left/Right to change type for new key
-        KeyCode::Left if is_new_key => {
+        KeyCode::Enter => submit_edit(app, client),
+        // PgUp/PgDn to change type for new key
+        KeyCode::PageUp if is_new_key => {
             if app.new_key_type_idx == 0 {
                 app.new_key_type_idx = app::KEY_TYPES.len() - 1;
             } else {
                 app.new_key_type_idx -= 1;
             }
         }
-        KeyCode::Right if is_new_key => {
+        KeyCode::PageDown if is_new_key => {
             app.new_key_type_idx = (app.new_key_type_idx + 1) % app::KEY_TYPES.len();
         }
-        KeyCode::Backspace => {
-            if let Some((_label, value)) = app.edit_fields.get_mut(app.edit_focus) {
-                value.pop();
+        // PgUp/PgDn to change the JSON.* command
+        KeyCode::PageUp if app.is_json_edit() => app.cycle_json_op(false),
+        KeyCode::PageDown if app.is_json_edit() => app.cycle_json_op(true),
+        // PgUp/PgDn to change the removal/trim command
+        KeyCode::PageUp if app.is_remove_edit() => app.cycle_remove_op(false),
+        KeyCode::PageDown if app.is_remove_edit() => app.cycle_remove_op(true),
+        _ => handle_text_input(app, code, modifiers),
+    }
+}
+
+/// Run the edit in the popup; multi-entry edits stay open for the next one
+fn submit_edit(app: &mut App, client: &mut RedisClient) {
+    match app.execute_edit(client) {
+        Ok(_) => {
+            let op_label = app.edit_op_label().to_string();
+            let key = app.edit_key.clone();
+            if app.is_multi_entry_edit() {
+                // Stay open for next entry, clear fields
+                app.reset_edit_fields_for_next();
+                app.status_message = format!(
+                    "{} on '{}' OK ({} added so far)",
+                    op_label, key, app.edit_multi_count
+                );
+                // The next entry is checked against the value as written
+                app.load_selected_value(client);
+            } else {
+                // Single-entry operation, close popup
+                app.cancel_edit();
+                app.status_message = format!("{} on '{}' OK", op_label, key);
+                app.refresh_keys(client);
+                app.load_selected_value(client);
             }
         }
-        KeyCode::Char(c) => {
-            if let Some((_label, value)) = app.edit_fields.get_mut(app.edit_focus) {
-                value.push(c);
+        Err(e) => {
+            app.status_message = format!("Error: {}", e);
+        }
+    }
+}
+
+/// The value changed on the server before an edit was saved
+fn handle_conflict_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        KeyCode::Up => app.scroll_conflict(-1),
+        KeyCode::Down => app.scroll_conflict(1),
+        KeyCode::PageUp => app.scroll_conflict(-10),
+        KeyCode::PageDown => app.scroll_conflict(10),
+        KeyCode::Char('o') => {
+            app.overwrite_conflict();
+            submit_edit(app, client);
+        }
+        KeyCode::Char('m') => match app.merge_conflict() {
+            // Removals are previewed again against the server's value
+            Ok(0) if app.is_remove_edit() => {
+                if let Err(e) = app.confirm_remove(client) {
+                    app.status_message = format!("Error: {}", e);
+                }
+            }
+            Ok(0) => submit_edit(app, client),
+            Ok(conflicts) => {
+                app.status_message = format!(
+                    "{} conflicting change(s) marked with <<<<<<< / >>>>>>>: resolve them and press Enter",
+                    conflicts
+                );
             }
+            Err(e) => app.status_message = format!("Error: {}", e),
+        },
+        KeyCode::Char('r') => {
+            app.edit_conflict = None;
+            app.cancel_edit();
+            app.load_selected_value(client);
+            app.status_message = "Reloaded; the edit was discarded".to_string();
+        }
+        KeyCode::Esc => {
+            app.edit_conflict = None;
+            app.input_mode = InputMode::Edit;
+        }
+        _ => {}
+    }
+}
+
+fn handle_undo_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        KeyCode::Up => app.move_undo_cursor(false),
+        KeyCode::Down => app.move_undo_cursor(true),
+        KeyCode::Enter => {
+            if let Some(idx) = app.undo_selected() {
+                app.status_message = match app.undo(client, idx) {
+                    Ok(msg) => msg,
+                    Err(e) => format!("Error: {}", e),
+                };
+            }
+        }
+        // Forget a snapshot without restoring it
+        KeyCode::Delete | KeyCode::Char('x') => {
+            if let Some(idx) = app.undo_selected() {
+                app.undo_stack.remove(idx);
+                app.move_undo_cursor(false);
+                app.move_undo_cursor(true);
+            }
+        }
+        KeyCode::Esc | KeyCode::Char('U') => app.input_mode = InputMode::Normal,
+        _ => {}
+    }
+}
+
+/// Cursor movement, selection and editing keys in the focused edit field
+fn handle_text_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+    let select = modifiers.contains(KeyModifiers::SHIFT);
+    let multiline = app.edit_field_multiline(app.edit_focus);
+
+    // Up/Down leave the field at its first/last line
+    if matches!(code, KeyCode::Up | KeyCode::Down) {
+        let down = code == KeyCode::Down;
+        let moved = app.focused_text().is_some_and(|(area, text)| area.vertical(text, down, select));
+        if !moved && down {
+            app.edit_next_field();
+        } else if !moved {
+            app.edit_prev_field();
         }
+        return;
+    }
+    let clipboard = app.clipboard.clone();
+    let Some((area, text)) = app.focused_text() else {
+        return;
+    };
+    let mut copied = None;
+    match code {
+        KeyCode::Enter if multiline => area.insert(text, "\n"),
+        KeyCode::Left if ctrl => area.word_left(text, select),
+        KeyCode::Right if ctrl => area.word_right(text, select),
+        KeyCode::Left => area.left(select),
+        KeyCode::Right => area.right(text, select),
+        KeyCode::Home => area.home(text, select),
+        KeyCode::End => area.end(text, select),
+        KeyCode::Backspace if ctrl || modifiers.contains(KeyModifiers::ALT) => area.delete_word(text),
+        KeyCode::Backspace => area.backspace(text),
+        KeyCode::Delete => area.delete(text),
+        KeyCode::Char('a') if ctrl => area.select_all(text),
+        KeyCode::Char('z') if ctrl => area.undo(text),
+        KeyCode::Char('y') if ctrl => area.redo(text),
+        KeyCode::Char('w') if ctrl => area.delete_word(text),
+        KeyCode::Char('c') if ctrl => copied = area.selected_text(text),
+        KeyCode::Char('x') if ctrl => copied = area.cut(text),
+        KeyCode::Char('v') if ctrl => area.insert(text, &clipboard),
+        KeyCode::Char(_) if ctrl => {}
+        KeyCode::Char(c) => area.insert(text, c.encode_utf8(&mut [0; 4])),
         _ => {}
     }
+    if let Some(copied) = copied {
+        app.clipboard = copied;
+    }
+    app.xadd_grow();
 }
 
+
 fn handle_signal_gen_input(app: &mut App, code: KeyCode) {
     match code {
         KeyCode::Esc => {
@@ -851,6 +1517,208 @@ fn handle_plot_limit_input(app: &mut App, code: KeyCode) {
     }
 }
 
+/// Keys shared by the form popups: Esc closes the popup, Tab/BackTab move
+/// between fields and typing edits the focused field
+fn handle_form_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+        }
+        KeyCode::Tab => app.edit_next_field(),
+        KeyCode::BackTab => app.edit_prev_field(),
+        KeyCode::Backspace => {
+            if let Some((_label, value)) = app.edit_fields.get_mut(app.edit_focus) {
+                value.pop();
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Some((_label, value)) = app.edit_fields.get_mut(app.edit_focus) {
+                value.push(c);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn handle_record_layout_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Enter => match app.apply_record_layout() {
+            Ok(_) => {
+                app.status_message = match &app.record_layout {
+                    Some(layout) => format!("Record layout: {}", layout.summary()),
+                    None => "Record layout cleared".to_string(),
+                };
+                app.input_mode = InputMode::Normal;
+            }
+            Err(e) => {
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_scaling_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Enter => match app.apply_scaling() {
+            Ok(_) => {
+                app.status_message = format!("Scaling: {}", app.scaling_summary());
+                app.input_mode = InputMode::Normal;
+            }
+            Err(e) => {
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_value_format_input(app: &mut App, code: KeyCode) {
+    match code {
+        KeyCode::Enter => match app.apply_value_format() {
+            Ok(msg) => {
+                app.status_message = msg;
+                app.input_mode = InputMode::Normal;
+            }
+            Err(e) => {
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_query_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        KeyCode::Enter => match app.run_query(client) {
+            Ok(msg) => {
+                app.input_mode = InputMode::Normal;
+                app.status_message = msg;
+            }
+            Err(e) => {
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_vector_compare_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        KeyCode::Enter => match app.run_vector_compare(client) {
+            Ok(msg) => {
+                app.status_message = msg;
+            }
+            Err(e) => {
+                app.vector_result.clear();
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_search_indexes_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+        }
+        KeyCode::Up => app.move_search_cursor(client, -1),
+        KeyCode::Down => app.move_search_cursor(client, 1),
+        KeyCode::Char('r') => app.open_search_browser(client),
+        KeyCode::Enter => app.start_search_query(),
+        _ => {}
+    }
+}
+
+fn handle_search_query_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::SearchIndexes;
+        }
+        // The command field is a selector
+        KeyCode::Left | KeyCode::Right if app.edit_focus == 0 => app.toggle_search_command(),
+        KeyCode::Backspace | KeyCode::Char(_) if app.edit_focus == 0 => {}
+        KeyCode::Enter => match app.run_search(client) {
+            Ok(msg) => {
+                app.status_message = msg;
+            }
+            Err(e) => {
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        _ => handle_form_input(app, code),
+    }
+}
+
+fn handle_search_results_input(app: &mut App, client: &mut RedisClient, code: KeyCode) {
+    match code {
+        // Back to the query form to refine it
+        KeyCode::Esc => app.start_search_query(),
+        KeyCode::Up => app.move_search_result_cursor(-1),
+        KeyCode::Down => app.move_search_result_cursor(1),
+        KeyCode::PageUp => app.move_search_result_cursor(-10),
+        KeyCode::PageDown => app.move_search_result_cursor(10),
+        KeyCode::Enter => app.open_search_result(client),
+        _ => {}
+    }
+}
+
+fn handle_profiles_input(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    match code {
+        KeyCode::Esc => {
+            app.input_mode = InputMode::Normal;
+        }
+        KeyCode::Up => {
+            app.profile_cursor = app.profile_cursor.saturating_sub(1);
+        }
+        KeyCode::Down if app.profile_cursor + 1 < app.profiles.profiles.len() => {
+            app.profile_cursor += 1;
+        }
+        KeyCode::Enter => match app.save_current_profile() {
+            Ok(pattern) => {
+                app.status_message = format!("Profile '{}' saved", pattern);
+            }
+            Err(e) => {
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        KeyCode::Delete => match app.delete_selected_profile() {
+            Ok(pattern) => {
+                app.status_message = format!("Profile '{}' deleted", pattern);
+            }
+            Err(e) => {
+                app.status_message = format!("Error: {}", e);
+            }
+        },
+        // Ctrl+L: apply the selected profile to the current key now
+        KeyCode::Char('l') if modifiers.contains(KeyModifiers::CONTROL) => {
+            match app.apply_selected_profile() {
+                Ok(pattern) => {
+                    app.status_message = format!("Profile '{}' applied", pattern);
+                    app.input_mode = InputMode::Normal;
+                }
+                Err(e) => {
+                    app.status_message = format!("Error: {}", e);
+                }
+            }
+        }
+        // Ctrl+P: copy the selected profile's pattern into the input
+        KeyCode::Char('p') if modifiers.contains(KeyModifiers::CONTROL) => {
+            if let Some(profile) = app.profiles.profiles.get(app.profile_cursor) {
+                app.profile_pattern_input = profile.pattern.clone();
+            }
+        }
+        KeyCode::Backspace => {
+            app.profile_pattern_input.pop();
+        }
+        KeyCode::Char(c) => {
+            app.profile_pattern_input.push(c);
+        }
+        _ => {}
+    }
+}
+
 fn handle_mouse_event(app: &mut App, mouse: MouseEvent) {
     let col = mouse.column;
     let row = mouse.row;
@@ -0,0 +1,859 @@
+use crate::config::glob_match;
+use crate::redis_client::{KeyInfo, RedisValue, StreamEntry};
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::BTreeMap;
+
+// Opcodes between keys
+const OP_SLOT_INFO: u8 = 0xF4;
+const OP_FUNCTION2: u8 = 0xF5;
+const OP_FUNCTION_PRE_GA: u8 = 0xF6;
+const OP_MODULE_AUX: u8 = 0xF7;
+const OP_IDLE: u8 = 0xF8;
+const OP_FREQ: u8 = 0xF9;
+const OP_AUX: u8 = 0xFA;
+const OP_RESIZEDB: u8 = 0xFB;
+const OP_EXPIRETIME_MS: u8 = 0xFC;
+const OP_EXPIRETIME: u8 = 0xFD;
+const OP_SELECTDB: u8 = 0xFE;
+const OP_EOF: u8 = 0xFF;
+
+// Value types
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_SET: u8 = 2;
+const TYPE_ZSET: u8 = 3;
+const TYPE_HASH: u8 = 4;
+const TYPE_ZSET_2: u8 = 5;
+const TYPE_MODULE_2: u8 = 7;
+const TYPE_HASH_ZIPMAP: u8 = 9;
+const TYPE_LIST_ZIPLIST: u8 = 10;
+const TYPE_SET_INTSET: u8 = 11;
+const TYPE_ZSET_ZIPLIST: u8 = 12;
+const TYPE_HASH_ZIPLIST: u8 = 13;
+const TYPE_LIST_QUICKLIST: u8 = 14;
+const TYPE_STREAM_LISTPACKS: u8 = 15;
+const TYPE_HASH_LISTPACK: u8 = 16;
+const TYPE_ZSET_LISTPACK: u8 = 17;
+const TYPE_LIST_QUICKLIST_2: u8 = 18;
+const TYPE_STREAM_LISTPACKS_2: u8 = 19;
+const TYPE_SET_LISTPACK: u8 = 20;
+const TYPE_STREAM_LISTPACKS_3: u8 = 21;
+const TYPE_HASH_METADATA: u8 = 24;
+const TYPE_HASH_LISTPACK_EX: u8 = 25;
+
+// Module value opcodes
+const MODULE_OP_EOF: u64 = 0;
+const MODULE_OP_SINT: u64 = 1;
+const MODULE_OP_UINT: u64 = 2;
+const MODULE_OP_FLOAT: u64 = 3;
+const MODULE_OP_DOUBLE: u64 = 4;
+const MODULE_OP_STRING: u64 = 5;
+
+/// Quicklist 2 node holding one large element instead of a listpack
+const QUICKLIST_NODE_PLAIN: u64 = 1;
+
+/// Stream entry flags in a listpack
+const STREAM_ITEM_DELETED: i64 = 1;
+const STREAM_ITEM_SAMEFIELDS: i64 = 2;
+
+/// A key read from an RDB file
+#[derive(Debug, Clone)]
+pub struct RdbKey {
+    pub key_type: String, // as TYPE would report it
+    pub encoding: String, // as OBJECT ENCODING would report it
+    pub value: RedisValue,
+    pub expires_at: Option<i64>, // ms since the epoch
+}
+
+/// Every key of an RDB snapshot, by database
+pub struct RdbFile {
+    pub path: String,
+    pub version: u32,
+    pub created_at: Option<i64>, // ms since the epoch, from the `ctime` aux field
+    pub dbs: BTreeMap<i64, BTreeMap<String, RdbKey>>,
+}
+
+impl RdbFile {
+    /// Read and parse a whole `dump.rdb`; the trailing checksum is not verified
+    pub fn open(path: &str) -> Result<Self> {
+        let data = std::fs::read(path).with_context(|| format!("Cannot read {}", path))?;
+        let mut rdb = Self {
+            path: path.to_string(),
+            version: 0,
+            created_at: None,
+            dbs: BTreeMap::new(),
+        };
+        rdb.parse(&data).with_context(|| format!("Cannot parse {}", path))?;
+        Ok(rdb)
+    }
+
+    fn parse(&mut self, data: &[u8]) -> Result<()> {
+        let mut r = Reader { data, pos: 0 };
+        let magic = r.bytes(9)?;
+        if &magic[..5] != b"REDIS" {
+            bail!("Not an RDB file (no REDIS header)");
+        }
+        self.version = std::str::from_utf8(&magic[5..])
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| anyhow!("Bad RDB version {:?}", String::from_utf8_lossy(&magic[5..])))?;
+
+        let mut db = 0i64;
+        let mut expires_at = None;
+        loop {
+            let at = r.pos;
+            match r.u8()? {
+                OP_EOF => break,
+                OP_SELECTDB => db = r.len()? as i64,
+                OP_EXPIRETIME => expires_at = Some(r.u32_le()? as i64 * 1000),
+                OP_EXPIRETIME_MS => expires_at = Some(r.u64_le()? as i64),
+                OP_RESIZEDB => {
+                    r.len()?;
+                    r.len()?;
+                }
+                OP_AUX => {
+                    let name = r.string()?;
+                    let value = r.string()?;
+                    if name == b"ctime" {
+                        self.created_at = String::from_utf8_lossy(&value).parse::<i64>().ok().and_then(|s| s.checked_mul(1000));
+                    }
+                }
+                OP_FREQ => {
+                    r.u8()?;
+                }
+                OP_IDLE => {
+                    r.len()?;
+                }
+                OP_MODULE_AUX => {
+                    r.len()?; // module id
+                    r.len()?; // when opcode
+                    r.len()?; // when
+                    r.skip_module_value()?;
+                }
+                OP_FUNCTION2 => {
+                    r.string()?;
+                }
+                OP_FUNCTION_PRE_GA => bail!("Functions from Redis 7.0 release candidates are not supported"),
+                OP_SLOT_INFO => {
+                    r.len()?;
+                    r.len()?;
+                    r.len()?;
+                }
+                value_type => {
+                    let key = String::from_utf8_lossy(&r.string()?).to_string();
+                    let (key_type, encoding, value) = r
+                        .value(value_type)
+                        .with_context(|| format!("Key '{}' (type {}) at byte {}", key, value_type, at))?;
+                    let entry = RdbKey { key_type, encoding: encoding.to_string(), value, expires_at: expires_at.take() };
+                    self.dbs.entry(db).or_default().insert(key, entry);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The db to show first: 0, else the lowest one holding keys
+    pub fn first_db(&self) -> i64 {
+        match self.dbs.keys().next() {
+            Some(db) if !self.dbs.contains_key(&0) => *db,
+            _ => 0,
+        }
+    }
+
+    fn key(&self, db: i64, key: &str) -> Option<&RdbKey> {
+        self.dbs.get(&db)?.get(key)
+    }
+
+    pub fn db_size(&self, db: i64) -> usize {
+        self.dbs.get(&db).map_or(0, |keys| keys.len())
+    }
+
+    /// Sorted names of the keys of `db` matching a glob, like SCAN MATCH
+    pub fn scan(&self, db: i64, pattern: &str) -> Vec<String> {
+        self.dbs
+            .get(&db)
+            .into_iter()
+            .flat_map(|keys| keys.keys())
+            .filter(|key| glob_match(pattern, key))
+            .cloned()
+            .collect()
+    }
+
+    /// PTTL as the key had it when the snapshot was taken (or now, if the
+    /// file does not say when that was): -2 if missing, -1 without expiry
+    pub fn pttl(&self, db: i64, key: &str) -> i64 {
+        let base = self.created_at.unwrap_or_else(crate::formats::now_epoch_millis);
+        match self.key(db, key) {
+            None => -2,
+            Some(RdbKey { expires_at: None, .. }) => -1,
+            Some(RdbKey { expires_at: Some(at), .. }) => at.saturating_sub(base).max(0),
+        }
+    }
+
+    pub fn key_info(&self, db: i64, key: &str) -> KeyInfo {
+        let pttl = self.pttl(db, key);
+        let entry = self.key(db, key);
+        KeyInfo {
+            name: key.to_string(),
+            key_type: entry.map_or("none", |e| e.key_type.as_str()).to_string(),
+            ttl: if pttl > 0 { pttl / 1000 } else { pttl },
+            size: -1,
+            encoding: entry.map_or("none", |e| e.encoding.as_str()).to_string(),
+        }
+    }
+
+    pub fn key_type(&self, db: i64, key: &str) -> String {
+        self.key(db, key).map_or("none", |e| e.key_type.as_str()).to_string()
+    }
+
+    pub fn value(&self, db: i64, key: &str) -> Result<RedisValue> {
+        let entry = self.key(db, key).ok_or_else(|| anyhow!("No key '{}' in db {} of {}", key, db, self.path))?;
+        Ok(match &entry.value {
+            // As many entries as the views read from a server
+            RedisValue::Stream(_) => RedisValue::Stream(self.xrange(db, key, "-", 500)?),
+            value => value.clone(),
+        })
+    }
+
+    /// A string, or one hash field
+    pub fn bytes(&self, db: i64, key: &str, field: Option<&str>) -> Option<Vec<u8>> {
+        match (&self.key(db, key)?.value, field) {
+            (RedisValue::String(bytes), None) => Some(bytes.clone()),
+            (RedisValue::Hash(pairs), Some(field)) => pairs.iter().find(|(f, _)| f == field).map(|(_, v)| v.clone()),
+            _ => None,
+        }
+    }
+
+    /// Like XRANGE key start + COUNT count
+    pub fn xrange(&self, db: i64, key: &str, start: &str, count: usize) -> Result<Vec<StreamEntry>> {
+        let Some(RedisValue::Stream(entries)) = self.key(db, key).map(|e| &e.value) else {
+            return Ok(Vec::new());
+        };
+        let parse = |id: &str| -> Option<(u64, u64)> {
+            let (ms, seq) = id.split_once('-').unwrap_or((id, "0"));
+            Some((ms.parse().ok()?, seq.parse().ok()?))
+        };
+        let from = match start {
+            "-" => None,
+            _ => match start.strip_prefix('(') {
+                Some(id) => Some((parse(id).ok_or_else(|| anyhow!("Bad stream ID '{}'", id))?, true)),
+                None => Some((parse(start).ok_or_else(|| anyhow!("Bad stream ID '{}'", start))?, false)),
+            },
+        };
+        Ok(entries
+            .iter()
+            .filter(|entry| match (from, parse(&entry.id)) {
+                (Some((from, true)), Some(id)) => id > from,
+                (Some((from, false)), Some(id)) => id >= from,
+                _ => true,
+            })
+            .take(count)
+            .cloned()
+            .collect())
+    }
+}
+
+/// Redis' module type names: 9 characters of 6 bits above a 10-bit version
+fn module_name(id: u64) -> String {
+    const SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut id = id >> 10;
+    let mut name = [0u8; 9];
+    for c in name.iter_mut().rev() {
+        *c = SYMBOLS[(id & 63) as usize];
+        id >>= 6;
+    }
+    String::from_utf8_lossy(&name).to_string()
+}
+
+/// Cursor over the file or an embedded ziplist/listpack
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len());
+        let end = end.ok_or_else(|| anyhow!("Unexpected end of data at byte {} (wanted {} more)", self.pos, n))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.bytes(N)?.try_into().expect("slice of N bytes"))
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32_le(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64_le(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// A length, or the special encoding of a string (int or LZF)
+    fn length(&mut self) -> Result<Length> {
+        let b = self.u8()?;
+        Ok(match b >> 6 {
+            0 => Length::Len(u64::from(b & 0x3f)),
+            1 => Length::Len(u64::from(b & 0x3f) << 8 | u64::from(self.u8()?)),
+            2 => match b {
+                0x80 => Length::Len(u64::from(u32::from_be_bytes(self.array()?))),
+                0x81 => Length::Len(u64::from_be_bytes(self.array()?)),
+                _ => bail!("Bad length byte {:#04x} at byte {}", b, self.pos - 1),
+            },
+            _ => Length::Special(b & 0x3f),
+        })
+    }
+
+    fn len(&mut self) -> Result<u64> {
+        match self.length()? {
+            Length::Len(n) => Ok(n),
+            Length::Special(_) => bail!("Expected a length at byte {}", self.pos - 1),
+        }
+    }
+
+    fn count(&mut self) -> Result<usize> {
+        usize::try_from(self.len()?).map_err(|_| anyhow!("Length too large at byte {}", self.pos))
+    }
+
+    fn string(&mut self) -> Result<Vec<u8>> {
+        match self.length()? {
+            Length::Len(n) => Ok(self.bytes(n as usize)?.to_vec()),
+            Length::Special(0) => Ok((self.u8()? as i8).to_string().into_bytes()),
+            Length::Special(1) => Ok(i16::from_le_bytes(self.array()?).to_string().into_bytes()),
+            Length::Special(2) => Ok(i32::from_le_bytes(self.array()?).to_string().into_bytes()),
+            Length::Special(3) => {
+                let compressed = self.count()?;
+                let size = self.count()?;
+                lzf_decompress(self.bytes(compressed)?, size)
+            }
+            Length::Special(n) => bail!("Unknown string encoding {} at byte {}", n, self.pos - 1),
+        }
+    }
+
+    fn text(&mut self) -> Result<String> {
+        Ok(String::from_utf8_lossy(&self.string()?).to_string())
+    }
+
+    /// A sorted set score of the first zset type, written as text
+    fn score_text(&mut self) -> Result<f64> {
+        match self.u8()? {
+            253 => Ok(f64::NAN),
+            254 => Ok(f64::INFINITY),
+            255 => Ok(f64::NEG_INFINITY),
+            n => {
+                let text = String::from_utf8_lossy(self.bytes(n as usize)?).to_string();
+                text.parse().map_err(|_| anyhow!("Bad score '{}'", text))
+            }
+        }
+    }
+
+    fn strings(&mut self) -> Result<Vec<Vec<u8>>> {
+        (0..self.count()?).map(|_| self.string()).collect()
+    }
+
+    /// Skip the opcode-tagged values a module wrote, up to its EOF
+    fn skip_module_value(&mut self) -> Result<()> {
+        loop {
+            match self.len()? {
+                MODULE_OP_EOF => return Ok(()),
+                MODULE_OP_SINT | MODULE_OP_UINT => {
+                    self.len()?;
+                }
+                MODULE_OP_FLOAT => {
+                    self.bytes(4)?;
+                }
+                MODULE_OP_DOUBLE => {
+                    self.bytes(8)?;
+                }
+                MODULE_OP_STRING => {
+                    self.string()?;
+                }
+                op => bail!("Unknown module opcode {} at byte {}", op, self.pos),
+            }
+        }
+    }
+
+    /// A value of `value_type`, with the TYPE and OBJECT ENCODING names
+    fn value(&mut self, value_type: u8) -> Result<(String, &'static str, RedisValue)> {
+        let (key_type, encoding, value) = match value_type {
+            TYPE_STRING => ("string", "raw", RedisValue::String(self.string()?)),
+            TYPE_LIST => ("list", "linkedlist", RedisValue::List(self.strings()?)),
+            TYPE_LIST_ZIPLIST => ("list", "ziplist", RedisValue::List(ziplist(&self.string()?)?)),
+            TYPE_LIST_QUICKLIST => {
+                let mut items = Vec::new();
+                for _ in 0..self.count()? {
+                    items.extend(ziplist(&self.string()?)?);
+                }
+                ("list", "quicklist", RedisValue::List(items))
+            }
+            TYPE_LIST_QUICKLIST_2 => {
+                let mut items = Vec::new();
+                for _ in 0..self.count()? {
+                    let container = self.len()?;
+                    let node = self.string()?;
+                    if container == QUICKLIST_NODE_PLAIN {
+                        items.push(node);
+                    } else {
+                        items.extend(listpack(&node)?);
+                    }
+                }
+                ("list", "quicklist", RedisValue::List(items))
+            }
+            TYPE_SET => ("set", "hashtable", RedisValue::Set(self.strings()?)),
+            TYPE_SET_INTSET => ("set", "intset", RedisValue::Set(intset(&self.string()?)?)),
+            TYPE_SET_LISTPACK => ("set", "listpack", RedisValue::Set(listpack(&self.string()?)?)),
+            TYPE_ZSET | TYPE_ZSET_2 => {
+                let mut pairs = Vec::new();
+                for _ in 0..self.count()? {
+                    let member = self.string()?;
+                    let score = if value_type == TYPE_ZSET_2 {
+                        f64::from_le_bytes(self.array()?)
+                    } else {
+                        self.score_text()?
+                    };
+                    pairs.push((member, score));
+                }
+                ("zset", "skiplist", RedisValue::ZSet(pairs))
+            }
+            TYPE_ZSET_ZIPLIST => ("zset", "ziplist", RedisValue::ZSet(score_pairs(ziplist(&self.string()?)?)?)),
+            TYPE_ZSET_LISTPACK => ("zset", "listpack", RedisValue::ZSet(score_pairs(listpack(&self.string()?)?)?)),
+            TYPE_HASH => {
+                let mut pairs = Vec::new();
+                for _ in 0..self.count()? {
+                    pairs.push((self.text()?, self.string()?));
+                }
+                ("hash", "hashtable", RedisValue::Hash(pairs))
+            }
+            TYPE_HASH_ZIPMAP => ("hash", "zipmap", RedisValue::Hash(zipmap(&self.string()?)?)),
+            TYPE_HASH_ZIPLIST => ("hash", "ziplist", RedisValue::Hash(field_pairs(ziplist(&self.string()?)?, 2))),
+            TYPE_HASH_LISTPACK => ("hash", "listpack", RedisValue::Hash(field_pairs(listpack(&self.string()?)?, 2))),
+            // Hashes with field TTLs (Redis 7.4+); the TTLs themselves are dropped
+            TYPE_HASH_LISTPACK_EX => {
+                self.bytes(8)?; // earliest field expiry
+                ("hash", "listpackex", RedisValue::Hash(field_pairs(listpack(&self.string()?)?, 3)))
+            }
+            TYPE_HASH_METADATA => {
+                self.bytes(8)?;
+                let mut pairs = Vec::new();
+                for _ in 0..self.count()? {
+                    self.len()?; // field TTL
+                    pairs.push((self.text()?, self.string()?));
+                }
+                ("hash", "hashtable", RedisValue::Hash(pairs))
+            }
+            TYPE_STREAM_LISTPACKS | TYPE_STREAM_LISTPACKS_2 | TYPE_STREAM_LISTPACKS_3 => {
+                ("stream", "stream", RedisValue::Stream(self.stream(value_type)?))
+            }
+            TYPE_MODULE_2 => {
+                let name = module_name(self.len()?);
+                self.skip_module_value()?;
+                let note = format!("{} module value (not decoded from RDB files)", name);
+                return Ok((name, "module", RedisValue::Unknown(note)));
+            }
+            other => bail!("Unsupported value type {}", other),
+        };
+        // The orders ZRANGE and HGETALL views use
+        let value = match value {
+            RedisValue::ZSet(mut pairs) => {
+                pairs.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+                RedisValue::ZSet(pairs)
+            }
+            RedisValue::Hash(mut pairs) => {
+                pairs.sort_by(|a, b| a.0.cmp(&b.0));
+                RedisValue::Hash(pairs)
+            }
+            other => other,
+        };
+        Ok((key_type.to_string(), encoding, value))
+    }
+
+    /// Stream entries, skipping the consumer groups that follow them
+    fn stream(&mut self, value_type: u8) -> Result<Vec<StreamEntry>> {
+        let mut entries = Vec::new();
+        for _ in 0..self.count()? {
+            let master = self.string()?;
+            if master.len() != 16 {
+                bail!("Bad stream node ID of {} bytes", master.len());
+            }
+            let ms = u64::from_be_bytes(master[..8].try_into().expect("8 bytes"));
+            let seq = u64::from_be_bytes(master[8..].try_into().expect("8 bytes"));
+            stream_node(ms, seq, &listpack(&self.string()?)?, &mut entries)?;
+        }
+        self.len()?; // length
+        self.len()?; // last ID
+        self.len()?;
+        if value_type >= TYPE_STREAM_LISTPACKS_2 {
+            for _ in 0..5 {
+                self.len()?; // first ID, max deleted ID, entries added
+            }
+        }
+        for _ in 0..self.count()? {
+            self.string()?; // group name
+            self.len()?; // last delivered ID
+            self.len()?;
+            if value_type >= TYPE_STREAM_LISTPACKS_2 {
+                self.len()?; // entries read
+            }
+            for _ in 0..self.count()? {
+                self.bytes(16 + 8)?; // ID, delivery time
+                self.len()?; // delivery count
+            }
+            for _ in 0..self.count()? {
+                self.string()?; // consumer name
+                self.bytes(8)?; // seen time
+                if value_type >= TYPE_STREAM_LISTPACKS_3 {
+                    self.bytes(8)?; // active time
+                }
+                for _ in 0..self.count()? {
+                    self.bytes(16)?; // pending ID
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+enum Length {
+    Len(u64),
+    Special(u8),
+}
+
+/// Decompress an LZF-compressed string of `size` bytes
+fn lzf_decompress(input: &[u8], size: usize) -> Result<Vec<u8>> {
+    // `size` comes from the file; LZF rarely expands data more than 4 times
+    let mut out: Vec<u8> = Vec::with_capacity(size.min(input.len().saturating_mul(4)));
+    let mut i = 0;
+    let truncated = || anyhow!("Truncated LZF data");
+    while i < input.len() {
+        let ctrl = input[i] as usize;
+        i += 1;
+        if ctrl < 32 {
+            let literal = input.get(i..i + ctrl + 1).ok_or_else(truncated)?;
+            out.extend_from_slice(literal);
+            i += ctrl + 1;
+        } else {
+            let mut len = ctrl >> 5;
+            if len == 7 {
+                len += *input.get(i).ok_or_else(truncated)? as usize;
+                i += 1;
+            }
+            let back = ((ctrl & 0x1f) << 8) + *input.get(i).ok_or_else(truncated)? as usize + 1;
+            i += 1;
+            let start = out.len().checked_sub(back).ok_or_else(|| anyhow!("Bad LZF back reference"))?;
+            // Byte by byte: the copy may overlap what it produces
+            for k in 0..len + 2 {
+                out.push(out[start + k]);
+            }
+        }
+        if out.len() > size {
+            bail!("LZF data is longer than {} bytes", size);
+        }
+    }
+    if out.len() != size {
+        bail!("LZF data gave {} bytes instead of {}", out.len(), size);
+    }
+    Ok(out)
+}
+
+/// Elements of a ziplist, integers as decimal text
+fn ziplist(blob: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut r = Reader::new(blob);
+    r.bytes(10)?; // total bytes, tail offset, count
+    let mut items = Vec::new();
+    loop {
+        match r.u8()? {
+            0xFF => return Ok(items),
+            0xFE => {
+                r.bytes(4)?; // long previous entry length
+            }
+            _ => {}
+        }
+        let enc = r.u8()?;
+        let item = match enc >> 6 {
+            0 => r.bytes(usize::from(enc & 0x3f))?.to_vec(),
+            1 => {
+                let len = usize::from(enc & 0x3f) << 8 | usize::from(r.u8()?);
+                r.bytes(len)?.to_vec()
+            }
+            2 => {
+                let len = u32::from_be_bytes(r.array()?) as usize;
+                r.bytes(len)?.to_vec()
+            }
+            _ => {
+                let n: i64 = match enc {
+                    0xC0 => i16::from_le_bytes(r.array()?).into(),
+                    0xD0 => i32::from_le_bytes(r.array()?).into(),
+                    0xE0 => i64::from_le_bytes(r.array()?),
+                    0xF0 => int24(r.array()?),
+                    0xFE => (r.u8()? as i8).into(),
+                    0xF1..=0xFD => i64::from(enc & 0x0f) - 1,
+                    _ => bail!("Bad ziplist encoding {:#04x}", enc),
+                };
+                n.to_string().into_bytes()
+            }
+        };
+        items.push(item);
+    }
+}
+
+/// Elements of a listpack, integers as decimal text
+fn listpack(blob: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut r = Reader::new(blob);
+    r.bytes(6)?; // total bytes, count
+    let mut items = Vec::new();
+    loop {
+        let start = r.pos;
+        let b = r.u8()?;
+        let item = if b == 0xFF {
+            return Ok(items);
+        } else if b & 0x80 == 0 {
+            (b & 0x7f).to_string().into_bytes()
+        } else if b & 0xC0 == 0x80 {
+            r.bytes(usize::from(b & 0x3f))?.to_vec()
+        } else if b & 0xE0 == 0xC0 {
+            let n = i64::from(b & 0x1f) << 8 | i64::from(r.u8()?);
+            // 13-bit two's complement
+            let n = if n >= 1 << 12 { n - (1 << 13) } else { n };
+            n.to_string().into_bytes()
+        } else if b & 0xF0 == 0xE0 {
+            let len = usize::from(b & 0x0f) << 8 | usize::from(r.u8()?);
+            r.bytes(len)?.to_vec()
+        } else {
+            let n: i64 = match b {
+                0xF0 => {
+                    let len = r.u32_le()? as usize;
+                    let item = r.bytes(len)?.to_vec();
+                    r.bytes(backlen_size(r.pos - start))?;
+                    items.push(item);
+                    continue;
+                }
+                0xF1 => i16::from_le_bytes(r.array()?).into(),
+                0xF2 => int24(r.array()?),
+                0xF3 => i32::from_le_bytes(r.array()?).into(),
+                0xF4 => i64::from_le_bytes(r.array()?),
+                _ => bail!("Bad listpack encoding {:#04x}", b),
+            };
+            n.to_string().into_bytes()
+        };
+        r.bytes(backlen_size(r.pos - start))?;
+        items.push(item);
+    }
+}
+
+/// Bytes of the back-length that ends a listpack entry of `len` bytes
+fn backlen_size(len: usize) -> usize {
+    match len {
+        0..=127 => 1,
+        128..=16_383 => 2,
+        16_384..=2_097_151 => 3,
+        2_097_152..=268_435_455 => 4,
+        _ => 5,
+    }
+}
+
+fn int24(bytes: [u8; 3]) -> i64 {
+    // Into the top of an i32 so the shift back extends the sign
+    i64::from(i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8)
+}
+
+fn intset(blob: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut r = Reader::new(blob);
+    let width = r.u32_le()?;
+    let count = r.u32_le()?;
+    (0..count)
+        .map(|_| {
+            let n: i64 = match width {
+                2 => i16::from_le_bytes(r.array()?).into(),
+                4 => i32::from_le_bytes(r.array()?).into(),
+                8 => i64::from_le_bytes(r.array()?),
+                _ => bail!("Bad intset width {}", width),
+            };
+            Ok(n.to_string().into_bytes())
+        })
+        .collect()
+}
+
+/// Field/value pairs of the hash encoding used before Redis 2.6
+fn zipmap(blob: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut r = Reader::new(blob);
+    r.u8()?; // count, if below 254
+    let mut pairs = Vec::new();
+    let len = |r: &mut Reader| -> Result<Option<usize>> {
+        Ok(match r.u8()? {
+            255 => None,
+            254 => Some(r.u32_le()? as usize),
+            n => Some(usize::from(n)),
+        })
+    };
+    while let Some(field_len) = len(&mut r)? {
+        let field = String::from_utf8_lossy(r.bytes(field_len)?).to_string();
+        let value_len = len(&mut r)?.ok_or_else(|| anyhow!("Zipmap ends inside an entry"))?;
+        let free = usize::from(r.u8()?);
+        pairs.push((field, r.bytes(value_len)?.to_vec()));
+        r.bytes(free)?;
+    }
+    Ok(pairs)
+}
+
+/// Hash fields from a flat field, value[, TTL] list
+fn field_pairs(items: Vec<Vec<u8>>, stride: usize) -> Vec<(String, Vec<u8>)> {
+    items
+        .chunks(stride)
+        .filter(|chunk| chunk.len() >= 2)
+        .map(|chunk| (String::from_utf8_lossy(&chunk[0]).to_string(), chunk[1].clone()))
+        .collect()
+}
+
+/// Sorted set members from a flat member, score list
+fn score_pairs(items: Vec<Vec<u8>>) -> Result<Vec<(Vec<u8>, f64)>> {
+    items
+        .chunks(2)
+        .map(|chunk| {
+            let text = String::from_utf8_lossy(chunk.get(1).ok_or_else(|| anyhow!("Member without a score"))?);
+            let score = text.parse().map_err(|_| anyhow!("Bad score '{}'", text))?;
+            Ok((chunk[0].clone(), score))
+        })
+        .collect()
+}
+
+/// Entries of one stream listpack node; IDs are stored relative to `ms-seq`
+fn stream_node(ms: u64, seq: u64, items: &[Vec<u8>], entries: &mut Vec<StreamEntry>) -> Result<()> {
+    let mut pos = 0;
+    let mut next = || -> Result<&Vec<u8>> {
+        pos += 1;
+        items.get(pos - 1).ok_or_else(|| anyhow!("Stream node ends early"))
+    };
+    let int = |item: &Vec<u8>| -> Result<i64> {
+        String::from_utf8_lossy(item).parse().map_err(|_| anyhow!("Bad number in stream node"))
+    };
+    let text = |item: &Vec<u8>| String::from_utf8_lossy(item).to_string();
+
+    // Master entry: live count, deleted count, the shared field names, 0
+    let count = int(next()?)?;
+    let deleted = int(next()?)?;
+    let master_fields: Vec<String> = (0..int(next()?)?).map(|_| next().map(text)).collect::<Result<_>>()?;
+    next()?;
+
+    for _ in 0..count.saturating_add(deleted) {
+        let flags = int(next()?)?;
+        let id = format!("{}-{}", ms.wrapping_add(int(next()?)? as u64), seq.wrapping_add(int(next()?)? as u64));
+        let fields = if flags & STREAM_ITEM_SAMEFIELDS != 0 {
+            master_fields.iter().map(|f| Ok((f.clone(), next()?.clone()))).collect::<Result<Vec<_>>>()?
+        } else {
+            (0..int(next()?)?).map(|_| Ok((text(next()?), next()?.clone()))).collect::<Result<Vec<_>>>()?
+        };
+        next()?; // element count, for walking backwards
+        if flags & STREAM_ITEM_DELETED == 0 {
+            entries.push(StreamEntry { id, fields });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `["a", 5, -300, "bb"]`, each entry followed by its back-length
+    const LISTPACK: &[u8] = &[
+        0x13, 0, 0, 0, 4, 0, // total bytes, count
+        0x81, b'a', 2, // 6-bit string
+        0x05, 1, // 7-bit uint
+        0xDE, 0xD4, 2, // 13-bit int -300
+        0x82, b'b', b'b', 3, // 6-bit string
+        0xFF,
+    ];
+
+    fn parse(data: &[u8]) -> RdbFile {
+        let mut rdb = RdbFile { path: "test.rdb".to_string(), version: 0, created_at: None, dbs: BTreeMap::new() };
+        rdb.parse(data).unwrap();
+        rdb
+    }
+
+    fn texts(items: &[Vec<u8>]) -> Vec<String> {
+        items.iter().map(|i| String::from_utf8_lossy(i).to_string()).collect()
+    }
+
+    #[test]
+    fn ziplist_entries() {
+        let blob = [
+            23, 0, 0, 0, 18, 0, 0, 0, 4, 0, // total bytes, tail offset, count
+            0, 0x01, b'x', // previous length, 6-bit string
+            3, 0xF8, // immediate 7
+            2, 0xFE, 0x9C, // int8 -100
+            3, 0xC0, 0xE8, 0x03, // int16 1000
+            0xFF,
+        ];
+        assert_eq!(texts(&ziplist(&blob).unwrap()), ["x", "7", "-100", "1000"]);
+        assert!(ziplist(&blob[..15]).is_err());
+    }
+
+    #[test]
+    fn listpack_entries() {
+        assert_eq!(texts(&listpack(LISTPACK).unwrap()), ["a", "5", "-300", "bb"]);
+        assert!(listpack(&LISTPACK[..12]).is_err());
+    }
+
+    #[test]
+    fn quicklist2_key() {
+        let mut data = b"REDIS0011".to_vec();
+        data.extend([OP_SELECTDB, 0]);
+        data.extend([18, 1, b'l', 2]); // quicklist 2, key "l", 2 nodes
+        data.extend([2, LISTPACK.len() as u8]); // packed node
+        data.extend(LISTPACK);
+        data.extend([1, 5]); // plain node
+        data.extend(b"PLAIN");
+        data.push(OP_EOF);
+        data.extend([0; 8]);
+
+        let rdb = parse(&data);
+        assert_eq!(rdb.key_type(0, "l"), "list");
+        match rdb.value(0, "l").unwrap() {
+            RedisValue::List(items) => assert_eq!(texts(&items), ["a", "5", "-300", "bb", "PLAIN"]),
+            _ => panic!("not a list"),
+        }
+    }
+
+    #[test]
+    fn stream_node_entries() {
+        let items: Vec<Vec<u8>> = [
+            "2", "1", "2", "temp", "hum", "0", // master entry
+            "2", "0", "0", "21.5", "40", "5", // same fields as the master
+            "0", "5", "1", "1", "_", "raw", "7", // own fields
+            "3", "9", "0", "x", "y", "5", // deleted
+        ]
+        .iter()
+        .map(|s| s.as_bytes().to_vec())
+        .collect();
+        let mut entries = Vec::new();
+        stream_node(1_700_000_000_000, 0, &items, &mut entries).unwrap();
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["1700000000000-0", "1700000000005-1"]);
+        assert_eq!(entries[0].fields[1], ("hum".to_string(), b"40".to_vec()));
+        assert_eq!(entries[1].fields, [("_".to_string(), b"raw".to_vec())]);
+        assert!(stream_node(0, 0, &items[..10], &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn corrupt_lengths() {
+        assert!(lzf_decompress(&[0, b'a'], usize::MAX).is_err());
+        assert_eq!(lzf_decompress(&[2, b'a', b'b', b'c', 0xE0, 0, 2], 12).unwrap(), b"abcabcabcabc");
+
+        let mut data = b"REDIS0011".to_vec();
+        data.extend([OP_SELECTDB, 0, OP_EXPIRETIME_MS]);
+        data.extend(i64::MIN.to_le_bytes());
+        data.extend([0, 1, b'k', 1, b'v', OP_EOF]);
+        assert_eq!(parse(&data).pttl(0, "k"), 0);
+    }
+}
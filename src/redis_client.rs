@@ -1,3 +1,5 @@
+use crate::formats::now_epoch_millis;
+use crate::rdb::RdbFile;
 use anyhow::{Context, Result};
 use redis::{Commands, ConnectionLike};
 use std::collections::HashMap;
@@ -19,6 +21,335 @@ pub struct StreamEntry {
     pub fields: Vec<(String, Vec<u8>)>,
 }
 
+/// XTRIM (or XADD) trimming: keep entries up to a length or from an ID on
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamTrim {
+    pub min_id: bool,        // MINID instead of MAXLEN
+    pub approximate: bool,   // `~`: only whole macro nodes are removed
+    pub threshold: String,   // the length, or the lowest ID kept
+    pub limit: Option<u64>,  // LIMIT on entries evicted (approximate only)
+}
+
+impl StreamTrim {
+    /// The trim arguments as they are sent, e.g. `MAXLEN ~ 1000`
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![if self.min_id { "MINID" } else { "MAXLEN" }.to_string()];
+        args.push(if self.approximate { "~" } else { "=" }.to_string());
+        args.push(self.threshold.clone());
+        if let (true, Some(limit)) = (self.approximate, self.limit) {
+            args.push("LIMIT".to_string());
+            args.push(limit.to_string());
+        }
+        args
+    }
+}
+
+/// A DUMP payload on its way to another server
+#[derive(Debug, Clone)]
+pub struct DumpedKey {
+    pub key: String,
+    pub payload: Vec<u8>,
+    pub ttl_ms: i64, // remaining, 0 = none
+}
+
+/// One write to the key of an edit, sent in MULTI/EXEC by `exec_watched`
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyWrite {
+    Set(Vec<u8>),
+    HSet(String, Vec<u8>),
+    LSet(i64, Vec<u8>),
+    SAdd(Vec<u8>),
+    ZAdd(f64, Vec<u8>),
+    JsonSet(String, String), // path, JSON
+    JsonDel(String),
+    HDel(String),
+    LRem(i64, Vec<u8>),
+    Pop { from_tail: bool, count: usize },
+    LTrim(i64, i64),
+    SRem(Vec<u8>),
+    ZRem(Vec<u8>),
+    ZRemRangeByScore(String, String),
+}
+
+impl KeyWrite {
+    fn command(&self, key: &str) -> redis::Cmd {
+        let name = match self {
+            KeyWrite::Set(_) => "SET",
+            KeyWrite::HSet(..) => "HSET",
+            KeyWrite::LSet(..) => "LSET",
+            KeyWrite::SAdd(_) => "SADD",
+            KeyWrite::ZAdd(..) => "ZADD",
+            KeyWrite::JsonSet(..) => "JSON.SET",
+            KeyWrite::JsonDel(_) => "JSON.DEL",
+            KeyWrite::HDel(_) => "HDEL",
+            KeyWrite::LRem(..) => "LREM",
+            KeyWrite::Pop { from_tail: true, .. } => "RPOP",
+            KeyWrite::Pop { from_tail: false, .. } => "LPOP",
+            KeyWrite::LTrim(..) => "LTRIM",
+            KeyWrite::SRem(_) => "SREM",
+            KeyWrite::ZRem(_) => "ZREM",
+            KeyWrite::ZRemRangeByScore(..) => "ZREMRANGEBYSCORE",
+        };
+        let mut cmd = redis::cmd(name);
+        cmd.arg(key);
+        match self {
+            KeyWrite::Set(value) | KeyWrite::SAdd(value) | KeyWrite::SRem(value) | KeyWrite::ZRem(value) => {
+                cmd.arg(value);
+            }
+            KeyWrite::HSet(field, value) => {
+                cmd.arg(field).arg(value);
+            }
+            KeyWrite::LSet(n, value) | KeyWrite::LRem(n, value) => {
+                cmd.arg(n).arg(value);
+            }
+            KeyWrite::ZAdd(score, member) => {
+                cmd.arg(score).arg(member);
+            }
+            KeyWrite::JsonSet(a, b) | KeyWrite::ZRemRangeByScore(a, b) => {
+                cmd.arg(a).arg(b);
+            }
+            KeyWrite::JsonDel(a) | KeyWrite::HDel(a) => {
+                cmd.arg(a);
+            }
+            KeyWrite::Pop { count, .. } => {
+                cmd.arg(count);
+            }
+            KeyWrite::LTrim(start, stop) => {
+                cmd.arg(start).arg(stop);
+            }
+        }
+        cmd
+    }
+}
+
+/// Range query for a RedisTimeSeries key (TS.RANGE / TS.REVRANGE)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TsQuery {
+    pub from: String, // "-" or a millisecond timestamp
+    pub to: String,   // "+" or a millisecond timestamp
+    pub aggregation: Option<(String, u64)>, // aggregator and bucket duration in ms
+    pub count: usize, // 0 = no limit
+    pub reverse: bool, // TS.REVRANGE: newest samples first
+}
+
+impl Default for TsQuery {
+    fn default() -> Self {
+        Self {
+            from: "-".to_string(),
+            to: "+".to_string(),
+            aggregation: None,
+            count: 5000,
+            reverse: true,
+        }
+    }
+}
+
+impl TsQuery {
+    /// The command as it is sent, for display
+    pub fn summary(&self) -> String {
+        let mut out = format!(
+            "{} {} {}",
+            if self.reverse { "TS.REVRANGE" } else { "TS.RANGE" },
+            self.from,
+            self.to
+        );
+        if let Some((agg, bucket)) = &self.aggregation {
+            out.push_str(&format!(" AGGREGATION {} {}", agg, bucket));
+        }
+        if self.count > 0 {
+            out.push_str(&format!(" COUNT {}", self.count));
+        }
+        out
+    }
+}
+
+/// A compaction rule of a time series (TS.CREATERULE)
+#[derive(Debug, Clone)]
+pub struct TsRule {
+    pub dest_key: String,
+    pub bucket_ms: i64,
+    pub aggregation: String,
+}
+
+/// A RedisTimeSeries key: TS.INFO metadata and the samples of a range query
+#[derive(Debug, Clone)]
+pub struct TimeSeries {
+    pub info: Vec<(String, String)>, // scalar TS.INFO fields in reply order
+    pub labels: Vec<(String, String)>,
+    pub rules: Vec<TsRule>,
+    pub samples: Vec<(i64, f64)>, // in query order
+    pub query: TsQuery,
+}
+
+/// Probabilistic structures: HyperLogLog strings and the RedisBloom types
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProbKind {
+    HyperLogLog,
+    Bloom,
+    Cuckoo,
+    CountMinSketch,
+    TopK,
+    TDigest,
+}
+
+impl ProbKind {
+    /// Kind for a module type name as reported by TYPE
+    pub fn from_type(key_type: &str) -> Option<Self> {
+        match key_type {
+            "MBbloom--" => Some(ProbKind::Bloom),
+            "MBbloomCF" => Some(ProbKind::Cuckoo),
+            "CMSk-TYPE" => Some(ProbKind::CountMinSketch),
+            "TopK-TYPE" => Some(ProbKind::TopK),
+            "TDIS-TYPE" => Some(ProbKind::TDigest),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProbKind::HyperLogLog => "HyperLogLog",
+            ProbKind::Bloom => "Bloom filter",
+            ProbKind::Cuckoo => "Cuckoo filter",
+            ProbKind::CountMinSketch => "Count-min sketch",
+            ProbKind::TopK => "Top-K",
+            ProbKind::TDigest => "t-digest",
+        }
+    }
+
+    /// Command used by the query popup
+    pub fn query_command(&self) -> &'static str {
+        match self {
+            ProbKind::HyperLogLog => "PFCOUNT",
+            ProbKind::Bloom => "BF.MEXISTS",
+            ProbKind::Cuckoo => "CF.MEXISTS",
+            ProbKind::CountMinSketch => "CMS.QUERY",
+            ProbKind::TopK => "TOPK.QUERY",
+            ProbKind::TDigest => "TDIGEST.QUANTILE",
+        }
+    }
+
+    /// Command used to add items from the edit popup
+    pub fn add_command(&self) -> &'static str {
+        match self {
+            ProbKind::HyperLogLog => "PFADD",
+            ProbKind::Bloom => "BF.MADD",
+            ProbKind::Cuckoo => "CF.ADD",
+            ProbKind::CountMinSketch => "CMS.INCRBY",
+            ProbKind::TopK => "TOPK.ADD",
+            ProbKind::TDigest => "TDIGEST.ADD",
+        }
+    }
+}
+
+/// Summary of a probabilistic structure: its INFO fields plus whatever
+/// can be listed (Top-K items) or estimated (t-digest distribution)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProbStructure {
+    pub kind: ProbKind,
+    pub info: Vec<(String, String)>,
+    pub top_items: Vec<(String, i64)>, // TOPK.LIST WITHCOUNT
+    pub histogram: Vec<(f64, f64)>,    // t-digest: bin center, estimated count
+}
+
+/// Number of bins of the estimated t-digest distribution
+const TDIGEST_BINS: usize = 40;
+
+/// Position of a geo set member (GEOPOS and GEOHASH)
+#[derive(Debug, Clone)]
+pub struct GeoMember {
+    pub member: String,
+    pub lon: f64,
+    pub lat: f64,
+    pub hash: String,
+}
+
+/// Center of a GEOSEARCH
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoCenter {
+    Member(String),
+    LonLat(f64, f64),
+}
+
+/// A GEOSEARCH match, nearest first
+#[derive(Debug, Clone)]
+pub struct GeoMatch {
+    pub member: String,
+    pub dist: f64,
+    pub lon: f64,
+    pub lat: f64,
+}
+
+/// Vector options of a RediSearch VECTOR attribute
+#[derive(Debug, Clone)]
+pub struct VectorInfo {
+    pub dim: usize,
+    pub data_type: String, // FLOAT32, FLOAT64, ...
+    pub metric: String,    // COSINE, L2, IP
+}
+
+/// One attribute of a RediSearch index schema
+#[derive(Debug, Clone)]
+pub struct SearchAttribute {
+    pub identifier: String, // hash field or JSONPath
+    pub name: String,       // attribute name used in queries
+    pub kind: String,       // TEXT, TAG, NUMERIC, GEO, VECTOR, ...
+    pub options: Vec<String>,
+    pub vector: Option<VectorInfo>,
+}
+
+/// FT.INFO of a RediSearch index
+#[derive(Debug, Clone)]
+pub struct SearchIndexInfo {
+    pub name: String,
+    pub key_type: String,
+    pub prefixes: Vec<String>,
+    pub stats: Vec<(String, String)>,
+    pub attributes: Vec<SearchAttribute>,
+}
+
+impl SearchIndexInfo {
+    /// The VECTOR attribute a result field belongs to, if any
+    pub fn vector_field(&self, field: &str) -> Option<&VectorInfo> {
+        self.attributes
+            .iter()
+            .find(|a| a.name == field || a.identifier == field)
+            .and_then(|a| a.vector.as_ref())
+    }
+
+    pub fn stat(&self, name: &str) -> Option<&str> {
+        self.stats.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+}
+
+/// FT.INFO fields shown in the index browser
+const SEARCH_STATS: &[&str] = &[
+    "num_docs",
+    "max_doc_id",
+    "num_terms",
+    "num_records",
+    "indexing",
+    "percent_indexed",
+    "hash_indexing_failures",
+    "total_indexing_time",
+    "inverted_sz_mb",
+    "vector_index_sz_mb",
+];
+
+/// One row of an FT.SEARCH or FT.AGGREGATE reply
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub id: Option<String>, // document key (FT.SEARCH only)
+    pub score: Option<String>,
+    pub fields: Vec<(String, Vec<u8>)>,
+}
+
+/// Result of FT.SEARCH or FT.AGGREGATE
+#[derive(Debug, Clone)]
+pub struct SearchResults {
+    pub total: i64,
+    pub hits: Vec<SearchHit>,
+}
+
 /// The value of a Redis key, typed by its Redis data type
 #[derive(Debug, Clone)]
 pub enum RedisValue {
@@ -28,12 +359,16 @@ pub enum RedisValue {
     ZSet(Vec<(Vec<u8>, f64)>),
     Hash(Vec<(String, Vec<u8>)>),
     Stream(Vec<StreamEntry>),
+    Json(serde_json::Value),
+    TimeSeries(TimeSeries),
+    Probabilistic(ProbStructure),
     Unknown(String),
 }
 
 #[allow(dead_code)]
 pub struct RedisClient {
-    connection: redis::Connection,
+    connection: Option<redis::Connection>, // None while browsing an RDB file
+    offline: Option<RdbFile>,
     pub url: String,
     pub db: i64,
 }
@@ -54,22 +389,63 @@ impl RedisClient {
             .unwrap_or(0);
 
         Ok(Self {
-            connection,
+            connection: Some(connection),
+            offline: None,
             url: url.to_string(),
             db: db as i64,
         })
     }
 
+    /// Browse an RDB file instead of a server; everything but reads fails
+    pub fn open_rdb(path: &str) -> Result<Self> {
+        let rdb = RdbFile::open(path)?;
+        Ok(Self {
+            connection: None,
+            db: rdb.first_db(),
+            offline: Some(rdb),
+            url: path.to_string(),
+        })
+    }
+
+    /// The RDB file being browsed, if any
+    pub fn rdb_path(&self) -> Option<&str> {
+        self.offline.as_ref().map(|rdb| rdb.path.as_str())
+    }
+
+    fn conn(&mut self) -> Result<&mut redis::Connection> {
+        match &mut self.connection {
+            Some(connection) => Ok(connection),
+            None => anyhow::bail!("Read-only: browsing the RDB file {}", self.url),
+        }
+    }
+
     pub fn select_db(&mut self, db: i64) -> Result<()> {
+        if self.offline.is_some() {
+            self.db = db;
+            return Ok(());
+        }
         redis::cmd("SELECT")
             .arg(db)
-            .exec(&mut self.connection)
+            .exec(self.conn()?)
             .with_context(|| format!("Failed to SELECT db {}", db))?;
         self.db = db;
         Ok(())
     }
 
+    /// `run_id` from INFO server, which tells servers apart whatever URL
+    /// reached them
+    pub fn run_id(&mut self) -> Result<String> {
+        let info: String = redis::cmd("INFO").arg("server").query(self.conn()?).context("Failed to INFO server")?;
+        info.lines()
+            .find_map(|line| line.strip_prefix("run_id:"))
+            .map(|id| id.trim().to_string())
+            .context("INFO server has no run_id")
+    }
+
     pub fn scan_keys(&mut self, pattern: &str) -> Result<Vec<String>> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.scan(self.db, pattern));
+        }
         let iter: redis::Iter<String> = redis::cmd("SCAN")
             .cursor_arg(0)
             .arg("MATCH")
@@ -77,7 +453,7 @@ impl RedisClient {
             .arg("COUNT")
             .arg(1000)
             .clone()
-            .iter(&mut self.connection)
+            .iter(self.conn()?)
             .context("Failed to SCAN keys")?;
 
         let mut keys: Vec<String> = iter.filter_map(|r| r.ok()).collect();
@@ -85,24 +461,58 @@ impl RedisClient {
         Ok(keys)
     }
 
+    /// Like `scan_keys`, but a failed SCAN page or a key that is not UTF-8
+    /// is an error instead of being left out, for actions on every match
+    pub fn scan_keys_strict(&mut self, pattern: &str) -> Result<Vec<String>> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.scan(self.db, pattern));
+        }
+        let iter: redis::Iter<Vec<u8>> = redis::cmd("SCAN")
+            .cursor_arg(0)
+            .arg("MATCH")
+            .arg(pattern)
+            .arg("COUNT")
+            .arg(1000)
+            .clone()
+            .iter(self.conn()?)
+            .context("Failed to SCAN keys")?;
+
+        let mut keys = Vec::new();
+        let mut binary = 0;
+        for key in iter {
+            match String::from_utf8(key.context("Failed to SCAN keys")?) {
+                Ok(key) => keys.push(key),
+                Err(_) => binary += 1,
+            }
+        }
+        if binary > 0 {
+            anyhow::bail!("{} matching key(s) are not valid UTF-8 and cannot be selected", binary);
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
     pub fn get_key_info(&mut self, key: &str) -> Result<KeyInfo> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.key_info(self.db, key));
+        }
         let key_type: String = redis::cmd("TYPE")
             .arg(key)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .unwrap_or_else(|_| "unknown".to_string());
 
-        let ttl: i64 = self.connection.ttl(key).unwrap_or(-2);
+        let ttl: i64 = self.conn()?.ttl(key).unwrap_or(-2);
 
         let size: i64 = redis::cmd("MEMORY")
             .arg("USAGE")
             .arg(key)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .unwrap_or(-1);
 
         let encoding: String = redis::cmd("OBJECT")
             .arg("ENCODING")
             .arg(key)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .unwrap_or_else(|_| "unknown".to_string());
 
         Ok(KeyInfo {
@@ -115,40 +525,49 @@ impl RedisClient {
     }
 
     pub fn get_value(&mut self, key: &str) -> Result<RedisValue> {
+        if let Some(rdb) = &self.offline {
+            return rdb.value(self.db, key);
+        }
         let key_type: String = redis::cmd("TYPE")
             .arg(key)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .unwrap_or_else(|_| "unknown".to_string());
 
         match key_type.as_str() {
             "string" => {
-                let val: Vec<u8> = self.connection.get(key).context("Failed to GET")?;
+                let val: Vec<u8> = self.conn()?.get(key).context("Failed to GET")?;
+                // A string that only starts like a HyperLogLog stays a string
+                if is_hyperloglog(&val) {
+                    if let Ok(hll) = self.get_hyperloglog(key, &val) {
+                        return Ok(RedisValue::Probabilistic(hll));
+                    }
+                }
                 Ok(RedisValue::String(val))
             }
             "list" => {
                 let vals: Vec<Vec<u8>> = self
-                    .connection
+                    .conn()?
                     .lrange(key, 0, -1)
                     .context("Failed to LRANGE")?;
                 Ok(RedisValue::List(vals))
             }
             "set" => {
                 let vals: Vec<Vec<u8>> = self
-                    .connection
+                    .conn()?
                     .smembers(key)
                     .context("Failed to SMEMBERS")?;
                 Ok(RedisValue::Set(vals))
             }
             "zset" => {
                 let vals: Vec<(Vec<u8>, f64)> = self
-                    .connection
+                    .conn()?
                     .zrange_withscores(key, 0, -1)
                     .context("Failed to ZRANGEBYSCORE")?;
                 Ok(RedisValue::ZSet(vals))
             }
             "hash" => {
                 let map: HashMap<String, Vec<u8>> = self
-                    .connection
+                    .conn()?
                     .hgetall(key)
                     .context("Failed to HGETALL")?;
                 let mut pairs: Vec<(String, Vec<u8>)> = map.into_iter().collect();
@@ -159,19 +578,46 @@ impl RedisClient {
                 let entries = self.get_stream_entries(key)?;
                 Ok(RedisValue::Stream(entries))
             }
-            other => Ok(RedisValue::Unknown(format!("Unsupported type: {}", other))),
+            "ReJSON-RL" => {
+                let raw: String = redis::cmd("JSON.GET")
+                    .arg(key)
+                    .arg("$")
+                    .query(self.conn()?)
+                    .context("Failed to JSON.GET")?;
+                // A `$` path returns the matches wrapped in an array
+                let value = match serde_json::from_str(&raw).context("Invalid JSON from JSON.GET")? {
+                    serde_json::Value::Array(mut matches) if matches.len() == 1 => matches.remove(0),
+                    other => other,
+                };
+                Ok(RedisValue::Json(value))
+            }
+            "TSDB-TYPE" => {
+                let series = self.get_timeseries(key, &TsQuery::default())?;
+                Ok(RedisValue::TimeSeries(series))
+            }
+            other => match ProbKind::from_type(other) {
+                Some(kind) => self.get_probabilistic(key, kind).map(RedisValue::Probabilistic),
+                None => Ok(RedisValue::Unknown(format!("Unsupported type: {}", other))),
+            },
         }
     }
 
     pub fn get_stream_entries(&mut self, key: &str) -> Result<Vec<StreamEntry>> {
-        // XRANGE key - + COUNT 500
+        self.xrange(key, "-", 500)
+    }
+
+    /// Up to `count` entries from `start` (an ID, `(ID` for after it, or `-`)
+    pub fn xrange(&mut self, key: &str, start: &str, count: usize) -> Result<Vec<StreamEntry>> {
+        if let Some(rdb) = &self.offline {
+            return rdb.xrange(self.db, key, start, count);
+        }
         let raw: Vec<redis::Value> = redis::cmd("XRANGE")
             .arg(key)
-            .arg("-")
+            .arg(start)
             .arg("+")
             .arg("COUNT")
-            .arg(500)
-            .query(&mut self.connection)
+            .arg(count)
+            .query(self.conn()?)
             .context("Failed to XRANGE")?;
 
         let mut entries = Vec::new();
@@ -215,6 +661,327 @@ impl RedisClient {
         Ok(entries)
     }
 
+    /// TS.INFO plus the samples selected by `query`
+    pub fn get_timeseries(&mut self, key: &str, query: &TsQuery) -> Result<TimeSeries> {
+        let raw: redis::Value = redis::cmd("TS.INFO")
+            .arg(key)
+            .query(self.conn()?)
+            .context("Failed to TS.INFO")?;
+
+        let mut info = Vec::new();
+        let mut labels = Vec::new();
+        let mut rules = Vec::new();
+        for (name, value) in reply_pairs(&raw) {
+            let name = reply_text(&name);
+            match name.as_str() {
+                "labels" => {
+                    labels = match value {
+                        // RESP3 replies labels as a map
+                        redis::Value::Map(_) => reply_pairs(&value)
+                            .iter()
+                            .map(|(k, v)| (reply_text(k), reply_text(v)))
+                            .collect(),
+                        _ => reply_list(&value)
+                            .iter()
+                            .filter_map(|label| {
+                                let parts = reply_list(label);
+                                Some((reply_text(parts.first()?), reply_text(parts.get(1)?)))
+                            })
+                            .collect(),
+                    };
+                }
+                "rules" => {
+                    // [[dest, bucket, aggregator, ...], ...], or a RESP3 map of dest => [bucket, aggregator, ...]
+                    let entries: Vec<(String, Vec<redis::Value>)> = match value {
+                        redis::Value::Map(_) => reply_pairs(&value)
+                            .iter()
+                            .map(|(k, v)| (reply_text(k), reply_list(v)))
+                            .collect(),
+                        _ => reply_list(&value)
+                            .iter()
+                            .filter_map(|rule| {
+                                let mut parts = reply_list(rule);
+                                (!parts.is_empty()).then(|| (reply_text(&parts.remove(0)), parts))
+                            })
+                            .collect(),
+                    };
+                    rules = entries
+                        .into_iter()
+                        .filter(|(_, parts)| parts.len() >= 2)
+                        .map(|(dest_key, parts)| TsRule {
+                            dest_key,
+                            bucket_ms: reply_text(&parts[0]).parse().unwrap_or(0),
+                            aggregation: reply_text(&parts[1]),
+                        })
+                        .collect();
+                }
+                // Chunk details are too verbose for the info block
+                "Chunks" | "chunks" => {}
+                _ => info.push((name, reply_text(&value))),
+            }
+        }
+
+        let mut cmd = redis::cmd(if query.reverse { "TS.REVRANGE" } else { "TS.RANGE" });
+        cmd.arg(key).arg(&query.from).arg(&query.to);
+        if query.count > 0 {
+            cmd.arg("COUNT").arg(query.count);
+        }
+        if let Some((agg, bucket)) = &query.aggregation {
+            cmd.arg("AGGREGATION").arg(agg).arg(bucket);
+        }
+        let raw: Vec<redis::Value> = cmd
+            .query(self.conn()?)
+            .context("Failed to TS.RANGE")?;
+        let samples = raw
+            .iter()
+            .filter_map(|sample| {
+                let parts = reply_list(sample);
+                let ts = reply_text(parts.first()?).parse().ok()?;
+                let value = reply_text(parts.get(1)?).parse().ok()?;
+                Some((ts, value))
+            })
+            .collect();
+
+        Ok(TimeSeries {
+            info,
+            labels,
+            rules,
+            samples,
+            query: query.clone(),
+        })
+    }
+
+    /// PFCOUNT and the header of a HyperLogLog string
+    fn get_hyperloglog(&mut self, key: &str, raw: &[u8]) -> Result<ProbStructure> {
+        let count: i64 = redis::cmd("PFCOUNT")
+            .arg(key)
+            .query(self.conn()?)
+            .context("Failed to PFCOUNT")?;
+        let encoding = match raw.get(4) {
+            Some(0) => "dense",
+            Some(1) => "sparse",
+            _ => "unknown",
+        };
+        Ok(ProbStructure {
+            kind: ProbKind::HyperLogLog,
+            info: vec![
+                ("PFCOUNT".to_string(), count.to_string()),
+                ("Encoding".to_string(), encoding.to_string()),
+                ("Bytes".to_string(), raw.len().to_string()),
+            ],
+            top_items: Vec::new(),
+            histogram: Vec::new(),
+        })
+    }
+
+    /// INFO of a RedisBloom structure, plus TOPK.LIST or a t-digest histogram
+    pub fn get_probabilistic(&mut self, key: &str, kind: ProbKind) -> Result<ProbStructure> {
+        let info_cmd = match kind {
+            ProbKind::Bloom => "BF.INFO",
+            ProbKind::Cuckoo => "CF.INFO",
+            ProbKind::CountMinSketch => "CMS.INFO",
+            ProbKind::TopK => "TOPK.INFO",
+            ProbKind::TDigest => "TDIGEST.INFO",
+            ProbKind::HyperLogLog => anyhow::bail!("HyperLogLog has no INFO command"),
+        };
+        let raw: redis::Value = redis::cmd(info_cmd)
+            .arg(key)
+            .query(self.conn()?)
+            .with_context(|| format!("Failed to {}", info_cmd))?;
+        let info: Vec<(String, String)> = reply_pairs(&raw)
+            .iter()
+            .map(|(k, v)| (reply_text(k), reply_text(v)))
+            .collect();
+
+        let mut top_items = Vec::new();
+        if kind == ProbKind::TopK {
+            let raw: Vec<redis::Value> = redis::cmd("TOPK.LIST")
+                .arg(key)
+                .arg("WITHCOUNT")
+                .query(self.conn()?)
+                .context("Failed to TOPK.LIST")?;
+            top_items = raw
+                .chunks_exact(2)
+                .map(|pair| (reply_text(&pair[0]), reply_text(&pair[1]).parse().unwrap_or(0)))
+                .collect();
+        }
+
+        let mut histogram = Vec::new();
+        if kind == ProbKind::TDigest {
+            let observations = info
+                .iter()
+                .find(|(k, _)| k == "Observations")
+                .and_then(|(_, v)| v.parse::<f64>().ok());
+            histogram = self.tdigest_histogram(key, observations.unwrap_or(1.0))?;
+        }
+
+        Ok(ProbStructure {
+            kind,
+            info,
+            top_items,
+            histogram,
+        })
+    }
+
+    /// Estimated distribution of a t-digest: the CDF sampled at evenly spaced
+    /// values between TDIGEST.MIN and TDIGEST.MAX, differenced into bins
+    fn tdigest_histogram(&mut self, key: &str, observations: f64) -> Result<Vec<(f64, f64)>> {
+        let min: redis::Value = redis::cmd("TDIGEST.MIN")
+            .arg(key)
+            .query(self.conn()?)
+            .context("Failed to TDIGEST.MIN")?;
+        let max: redis::Value = redis::cmd("TDIGEST.MAX")
+            .arg(key)
+            .query(self.conn()?)
+            .context("Failed to TDIGEST.MAX")?;
+        let (min, max) = match (reply_text(&min).parse::<f64>(), reply_text(&max).parse::<f64>()) {
+            (Ok(min), Ok(max)) if min.is_finite() && max.is_finite() && max > min => (min, max),
+            _ => return Ok(Vec::new()), // empty digest or a single value
+        };
+        let width = (max - min) / TDIGEST_BINS as f64;
+        let edges: Vec<f64> = (0..=TDIGEST_BINS).map(|i| min + width * i as f64).collect();
+        let raw: Vec<redis::Value> = redis::cmd("TDIGEST.CDF")
+            .arg(key)
+            .arg(&edges)
+            .query(self.conn()?)
+            .context("Failed to TDIGEST.CDF")?;
+        let cdf: Vec<f64> = raw.iter().map(|v| reply_text(v).parse().unwrap_or(0.0)).collect();
+        Ok(cdf
+            .windows(2)
+            .zip(edges.windows(2))
+            .map(|(c, e)| ((e[0] + e[1]) / 2.0, ((c[1] - c[0]) * observations).max(0.0)))
+            .collect())
+    }
+
+    /// Run the kind's query command for each item (PFCOUNT unions the given
+    /// keys instead). Returns (item, reply) pairs.
+    pub fn prob_query(&mut self, key: &str, kind: ProbKind, items: &[String]) -> Result<Vec<(String, String)>> {
+        let command = kind.query_command();
+        let mut cmd = redis::cmd(command);
+        cmd.arg(key).arg(items);
+        let raw: redis::Value = cmd
+            .query(self.conn()?)
+            .with_context(|| format!("Failed to {}", command))?;
+        if kind == ProbKind::HyperLogLog {
+            let mut union = vec![key.to_string()];
+            union.extend(items.iter().cloned());
+            return Ok(vec![(union.join(" ∪ "), reply_text(&raw))]);
+        }
+        let replies = reply_list(&raw);
+        Ok(items
+            .iter()
+            .zip(replies.iter())
+            .map(|(item, reply)| {
+                let text = reply_text(reply);
+                let text = match (kind, text.as_str()) {
+                    (ProbKind::Bloom | ProbKind::Cuckoo, "1" | "true") => "maybe present".to_string(),
+                    (ProbKind::Bloom | ProbKind::Cuckoo, "0" | "false") => "absent".to_string(),
+                    (ProbKind::TopK, "1" | "true") => "in top-k".to_string(),
+                    (ProbKind::TopK, "0" | "false") => "not in top-k".to_string(),
+                    _ => text,
+                };
+                (item.clone(), text)
+            })
+            .collect())
+    }
+
+    /// Add items with the kind's add command (CMS items are incremented by 1)
+    pub fn prob_add(&mut self, key: &str, kind: ProbKind, items: &[String]) -> Result<()> {
+        let command = kind.add_command();
+        if kind == ProbKind::Cuckoo {
+            // CF.ADD takes a single item
+            for item in items {
+                let _: redis::Value = redis::cmd(command)
+                    .arg(key)
+                    .arg(item)
+                    .query(self.conn()?)
+                    .context("Failed to CF.ADD")?;
+            }
+            return Ok(());
+        }
+        let mut cmd = redis::cmd(command);
+        cmd.arg(key);
+        for item in items {
+            cmd.arg(item);
+            if kind == ProbKind::CountMinSketch {
+                cmd.arg(1);
+            }
+        }
+        let _: redis::Value = cmd
+            .query(self.conn()?)
+            .with_context(|| format!("Failed to {}", command))?;
+        Ok(())
+    }
+
+    /// GEOPOS and GEOHASH of the given members of a geo set.
+    /// Members without a position are skipped.
+    pub fn geo_members(&mut self, key: &str, members: &[Vec<u8>]) -> Result<Vec<GeoMember>> {
+        let mut out = Vec::with_capacity(members.len());
+        for chunk in members.chunks(1000) {
+            let positions: Vec<redis::Value> = redis::cmd("GEOPOS")
+                .arg(key)
+                .arg(chunk)
+                .query(self.conn()?)
+                .context("Failed to GEOPOS")?;
+            let hashes: Vec<redis::Value> = redis::cmd("GEOHASH")
+                .arg(key)
+                .arg(chunk)
+                .query(self.conn()?)
+                .context("Failed to GEOHASH")?;
+            for ((member, pos), hash) in chunk.iter().zip(&positions).zip(&hashes) {
+                if let Some((lon, lat)) = reply_coord(pos) {
+                    out.push(GeoMember {
+                        member: String::from_utf8_lossy(member).to_string(),
+                        lon,
+                        lat,
+                        hash: reply_text(hash),
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    /// GEOSEARCH by radius around a member or a `(lon, lat)` point
+    pub fn geo_search(
+        &mut self,
+        key: &str,
+        center: &GeoCenter,
+        radius: f64,
+        unit: &str,
+        count: usize,
+    ) -> Result<Vec<GeoMatch>> {
+        let mut cmd = redis::cmd("GEOSEARCH");
+        cmd.arg(key);
+        match center {
+            GeoCenter::LonLat(lon, lat) => cmd.arg("FROMLONLAT").arg(lon).arg(lat),
+            GeoCenter::Member(member) => cmd.arg("FROMMEMBER").arg(member),
+        };
+        cmd.arg("BYRADIUS").arg(radius).arg(unit).arg("ASC");
+        if count > 0 {
+            cmd.arg("COUNT").arg(count);
+        }
+        let raw: Vec<redis::Value> = cmd
+            .arg("WITHDIST")
+            .arg("WITHCOORD")
+            .query(self.conn()?)
+            .context("Failed to GEOSEARCH")?;
+        // Each match is [member, dist, [lon, lat]]
+        Ok(raw
+            .iter()
+            .filter_map(|m| {
+                let parts = reply_list(m);
+                let (lon, lat) = reply_coord(parts.get(2)?)?;
+                Some(GeoMatch {
+                    member: reply_text(parts.first()?),
+                    dist: reply_text(parts.get(1)?).parse().ok()?,
+                    lon,
+                    lat,
+                })
+            })
+            .collect())
+    }
+
     /// Blocking XREAD for new entries after `last_id`.
     /// Blocks up to `timeout_ms` milliseconds (0 = forever).
     /// Returns new entries (empty vec if timeout).
@@ -227,7 +994,7 @@ impl RedisClient {
             .arg("STREAMS")
             .arg(key)
             .arg(last_id)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .context("Failed to XREAD")?;
 
         // XREAD returns: nil if no data, or array of [key, [[id, [field, val, ...]], ...]]
@@ -281,16 +1048,27 @@ impl RedisClient {
         Ok(entries)
     }
 
+    pub fn key_type(&mut self, key: &str) -> Result<String> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.key_type(self.db, key));
+        }
+        let key_type: String = redis::cmd("TYPE").arg(key).query(self.conn()?).context("Failed to TYPE")?;
+        Ok(key_type)
+    }
+
     pub fn delete_key(&mut self, key: &str) -> Result<()> {
-        let _: () = self.connection
+        let _: () = self.conn()?
             .del(key)
             .context("Failed to DEL key")?;
         Ok(())
     }
 
     pub fn get_db_size(&mut self) -> Result<i64> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.db_size(self.db) as i64);
+        }
         let size: i64 = redis::cmd("DBSIZE")
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .unwrap_or(0);
         Ok(size)
     }
@@ -299,30 +1077,68 @@ impl RedisClient {
     pub fn get_info_section(&mut self, section: &str) -> Result<String> {
         let info: String = redis::cmd("INFO")
             .arg(section)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .unwrap_or_default();
         Ok(info)
     }
 
     pub fn is_connected(&mut self) -> bool {
-        self.connection.is_open()
+        self.connection.as_mut().is_some_and(|c| c.is_open())
+    }
+
+    /// Raw bytes of a string key, or of one hash field when `field` is given
+    pub fn get_bytes(&mut self, key: &str, field: Option<&str>) -> Result<Option<Vec<u8>>> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.bytes(self.db, key, field));
+        }
+        let bytes: Option<Vec<u8>> = match field {
+            Some(field) => self.conn()?.hget(key, field).context("Failed to HGET")?,
+            None => self.conn()?.get(key).context("Failed to GET")?,
+        };
+        Ok(bytes)
+    }
+
+    pub fn lindex(&mut self, key: &str, index: i64) -> Result<Option<Vec<u8>>> {
+        let bytes: Option<Vec<u8>> = self.conn()?.lindex(key, index as isize).context("Failed to LINDEX")?;
+        Ok(bytes)
+    }
+
+    /// The raw JSON.GET reply for the whole document (`$`)
+    pub fn json_get(&mut self, key: &str) -> Result<Option<String>> {
+        let raw: Option<String> = redis::cmd("JSON.GET")
+            .arg(key)
+            .arg("$")
+            .query(self.conn()?)
+            .context("Failed to JSON.GET")?;
+        Ok(raw)
     }
 
     // ─── Write operations ────────────────────────────────────
 
     pub fn set_string(&mut self, key: &str, value: &str) -> Result<()> {
-        let _: () = self.connection.set(key, value).context("Failed to SET")?;
+        let _: () = self.conn()?.set(key, value).context("Failed to SET")?;
         Ok(())
     }
 
     pub fn set_bytes(&mut self, key: &str, value: &[u8]) -> Result<()> {
-        let _: () = self.connection.set(key, value).context("Failed to SET bytes")?;
+        let _: () = self.conn()?.set(key, value).context("Failed to SET bytes")?;
         Ok(())
     }
 
+    /// Set one bit, returning its previous value
+    pub fn setbit(&mut self, key: &str, offset: u64, value: bool) -> Result<bool> {
+        let previous: i64 = redis::cmd("SETBIT")
+            .arg(key)
+            .arg(offset)
+            .arg(value as u8)
+            .query(self.conn()?)
+            .context("Failed to SETBIT")?;
+        Ok(previous == 1)
+    }
+
     pub fn hset(&mut self, key: &str, field: &str, value: &str) -> Result<()> {
         let _: () = self
-            .connection
+            .conn()?
             .hset(key, field, value)
             .context("Failed to HSET")?;
         Ok(())
@@ -330,7 +1146,7 @@ impl RedisClient {
 
     pub fn hset_bytes(&mut self, key: &str, field: &str, value: &[u8]) -> Result<()> {
         let _: () = self
-            .connection
+            .conn()?
             .hset(key, field, value)
             .context("Failed to HSET bytes")?;
         Ok(())
@@ -338,7 +1154,7 @@ impl RedisClient {
 
     pub fn rpush(&mut self, key: &str, value: &str) -> Result<()> {
         let _: i64 = self
-            .connection
+            .conn()?
             .rpush(key, value)
             .context("Failed to RPUSH")?;
         Ok(())
@@ -346,35 +1162,25 @@ impl RedisClient {
 
     pub fn rpush_bytes(&mut self, key: &str, value: &[u8]) -> Result<()> {
         let _: i64 = self
-            .connection
+            .conn()?
             .rpush(key, value)
             .context("Failed to RPUSH bytes")?;
         Ok(())
     }
 
-    pub fn lset(&mut self, key: &str, index: i64, value: &str) -> Result<()> {
-        let _: () = redis::cmd("LSET")
-            .arg(key)
-            .arg(index)
-            .arg(value)
-            .query(&mut self.connection)
-            .context("Failed to LSET")?;
-        Ok(())
-    }
-
     pub fn lset_bytes(&mut self, key: &str, index: i64, value: &[u8]) -> Result<()> {
         let _: () = redis::cmd("LSET")
             .arg(key)
             .arg(index)
             .arg(value)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .context("Failed to LSET bytes")?;
         Ok(())
     }
 
     pub fn sadd(&mut self, key: &str, member: &str) -> Result<()> {
         let _: i64 = self
-            .connection
+            .conn()?
             .sadd(key, member)
             .context("Failed to SADD")?;
         Ok(())
@@ -382,7 +1188,7 @@ impl RedisClient {
 
     pub fn sadd_bytes(&mut self, key: &str, member: &[u8]) -> Result<()> {
         let _: i64 = self
-            .connection
+            .conn()?
             .sadd(key, member)
             .context("Failed to SADD bytes")?;
         Ok(())
@@ -390,7 +1196,7 @@ impl RedisClient {
 
     pub fn zadd(&mut self, key: &str, score: f64, member: &str) -> Result<()> {
         let _: i64 = self
-            .connection
+            .conn()?
             .zadd(key, member, score)
             .context("Failed to ZADD")?;
         Ok(())
@@ -398,7 +1204,7 @@ impl RedisClient {
 
     pub fn zadd_bytes(&mut self, key: &str, score: f64, member: &[u8]) -> Result<()> {
         let _: i64 = self
-            .connection
+            .conn()?
             .zadd(key, member, score)
             .context("Failed to ZADD bytes")?;
         Ok(())
@@ -410,7 +1216,7 @@ impl RedisClient {
             .arg("*")
             .arg(field)
             .arg(value)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .context("Failed to XADD")?;
         Ok(())
     }
@@ -421,20 +1227,233 @@ impl RedisClient {
             .arg("*")
             .arg(field)
             .arg(value)
-            .query(&mut self.connection)
+            .query(self.conn()?)
             .context("Failed to XADD binary")?;
         Ok(())
     }
 
+    /// XADD with any number of fields, an explicit (`ms-seq`), partial
+    /// (`ms-*`) or auto (`*`) ID, and optional trimming. Returns the new ID,
+    /// or None when NOMKSTREAM is set and the stream does not exist.
+    pub fn xadd_entry(
+        &mut self,
+        key: &str,
+        id: &str,
+        trim: Option<&StreamTrim>,
+        nomkstream: bool,
+        fields: &[(String, Vec<u8>)],
+    ) -> Result<Option<String>> {
+        let mut cmd = redis::cmd("XADD");
+        cmd.arg(key);
+        if nomkstream {
+            cmd.arg("NOMKSTREAM");
+        }
+        if let Some(trim) = trim {
+            cmd.arg(trim.args());
+        }
+        cmd.arg(id);
+        for (field, value) in fields {
+            cmd.arg(field).arg(value);
+        }
+        let id: Option<String> = cmd.query(self.conn()?).context("Failed to XADD")?;
+        Ok(id)
+    }
+
+    // ─── Watched writes ──────────────────────────────────────
+
+    /// WATCH a key, so that the next `exec_watched` writes nothing if
+    /// another client changes it first
+    pub fn watch(&mut self, key: &str) -> Result<()> {
+        let _: () = redis::cmd("WATCH").arg(key).query(self.conn()?).context("Failed to WATCH")?;
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self) -> Result<()> {
+        let _: () = redis::cmd("UNWATCH").query(self.conn()?).context("Failed to UNWATCH")?;
+        Ok(())
+    }
+
+    /// Send the writes to `key` in one MULTI/EXEC. Returns false, with
+    /// nothing written, when a key WATCHed since the last EXEC has changed.
+    pub fn exec_watched(&mut self, key: &str, writes: &[KeyWrite]) -> Result<bool> {
+        let mut pipe = redis::pipe();
+        pipe.atomic();
+        for write in writes {
+            pipe.add_command(write.command(key));
+        }
+        let replies: Option<Vec<redis::Value>> = pipe.query(self.conn()?).context("Failed to write")?;
+        let Some(replies) = replies else {
+            return Ok(false);
+        };
+        for (write, reply) in writes.iter().zip(&replies) {
+            match (write, reply) {
+                (KeyWrite::JsonSet(path, _), redis::Value::Nil) => {
+                    anyhow::bail!("JSON.SET: parent of {} does not exist", path)
+                }
+                (KeyWrite::JsonDel(path), redis::Value::Int(0)) => anyhow::bail!("Nothing at {}", path),
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+
+    // ─── Element deletes ─────────────────────────────────────
+
+    pub fn hdel(&mut self, key: &str, field: &str) -> Result<i64> {
+        let removed: i64 = self.conn()?.hdel(key, field).context("Failed to HDEL")?;
+        Ok(removed)
+    }
+
+    /// Remove the element at one list index if it still holds `expected`.
+    /// Redis has no command for this, so a script checks it with LINDEX,
+    /// overwrites it with a unique tombstone and LREMs that, all atomically.
+    pub fn ldel_index(&mut self, key: &str, index: i64, expected: &[u8]) -> Result<()> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let tombstone = format!("__redis_tui_deleted_{}__", nanos);
+        let script = redis::Script::new(
+            r"
+            if redis.call('LINDEX', KEYS[1], ARGV[1]) ~= ARGV[2] then
+                return 0
+            end
+            redis.call('LSET', KEYS[1], ARGV[1], ARGV[3])
+            redis.call('LREM', KEYS[1], 1, ARGV[3])
+            return 1
+            ",
+        );
+        let removed: i64 = script
+            .key(key)
+            .arg(index)
+            .arg(expected)
+            .arg(&tombstone)
+            .invoke(self.conn()?)
+            .context("Failed to remove the list element")?;
+        if removed == 0 {
+            anyhow::bail!("Element [{}] of {} changed on the server; reload and try again", index, key);
+        }
+        Ok(())
+    }
+
+    pub fn srem(&mut self, key: &str, member: &[u8]) -> Result<i64> {
+        let removed: i64 = self.conn()?.srem(key, member).context("Failed to SREM")?;
+        Ok(removed)
+    }
+
+    pub fn zrem(&mut self, key: &str, member: &[u8]) -> Result<i64> {
+        let removed: i64 = self.conn()?.zrem(key, member).context("Failed to ZREM")?;
+        Ok(removed)
+    }
+
+    pub fn xdel(&mut self, key: &str, id: &str) -> Result<i64> {
+        let removed: i64 = redis::cmd("XDEL")
+            .arg(key)
+            .arg(id)
+            .query(self.conn()?)
+            .context("Failed to XDEL")?;
+        Ok(removed)
+    }
+
+    // ─── Trims ───────────────────────────────────────────────
+
+    pub fn xtrim(&mut self, key: &str, trim: &StreamTrim) -> Result<i64> {
+        let removed: i64 = redis::cmd("XTRIM")
+            .arg(key)
+            .arg(trim.args())
+            .query(self.conn()?)
+            .context("Failed to XTRIM")?;
+        Ok(removed)
+    }
+
+    // ─── Removal previews ────────────────────────────────────
+
+    pub fn llen(&mut self, key: &str) -> Result<i64> {
+        let len: i64 = self.conn()?.llen(key).context("Failed to LLEN")?;
+        Ok(len)
+    }
+
+    pub fn xlen(&mut self, key: &str) -> Result<i64> {
+        let len: i64 = redis::cmd("XLEN").arg(key).query(self.conn()?).context("Failed to XLEN")?;
+        Ok(len)
+    }
+
+    pub fn zcount(&mut self, key: &str, min: &str, max: &str) -> Result<i64> {
+        let count: i64 = self.conn()?.zcount(key, min, max).context("Failed to ZCOUNT")?;
+        Ok(count)
+    }
+
+    /// Positions of `value` in a list, as many as LREM with `count` would remove
+    pub fn lpos_count(&mut self, key: &str, value: &[u8], count: i64) -> Result<usize> {
+        let positions: Vec<i64> = redis::cmd("LPOS")
+            .arg(key)
+            .arg(value)
+            .arg("RANK")
+            .arg(if count < 0 { -1 } else { 1 })
+            .arg("COUNT")
+            .arg(count.unsigned_abs())
+            .query(self.conn()?)
+            .context("Failed to LPOS")?;
+        Ok(positions.len())
+    }
+
+    /// How many of `fields` exist in a hash
+    pub fn hexists_count(&mut self, key: &str, fields: &[String]) -> Result<usize> {
+        let values: Vec<Option<Vec<u8>>> = redis::cmd("HMGET")
+            .arg(key)
+            .arg(fields)
+            .query(self.conn()?)
+            .context("Failed to HMGET")?;
+        Ok(values.iter().filter(|v| v.is_some()).count())
+    }
+
+    pub fn sismember(&mut self, key: &str, member: &[u8]) -> Result<bool> {
+        let found: bool = self.conn()?.sismember(key, member).context("Failed to SISMEMBER")?;
+        Ok(found)
+    }
+
+    pub fn zscore(&mut self, key: &str, member: &[u8]) -> Result<Option<f64>> {
+        let score: Option<f64> = self.conn()?.zscore(key, member).context("Failed to ZSCORE")?;
+        Ok(score)
+    }
+
+    /// How many of `ids` exist in a stream
+    pub fn xexists_count(&mut self, key: &str, ids: &[String]) -> Result<usize> {
+        let mut found = 0;
+        for id in ids {
+            let entries: Vec<redis::Value> = redis::cmd("XRANGE")
+                .arg(key)
+                .arg(id)
+                .arg(id)
+                .query(self.conn()?)
+                .context("Failed to XRANGE")?;
+            found += entries.len();
+        }
+        Ok(found)
+    }
+
+    /// Entries with an ID below `id`, counting at most `cap + 1`
+    pub fn xcount_before(&mut self, key: &str, id: &str, cap: usize) -> Result<usize> {
+        let entries: Vec<redis::Value> = redis::cmd("XRANGE")
+            .arg(key)
+            .arg("-")
+            .arg(format!("({}", id))
+            .arg("COUNT")
+            .arg(cap + 1)
+            .query(self.conn()?)
+            .context("Failed to XRANGE")?;
+        Ok(entries.len())
+    }
+
     pub fn set_ttl(&mut self, key: &str, ttl: i64) -> Result<()> {
         if ttl < 0 {
             let _: () = redis::cmd("PERSIST")
                 .arg(key)
-                .query(&mut self.connection)
+                .query(self.conn()?)
                 .context("Failed to PERSIST")?;
         } else {
             let _: () = self
-                .connection
+                .conn()?
                 .expire(key, ttl)
                 .context("Failed to EXPIRE")?;
         }
@@ -443,9 +1462,487 @@ impl RedisClient {
 
     pub fn rename_key(&mut self, old_key: &str, new_key: &str) -> Result<()> {
         let _: () = self
-            .connection
+            .conn()?
             .rename(old_key, new_key)
             .context("Failed to RENAME")?;
         Ok(())
     }
+
+    /// TS.ADD a sample; `timestamp` is `*` for the server time.
+    /// Returns the timestamp the sample was stored at.
+    pub fn ts_add(&mut self, key: &str, timestamp: &str, value: &str) -> Result<i64> {
+        let stored: i64 = redis::cmd("TS.ADD")
+            .arg(key)
+            .arg(timestamp)
+            .arg(value)
+            .query(self.conn()?)
+            .context("Failed to TS.ADD")?;
+        Ok(stored)
+    }
+
+    // ─── Snapshots ───────────────────────────────────────────
+
+    /// DUMP payload of a key and its absolute expiry in ms since the epoch,
+    /// None if the key does not exist
+    pub fn dump(&mut self, key: &str) -> Result<Option<(Vec<u8>, Option<i64>)>> {
+        let payload: Option<Vec<u8>> = redis::cmd("DUMP")
+            .arg(key)
+            .query(self.conn()?)
+            .context("Failed to DUMP")?;
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+        let expires_at: i64 = redis::cmd("PEXPIRETIME")
+            .arg(key)
+            .query(self.conn()?)
+            .or_else(|_| -> Result<i64> {
+                // Before Redis 7: derive it from the remaining TTL
+                let pttl: i64 = redis::cmd("PTTL").arg(key).query(self.conn()?).context("Failed to PTTL")?;
+                Ok(if pttl > 0 { now_epoch_millis() + pttl } else { pttl })
+            })?;
+        Ok(Some((payload, (expires_at > 0).then_some(expires_at))))
+    }
+
+    /// RESTORE a DUMP payload over whatever the key holds now
+    pub fn restore(&mut self, key: &str, payload: &[u8], expires_at: Option<i64>) -> Result<()> {
+        let mut cmd = redis::cmd("RESTORE");
+        cmd.arg(key).arg(expires_at.unwrap_or(0)).arg(payload).arg("REPLACE");
+        if expires_at.is_some() {
+            cmd.arg("ABSTTL");
+        }
+        cmd.exec(self.conn()?).context("Failed to RESTORE")?;
+        Ok(())
+    }
+
+    // ─── Bulk operations ─────────────────────────────────────
+
+    /// Send `command key args...` for every key in one pipeline and return
+    /// the integer replies (EXPIRE, PERSIST, MOVE, ...)
+    pub fn per_key(&mut self, command: &str, keys: &[String], args: &[String]) -> Result<Vec<i64>> {
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd(command).arg(key).arg(args);
+        }
+        let replies: Vec<i64> = pipe
+            .query(self.conn()?)
+            .with_context(|| format!("Failed to {}", command))?;
+        Ok(replies)
+    }
+
+    /// RENAMENX each (old, new) pair in one pipeline; 0 where the new name
+    /// was taken
+    pub fn renamenx_many(&mut self, pairs: &[(String, String)]) -> Result<Vec<i64>> {
+        let mut pipe = redis::pipe();
+        for (old, new) in pairs {
+            pipe.cmd("RENAMENX").arg(old).arg(new);
+        }
+        let replies: Vec<i64> = pipe.query(self.conn()?).context("Failed to RENAMENX")?;
+        Ok(replies)
+    }
+
+    /// MEMORY USAGE of each key in one pipeline; None for keys that are gone
+    pub fn memory_usage(&mut self, keys: &[String]) -> Result<Vec<Option<i64>>> {
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("MEMORY").arg("USAGE").arg(key);
+        }
+        let sizes: Vec<Option<i64>> = pipe.query(self.conn()?).context("Failed to get MEMORY USAGE")?;
+        Ok(sizes)
+    }
+
+    pub fn exists(&mut self, key: &str) -> Result<bool> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.pttl(self.db, key) != -2);
+        }
+        let n: i64 = redis::cmd("EXISTS").arg(key).query(self.conn()?).context("Failed to EXISTS")?;
+        Ok(n > 0)
+    }
+
+    /// Send raw commands (name and arguments) in one pipeline
+    pub fn run_commands(&mut self, commands: &[Vec<Vec<u8>>]) -> Result<()> {
+        let mut pipe = redis::pipe();
+        for command in commands {
+            if let Some((name, args)) = command.split_first() {
+                pipe.cmd(&String::from_utf8_lossy(name)).arg(args);
+            }
+        }
+        pipe.exec(self.conn()?).context("Failed to run commands")?;
+        Ok(())
+    }
+
+    /// COPY each key to `db` on this server in one pipeline; 0 where the
+    /// key exists there (and `replace` is off) or is gone
+    pub fn copy_many(&mut self, keys: &[String], db: i64, replace: bool) -> Result<Vec<i64>> {
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("COPY").arg(key).arg(key).arg("DB").arg(db);
+            if replace {
+                pipe.arg("REPLACE");
+            }
+        }
+        let replies: Vec<i64> = pipe.query(self.conn()?).context("Failed to COPY")?;
+        Ok(replies)
+    }
+
+    /// DUMP each key with its remaining TTL in one pipeline, skipping keys
+    /// that are gone
+    pub fn dump_many(&mut self, keys: &[String]) -> Result<Vec<DumpedKey>> {
+        let mut pipe = redis::pipe();
+        for key in keys {
+            pipe.cmd("DUMP").arg(key).cmd("PTTL").arg(key);
+        }
+        let replies: Vec<(Option<Vec<u8>>, i64)> = pipe.query(self.conn()?).context("Failed to DUMP")?;
+        Ok(keys
+            .iter()
+            .zip(replies)
+            .filter_map(|(key, (payload, pttl))| {
+                Some(DumpedKey { key: key.clone(), payload: payload?, ttl_ms: pttl.max(0) })
+            })
+            .collect())
+    }
+
+    /// RESTORE dumped keys in one pipeline; each result is the server's
+    /// error for that key, e.g. BUSYKEY when it exists and `replace` is off
+    pub fn restore_many(&mut self, dumps: &[DumpedKey], replace: bool) -> Result<Vec<Result<(), String>>> {
+        let mut pipe = redis::pipe();
+        for dump in dumps {
+            pipe.cmd("RESTORE").arg(&dump.key).arg(dump.ttl_ms).arg(&dump.payload);
+            if replace {
+                pipe.arg("REPLACE");
+            }
+        }
+        let replies: Vec<redis::RedisResult<redis::Value>> =
+            pipe.ignore_errors().query(self.conn()?).context("Failed to RESTORE")?;
+        Ok(replies.into_iter().map(|r| r.map(|_| ()).map_err(|e| e.to_string())).collect())
+    }
+
+    pub fn pttl(&mut self, key: &str) -> Result<i64> {
+        if let Some(rdb) = &self.offline {
+            return Ok(rdb.pttl(self.db, key));
+        }
+        let pttl: i64 = redis::cmd("PTTL").arg(key).query(self.conn()?).context("Failed to PTTL")?;
+        Ok(pttl)
+    }
+
+    // ─── RedisJSON ───────────────────────────────────────────
+
+    pub fn json_set(&mut self, key: &str, path: &str, json: &str) -> Result<()> {
+        let reply: Option<String> = redis::cmd("JSON.SET")
+            .arg(key)
+            .arg(path)
+            .arg(json)
+            .query(self.conn()?)
+            .context("Failed to JSON.SET")?;
+        match reply {
+            Some(_) => Ok(()),
+            None => anyhow::bail!("JSON.SET: parent of {} does not exist", path),
+        }
+    }
+
+    /// Append a JSON value to every array matching the path.
+    /// Returns the new lengths (`None` where the match is not an array).
+    pub fn json_arrappend(&mut self, key: &str, path: &str, json: &str) -> Result<Vec<Option<i64>>> {
+        let lengths: Vec<Option<i64>> = redis::cmd("JSON.ARRAPPEND")
+            .arg(key)
+            .arg(path)
+            .arg(json)
+            .query(self.conn()?)
+            .context("Failed to JSON.ARRAPPEND")?;
+        Ok(lengths)
+    }
+
+    /// Increment every number matching the path; returns the new values as JSON.
+    pub fn json_numincrby(&mut self, key: &str, path: &str, by: &str) -> Result<String> {
+        let values: String = redis::cmd("JSON.NUMINCRBY")
+            .arg(key)
+            .arg(path)
+            .arg(by)
+            .query(self.conn()?)
+            .context("Failed to JSON.NUMINCRBY")?;
+        Ok(values)
+    }
+
+    // ─── RediSearch ──────────────────────────────────────────
+
+    pub fn ft_list(&mut self) -> Result<Vec<String>> {
+        let raw: redis::Value = redis::cmd("FT._LIST")
+            .query(self.conn()?)
+            .context("Failed to FT._LIST")?;
+        let mut names: Vec<String> = reply_list(&raw).iter().map(reply_text).collect();
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn ft_info(&mut self, index: &str) -> Result<SearchIndexInfo> {
+        let raw: redis::Value = redis::cmd("FT.INFO")
+            .arg(index)
+            .query(self.conn()?)
+            .context("Failed to FT.INFO")?;
+        let mut info = SearchIndexInfo {
+            name: index.to_string(),
+            key_type: String::new(),
+            prefixes: Vec::new(),
+            stats: Vec::new(),
+            attributes: Vec::new(),
+        };
+        let pairs: Vec<(String, redis::Value)> =
+            reply_pairs(&raw).into_iter().map(|(k, v)| (reply_text(&k), v)).collect();
+        for (name, value) in &pairs {
+            match name.as_str() {
+                "index_definition" => {
+                    for (k, v) in reply_pairs(value) {
+                        match reply_text(&k).as_str() {
+                            "key_type" => info.key_type = reply_text(&v),
+                            "prefixes" => info.prefixes = reply_list(&v).iter().map(reply_text).collect(),
+                            _ => {}
+                        }
+                    }
+                }
+                "attributes" => {
+                    info.attributes = reply_list(value).iter().map(parse_search_attribute).collect();
+                }
+                _ => {}
+            }
+        }
+        for stat in SEARCH_STATS {
+            if let Some((_, v)) = pairs.iter().find(|(k, _)| k == stat) {
+                info.stats.push((stat.to_string(), reply_text(v)));
+            }
+        }
+        Ok(info)
+    }
+
+    /// Run FT.SEARCH or FT.AGGREGATE with extra arguments (LIMIT, SORTBY, LOAD, ...)
+    pub fn ft_query(&mut self, command: &str, index: &str, query: &str, args: &[String]) -> Result<SearchResults> {
+        let raw: redis::Value = redis::cmd(command)
+            .arg(index)
+            .arg(query)
+            .arg(args)
+            .query(self.conn()?)
+            .with_context(|| format!("Failed to {}", command))?;
+        Ok(parse_search_reply(&raw, command == "FT.AGGREGATE", args))
+    }
+}
+
+// ─── Generic reply helpers ───────────────────────────────────
+
+/// A scalar reply as text (numbers formatted, nil as empty)
+/// Size of a HyperLogLog header: `HYLL`, encoding, 3 unused bytes and the
+/// cached cardinality
+const HLL_HEADER: usize = 16;
+/// A dense HyperLogLog: the header plus 16384 6-bit registers
+const HLL_DENSE_SIZE: usize = HLL_HEADER + 16384 * 6 / 8;
+
+/// Whether a string has the header Redis writes for a HyperLogLog
+fn is_hyperloglog(raw: &[u8]) -> bool {
+    if raw.len() < HLL_HEADER || !raw.starts_with(b"HYLL") || raw[5..8] != [0, 0, 0] {
+        return false;
+    }
+    match raw[4] {
+        0 => raw.len() == HLL_DENSE_SIZE,
+        1 => true,
+        _ => false,
+    }
+}
+
+fn reply_text(value: &redis::Value) -> String {
+    match value {
+        redis::Value::BulkString(b) => String::from_utf8_lossy(b).to_string(),
+        redis::Value::SimpleString(s) => s.clone(),
+        redis::Value::VerbatimString { text, .. } => text.clone(),
+        redis::Value::Int(i) => i.to_string(),
+        redis::Value::Double(d) => d.to_string(),
+        redis::Value::Boolean(b) => b.to_string(),
+        redis::Value::Okay => "OK".to_string(),
+        redis::Value::Nil => String::new(),
+        other => format!("{:?}", other),
+    }
+}
+
+/// The elements of an array (or set) reply
+fn reply_list(value: &redis::Value) -> Vec<redis::Value> {
+    match value {
+        redis::Value::Array(items) | redis::Value::Set(items) => items.clone(),
+        _ => Vec::new(),
+    }
+}
+
+/// Name/value pairs from a flat `[name, value, ...]` array or a RESP3 map,
+/// as returned by the various INFO commands of modules
+fn reply_pairs(value: &redis::Value) -> Vec<(redis::Value, redis::Value)> {
+    match value {
+        redis::Value::Map(pairs) => pairs.clone(),
+        redis::Value::Array(items) => items
+            .chunks_exact(2)
+            .map(|pair| (pair[0].clone(), pair[1].clone()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A `[lon, lat]` reply (nil for missing members)
+fn reply_coord(value: &redis::Value) -> Option<(f64, f64)> {
+    let parts = reply_list(value);
+    let lon = reply_text(parts.first()?).parse().ok()?;
+    let lat = reply_text(parts.get(1)?).parse().ok()?;
+    Some((lon, lat))
+}
+
+/// Raw bytes of a string reply (other scalars as their text)
+fn reply_bytes(value: &redis::Value) -> Vec<u8> {
+    match value {
+        redis::Value::BulkString(b) => b.clone(),
+        other => reply_text(other).into_bytes(),
+    }
+}
+
+/// Field/value pairs of a search result, keeping binary values intact
+fn reply_fields(value: &redis::Value) -> Vec<(String, Vec<u8>)> {
+    reply_pairs(value).iter().map(|(k, v)| (reply_text(k), reply_bytes(v))).collect()
+}
+
+/// One FT.INFO attribute: a flat `[identifier, x, attribute, y, type, TEXT, ...]`
+/// array (flags like SORTABLE have no value) or a RESP3 map
+fn parse_search_attribute(value: &redis::Value) -> SearchAttribute {
+    // Options that are followed by a value; everything else is a flag
+    const VALUED: &[&str] = &[
+        "identifier", "attribute", "type", "weight", "separator", "phonetic", "algorithm", "data_type",
+        "dim", "distance_metric", "m", "ef_construction", "ef_runtime", "epsilon", "initial_cap", "block_size",
+    ];
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let mut options = Vec::new();
+    match value {
+        redis::Value::Map(map) => {
+            for (k, v) in map {
+                match v {
+                    redis::Value::Array(flags) | redis::Value::Set(flags) => {
+                        options.extend(flags.iter().map(reply_text));
+                    }
+                    _ => pairs.push((reply_text(k).to_lowercase(), reply_text(v))),
+                }
+            }
+        }
+        _ => {
+            let items = reply_list(value);
+            let mut iter = items.iter().map(reply_text);
+            while let Some(token) = iter.next() {
+                if VALUED.contains(&token.to_lowercase().as_str()) {
+                    pairs.push((token.to_lowercase(), iter.next().unwrap_or_default()));
+                } else {
+                    options.push(token);
+                }
+            }
+        }
+    }
+    let get = |name: &str| pairs.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+    let kind = get("type").unwrap_or_default();
+    let vector = (kind == "VECTOR").then(|| VectorInfo {
+        dim: get("dim").and_then(|d| d.parse().ok()).unwrap_or(0),
+        data_type: get("data_type").unwrap_or_else(|| "FLOAT32".to_string()).to_uppercase(),
+        metric: get("distance_metric").unwrap_or_default(),
+    });
+    for (k, v) in &pairs {
+        if !matches!(k.as_str(), "identifier" | "attribute" | "type") {
+            options.push(format!("{}={}", k.to_uppercase(), v));
+        }
+    }
+    let identifier = get("identifier").unwrap_or_default();
+    SearchAttribute {
+        name: get("attribute").unwrap_or_else(|| identifier.clone()),
+        identifier,
+        kind,
+        options,
+        vector,
+    }
+}
+
+/// FT.SEARCH (`[total, id, [fields], ...]`, shaped by WITHSCORES, NOCONTENT, ...)
+/// or FT.AGGREGATE (`[total, [fields], ...]`) reply, in RESP2 or RESP3
+fn parse_search_reply(raw: &redis::Value, aggregate: bool, args: &[String]) -> SearchResults {
+    let has = |flag: &str| args.iter().any(|a| a.eq_ignore_ascii_case(flag));
+    if let redis::Value::Map(_) = raw {
+        return parse_search_map(raw);
+    }
+    let mut items = reply_list(raw);
+    // WITHCURSOR wraps the reply as [results, cursor]
+    if aggregate && has("WITHCURSOR") {
+        items = items.first().map(reply_list).unwrap_or_default();
+    }
+    let total = items.first().map(|v| reply_text(v).parse().unwrap_or(0)).unwrap_or(0);
+    let mut rest = items.iter().skip(1).peekable();
+    if aggregate {
+        let hits = rest
+            .map(|row| SearchHit { id: None, score: None, fields: reply_fields(row) })
+            .collect();
+        return SearchResults { total, hits };
+    }
+    let extras = has("WITHPAYLOADS") as usize + has("WITHSORTKEYS") as usize;
+    let mut hits = Vec::new();
+    while let Some(id) = rest.next() {
+        let score = if has("WITHSCORES") { rest.next().map(reply_text) } else { None };
+        for _ in 0..extras {
+            rest.next();
+        }
+        let fields = match rest.next_if(|v| matches!(v, redis::Value::Array(_)) && !has("NOCONTENT")) {
+            Some(row) => reply_fields(row),
+            None => Vec::new(),
+        };
+        hits.push(SearchHit { id: Some(reply_text(id)), score, fields });
+    }
+    SearchResults { total, hits }
+}
+
+/// RESP3 FT.SEARCH / FT.AGGREGATE reply:
+/// `{total_results, results: [{id, extra_attributes: {...}, ...}], ...}`
+fn parse_search_map(value: &redis::Value) -> SearchResults {
+    let mut total = 0;
+    let mut hits = Vec::new();
+    for (k, v) in reply_pairs(value) {
+        match reply_text(&k).as_str() {
+            "total_results" => total = reply_text(&v).parse().unwrap_or(0),
+            "results" => {
+                for row in reply_list(&v) {
+                    let mut hit = SearchHit { id: None, score: None, fields: Vec::new() };
+                    for (k, v) in reply_pairs(&row) {
+                        match reply_text(&k).as_str() {
+                            "id" => hit.id = Some(reply_text(&v)),
+                            "score" => hit.score = Some(reply_text(&v)),
+                            "extra_attributes" => hit.fields = reply_fields(&v),
+                            _ => {}
+                        }
+                    }
+                    hits.push(hit);
+                }
+            }
+            _ => {}
+        }
+    }
+    SearchResults { total, hits }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hll(encoding: u8, len: usize) -> Vec<u8> {
+        let mut raw = b"HYLL".to_vec();
+        raw.push(encoding);
+        raw.resize(len, 0);
+        raw
+    }
+
+    #[test]
+    fn hyperloglog_header() {
+        assert!(is_hyperloglog(&hll(0, HLL_DENSE_SIZE)));
+        assert!(is_hyperloglog(&hll(1, HLL_HEADER + 5)));
+        // Dense strings have a fixed size, the encoding is 0 or 1 and the
+        // unused bytes are zero
+        assert!(!is_hyperloglog(&hll(0, HLL_DENSE_SIZE - 1)));
+        assert!(!is_hyperloglog(&hll(2, HLL_HEADER + 5)));
+        let mut unused = hll(1, HLL_HEADER + 5);
+        unused[6] = 1;
+        assert!(!is_hyperloglog(&unused));
+        assert!(!is_hyperloglog(b"HYLL and some text"));
+        assert!(!is_hyperloglog(b"HYLL"));
+    }
 }
@@ -0,0 +1,464 @@
+use ratatui::prelude::*;
+
+/// Most undo steps kept per field
+const UNDO_LIMIT: usize = 100;
+
+/// Cursor, selection and undo history for editing the text fields of a form.
+/// The text itself stays with its owner and is passed to every call; the
+/// state of the focused field is kept here and that of the others is parked
+/// until the focus comes back.
+#[derive(Debug, Clone, Default)]
+pub struct TextArea {
+    cursor: usize,         // char index into the text
+    anchor: Option<usize>, // other end of the selection
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    typing: bool, // coalesce a run of typed characters into one undo step
+    /// Field index and text as last left by this editor; anything else means
+    /// the focus moved or the text was replaced
+    bound: Option<(usize, String)>,
+    /// State of the other fields, each bound to its own index and text
+    parked: Vec<TextArea>,
+}
+
+fn char_count(text: &str) -> usize {
+    text.chars().count()
+}
+
+fn byte_index(text: &str, chars: usize) -> usize {
+    text.char_indices().nth(chars).map_or(text.len(), |(i, _)| i)
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl TextArea {
+    /// Attach to `field` holding `text`. The field that had the focus is
+    /// parked; `field` resumes its cursor and history if its text is as this
+    /// editor left it, and otherwise starts at the end with no history.
+    pub fn bind(&mut self, field: usize, text: &str) {
+        if self.is_bound(field, text) {
+            return;
+        }
+        let mut parked = std::mem::take(&mut self.parked);
+        if let Some((left, _)) = &self.bound {
+            let left = *left;
+            parked.retain(|p| !p.is_field(left));
+            parked.push(std::mem::take(self));
+        }
+        let resumed = parked.iter().position(|p| p.is_bound(field, text)).map(|i| parked.remove(i));
+        parked.retain(|p| !p.is_field(field));
+        *self = resumed.unwrap_or_else(|| TextArea {
+            cursor: char_count(text),
+            bound: Some((field, text.to_string())),
+            ..TextArea::default()
+        });
+        self.parked = parked;
+    }
+
+    fn is_field(&self, field: usize) -> bool {
+        self.bound.as_ref().is_some_and(|(f, _)| *f == field)
+    }
+
+    fn is_bound(&self, field: usize, text: &str) -> bool {
+        self.bound.as_ref().is_some_and(|(f, t)| *f == field && t == text)
+    }
+
+    fn touch(&mut self, text: &str) {
+        if let Some((_, seen)) = &mut self.bound {
+            *seen = text.to_string();
+        }
+    }
+
+    /// Cursor as (line, column) in chars
+    pub fn position(&self, text: &str) -> (usize, usize) {
+        let before: String = text.chars().take(self.cursor).collect();
+        let row = before.matches('\n').count();
+        let col = before.rsplit('\n').next().map_or(0, char_count);
+        (row, col)
+    }
+
+    fn index_of(text: &str, row: usize, col: usize) -> usize {
+        let mut index = 0;
+        for (i, line) in text.split('\n').enumerate() {
+            let len = char_count(line);
+            if i == row {
+                return index + col.min(len);
+            }
+            index += len + 1;
+        }
+        char_count(text)
+    }
+
+    /// The selected char range, if any
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        match self.anchor {
+            Some(a) if a != self.cursor => Some((a.min(self.cursor), a.max(self.cursor))),
+            _ => None,
+        }
+    }
+
+    pub fn selected_text(&self, text: &str) -> Option<String> {
+        self.selection()
+            .map(|(start, end)| text.chars().skip(start).take(end - start).collect())
+    }
+
+    fn snapshot(&mut self, text: &str, typing: bool) {
+        if !(typing && self.typing) {
+            self.undo.push((text.to_string(), self.cursor));
+            if self.undo.len() > UNDO_LIMIT {
+                self.undo.remove(0);
+            }
+        }
+        self.redo.clear();
+        self.typing = typing;
+    }
+
+    fn delete_range(&mut self, text: &mut String, start: usize, end: usize) {
+        let (a, b) = (byte_index(text, start), byte_index(text, end));
+        text.replace_range(a..b, "");
+        self.cursor = start;
+        self.anchor = None;
+    }
+
+    /// Type or paste `s` over the selection
+    pub fn insert(&mut self, text: &mut String, s: &str) {
+        let typing = char_count(s) == 1 && !s.starts_with(char::is_whitespace);
+        self.snapshot(text, typing);
+        if let Some((start, end)) = self.selection() {
+            self.delete_range(text, start, end);
+        }
+        self.anchor = None;
+        let at = byte_index(text, self.cursor);
+        text.insert_str(at, s);
+        self.cursor += char_count(s);
+        self.touch(text);
+    }
+
+    pub fn backspace(&mut self, text: &mut String) {
+        if let Some((start, end)) = self.selection() {
+            self.snapshot(text, false);
+            self.delete_range(text, start, end);
+        } else if self.cursor > 0 {
+            self.snapshot(text, false);
+            self.delete_range(text, self.cursor - 1, self.cursor);
+        }
+        self.touch(text);
+    }
+
+    pub fn delete(&mut self, text: &mut String) {
+        if let Some((start, end)) = self.selection() {
+            self.snapshot(text, false);
+            self.delete_range(text, start, end);
+        } else if self.cursor < char_count(text) {
+            self.snapshot(text, false);
+            self.delete_range(text, self.cursor, self.cursor + 1);
+        }
+        self.touch(text);
+    }
+
+    /// Delete back to the start of the word (Ctrl+W)
+    pub fn delete_word(&mut self, text: &mut String) {
+        if self.selection().is_some() {
+            return self.backspace(text);
+        }
+        let start = self.word_left_of(text);
+        if start < self.cursor {
+            self.snapshot(text, false);
+            self.delete_range(text, start, self.cursor);
+        }
+        self.touch(text);
+    }
+
+    fn move_to(&mut self, to: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = to;
+        self.typing = false;
+    }
+
+    pub fn left(&mut self, select: bool) {
+        let to = match self.selection() {
+            Some((start, _)) if !select => start,
+            _ => self.cursor.saturating_sub(1),
+        };
+        self.move_to(to, select);
+    }
+
+    pub fn right(&mut self, text: &str, select: bool) {
+        let to = match self.selection() {
+            Some((_, end)) if !select => end,
+            _ => (self.cursor + 1).min(char_count(text)),
+        };
+        self.move_to(to, select);
+    }
+
+    fn word_left_of(&self, text: &str) -> usize {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = self.cursor;
+        while i > 0 && !is_word(chars[i - 1]) {
+            i -= 1;
+        }
+        while i > 0 && is_word(chars[i - 1]) {
+            i -= 1;
+        }
+        i
+    }
+
+    pub fn word_left(&mut self, text: &str, select: bool) {
+        let to = self.word_left_of(text);
+        self.move_to(to, select);
+    }
+
+    pub fn word_right(&mut self, text: &str, select: bool) {
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = self.cursor;
+        while i < chars.len() && !is_word(chars[i]) {
+            i += 1;
+        }
+        while i < chars.len() && is_word(chars[i]) {
+            i += 1;
+        }
+        self.move_to(i, select);
+    }
+
+    pub fn home(&mut self, text: &str, select: bool) {
+        let (row, _) = self.position(text);
+        self.move_to(Self::index_of(text, row, 0), select);
+    }
+
+    pub fn end(&mut self, text: &str, select: bool) {
+        let (row, _) = self.position(text);
+        self.move_to(Self::index_of(text, row, usize::MAX), select);
+    }
+
+    /// Move a line up or down; false when already on the first/last line
+    pub fn vertical(&mut self, text: &str, down: bool, select: bool) -> bool {
+        let (row, col) = self.position(text);
+        let lines = text.matches('\n').count() + 1;
+        if (!down && row == 0) || (down && row + 1 >= lines) {
+            return false;
+        }
+        let row = if down { row + 1 } else { row - 1 };
+        self.move_to(Self::index_of(text, row, col), select);
+        true
+    }
+
+    pub fn select_all(&mut self, text: &str) {
+        self.anchor = Some(0);
+        self.cursor = char_count(text);
+        self.typing = false;
+    }
+
+    /// Remove and return the selection (Ctrl+X)
+    pub fn cut(&mut self, text: &mut String) -> Option<String> {
+        let cut = self.selected_text(text)?;
+        self.backspace(text);
+        Some(cut)
+    }
+
+    pub fn undo(&mut self, text: &mut String) {
+        if let Some((before, cursor)) = self.undo.pop() {
+            self.redo.push((std::mem::replace(text, before), self.cursor));
+            self.cursor = cursor.min(char_count(text));
+            self.anchor = None;
+            self.typing = false;
+            self.touch(text);
+        }
+    }
+
+    pub fn redo(&mut self, text: &mut String) {
+        if let Some((after, cursor)) = self.redo.pop() {
+            self.undo.push((std::mem::replace(text, after), self.cursor));
+            self.cursor = cursor.min(char_count(text));
+            self.anchor = None;
+            self.typing = false;
+            self.touch(text);
+        }
+    }
+
+    /// Draw `text` of `field` as at most `max_lines` lines of `width` cells,
+    /// with the selection highlighted and the cursor as a reversed cell.
+    /// Lines scroll to keep the cursor in view.
+    pub fn render(&self, field: usize, text: &str, width: usize, max_lines: usize, style: Style) -> Vec<Line<'static>> {
+        // Not focused: show it as `bind` would leave it
+        let mut fresh = TextArea::default();
+        let state = if self.is_bound(field, text) {
+            self
+        } else if let Some(parked) = self.parked.iter().find(|p| p.is_bound(field, text)) {
+            parked
+        } else {
+            fresh.bind(field, text);
+            &fresh
+        };
+        let (cursor_row, cursor_col) = state.position(text);
+        let selection = state.selection();
+        let selected_style = style.bg(Color::Blue);
+        let cursor_style = style.add_modifier(Modifier::REVERSED);
+
+        let lines: Vec<&str> = text.split('\n').collect();
+        let max_lines = max_lines.max(1);
+        let first_row = (cursor_row + 1).saturating_sub(max_lines);
+        let width = width.max(2);
+
+        let mut out = Vec::new();
+        let mut index = lines[..first_row].iter().map(|l| char_count(l) + 1).sum::<usize>();
+        for (row, line) in lines.iter().enumerate().skip(first_row).take(max_lines) {
+            let chars: Vec<char> = line.chars().collect();
+            // Scroll the cursor line sideways, leaving room for the cursor cell
+            let offset = if row == cursor_row { (cursor_col + 1).saturating_sub(width) } else { 0 };
+            let mut spans: Vec<Span<'static>> = Vec::new();
+            let end = (offset + width).min(chars.len() + 1);
+            for col in offset..end {
+                let at = index + col;
+                let ch = chars.get(col).copied().unwrap_or(' ');
+                let cell_style = if row == cursor_row && col == cursor_col {
+                    cursor_style
+                } else if selection.is_some_and(|(s, e)| at >= s && at < e) && col < chars.len() {
+                    selected_style
+                } else {
+                    style
+                };
+                if col == chars.len() && !(row == cursor_row && col == cursor_col) {
+                    break;
+                }
+                match spans.last_mut() {
+                    Some(last) if last.style == cell_style => last.content.to_mut().push(ch),
+                    _ => spans.push(Span::styled(ch.to_string(), cell_style)),
+                }
+            }
+            out.push(Line::from(spans));
+            index += chars.len() + 1;
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(area: &mut TextArea, text: &mut String, s: &str) {
+        for c in s.chars() {
+            area.insert(text, &c.to_string());
+        }
+    }
+
+    #[test]
+    fn word_motion() {
+        let text = "foo_bar  baz.qux";
+        let mut area = TextArea::default();
+        area.bind(0, text);
+        area.word_left(text, false);
+        assert_eq!(area.cursor, 13);
+        area.word_left(text, false);
+        assert_eq!(area.cursor, 9);
+        area.word_left(text, false);
+        assert_eq!(area.cursor, 0);
+        area.word_right(text, false);
+        assert_eq!(area.cursor, 7);
+        area.word_right(text, false);
+        assert_eq!(area.cursor, 12);
+
+        let mut text = text.to_string();
+        area.delete_word(&mut text);
+        assert_eq!(text, "foo_bar  .qux");
+        assert_eq!(area.cursor, 9);
+    }
+
+    #[test]
+    fn selection() {
+        let mut text = "hello world".to_string();
+        let mut area = TextArea::default();
+        area.bind(0, &text);
+        area.word_left(&text, true);
+        assert_eq!(area.selected_text(&text).as_deref(), Some("world"));
+        // Moving without Shift collapses to the selection's edge
+        area.left(false);
+        assert_eq!((area.cursor, area.selection()), (6, None));
+
+        area.end(&text, true);
+        area.insert(&mut text, "there");
+        assert_eq!(text, "hello there");
+        area.select_all(&text);
+        assert_eq!(area.cut(&mut text).as_deref(), Some("hello there"));
+        assert_eq!(text, "");
+
+        // Home/End and Up/Down stay on their line
+        let text = "ab\ncdef\ng".to_string();
+        area.bind(1, &text);
+        area.vertical(&text, false, false);
+        assert_eq!(area.position(&text), (1, 1));
+        area.end(&text, false);
+        assert_eq!(area.position(&text), (1, 4));
+        area.home(&text, true);
+        assert_eq!(area.selected_text(&text).as_deref(), Some("cdef"));
+        assert!(area.vertical(&text, true, false));
+        assert_eq!(area.position(&text), (2, 0));
+        assert!(!area.vertical(&text, true, false));
+    }
+
+    #[test]
+    fn undo_coalesces_typing() {
+        let mut text = String::new();
+        let mut area = TextArea::default();
+        area.bind(0, &text);
+        typed(&mut area, &mut text, "one");
+        area.insert(&mut text, " ");
+        typed(&mut area, &mut text, "two");
+        assert_eq!(text, "one two");
+        area.undo(&mut text);
+        assert_eq!(text, "one ");
+        area.undo(&mut text);
+        assert_eq!(text, "one");
+        area.undo(&mut text);
+        assert_eq!(text, "");
+        area.redo(&mut text);
+        area.redo(&mut text);
+        assert_eq!(text, "one ");
+
+        // Moving the cursor ends the run
+        typed(&mut area, &mut text, "ab");
+        area.left(false);
+        typed(&mut area, &mut text, "x");
+        assert_eq!(text, "one axb");
+        area.undo(&mut text);
+        assert_eq!(text, "one ab");
+        // A new edit drops the redo steps
+        area.insert(&mut text, "!");
+        area.redo(&mut text);
+        assert_eq!(text, "one a!b");
+    }
+
+    #[test]
+    fn history_is_kept_per_field() {
+        let mut fields = vec![String::new(), String::new()];
+        let mut area = TextArea::default();
+        area.bind(0, &fields[0]);
+        typed(&mut area, &mut fields[0], "key");
+        area.bind(1, &fields[1]);
+        typed(&mut area, &mut fields[1], "value");
+
+        // Back on the first field, its own history is undone
+        area.bind(0, &fields[0]);
+        assert_eq!(area.cursor, 3);
+        area.undo(&mut fields[0]);
+        assert_eq!(fields, ["", "value"]);
+        area.bind(1, &fields[1]);
+        area.undo(&mut fields[1]);
+        assert_eq!(fields, ["", ""]);
+
+        // A field whose text was replaced meanwhile starts afresh
+        area.redo(&mut fields[1]);
+        area.bind(0, &fields[0]);
+        fields[1] = "other".to_string();
+        area.bind(1, &fields[1]);
+        assert_eq!(area.cursor, 5);
+        area.undo(&mut fields[1]);
+        assert_eq!(fields[1], "other");
+    }
+}
@@ -1,12 +1,19 @@
-use crate::app::{App, EditOperation, InputMode, Panel, PlotFocus, KEY_TYPES, WAVE_TYPES};
+use crate::app::{
+    format_table, App, EditOperation, InputMode, Panel, PlotFocus, XAxis, BULK_ACTIONS, EXPORT_SCOPES,
+    KEY_TYPES, TS_AGGREGATORS, WAVE_TYPES,
+};
 use crate::data::DataType;
+use crate::diff::DiffLine;
+use crate::export::ExportFormat;
+use crate::formats::{ValueFormat, format_epoch_millis};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols,
     text::{Line, Span},
     widgets::{
-        Axis, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, Wrap,
+        canvas::{Canvas, Map, MapResolution, Points},
+        Axis, Block, Borders, Chart, Clear, Dataset, Gauge, GraphType, List, ListItem, Paragraph, Wrap,
     },
     Frame,
 };
@@ -38,8 +45,26 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         InputMode::Confirm => draw_confirm_popup(frame, app, size),
         InputMode::Help => draw_help_popup(frame, app, size),
         InputMode::Edit => draw_edit_popup(frame, app, size),
+        InputMode::Conflict => draw_conflict_popup(frame, app, size),
+        InputMode::UndoHistory => draw_undo_popup(frame, app, size),
+        InputMode::Bulk => draw_bulk_popup(frame, app, size),
+        InputMode::BulkProgress => draw_bulk_progress_popup(frame, app, size),
+        InputMode::PatternDelete => draw_pattern_delete_popup(frame, app, size),
+        InputMode::Export => draw_export_popup(frame, app, size),
+        InputMode::Import => draw_import_popup(frame, app, size),
+        InputMode::Copy => draw_copy_popup(frame, app, size),
         InputMode::PlotLimit => draw_plot_limit_popup(frame, app, size),
         InputMode::SignalGen => draw_signal_gen_popup(frame, app, size),
+        InputMode::RecordLayout => draw_record_layout_popup(frame, app, size),
+        InputMode::Scaling => draw_scaling_popup(frame, app, size),
+        InputMode::Profiles => draw_profiles_popup(frame, app, size),
+        InputMode::ValueFormat => draw_value_format_popup(frame, app, size),
+        InputMode::Query if app.is_viewing_timeseries() => draw_ts_query_popup(frame, app, size),
+        InputMode::Query => draw_query_popup(frame, app, size),
+        InputMode::VectorCompare => draw_vector_compare_popup(frame, app, size),
+        InputMode::SearchIndexes => draw_search_indexes_popup(frame, app, size),
+        InputMode::SearchQuery => draw_search_query_popup(frame, app, size),
+        InputMode::SearchResults => draw_search_results_popup(frame, app, size),
         InputMode::Normal => {}
     }
 }
@@ -103,10 +128,11 @@ fn draw_key_list(frame: &mut Frame, app: &mut App, area: Rect) {
         BORDER_INACTIVE
     };
 
-    let title = format!(
-        " Keys ({}) [/]Filter [r]Refresh ",
-        app.keys.len()
-    );
+    let title = if app.marked_keys.is_empty() {
+        format!(" Keys ({}) [/]Filter [r]Refresh ", app.keys.len())
+    } else {
+        format!(" Keys ({}, {} marked) [b]Bulk [Esc]Unmark ", app.keys.len(), app.marked_keys.len())
+    };
 
     let items: Vec<ListItem> = app
         .keys
@@ -119,14 +145,17 @@ fn draw_key_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 ("???", Color::DarkGray)
             };
 
-            let line = Line::from(vec![
-                Span::styled(
-                    format!("{:<6}", type_badge.0),
-                    Style::default().fg(type_badge.1),
-                ),
-                Span::raw(key),
-            ]);
-            ListItem::new(line)
+            let mut spans = Vec::new();
+            if !app.marked_keys.is_empty() {
+                let mark = if app.marked_keys.contains(key) { "● " } else { "  " };
+                spans.push(Span::styled(mark, Style::default().fg(Color::Magenta)));
+            }
+            spans.push(Span::styled(
+                format!("{:<6}", type_badge.0),
+                Style::default().fg(type_badge.1),
+            ));
+            spans.push(Span::raw(key));
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -148,13 +177,26 @@ fn draw_key_list(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_stateful_widget(list, area, &mut app.key_list_state);
 }
 
-fn draw_value_view(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_value_view(frame: &mut Frame, app: &mut App, area: Rect) {
     let border_color = if app.active_panel == Panel::ValueView {
         BORDER_ACTIVE
     } else {
         BORDER_INACTIVE
     };
 
+    // The selected element's detail pane takes the lower half
+    let is_element = app.is_element_view();
+    let (area, detail_area) = if is_element && app.element_detail {
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(area);
+        (parts[0], Some(parts[1]))
+    } else {
+        (area, None)
+    };
+
+    let value_lines = app.format_value();
     let mut lines: Vec<Line> = Vec::new();
 
     // Key metadata header
@@ -192,29 +234,104 @@ fn draw_value_view(frame: &mut Frame, app: &App, area: Rect) {
     }
 
     // Value content
-    let value_lines = app.format_value();
-    for line in &value_lines {
-        lines.push(Line::from(Span::raw(line)));
+    let header_len = lines.len() as u16;
+    let is_json = app.is_viewing_json();
+    let is_bitmap = app.is_bitmap_view();
+    let cursor = app.value_cursor();
+    let cursor_style = if app.active_panel == Panel::ValueView {
+        Style::default().fg(Color::Black).bg(HIGHLIGHT_COLOR)
+    } else {
+        Style::default().add_modifier(Modifier::REVERSED)
+    };
+    for (i, line) in value_lines.iter().enumerate() {
+        match cursor {
+            // Selected JSON node: the whole row
+            Some((row, None)) if row == i => lines.push(Line::from(Span::styled(line, cursor_style))),
+            // Element table column header and rule
+            Some(_) if is_element && i == 0 => lines.push(Line::from(Span::styled(
+                line,
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            ))),
+            Some(_) if is_element && i == 1 => {
+                lines.push(Line::from(Span::styled(line, Style::default().fg(Color::DarkGray))))
+            }
+            // Selected bitmap cell: a single character
+            Some((row, Some(col))) if row == i && col < line.chars().count() => {
+                let chars: Vec<char> = line.chars().collect();
+                lines.push(Line::from(vec![
+                    Span::raw(chars[..col].iter().collect::<String>()),
+                    Span::styled(chars[col..=col].iter().collect::<String>(), cursor_style),
+                    Span::raw(chars[col + 1..].iter().collect::<String>()),
+                ]));
+            }
+            _ => lines.push(Line::from(Span::raw(line))),
+        }
+    }
+
+    // Keep the JSON tree or bitmap cursor on screen (those rows are not wrapped)
+    if let Some((row, _)) = cursor {
+        let visible = area.height.saturating_sub(2).max(1);
+        let cursor_line = header_len + row as u16;
+        if cursor_line < visible {
+            app.value_scroll = 0;
+        } else if cursor_line < app.value_scroll {
+            app.value_scroll = cursor_line;
+        } else if cursor_line >= app.value_scroll + visible {
+            app.value_scroll = cursor_line + 1 - visible;
+        }
     }
 
+    let profile_label = match &app.active_profile {
+        Some(pattern) => format!("[P]{} ", pattern),
+        None => String::new(),
+    };
     let title = format!(
-        " Value [t]{} [e]{} {}",
+        " Value [t]{} [e]{} [L]{} [F]{} {}{}",
         app.data_type,
         app.endianness,
-        if app.active_panel == Panel::ValueView { "[Up/Down]Scroll" } else { "" }
+        if app.record_layout.is_some() { "struct" } else { "layout" },
+        app.formats.summary(),
+        profile_label,
+        match (app.active_panel == Panel::ValueView, is_json, is_bitmap, is_element) {
+            (true, true, _, _) => "[Enter]Fold [s]JSON.*",
+            (true, _, true, _) => "[Enter]SETBIT [Q]BITFIELD [B]Bytes",
+            (true, _, _, true) => "[Enter]Detail [s]Edit [d]Delete",
+            (true, _, _, _) => "[Up/Down]Scroll",
+            _ => "",
+        }
     );
 
-    let paragraph = Paragraph::new(lines)
+    let mut paragraph = Paragraph::new(lines)
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(border_color))
                 .title(title),
         )
-        .wrap(Wrap { trim: false })
         .scroll((app.value_scroll, 0));
+    if cursor.is_none() {
+        paragraph = paragraph.wrap(Wrap { trim: false });
+    }
 
     frame.render_widget(paragraph, area);
+
+    if let Some(detail_area) = detail_area {
+        let detail = Paragraph::new(
+            app.element_detail_lines()
+                .iter()
+                .map(|l| Line::from(Span::raw(l.clone())))
+                .collect::<Vec<_>>(),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .title(format!(" Element {} [PgUp/PgDn]Scroll [Enter]Close ", app.element_cursor)),
+        )
+        .wrap(Wrap { trim: false })
+        .scroll((app.detail_scroll, 0));
+        frame.render_widget(detail, detail_area);
+    }
 }
 
 fn draw_data_plot(frame: &mut Frame, app: &mut App, area: Rect) {
@@ -236,11 +353,29 @@ fn draw_data_plot(frame: &mut Frame, app: &mut App, area: Rect) {
             PlotFocus::FFT => " [FFT]",
         }
     } else { "" };
+    let member_label = match app.record_field_name() {
+        Some(name) => format!(" [m]{}", name),
+        None => String::new(),
+    };
+    let scaling_label = if app.scaling.is_identity() {
+        String::new()
+    } else {
+        format!(" [o]{}", app.scaling_summary())
+    };
+    let detect_label = match app.detection_label() {
+        Some(label) => format!(" [A]{}", label),
+        None => String::new(),
+    };
     let title = format!(
-        " Plot [a]{} [f]FFT:{}{}{}",
-        focused_limits, fft_label, log_label, focus_label
+        " Plot [a]{} [f]FFT:{}{}{}{}{}{}",
+        focused_limits, fft_label, log_label, focus_label, member_label, scaling_label, detect_label
     );
 
+    if app.is_geo_view() {
+        draw_geo_map(frame, app, area, border_color);
+        return;
+    }
+
     if app.plot_data.is_empty() {
         let msg = Paragraph::new("No plottable data. Select a key with binary data.")
             .style(Style::default().fg(Color::DarkGray))
@@ -306,12 +441,8 @@ fn draw_signal_chart(frame: &mut Frame, app: &mut App, area: Rect, title: &str,
         return;
     }
 
-    let data_points: Vec<(f64, f64)> = app
-        .plot_data
-        .iter()
-        .enumerate()
-        .map(|(i, v)| (i as f64, *v))
-        .collect();
+    let data_points = app.signal_points();
+    let is_time = app.plot_x_axis == XAxis::Time;
 
     let (x_lo, x_hi) = app.signal_x_bounds();
 
@@ -332,21 +463,42 @@ fn draw_signal_chart(frame: &mut Frame, app: &mut App, area: Rect, title: &str,
 
     // Build title with hover coords
     let hover_suffix = if !app.hover_in_fft {
-        if let (Some(hx), Some(hy)) = (app.hover_data_x, app.hover_data_y) {
-            format!(" x:{:.1} y:{:.2}", hx, hy)
-        } else {
-            String::new()
+        match (app.hover_data_x, app.hover_data_y) {
+            (Some(hx), Some(hy)) if is_time => format!(" t:{} y:{:.2}", format_epoch_millis(hx as i64), hy),
+            (Some(hx), Some(hy)) => format!(" x:{:.1} y:{:.2}", hx, hy),
+            _ => String::new(),
         }
     } else {
         String::new()
     };
     let full_title = format!("{}{} ", title, hover_suffix);
 
+    let x_name = match app.plot_x_axis {
+        XAxis::Index => "Index",
+        XAxis::Time => "Time (UTC)",
+        XAxis::Value => "Value",
+    };
+    let x_title = if app.scaling.decimation > 1 {
+        format!("{} (1/{})", x_name, app.scaling.decimation)
+    } else {
+        x_name.to_string()
+    };
+    let x_label = |x: f64| match app.plot_x_axis {
+        XAxis::Index => format!("{:.0}", x),
+        XAxis::Time => time_axis_label(x, x_hi - x_lo),
+        XAxis::Value => format!("{:.2}", x),
+    };
+    let y_title = if app.scaling.unit.is_empty() {
+        "Value".to_string()
+    } else {
+        format!("Value ({})", app.scaling.unit)
+    };
+
     let marker = safe_marker(area);
     let datasets = vec![Dataset::default()
         .name(format!("{} values", app.plot_data.len()))
         .marker(marker)
-        .graph_type(GraphType::Line)
+        .graph_type(if app.plot_bars { GraphType::Bar } else { GraphType::Line })
         .style(Style::default().fg(Color::Cyan))
         .data(&data_points)];
 
@@ -359,17 +511,17 @@ fn draw_signal_chart(frame: &mut Frame, app: &mut App, area: Rect, title: &str,
         )
         .x_axis(
             Axis::default()
-                .title("Index")
+                .title(x_title)
                 .bounds([x_lo, x_hi])
                 .labels(vec![
-                    Line::from(format!("{:.0}", x_lo)),
-                    Line::from(format!("{:.0}", (x_lo + x_hi) / 2.0)),
-                    Line::from(format!("{:.0}", x_hi)),
+                    Line::from(x_label(x_lo)),
+                    Line::from(x_label((x_lo + x_hi) / 2.0)),
+                    Line::from(x_label(x_hi)),
                 ]),
         )
         .y_axis(
             Axis::default()
-                .title("Value")
+                .title(y_title)
                 .bounds([y_lo, y_hi])
                 .labels(vec![
                     Line::from(format!("{:.2}", y_lo)),
@@ -397,6 +549,102 @@ fn draw_signal_chart(frame: &mut Frame, app: &mut App, area: Rect, title: &str,
     }
 }
 
+/// Geo set members on a lon/lat map with the world outline; the last
+/// GEOSEARCH center and matches are highlighted
+fn draw_geo_map(frame: &mut Frame, app: &mut App, area: Rect, border_color: Color) {
+    // Mouse pan/zoom applies to charts only
+    app.signal_chart_area = None;
+    app.fft_chart_area = None;
+
+    let points: Vec<(f64, f64)> = app
+        .geo_points
+        .as_deref()
+        .unwrap_or_default()
+        .iter()
+        .map(|p| (p.lon, p.lat))
+        .collect();
+    let search = app.geo_search.as_ref();
+    let matches: Vec<(f64, f64)> = search
+        .map(|s| s.matches.iter().map(|m| (m.lon, m.lat)).collect())
+        .unwrap_or_default();
+    let center: Vec<(f64, f64)> = search.and_then(|s| s.center).into_iter().collect();
+
+    // Fit the view to the members (and search center), padded, within the world
+    let all = points.iter().chain(&center);
+    let (mut lon_lo, mut lon_hi, mut lat_lo, mut lat_hi) = all.fold(
+        (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY),
+        |(a, b, c, d), &(lon, lat)| (a.min(lon), b.max(lon), c.min(lat), d.max(lat)),
+    );
+    if !lon_lo.is_finite() {
+        (lon_lo, lon_hi, lat_lo, lat_hi) = (-180.0, 180.0, -90.0, 90.0);
+    }
+    let pad_lon = ((lon_hi - lon_lo) * 0.1).max(0.5);
+    let pad_lat = ((lat_hi - lat_lo) * 0.1).max(0.5);
+    let x_bounds = [(lon_lo - pad_lon).max(-180.0), (lon_hi + pad_lon).min(180.0)];
+    let y_bounds = [(lat_lo - pad_lat).max(-90.0), (lat_hi + pad_lat).min(90.0)];
+
+    let labels: Vec<(f64, f64, String)> = if points.len() <= 20 {
+        app.geo_points
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|p| (p.lon, p.lat, p.member.clone()))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let search_label = match search {
+        Some(s) => format!(" {} within {} {}", s.matches.len(), s.radius, s.unit),
+        None => String::new(),
+    };
+    let title = format!(
+        " Map [G]geo [Q]search {} members{} lon {:.2}..{:.2} lat {:.2}..{:.2} ",
+        points.len(),
+        search_label,
+        x_bounds[0],
+        x_bounds[1],
+        y_bounds[0],
+        y_bounds[1]
+    );
+
+    let canvas = Canvas::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(border_color))
+                .title(title),
+        )
+        .marker(safe_marker(area))
+        .x_bounds(x_bounds)
+        .y_bounds(y_bounds)
+        .paint(|ctx| {
+            ctx.draw(&Map {
+                color: Color::DarkGray,
+                resolution: MapResolution::High,
+            });
+            ctx.layer();
+            ctx.draw(&Points { coords: &points, color: Color::Cyan });
+            ctx.draw(&Points { coords: &matches, color: Color::Yellow });
+            ctx.draw(&Points { coords: &center, color: Color::Red });
+            for (lon, lat, name) in &labels {
+                ctx.print(*lon, *lat, Span::styled(format!(" {}", name), Style::default().fg(Color::White)));
+            }
+        });
+    frame.render_widget(canvas, area);
+}
+
+/// Axis label for a millisecond timestamp, with precision to suit the visible span
+fn time_axis_label(ms: f64, span_ms: f64) -> String {
+    let iso = format_epoch_millis(ms as i64); // 2024-01-31T12:34:56.789Z
+    if span_ms < 60_000.0 {
+        iso[11..23].to_string()
+    } else if span_ms < 86_400_000.0 {
+        iso[11..19].to_string()
+    } else {
+        iso[5..16].replace('T', " ")
+    }
+}
+
 fn draw_fft_chart(frame: &mut Frame, app: &mut App, area: Rect, _border_color: Color) {
     if area.width < 12 || area.height < 5 {
         return;
@@ -504,6 +752,11 @@ fn draw_status_bar(frame: &mut Frame, app: &App, area: Rect) {
         "Disconnected"
     };
 
+    let (status_text, status_color) = match &app.rdb_path {
+        Some(path) => (format!("RDB {} (read-only)", path), Color::Yellow),
+        None => (status_text.to_string(), status_color),
+    };
+
     let line = Line::from(vec![
         Span::raw(" "),
         Span::styled(status_text, Style::default().fg(status_color)),
@@ -534,21 +787,27 @@ fn draw_filter_popup(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_confirm_popup(frame: &mut Frame, app: &App, area: Rect) {
-    let popup_area = centered_rect(50, 5, area);
+    // Bulk previews list details below the question
+    let detail_lines = app.confirm_action.as_deref().map_or(0, |a| a.lines().count().saturating_sub(1));
+    let popup_area = centered_rect(60, 7 + detail_lines as u16, area);
     frame.render_widget(Clear, popup_area);
 
     let msg = if let Some(action) = &app.confirm_action {
-        format!("{}?\n\n[y] Yes  [n/Esc] No", action)
+        let (question, details) = action.split_once('\n').unwrap_or((action, ""));
+        let details = if details.is_empty() { String::new() } else { format!("\n{}", details) };
+        format!("{}?{}\n\n[y] Yes  [n/Esc] No", question, details)
     } else {
         "Confirm? [y/n]".to_string()
     };
 
-    let popup = Paragraph::new(msg).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Red))
-            .title(" Confirm "),
-    );
+    let popup = Paragraph::new(msg)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title(" Confirm "),
+        );
     frame.render_widget(popup, popup_area);
 }
 
@@ -598,6 +857,14 @@ fn draw_help_popup(frame: &mut Frame, app: &App, area: Rect) {
             Span::raw("Edit the selected key's value"),
         ]),
         Line::from(Span::styled("            Ctrl+B toggles binary encoding mode", dim)),
+        Line::from(Span::styled("            Shift+arrows select, Ctrl+Z undo, Alt+Enter new line", dim)),
+        Line::from(Span::styled("            Changed on the server meanwhile: o/m/r overwrite, merge, reload", dim)),
+        Line::from(Span::styled("            Streams: XADD with many fields, ID, trim, per-value Ctrl+B", dim)),
+        Line::from(vec![
+            Span::styled("  E        ", key_style),
+            Span::raw("Edit the value in $EDITOR (string, JSON, hash field,"),
+        ]),
+        Line::from(Span::styled("            list element); binary as one number per line", dim)),
         Line::from(vec![
             Span::styled("  n        ", key_style),
             Span::raw("Create a new key (string, list, hash, set, stream)"),
@@ -606,10 +873,49 @@ fn draw_help_popup(frame: &mut Frame, app: &App, area: Rect) {
             Span::styled("  d        ", key_style),
             Span::raw("Delete the selected key (with confirmation)"),
         ]),
+        Line::from(vec![
+            Span::styled("  D        ", key_style),
+            Span::raw("Remove or trim elements of a collection"),
+        ]),
+        Line::from(Span::styled("            HDEL, LREM, LPOP/RPOP, LTRIM, SREM, ZREM, ZREMRANGEBYSCORE,", dim)),
+        Line::from(Span::styled("            XDEL, XTRIM; PgUp/PgDn picks, Enter previews the count", dim)),
         Line::from(vec![
             Span::styled("  R        ", key_style),
             Span::raw("Rename the selected key"),
         ]),
+        Line::from(vec![
+            Span::styled("  u / U    ", key_style),
+            Span::raw("Undo the last delete/overwrite / show the undo history"),
+        ]),
+        Line::from(vec![
+            Span::styled("  Space    ", key_style),
+            Span::raw("Mark the selected key; * marks all listed, ! inverts"),
+        ]),
+        Line::from(vec![
+            Span::styled("  b        ", key_style),
+            Span::raw("Bulk action on the marked keys: delete, set/clear TTL,"),
+        ]),
+        Line::from(Span::styled("            move to a db, rename a prefix, export as NDJSON", dim)),
+        Line::from(vec![
+            Span::styled("  X        ", key_style),
+            Span::raw("Delete every key matching a pattern, after a preview"),
+        ]),
+        Line::from(Span::styled("            and typing the key count; UNLINK in batches", dim)),
+        Line::from(vec![
+            Span::styled("  W        ", key_style),
+            Span::raw("Export keys, the filter matches or the whole db to a file"),
+        ]),
+        Line::from(Span::styled("            .ndjson/.json, .csv (blobs decoded) or .resp for redis-cli --pipe", dim)),
+        Line::from(vec![
+            Span::styled("  O        ", key_style),
+            Span::raw("Import an export file, a CSV of numbers or a command file"),
+        ]),
+        Line::from(Span::styled("            existing keys are skipped, overwritten or renamed", dim)),
+        Line::from(vec![
+            Span::styled("  C        ", key_style),
+            Span::raw("Copy or move keys to another db or saved server"),
+        ]),
+        Line::from(Span::styled("            COPY/MOVE, or DUMP + RESTORE keeping TTLs; by pattern too", dim)),
         Line::from(vec![
             Span::styled("  z        ", key_style),
             Span::raw("Set TTL (expiry) on the selected key in seconds"),
@@ -619,6 +925,53 @@ fn draw_help_popup(frame: &mut Frame, app: &App, area: Rect) {
             Span::raw("Switch to Redis database 0-9"),
         ]),
         Line::from(""),
+        // --- Value View ---
+        Line::from(vec![Span::styled("Value View", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))]),
+        Line::from(vec![
+            Span::styled("  Up/Down  ", key_style),
+            Span::raw("Hashes, lists, sets, zsets, streams: select an element"),
+        ]),
+        Line::from(Span::styled("            Enter opens the decoded element, PgUp/PgDn scroll it", dim)),
+        Line::from(Span::styled("            s edits it in place, d deletes it (HDEL/LREM/SREM/ZREM/XDEL)", dim)),
+        Line::from(vec![
+            Span::styled("  F        ", key_style),
+            Span::raw("Choose a value format (msgpack, cbor, protobuf, ...)"),
+        ]),
+        Line::from(Span::styled("            auto detects compressed/binary formats; per field too", dim)),
+        Line::from(vec![
+            Span::styled("  Enter    ", key_style),
+            Span::raw("JSON keys: fold/unfold the selected node"),
+        ]),
+        Line::from(Span::styled("            Up/Down select, Left/Right collapse/expand", dim)),
+        Line::from(Span::styled("            s edits at the node's path (JSON.SET/DEL/ARRAPPEND/NUMINCRBY)", dim)),
+        Line::from(vec![
+            Span::styled("  Q        ", key_style),
+            Span::raw("Query the key: time series range/aggregation,"),
+        ]),
+        Line::from(Span::styled("            BF/CF EXISTS, CMS/TOPK QUERY, TDIGEST QUANTILE, PFCOUNT", dim)),
+        Line::from(Span::styled("            s adds samples/items (TS.ADD, BF.MADD, PFADD, ...)", dim)),
+        Line::from(vec![
+            Span::styled("  G        ", key_style),
+            Span::raw("Toggle the geo view of a sorted set (table + map)"),
+        ]),
+        Line::from(Span::styled("            Q searches by radius (GEOSEARCH)", dim)),
+        Line::from(vec![
+            Span::styled("  B        ", key_style),
+            Span::raw("Toggle the bitmap view of a string (bit grid)"),
+        ]),
+        Line::from(Span::styled("            Arrows select a bit, Enter/Space toggles it (SETBIT)", dim)),
+        Line::from(Span::styled("            Q decodes BITFIELD integers, e.g. u8 0; i5 100; u12 #2 x16", dim)),
+        Line::from(vec![
+            Span::styled("  V        ", key_style),
+            Span::raw("Cosine similarity of two keys' vectors"),
+        ]),
+        Line::from(Span::styled("            Hash fields holding FLOAT32 embeddings show dim, norm, sparkline", dim)),
+        Line::from(vec![
+            Span::styled("  I        ", key_style),
+            Span::raw("RediSearch indexes (FT._LIST, FT.INFO)"),
+        ]),
+        Line::from(Span::styled("            Enter runs FT.SEARCH/FT.AGGREGATE; Enter on a result opens its key", dim)),
+        Line::from(""),
         // --- Streams ---
         Line::from(vec![Span::styled("Streams", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))]),
         Line::from(vec![
@@ -646,6 +999,11 @@ fn draw_help_popup(frame: &mut Frame, app: &App, area: Rect) {
             Span::styled("  e        ", key_style),
             Span::raw("Toggle byte order: Little-Endian ↔ Big-Endian"),
         ]),
+        Line::from(vec![
+            Span::styled("  A        ", key_style),
+            Span::raw("Toggle data type auto-detection for binary blobs"),
+        ]),
+        Line::from(Span::styled("            Confident guesses are applied on load; see the plot title", dim)),
         Line::from(vec![
             Span::styled("  a        ", key_style),
             Span::raw("Auto-fit axis limits to data range"),
@@ -667,6 +1025,24 @@ fn draw_help_popup(frame: &mut Frame, app: &App, area: Rect) {
             Span::raw("Toggle FFT Y-axis: linear ↔ log₁₀ scale"),
         ]),
         Line::from(Span::styled("            Use Up/Down to switch focus between Signal and FFT", dim)),
+        Line::from(vec![
+            Span::styled("  L        ", key_style),
+            Span::raw("Define a record layout (array of C structs)"),
+        ]),
+        Line::from(Span::styled("            e.g. u32 ts; i16 x; i16 y; f32 v  (header N, pad N, packed)", dim)),
+        Line::from(vec![
+            Span::styled("  m / M    ", key_style),
+            Span::raw("Cycle the record member being plotted"),
+        ]),
+        Line::from(vec![
+            Span::styled("  o        ", key_style),
+            Span::raw("Header offset, sample limit, decimation, gain/offset"),
+        ]),
+        Line::from(vec![
+            Span::styled("  P        ", key_style),
+            Span::raw("Manage decode profiles (saved per key or glob)"),
+        ]),
+        Line::from(Span::styled("            Matching profiles are applied when a key is loaded", dim)),
         Line::from(""),
         // --- Mouse ---
         Line::from(vec![Span::styled("Mouse (Plot)", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))]),
@@ -762,124 +1138,776 @@ fn draw_plot_limit_popup(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(popup, popup_area);
 }
 
-fn draw_signal_gen_popup(frame: &mut Frame, app: &App, area: Rect) {
-    let popup_area = centered_rect(60, 18, area);
+fn draw_record_layout_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, 13, area);
     frame.render_widget(Clear, popup_area);
 
     let mut lines: Vec<Line> = Vec::new();
     lines.push(Line::from(Span::styled(
-        "Signal Generator",
+        "Record Layout",
         Style::default()
             .fg(Color::Yellow)
             .add_modifier(Modifier::BOLD),
     )));
+    lines.push(Line::from(Span::styled(
+        "e.g. u32 ts; i16 x; i16 y; f32 v   (char name[8], pad 2, empty = off)",
+        Style::default().fg(Color::DarkGray),
+    )));
     lines.push(Line::from(""));
 
-    // Row 0: Wave Type selector
-    let wave_focused = app.signal_gen_focus == 0;
-    let wave_indicator = if wave_focused { "> " } else { "  " };
-    let wave_label_style = if wave_focused {
-        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::Yellow)
-    };
-    lines.push(Line::from(vec![
-        Span::styled(wave_indicator, Style::default().fg(Color::Cyan)),
-        Span::styled("Wave: ", wave_label_style),
-        Span::styled("< ", Style::default().fg(Color::DarkGray)),
-        Span::styled(
-            WAVE_TYPES[app.signal_gen_wave_idx],
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(" >", Style::default().fg(Color::DarkGray)),
-        if wave_focused {
-            Span::raw("  (Left/Right)")
-        } else {
-            Span::raw("")
-        },
-    ]));
+    lines.extend(form_field_lines(app));
 
-    // Row 1: Data Type selector
-    let dtype_focused = app.signal_gen_focus == 1;
-    let dtype_indicator = if dtype_focused { "> " } else { "  " };
-    let dtype_label_style = if dtype_focused {
-        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+    lines.push(Line::from(""));
+    if let Some(layout) = &app.record_layout {
+        lines.push(Line::from(vec![
+            Span::styled("Active: ", Style::default().fg(Color::Yellow)),
+            Span::styled(layout.summary(), Style::default().fg(Color::Green)),
+        ]));
     } else {
-        Style::default().fg(Color::Yellow)
-    };
-    let dtype_name = format!("{}", DataType::all()[app.signal_gen_dtype_idx]);
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(""));
     lines.push(Line::from(vec![
-        Span::styled(dtype_indicator, Style::default().fg(Color::Cyan)),
-        Span::styled("Type: ", dtype_label_style),
-        Span::styled("< ", Style::default().fg(Color::DarkGray)),
-        Span::styled(
-            dtype_name,
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
-        ),
-        Span::styled(" >", Style::default().fg(Color::DarkGray)),
-        if dtype_focused {
-            Span::raw("  (Left/Right)")
-        } else {
-            Span::raw("")
-        },
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Apply  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Cancel  "),
+        Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Next line"),
     ]));
 
-    lines.push(Line::from(""));
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+                .title(" Record Layout "),
+        );
+    frame.render_widget(popup, popup_area);
+}
 
-    // Rows 2-5: Text input fields
-    for (i, (label, value)) in app.signal_gen_fields.iter().enumerate() {
-        let focus_idx = i + 2; // offset by 2 selector rows
-        let is_focused = app.signal_gen_focus == focus_idx;
-        let indicator = if is_focused { "> " } else { "  " };
-        let label_style = if is_focused {
-            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(Color::Yellow)
-        };
-        let input_style = if is_focused {
-            Style::default().fg(Color::White).bg(Color::DarkGray)
-        } else {
-            Style::default().fg(Color::White)
-        };
-        let cursor = if is_focused { "_" } else { "" };
-        lines.push(Line::from(vec![
-            Span::styled(indicator, Style::default().fg(Color::Cyan)),
-            Span::styled(format!("{}: ", label), label_style),
-            Span::styled(format!("{}{}", value, cursor), input_style),
-        ]));
-    }
+fn draw_scaling_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(50, 15, area);
+    frame.render_widget(Clear, popup_area);
 
+    let mut lines: Vec<Line> = Vec::new();
+    lines.push(Line::from(Span::styled(
+        "Sample Scaling",
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(Span::styled(
+        "value = raw * gain + offset",
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines.push(Line::from(""));
+    lines.extend(form_field_lines(app));
     lines.push(Line::from(""));
     lines.push(Line::from(vec![
         Span::styled("[Enter]", Style::default().fg(Color::Green)),
-        Span::raw(" Start  "),
+        Span::raw(" Apply  "),
         Span::styled("[Esc]", Style::default().fg(Color::Red)),
         Span::raw(" Cancel  "),
         Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
-        Span::raw(" Next"),
+        Span::raw(" Next line"),
     ]));
 
     let popup = Paragraph::new(lines).block(
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(HIGHLIGHT_COLOR))
-            .title(" Signal Generator "),
+            .title(" Scaling "),
     );
     frame.render_widget(popup, popup_area);
 }
 
-fn draw_edit_popup(frame: &mut Frame, app: &App, area: Rect) {
-    let field_count = app.edit_fields.len();
-    let is_new_key = app.edit_operation == Some(EditOperation::NewKey);
-    let is_multi = app.is_multi_entry_edit();
-    let extra_type = if is_new_key { 2 } else { 0 };
-    let extra_count = if is_multi && app.edit_multi_count > 0 { 1 } else { 0 };
-    let extra_binary = if app.edit_binary_mode { 2 } else { 1 }; // binary mode row + type/endian row
-    let height = (5 + field_count * 2 + extra_type + extra_count + extra_binary).min(24) as u16;
-    let popup_area = centered_rect(60, height, area);
+fn draw_value_format_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, 14, area);
     frame.render_widget(Clear, popup_area);
 
-    let title = if is_new_key {
+    let names: Vec<String> = ValueFormat::all().iter().map(|f| f.to_string()).collect();
+    let mut lines: Vec<Line> = vec![
+        Line::from(Span::styled(
+            "Value Format",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(names.join(" "), Style::default().fg(Color::DarkGray))),
+        Line::from(Span::styled(
+            "Field: hash/stream field name or list index (auto clears an override)",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(form_field_lines(app));
+    lines.push(Line::from(""));
+    let overrides: Vec<String> = app
+        .formats
+        .field_formats
+        .iter()
+        .map(|(field, fmt)| format!("{}={}", field, fmt))
+        .collect();
+    lines.push(Line::from(vec![
+        Span::styled("Fields: ", Style::default().fg(Color::Yellow)),
+        Span::styled(
+            if overrides.is_empty() { "(none)".to_string() } else { overrides.join(", ") },
+            Style::default().fg(Color::Green),
+        ),
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Apply  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Cancel  "),
+        Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Next line"),
+    ]));
+
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+                .title(" Format "),
+        );
+    frame.render_widget(popup, popup_area);
+}
+
+/// Query popup for probabilistic structures and geo sets
+fn draw_query_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, app.edit_fields.len() as u16 + 9, area);
+    frame.render_widget(Clear, popup_area);
+
+    let (command, hint) = if app.is_geo_view() {
+        ("GEOSEARCH ... BYRADIUS", "Center is a member name or lon,lat; nearest matches first")
+    } else if app.is_bitmap_view() {
+        ("BITFIELD ... GET", "e.g. u8 0; i5 100; u12 #2 x16  (#N = N * width, xN = N values)")
+    } else {
+        (
+            app.prob_kind().map_or("", |k| k.query_command()),
+            "Space separated; results are listed below the info block",
+        )
+    };
+    let mut lines: Vec<Line> = vec![
+        Line::from(Span::styled(
+            command,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))),
+        Line::from(""),
+    ];
+    lines.extend(form_field_lines(app));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Query  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Cancel"),
+    ]));
+
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+                .title(" Query "),
+        );
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_ts_query_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, 15, area);
+    frame.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = vec![
+        Line::from(Span::styled(
+            "Time Series Range",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!("Aggregation: none {}", TS_AGGREGATORS.join(" ")),
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "desc + Count fetches the newest samples (TS.REVRANGE)",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(form_field_lines(app));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Query  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Cancel  "),
+        Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Next line"),
+    ]));
+
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+                .title(" TS.RANGE "),
+        );
+    frame.render_widget(popup, popup_area);
+}
+
+/// Render `app.edit_fields` as labelled input lines with the focused one highlighted
+fn draw_vector_compare_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, app.edit_fields.len() as u16 + app.vector_result.len() as u16 + 9, area);
+    frame.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = vec![
+        Line::from(Span::styled(
+            "Vector Similarity",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "FLOAT32 blobs (hash fields or strings) or JSON arrays of numbers",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(form_field_lines(app));
+    if !app.vector_result.is_empty() {
+        lines.push(Line::from(""));
+        for line in &app.vector_result {
+            lines.push(Line::from(Span::styled(line.clone(), Style::default().fg(Color::Green))));
+        }
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Compare  "),
+        Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Next field  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Close"),
+    ]));
+
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+                .title(" Compare "),
+        );
+    frame.render_widget(popup, popup_area);
+}
+
+/// RediSearch index list with the FT.INFO of the selected index
+fn draw_search_indexes_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(90, area.height.saturating_sub(4).min(30), area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+        .title(" RediSearch Indexes ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(1)])
+        .split(inner);
+    let cols = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+        .split(rows[0]);
+
+    // Index list, keeping the cursor visible
+    let visible = cols[0].height as usize;
+    let first = app.search_cursor.saturating_sub(visible.saturating_sub(1));
+    let mut list: Vec<Line> = Vec::new();
+    if app.search_indexes.is_empty() {
+        list.push(Line::from(Span::styled("  (no indexes)", Style::default().fg(Color::DarkGray))));
+    }
+    for (i, name) in app.search_indexes.iter().enumerate().skip(first).take(visible) {
+        let selected = i == app.search_cursor;
+        list.push(Line::from(vec![
+            Span::styled(if selected { "> " } else { "  " }, Style::default().fg(Color::Cyan)),
+            Span::styled(
+                name.clone(),
+                if selected {
+                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                },
+            ),
+        ]));
+    }
+    let list_block = Block::default()
+        .borders(Borders::RIGHT)
+        .border_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(Paragraph::new(list).block(list_block), cols[0]);
+
+    let mut info_lines: Vec<Line> = Vec::new();
+    if let Some(info) = &app.search_info {
+        let key_style = Style::default().fg(Color::Yellow);
+        info_lines.push(Line::from(vec![
+            Span::styled(format!(" {} ", info.name), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::styled(
+                format!("on {} prefixes {}", info.key_type, info.prefixes.join(" ")),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+        // Indexing progress
+        let percent = info.stat("percent_indexed").and_then(|p| p.parse::<f64>().ok()).unwrap_or(1.0);
+        let bar_width = 30;
+        let filled = ((percent.clamp(0.0, 1.0)) * bar_width as f64).round() as usize;
+        info_lines.push(Line::from(vec![
+            Span::styled(" indexed ", key_style),
+            Span::styled("█".repeat(filled), Style::default().fg(Color::Green)),
+            Span::styled("░".repeat(bar_width - filled), Style::default().fg(Color::DarkGray)),
+            Span::raw(format!(
+                " {:.1}%{}",
+                percent * 100.0,
+                if info.stat("indexing").is_some_and(|v| v != "0") { "  (indexing)" } else { "" }
+            )),
+        ]));
+        for (name, value) in &info.stats {
+            info_lines.push(Line::from(vec![
+                Span::styled(format!(" {:<24}", name), key_style),
+                Span::raw(value.clone()),
+            ]));
+        }
+        info_lines.push(Line::from(""));
+        info_lines.push(Line::from(Span::styled(
+            " Schema",
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )));
+        for attr in &info.attributes {
+            let name = if attr.name == attr.identifier {
+                attr.name.clone()
+            } else {
+                format!("{} AS {}", attr.identifier, attr.name)
+            };
+            let detail = match &attr.vector {
+                Some(v) => format!("{} dim {} {}", v.data_type, v.dim, v.metric),
+                None => attr.options.join(" "),
+            };
+            info_lines.push(Line::from(vec![
+                Span::styled(format!(" {:<8} ", attr.kind), Style::default().fg(Color::Green)),
+                Span::styled(name, Style::default().fg(Color::White)),
+                Span::styled(format!("  {}", detail), Style::default().fg(Color::DarkGray)),
+            ]));
+        }
+    }
+    frame.render_widget(Paragraph::new(info_lines), cols[1]);
+
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Up/Down]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Select  "),
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Query  "),
+            Span::styled("[r]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Reload  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Close"),
+        ])),
+        rows[1],
+    );
+}
+
+fn draw_search_query_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(70, app.edit_fields.len() as u16 + 10, area);
+    frame.render_widget(Clear, popup_area);
+
+    let index = app.search_info.as_ref().map_or("", |i| i.name.as_str());
+    let mut lines: Vec<Line> = vec![
+        Line::from(Span::styled(
+            format!("Query {}", index),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            "Arguments e.g. LIMIT 0 20 SORTBY price DESC, or for FT.AGGREGATE",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "LOAD 1 @__key GROUPBY 1 @brand REDUCE COUNT 0 AS n",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(""),
+    ];
+    let mut fields = form_field_lines(app);
+    // The command is picked with Left/Right rather than typed
+    if let Some((label, value)) = app.edit_fields.first() {
+        let focused = app.edit_focus == 0;
+        fields[0] = Line::from(vec![
+            Span::styled(if focused { "> " } else { "  " }, Style::default().fg(Color::Cyan)),
+            Span::styled(
+                format!("{}: ", label),
+                if focused {
+                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Yellow)
+                },
+            ),
+            Span::styled(format!("< {} >", value), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        ]);
+    }
+    lines.extend(fields);
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Run  "),
+        Span::styled("[Left/Right]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Command  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Back"),
+    ]));
+
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+                .title(" RediSearch Query "),
+        );
+    frame.render_widget(popup, popup_area);
+}
+
+/// Widest a results table column gets before its cells are cut
+const SEARCH_COLUMN_WIDTH: usize = 40;
+
+/// Most lines of a multi-line value shown while editing it
+const EDIT_TEXT_LINES: usize = 10;
+
+fn draw_search_results_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(95, area.height.saturating_sub(2), area);
+    frame.render_widget(Clear, popup_area);
+
+    let (columns, rows) = app.search_table();
+    let table = format_table(&columns, &rows, SEARCH_COLUMN_WIDTH);
+
+    let mut lines: Vec<Line> = vec![Line::from(Span::styled(
+        format!("  {}", table[0]),
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+    ))];
+    if rows.is_empty() {
+        lines.push(Line::from(Span::styled("  (no results)", Style::default().fg(Color::DarkGray))));
+    }
+    // Header, footer and borders take 4 rows
+    let visible = popup_area.height.saturating_sub(4) as usize;
+    let first = app.search_result_cursor.saturating_sub(visible.saturating_sub(1));
+    for (i, row) in table[1..].iter().enumerate().skip(first).take(visible) {
+        let selected = i == app.search_result_cursor;
+        let style = if selected {
+            Style::default().fg(Color::Black).bg(HIGHLIGHT_COLOR)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if selected { "> " } else { "  " }, Style::default().fg(Color::Cyan)),
+            Span::styled(row.as_str(), style),
+        ]));
+    }
+
+    let title = match &app.search_results {
+        Some(results) => format!(" {} total, {} shown ", results.total, results.hits.len()),
+        None => String::new(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+        .title(format!(" Results{}", title));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    frame.render_widget(Paragraph::new(lines), parts[0]);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Open key  "),
+            Span::styled("[Up/Down/PgUp/PgDn]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Select  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Back to query"),
+        ])),
+        parts[1],
+    );
+}
+
+fn form_field_lines(app: &App) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    for (i, (label, value)) in app.edit_fields.iter().enumerate() {
+        let is_focused = i == app.edit_focus;
+        let cursor = if is_focused { "_" } else { "" };
+        let indicator = if is_focused { "> " } else { "  " };
+        let label_style = if is_focused {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Yellow)
+        };
+        let input_style = if is_focused {
+            Style::default().fg(Color::White).bg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(indicator, Style::default().fg(Color::Cyan)),
+            Span::styled(format!("{}: ", label), label_style),
+            Span::styled(format!("{}{}", value, cursor), input_style),
+        ]));
+    }
+    lines
+}
+
+fn draw_profiles_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let list_rows = app.profiles.profiles.len().clamp(1, 12) as u16;
+    let unreadable_rows = app.profiles.unreadable.len().min(3) as u16;
+    let popup_area = centered_rect(80, list_rows + unreadable_rows + 11, area);
+    frame.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    lines.push(Line::from(Span::styled(
+        "Decode Profiles",
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(Span::styled(
+        format!("Stored in {}", app.profiles.path.display()),
+        Style::default().fg(Color::DarkGray),
+    )));
+    if let Some(err) = &app.profile_load_error {
+        lines.push(Line::from(Span::styled(
+            format!("Load error: {}", err),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    if app.profiles.profiles.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  (no profiles yet)",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    // Keep the cursor visible when the list is longer than the popup
+    let first = app.profile_cursor.saturating_sub(list_rows as usize - 1);
+    for (i, profile) in app
+        .profiles
+        .profiles
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(list_rows as usize)
+    {
+        let is_selected = i == app.profile_cursor;
+        let is_active = app.active_profile.as_deref() == Some(profile.pattern.as_str());
+        let indicator = if is_selected { "> " } else { "  " };
+        let pattern_style = if is_selected {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(indicator, Style::default().fg(Color::Cyan)),
+            Span::styled(if is_active { "* " } else { "  " }, Style::default().fg(Color::Green)),
+            Span::styled(profile.pattern.clone(), pattern_style),
+            Span::styled("  →  ", Style::default().fg(Color::DarkGray)),
+            Span::styled(profile.summary(), Style::default().fg(Color::Gray)),
+        ]));
+    }
+    // Entries kept verbatim because they could not be read
+    for (_, reason) in app.profiles.unreadable.iter().take(unreadable_rows as usize) {
+        lines.push(Line::from(Span::styled(
+            format!("  ! Unreadable, kept as-is: {}", reason),
+            Style::default().fg(Color::Red),
+        )));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("Save current settings as: ", Style::default().fg(Color::Yellow)),
+        Span::styled(
+            format!("{}_", app.profile_pattern_input),
+            Style::default().fg(Color::White).bg(Color::DarkGray),
+        ),
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Save  "),
+        Span::styled("[Del]", Style::default().fg(Color::Red)),
+        Span::raw(" Delete  "),
+        Span::styled("[Ctrl+L]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Apply  "),
+        Span::styled("[Ctrl+P]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Copy pattern  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Close"),
+    ]));
+
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+            .title(" Profiles "),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_signal_gen_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 18, area);
+    frame.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    lines.push(Line::from(Span::styled(
+        "Signal Generator",
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )));
+    lines.push(Line::from(""));
+
+    // Row 0: Wave Type selector
+    let wave_focused = app.signal_gen_focus == 0;
+    let wave_indicator = if wave_focused { "> " } else { "  " };
+    let wave_label_style = if wave_focused {
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Yellow)
+    };
+    lines.push(Line::from(vec![
+        Span::styled(wave_indicator, Style::default().fg(Color::Cyan)),
+        Span::styled("Wave: ", wave_label_style),
+        Span::styled("< ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            WAVE_TYPES[app.signal_gen_wave_idx],
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" >", Style::default().fg(Color::DarkGray)),
+        if wave_focused {
+            Span::raw("  (Left/Right)")
+        } else {
+            Span::raw("")
+        },
+    ]));
+
+    // Row 1: Data Type selector
+    let dtype_focused = app.signal_gen_focus == 1;
+    let dtype_indicator = if dtype_focused { "> " } else { "  " };
+    let dtype_label_style = if dtype_focused {
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Yellow)
+    };
+    let dtype_name = format!("{}", DataType::all()[app.signal_gen_dtype_idx]);
+    lines.push(Line::from(vec![
+        Span::styled(dtype_indicator, Style::default().fg(Color::Cyan)),
+        Span::styled("Type: ", dtype_label_style),
+        Span::styled("< ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            dtype_name,
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" >", Style::default().fg(Color::DarkGray)),
+        if dtype_focused {
+            Span::raw("  (Left/Right)")
+        } else {
+            Span::raw("")
+        },
+    ]));
+
+    lines.push(Line::from(""));
+
+    // Rows 2-5: Text input fields
+    for (i, (label, value)) in app.signal_gen_fields.iter().enumerate() {
+        let focus_idx = i + 2; // offset by 2 selector rows
+        let is_focused = app.signal_gen_focus == focus_idx;
+        let indicator = if is_focused { "> " } else { "  " };
+        let label_style = if is_focused {
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Yellow)
+        };
+        let input_style = if is_focused {
+            Style::default().fg(Color::White).bg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        let cursor = if is_focused { "_" } else { "" };
+        lines.push(Line::from(vec![
+            Span::styled(indicator, Style::default().fg(Color::Cyan)),
+            Span::styled(format!("{}: ", label), label_style),
+            Span::styled(format!("{}{}", value, cursor), input_style),
+        ]));
+    }
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Start  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Cancel  "),
+        Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Next"),
+    ]));
+
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+            .title(" Signal Generator "),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_edit_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let field_count = app.edit_fields.len();
+    let is_new_key = app.edit_operation == Some(EditOperation::NewKey);
+    let is_multi = app.is_multi_entry_edit();
+    let extra_type = if is_new_key { 2 } else { 0 };
+    let extra_count = if is_multi && app.edit_multi_count > 0 { 1 } else { 0 };
+    let extra_binary = match (app.edit_supports_binary(), app.edit_binary_mode) {
+        (true, true) => 2, // binary mode row + type/endian row
+        (true, false) => 1,
+        (false, _) => 0,
+    };
+    let extra_command = usize::from(app.is_json_edit() || app.is_remove_edit()); // command row
+    // XADD grows a pair of rows per field, so its rows are not spaced out
+    let (field_rows, extra_xadd) = if app.is_xadd_edit() { (field_count, 3) } else { (field_count * 2, 0) };
+    // The focused multi-line value shows up to EDIT_TEXT_LINES lines plus a key hint
+    let focused_multiline = app.edit_field_multiline(app.edit_focus);
+    let extra_text = match app.edit_fields.get(app.edit_focus) {
+        Some((_, value)) if focused_multiline => value.split('\n').count().min(EDIT_TEXT_LINES),
+        _ => 0,
+    };
+    let height = (5 + field_rows + extra_type + extra_count + extra_binary + extra_command + extra_xadd + extra_text)
+        .min(area.height.saturating_sub(2).max(24) as usize) as u16;
+    let popup_area = centered_rect(60, height, area);
+    let inner_width = popup_area.width.saturating_sub(2) as usize;
+    frame.render_widget(Clear, popup_area);
+
+    let title = if is_new_key {
         " New Key ".to_string()
     } else {
         format!(" Edit: {} ", app.edit_key)
@@ -918,18 +1946,35 @@ fn draw_edit_popup(frame: &mut Frame, app: &App, area: Rect) {
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled(" >", Style::default().fg(Color::DarkGray)),
-            Span::raw("  (Left/Right to change)"),
+            Span::raw("  (PgUp/PgDn to change)"),
         ]));
     }
 
-    // Binary mode toggle
-    let bin_label = if app.edit_binary_mode { "ON" } else { "OFF" };
-    let bin_color = if app.edit_binary_mode { Color::Green } else { Color::DarkGray };
-    lines.push(Line::from(vec![
-        Span::styled("Binary: ", Style::default().fg(Color::Yellow)),
-        Span::styled(bin_label, Style::default().fg(bin_color).add_modifier(Modifier::BOLD)),
-        Span::styled("  [Ctrl+B]toggle", Style::default().fg(Color::DarkGray)),
-    ]));
+    // JSON.* or removal command selector
+    if app.is_json_edit() || app.is_remove_edit() {
+        lines.push(Line::from(vec![
+            Span::styled("Command: ", Style::default().fg(Color::Yellow)),
+            Span::styled("< ", Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                app.edit_op_label(),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(" >", Style::default().fg(Color::DarkGray)),
+            Span::raw("  (PgUp/PgDn to change)"),
+        ]));
+    }
+    if app.edit_supports_binary() {
+        // Binary mode toggle
+        let bin_label = if app.edit_binary_mode { "ON" } else { "OFF" };
+        let bin_color = if app.edit_binary_mode { Color::Green } else { Color::DarkGray };
+        lines.push(Line::from(vec![
+            Span::styled("Binary: ", Style::default().fg(Color::Yellow)),
+            Span::styled(bin_label, Style::default().fg(bin_color).add_modifier(Modifier::BOLD)),
+            Span::styled("  [Ctrl+B]toggle", Style::default().fg(Color::DarkGray)),
+        ]));
+    }
 
     // Data type & endianness selectors (only when binary mode is on)
     let dtype_name = format!("{}", DataType::all()[app.edit_binary_dtype_idx]);
@@ -957,19 +2002,56 @@ fn draw_edit_popup(frame: &mut Frame, app: &App, area: Rect) {
             Style::default().fg(Color::Yellow)
         };
 
-        let cursor = if is_focused { "_" } else { "" };
         let input_style = if is_focused {
             Style::default().fg(Color::White).bg(Color::DarkGray)
         } else {
             Style::default().fg(Color::White)
         };
         let indicator = if is_focused { "> " } else { "  " };
-        let display_val = format!("{}{}", value, cursor);
-        lines.push(Line::from(vec![
+        let prefix = format!("{}: ", label);
+        let prefix_width = 2 + prefix.chars().count();
+        let head = vec![
             Span::styled(indicator, Style::default().fg(Color::Cyan)),
-            Span::styled(format!("{}: ", label), label_style),
-            Span::styled(display_val, input_style),
-        ]));
+            Span::styled(prefix, label_style),
+        ];
+
+        if is_focused {
+            // Continuation lines line up under the first
+            let width = inner_width.saturating_sub(prefix_width).max(8);
+            let rendered = app.edit_text.render(i, value, width, EDIT_TEXT_LINES, input_style);
+            for (row, text_line) in rendered.into_iter().enumerate() {
+                let mut spans = if row == 0 { head.clone() } else { vec![Span::raw(" ".repeat(prefix_width))] };
+                spans.extend(text_line.spans);
+                lines.push(Line::from(spans));
+            }
+        } else {
+            let mut spans = head;
+            let mut rows = value.lines();
+            spans.push(Span::styled(rows.next().unwrap_or("").to_string(), input_style));
+            let more = rows.count();
+            if more > 0 {
+                spans.push(Span::styled(format!(" ⏎ +{} lines", more), Style::default().fg(Color::DarkGray)));
+            }
+            lines.push(Line::from(spans));
+        }
+    }
+
+    if focused_multiline {
+        lines.push(Line::from(Span::styled(
+            "  [Alt+Enter]new line [Shift+arrows]select [Ctrl+Z/Y]undo/redo",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    if app.is_xadd_edit() {
+        lines.push(Line::from(Span::styled(
+            "  (a new pair appears once the last field is named)",
+            Style::default().fg(Color::DarkGray),
+        )));
+        lines.push(Line::from(Span::styled(
+            "  [Ctrl+B]binary value [Ctrl+T]type [Ctrl+E]endian",
+            Style::default().fg(Color::DarkGray),
+        )));
     }
 
     // Show hint about value format when binary mode is on
@@ -983,7 +2065,16 @@ fn draw_edit_popup(frame: &mut Frame, app: &App, area: Rect) {
     lines.push(Line::from(""));
 
     // Footer with context-aware instructions
-    if is_multi {
+    if app.is_remove_edit() {
+        lines.push(Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Preview & confirm  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Cancel  "),
+            Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Next line"),
+        ]));
+    } else if is_multi {
         lines.push(Line::from(vec![
             Span::styled("[Enter]", Style::default().fg(Color::Green)),
             Span::raw(" Add entry  "),
@@ -1012,6 +2103,552 @@ fn draw_edit_popup(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(popup, popup_area);
 }
 
+fn draw_undo_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(80, area.height.saturating_sub(4).min(24), area);
+    frame.render_widget(Clear, popup_area);
+
+    let (columns, rows) = app.undo_table();
+    let table = format_table(&columns, &rows, SEARCH_COLUMN_WIDTH);
+    let mut lines: Vec<Line> = vec![Line::from(Span::styled(
+        format!("  {}", table[0]),
+        Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+    ))];
+    if rows.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  (nothing yet: deletes, overwrites, removals and renames are kept here)",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    // Header, footer and borders take 4 rows
+    let visible = popup_area.height.saturating_sub(4) as usize;
+    let first = app.undo_cursor.saturating_sub(visible.saturating_sub(1));
+    for (i, row) in table[1..].iter().enumerate().skip(first).take(visible) {
+        let selected = i == app.undo_cursor;
+        let style = if selected {
+            Style::default().fg(Color::Black).bg(HIGHLIGHT_COLOR)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if selected { "> " } else { "  " }, Style::default().fg(Color::Cyan)),
+            Span::styled(row.as_str(), style),
+        ]));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+        .title(format!(" Undo history ({}) ", app.undo_stack.len()));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    frame.render_widget(Paragraph::new(lines), parts[0]);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Restore  "),
+            Span::styled("[x]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Forget  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Close"),
+        ])),
+        parts[1],
+    );
+}
+
+fn draw_bulk_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 9 + app.edit_fields.len() as u16, area);
+    frame.render_widget(Clear, popup_area);
+
+    let mut lines: Vec<Line> = vec![
+        Line::from(vec![
+            Span::styled("Action: ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("< {} >", app.bulk_action().label()),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  ({}/{}, ←/→ to change)", app.bulk_action_idx + 1, BULK_ACTIONS.len()),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::from(""),
+    ];
+    for (i, (label, value)) in app.edit_fields.iter().enumerate() {
+        let is_focused = i == app.edit_focus;
+        let (indicator, label_style, input_style) = if is_focused {
+            (
+                "> ",
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                Style::default().fg(Color::White).bg(Color::DarkGray),
+            )
+        } else {
+            ("  ", Style::default().fg(Color::Yellow), Style::default().fg(Color::White))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(indicator, Style::default().fg(Color::Cyan)),
+            Span::styled(format!("{}: ", label), label_style),
+            Span::styled(format!("{}{}", value, if is_focused { "_" } else { "" }), input_style),
+        ]));
+    }
+    if app.edit_fields.is_empty() {
+        lines.push(Line::from(Span::styled("  (nothing to fill in)", Style::default().fg(Color::DarkGray))));
+    }
+    lines.push(Line::from(""));
+    let targets = if app.marked_keys.is_empty() { "the selected key" } else { "the marked keys" };
+    lines.push(Line::from(Span::styled(
+        format!("Runs on {}; Enter shows what it will do first", targets),
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Preview  "),
+        Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Next field  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Cancel"),
+    ]));
+
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+            .title(format!(" Bulk action ({} keys) ", app.bulk_targets().len())),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_bulk_progress_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 8, area);
+    frame.render_widget(Clear, popup_area);
+    let Some(job) = &app.bulk_job else {
+        return;
+    };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+        .title(format!(" {} ", job.action.label()));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let ratio = if job.keys.is_empty() { 1.0 } else { job.done as f64 / job.keys.len() as f64 };
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(HIGHLIGHT_COLOR).bg(Color::DarkGray))
+            .ratio(ratio.clamp(0.0, 1.0))
+            .label(format!("{} / {}", job.done, job.keys.len())),
+        parts[0],
+    );
+    let mut counts = vec![Span::styled(format!("{} applied", job.changed), Style::default().fg(Color::Green))];
+    if job.failed > 0 {
+        counts.push(Span::raw("  "));
+        counts.push(Span::styled(format!("{} failed", job.failed), Style::default().fg(Color::Red)));
+    }
+    frame.render_widget(Paragraph::new(Line::from(counts)), parts[1]);
+    if let Some(e) = &job.last_error {
+        frame.render_widget(
+            Paragraph::new(e.as_str()).style(Style::default().fg(Color::Red)).wrap(Wrap { trim: true }),
+            parts[2],
+        );
+    }
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Stop after this batch"),
+        ])),
+        parts[3],
+    );
+}
+
+fn draw_pattern_delete_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let label_style = Style::default().fg(Color::Yellow);
+    let dim = Style::default().fg(Color::DarkGray);
+    let input_style = Style::default().fg(Color::White).bg(Color::DarkGray);
+    let mut lines: Vec<Line> = Vec::new();
+    let footer;
+
+    if let Some(preview) = &app.pattern_delete {
+        let memory = format_size(preview.memory);
+        lines.push(Line::from(vec![
+            Span::styled("Pattern: ", label_style),
+            Span::raw(preview.pattern.as_str()),
+            Span::styled(format!("  (db {})", app.db), dim),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("Keys:    ", label_style),
+            Span::styled(preview.keys.len().to_string(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("Memory:  ", label_style),
+            Span::raw(if preview.memory_estimated { format!("≈ {} (estimated from a sample)", memory) } else { memory }),
+        ]));
+        let prefixes: Vec<String> = app
+            .pattern_delete_prefixes()
+            .iter()
+            .take(5)
+            .map(|(prefix, n)| format!("{} ×{}", prefix, n))
+            .collect();
+        lines.push(Line::from(vec![Span::styled("Prefixes: ", label_style), Span::raw(prefixes.join(", "))]));
+        lines.push(Line::from(""));
+        for key in preview.keys.iter().take(8) {
+            lines.push(Line::from(Span::styled(format!("  {}", key), Style::default().fg(Color::White))));
+        }
+        if preview.keys.len() > 8 {
+            lines.push(Line::from(Span::styled(format!("  … and {} more", preview.keys.len() - 8), dim)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::styled(format!("Type {} to delete them: ", preview.keys.len()), label_style),
+            Span::styled(format!("{}_", preview.typed), input_style),
+        ]));
+        footer = Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Delete  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Back"),
+        ]);
+    } else {
+        lines.push(Line::from(vec![
+            Span::styled("Pattern: ", label_style),
+            Span::styled(format!("{}_", app.edit_fields[0].1), input_style),
+        ]));
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!("Glob pattern for SCAN in db {}, e.g. tmp:* or session:2024-*", app.db),
+            dim,
+        )));
+        lines.push(Line::from(Span::styled("Nothing is deleted before the preview is confirmed", dim)));
+        footer = Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Preview  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Cancel"),
+        ]);
+    }
+
+    let popup_area = centered_rect(60, lines.len() as u16 + 3, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(" Delete keys by pattern ");
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    frame.render_widget(Paragraph::new(lines), parts[0]);
+    frame.render_widget(Paragraph::new(footer), parts[1]);
+}
+
+fn draw_export_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 13, area);
+    frame.render_widget(Clear, popup_area);
+    let label_style = Style::default().fg(Color::Yellow);
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let path = app.edit_fields[0].1.trim();
+    let format = match ExportFormat::from_path(path) {
+        Ok(ExportFormat::Csv) => Span::raw(format!(
+            "CSV, binary values as {} {}",
+            app.data_type, app.endianness
+        )),
+        Ok(format) => Span::raw(format.label()),
+        Err(_) => Span::styled("unknown extension", Style::default().fg(Color::Red)),
+    };
+    let file_note = if std::path::Path::new(path).exists() { "  (exists, will be replaced)" } else { "" };
+    let lines = vec![
+        Line::from(vec![
+            Span::styled("Export: ", label_style),
+            Span::styled(
+                format!("< {} >", app.export_scope_label(app.export_scope())),
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(format!("  ({}/{})", app.export_scope_idx + 1, EXPORT_SCOPES.len()), dim),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("> File: ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("{}_", app.edit_fields[0].1), Style::default().fg(Color::White).bg(Color::DarkGray)),
+            Span::styled(file_note, Style::default().fg(Color::Red)),
+        ]),
+        Line::from(vec![Span::styled("  Format: ", label_style), format]),
+        Line::from(""),
+        Line::from(Span::styled(".ndjson / .jsonl  one object per key, with type and TTL", dim)),
+        Line::from(Span::styled(".json            the same objects in one array", dim)),
+        Line::from(Span::styled(".csv             key,type,id,field,value rows", dim)),
+        Line::from(Span::styled(".resp / .redis   commands for redis-cli --pipe", dim)),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Export  "),
+            Span::styled("[←/→]", Style::default().fg(Color::Yellow)),
+            Span::raw(" What  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Cancel"),
+        ]),
+    ];
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+            .title(" Export keys "),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_import_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(60, 16, area);
+    frame.render_widget(Clear, popup_area);
+    let label_style = Style::default().fg(Color::Yellow);
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let path = app.edit_fields[0].1.trim();
+    let format = ExportFormat::from_path(path);
+    let is_csv = matches!(format, Ok(ExportFormat::Csv));
+    let mut lines = Vec::new();
+    for (i, (label, value)) in app.edit_fields.iter().enumerate() {
+        // The CSV fields only matter for .csv files
+        if i >= 2 && !is_csv {
+            continue;
+        }
+        let focused = i == app.edit_focus;
+        let marker = if focused { "> " } else { "  " };
+        let name_style = if focused { Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD) } else { label_style };
+        let value_span = match i {
+            0 | 2 if focused => Span::styled(format!("{}_", value), Style::default().fg(Color::White).bg(Color::DarkGray)),
+            2 if value.is_empty() => Span::styled(
+                std::path::Path::new(path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+                dim,
+            ),
+            0 | 2 => Span::raw(value.as_str()),
+            _ if focused => Span::styled(format!("< {} >", value), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            _ => Span::raw(value.as_str()),
+        };
+        lines.push(Line::from(vec![Span::styled(format!("{}{}: ", marker, label), name_style), value_span]));
+    }
+    lines.push(Line::from(vec![
+        Span::styled("  Format: ", label_style),
+        match &format {
+            Ok(format) => Span::raw(format.label()),
+            Err(_) => Span::styled("unknown extension", Style::default().fg(Color::Red)),
+        },
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(".ndjson / .json   keys as written by export (W)", dim)));
+    lines.push(Line::from(Span::styled(".csv              numeric columns as a blob, or a stream entry per row", dim)));
+    lines.push(Line::from(Span::styled(".resp / .redis    RESP or redis-cli command lines", dim)));
+    lines.push(Line::from(Span::styled(
+        "Existing keys: skip, overwrite, or rename to <key>:imported",
+        dim,
+    )));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[Enter]", Style::default().fg(Color::Green)),
+        Span::raw(" Import  "),
+        Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Field  "),
+        Span::styled("[←/→]", Style::default().fg(Color::Yellow)),
+        Span::raw(" Choose  "),
+        Span::styled("[Esc]", Style::default().fg(Color::Red)),
+        Span::raw(" Cancel"),
+    ]));
+    let popup = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+            .title(format!(" Import into db {} ", app.db)),
+    );
+    frame.render_widget(popup, popup_area);
+}
+
+fn draw_copy_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let label_style = Style::default().fg(Color::Yellow);
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines: Vec<Line> = Vec::new();
+    let footer;
+    let title;
+
+    if let Some(plan) = &app.copy_plan {
+        let verb = if plan.moving { "Move" } else { "Copy" };
+        title = format!(" {} keys ", verb);
+        let method = match (plan.same_server, plan.moving, plan.replace) {
+            (true, true, false) => "MOVE",
+            (true, true, true) => "COPY … REPLACE, then UNLINK",
+            (true, false, false) => "COPY",
+            (true, false, true) => "COPY … REPLACE",
+            (false, true, _) => "DUMP + RESTORE, then UNLINK",
+            (false, false, _) => "DUMP + RESTORE",
+        };
+        lines.push(Line::from(vec![
+            Span::styled("Keys:     ", label_style),
+            Span::styled(plan.keys.len().to_string(), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  from db {}", app.db), dim),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("To:       ", label_style),
+            Span::raw(format!("{} db {}", plan.server, plan.db)),
+        ]));
+        lines.push(Line::from(vec![Span::styled("Method:   ", label_style), Span::raw(method)]));
+        let existing = match plan.existing {
+            0 => Span::raw("none"),
+            n if plan.replace => Span::styled(format!("{} (replaced)", n), Style::default().fg(Color::Red)),
+            n => Span::raw(format!("{} (left alone)", n)),
+        };
+        lines.push(Line::from(vec![Span::styled("There:    ", label_style), existing]));
+        let prefixes: Vec<String> =
+            app.copy_prefixes().iter().take(5).map(|(prefix, n)| format!("{} ×{}", prefix, n)).collect();
+        lines.push(Line::from(vec![Span::styled("Prefixes: ", label_style), Span::raw(prefixes.join(", "))]));
+        lines.push(Line::from(""));
+        for key in plan.keys.iter().take(6) {
+            lines.push(Line::from(Span::styled(format!("  {}", key), Style::default().fg(Color::White))));
+        }
+        if plan.keys.len() > 6 {
+            lines.push(Line::from(Span::styled(format!("  … and {} more", plan.keys.len() - 6), dim)));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("TTLs are kept; copies are not in the undo history", dim)));
+        footer = Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(format!(" {}  ", verb)),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Back"),
+        ]);
+    } else {
+        title = " Copy keys ".to_string();
+        for (i, (label, value)) in app.edit_fields.iter().enumerate() {
+            let focused = i == app.edit_focus;
+            let marker = if focused { "> " } else { "  " };
+            let name_style =
+                if focused { Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD) } else { label_style };
+            let value_span = match i {
+                0 if value.is_empty() => Span::styled(
+                    match app.marked_keys.len() {
+                        0 => "(the selected key)".to_string(),
+                        n => format!("({} marked keys)", n),
+                    },
+                    if focused { Style::default().fg(Color::Gray).bg(Color::DarkGray) } else { dim },
+                ),
+                0..=2 if focused => {
+                    Span::styled(format!("{}_", value), Style::default().fg(Color::White).bg(Color::DarkGray))
+                }
+                _ if focused => {
+                    Span::styled(format!("< {} >", value), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+                }
+                _ => Span::raw(value.as_str()),
+            };
+            lines.push(Line::from(vec![Span::styled(format!("{}{}: ", marker, label), name_style), value_span]));
+        }
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Keys: a SCAN pattern, or empty for the marked/selected keys", dim)));
+        lines.push(Line::from(Span::styled(
+            "Server: ←/→ through servers.json, a redis:// URL, or name=URL to save it",
+            dim,
+        )));
+        lines.push(Line::from(Span::styled(
+            "Same server: COPY / MOVE; another server: DUMP + RESTORE with TTL",
+            dim,
+        )));
+        footer = Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(Color::Green)),
+            Span::raw(" Preview  "),
+            Span::styled("[Tab]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Field  "),
+            Span::styled("[←/→]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Choose  "),
+            Span::styled("[Esc]", Style::default().fg(Color::Red)),
+            Span::raw(" Cancel"),
+        ]);
+    }
+
+    let popup_area = centered_rect(60, lines.len() as u16 + 3, area);
+    frame.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(HIGHLIGHT_COLOR))
+        .title(title);
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    frame.render_widget(Paragraph::new(lines), parts[0]);
+    frame.render_widget(Paragraph::new(footer), parts[1]);
+}
+
+fn draw_conflict_popup(frame: &mut Frame, app: &App, area: Rect) {
+    let popup_area = centered_rect(80, area.height.saturating_sub(4).min(30), area);
+    frame.render_widget(Clear, popup_area);
+    let Some(conflict) = &app.edit_conflict else {
+        return;
+    };
+
+    let removed = conflict.diff.iter().filter(|l| matches!(l, DiffLine::Removed(_))).count();
+    let added = conflict.diff.iter().filter(|l| matches!(l, DiffLine::Added(_))).count();
+    let mut lines: Vec<Line> = vec![
+        Line::from(vec![
+            Span::styled("Since it was loaded: ", Style::default().fg(Color::Yellow)),
+            Span::styled(format!("-{} ", removed), Style::default().fg(Color::Red)),
+            Span::styled(format!("+{}", added), Style::default().fg(Color::Green)),
+            Span::styled(" line(s) on the server", Style::default().fg(Color::Yellow)),
+        ]),
+        Line::from(""),
+    ];
+    if removed + added == 0 {
+        lines.push(Line::from(Span::styled(
+            "  (the change is inside a binary value)",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    for line in conflict.diff.iter().skip(conflict.scroll) {
+        lines.push(match line {
+            DiffLine::Same(text) => Line::from(Span::raw(format!("  {}", text))),
+            DiffLine::Removed(text) => Line::from(Span::styled(format!("- {}", text), Style::default().fg(Color::Red))),
+            DiffLine::Added(text) => Line::from(Span::styled(format!("+ {}", text), Style::default().fg(Color::Green))),
+            DiffLine::Skipped(n) => Line::from(Span::styled(
+                format!("  … {} unchanged line(s)", n),
+                Style::default().fg(Color::DarkGray),
+            )),
+        });
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red))
+        .title(format!(" Changed on the server: {} ", app.edit_key));
+    let inner = block.inner(popup_area);
+    frame.render_widget(block, popup_area);
+    let parts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+    frame.render_widget(Paragraph::new(lines), parts[0]);
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("[o]", Style::default().fg(Color::Red)),
+            Span::raw(" Overwrite  "),
+            Span::styled("[m]", Style::default().fg(Color::Green)),
+            Span::raw(" Merge  "),
+            Span::styled("[r]", Style::default().fg(Color::Yellow)),
+            Span::raw(" Reload  "),
+            Span::styled("[Esc]", Style::default().fg(Color::DarkGray)),
+            Span::raw(" Back to edit"),
+        ])),
+        parts[1],
+    );
+}
+
 /// Draw crosshair tick marks at the hover position on both axes
 fn draw_crosshair(
     frame: &mut Frame,
@@ -1120,6 +2757,13 @@ fn type_badge(key_type: &str) -> (&str, Color) {
         "zset" => ("ZSET", Color::Yellow),
         "hash" => ("HASH", Color::Red),
         "stream" => ("STRM", Color::Cyan),
+        "ReJSON-RL" => ("JSON", Color::LightGreen),
+        "TSDB-TYPE" => ("TS", Color::LightBlue),
+        "MBbloom--" => ("BF", Color::LightMagenta),
+        "MBbloomCF" => ("CF", Color::LightMagenta),
+        "CMSk-TYPE" => ("CMS", Color::LightMagenta),
+        "TopK-TYPE" => ("TOPK", Color::LightMagenta),
+        "TDIS-TYPE" => ("TDIG", Color::LightMagenta),
         _ => ("???", Color::DarkGray),
     }
 }
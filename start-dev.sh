@@ -49,22 +49,28 @@ $CLI SET "string:ephemeral" "I expire in 300 seconds" >/dev/null
 $CLI EXPIRE "string:ephemeral" 300 >/dev/null
 
 # ─── Two plain blobs (non-stream) ─────────────────────────
+echo "[*] Building redis-tui..."
+cargo build --quiet
+TUI="target/debug/redis-tui --port $REDIS_PORT"
+DATA_DIR=$(mktemp -d)
+trap 'rm -rf "$DATA_DIR"' EXIT
+
+# Load a CSV of numbers: import_csv <key> <stream|blob> <dtype> <file>
+import_csv() {
+    $TUI --import "$4" --csv-key "$1" --csv-into "$2" --csv-type "$3" --on-conflict overwrite --no-tui
+}
+
 echo "[*] Generating blobs..."
 
 # float32 blob - 1k elements, multi-freq sine
-python3 -c "
-import struct, math, sys
-n = 1000
-vals = [math.sin(i*0.01) + 0.5*math.sin(i*0.05) + 0.25*math.sin(i*0.13) for i in range(n)]
-sys.stdout.buffer.write(struct.pack(f'<{n}f', *vals))
-" | $CLI -x SET "blob:float32_1k" >/dev/null
+awk 'BEGIN { for (i = 0; i < 1000; i++) printf "%.9g\n", sin(i*0.01) + 0.5*sin(i*0.05) + 0.25*sin(i*0.13) }' \
+    > "$DATA_DIR/float32_1k.csv"
+import_csv "blob:float32_1k" blob float32 "$DATA_DIR/float32_1k.csv"
 echo "  blob:float32_1k"
 
 # random bytes blob for hex view
-python3 -c "
-import os, sys
-sys.stdout.buffer.write(os.urandom(256))
-" | $CLI -x SET "blob:random_256b" >/dev/null
+awk 'BEGIN { srand(); for (i = 0; i < 256; i++) print int(rand() * 256) }' > "$DATA_DIR/random_256b.csv"
+import_csv "blob:random_256b" blob uint8 "$DATA_DIR/random_256b.csv"
 echo "  blob:random_256b"
 
 # ─── Hashes ───────────────────────────────────────────────
@@ -93,19 +99,16 @@ $CLI XADD "stream:app_log" "*" level ERROR msg "Database connection lost" servic
 
 # Small sensor stream (20 entries, float32 _data)
 echo "[*] Generating small sensor stream..."
-for i in $(seq 0 19); do
-    python3 -c "
-import struct, math, sys
-t = $i * 0.5
-temp = 20.0 + 5.0 * math.sin(t) + 0.5 * (($i * 7) % 3 - 1)
-humidity = 60.0 + 10.0 * math.cos(t * 0.7)
-pressure = 1013.25 + 2.0 * math.sin(t * 0.3)
-accel_x = 0.01 * math.sin(t * 2.0)
-accel_y = 0.01 * math.cos(t * 2.0)
-accel_z = 9.81 + 0.005 * math.sin(t * 5.0)
-sys.stdout.buffer.write(struct.pack('<6f', temp, humidity, pressure, accel_x, accel_y, accel_z))
-" | $CLI -x XADD "stream:sensor_data" "*" sensor_id "env-001" _ >/dev/null
-done
+awk 'BEGIN {
+    print "sensor_id,temp,humidity,pressure,accel_x,accel_y,accel_z"
+    for (i = 0; i < 20; i++) {
+        t = i * 0.5
+        printf "env-001,%.9g,%.9g,%.9g,%.9g,%.9g,%.9g\n",
+            20.0 + 5.0*sin(t) + 0.5*((i*7) % 3 - 1), 60.0 + 10.0*cos(t*0.7), 1013.25 + 2.0*sin(t*0.3),
+            0.01*sin(t*2.0), 0.01*cos(t*2.0), 9.81 + 0.005*sin(t*5.0)
+    }
+}' > "$DATA_DIR/sensor_data.csv"
+import_csv "stream:sensor_data" stream float32 "$DATA_DIR/sensor_data.csv"
 
 # ─── Big streams with binary _data ───────────────────────
 echo "[*] Generating big streams..."
@@ -114,76 +117,53 @@ generate_big_stream() {
     local key=$1
     local count=$2
     local dtype=$3
-    local fmt=$4
-    local values_per_entry=$5
+    local values_per_entry=$4
 
     echo "  ${key} (${count} entries, ${dtype})..."
 
-    python3 -c "
-import struct, math, sys
-
-key = '${key}'
-count = ${count}
-fmt = '${fmt}'
-vpe = ${values_per_entry}
-dtype = '${dtype}'
-
-for i in range(count):
     # Each entry is a waveform: multi-freq sine, shifted by entry index
-    phase_offset = i * 0.3
-    vals = []
-    for j in range(vpe):
-        t = j / vpe * 2 * math.pi + phase_offset
-        v = math.sin(t) + 0.5 * math.sin(3 * t) + 0.25 * math.sin(7 * t)
-        if dtype == 'float32' or dtype == 'float64':
-            vals.append(v)
-        elif dtype == 'int16':
-            vals.append(int(max(-32768, min(32767, 16000 * v))))
-        elif dtype == 'uint16':
-            vals.append(int(max(0, min(65535, 32768 + 18000 * v))))
-        elif dtype == 'int32':
-            vals.append(int(max(-2**31, min(2**31-1, int(1e8 * v)))))
-        elif dtype == 'uint32':
-            vals.append(int(max(0, min(2**32-1, 2**31 + int(5e8 * v)))))
-        elif dtype == 'int8':
-            vals.append(int(max(-128, min(127, 72 * v))))
-        elif dtype == 'uint8':
-            vals.append(int(max(0, min(255, 128 + 72 * v))))
-        else:
-            vals.append(v)
-
-    blob = struct.pack(fmt, *vals)
-
-    parts = ['XADD', key, '*', 'source', 'gen', '_']
-    resp = f'*{len(parts) + 1}\r\n'
-    for p in parts:
-        b = p.encode()
-        resp += f'\${len(b)}\r\n'
-        sys.stdout.buffer.write(resp.encode())
-        sys.stdout.buffer.write(b)
-        sys.stdout.buffer.write(b'\r\n')
-        resp = ''
-    sys.stdout.buffer.write(f'\${len(blob)}\r\n'.encode())
-    sys.stdout.buffer.write(blob)
-    sys.stdout.buffer.write(b'\r\n')
-" | $CLI --pipe >/dev/null 2>&1
+    awk -v count="$count" -v vpe="$values_per_entry" -v dtype="$dtype" '
+    function clamp(x, lo, hi) { return x < lo ? lo : (x > hi ? hi : x) }
+    BEGIN {
+        pi = atan2(0, -1)
+        printf "source"
+        for (j = 0; j < vpe; j++) printf ",v%d", j
+        print ""
+        for (i = 0; i < count; i++) {
+            printf "gen"
+            for (j = 0; j < vpe; j++) {
+                t = j / vpe * 2 * pi + i * 0.3
+                v = sin(t) + 0.5 * sin(3 * t) + 0.25 * sin(7 * t)
+                if (dtype == "float32")     printf ",%.9g", v
+                else if (dtype == "float64") printf ",%.17g", v
+                else if (dtype == "int16")  printf ",%.0f", int(clamp(16000 * v, -32768, 32767))
+                else if (dtype == "uint16") printf ",%.0f", int(clamp(32768 + 18000 * v, 0, 65535))
+                else if (dtype == "int32")  printf ",%.0f", int(1e8 * v)
+                else if (dtype == "uint32") printf ",%.0f", 2^31 + int(5e8 * v)
+                else if (dtype == "int8")   printf ",%.0f", int(clamp(72 * v, -128, 127))
+                else if (dtype == "uint8")  printf ",%.0f", int(clamp(128 + 72 * v, 0, 255))
+            }
+            print ""
+        }
+    }' > "$DATA_DIR/big_stream.csv"
+    import_csv "$key" stream "$dtype" "$DATA_DIR/big_stream.csv"
 }
 
-# Args: key, num_entries, dtype, struct_fmt, values_per_entry
+# Args: key, num_entries, dtype, values_per_entry
 # Key names reflect values_per_entry (what gets plotted per entry)
-generate_big_stream "stream:float32_500"  100  "float32" "<500f" 500
-generate_big_stream "stream:float64_200"  100  "float64" "<200d" 200
-generate_big_stream "stream:int16_1000"   100  "int16"   "<1000h" 1000
-generate_big_stream "stream:uint16_500"   100  "uint16"  "<500H" 500
-generate_big_stream "stream:uint8_2000"   100  "uint8"   "<2000B" 2000
-generate_big_stream "stream:int8_1000"    100  "int8"    "<1000b" 1000
-generate_big_stream "stream:int32_200"    100  "int32"   "<200i" 200
-generate_big_stream "stream:uint32_200"   100  "uint32"  "<200I" 200
+generate_big_stream "stream:float32_500"  100  "float32" 500
+generate_big_stream "stream:float64_200"  100  "float64" 200
+generate_big_stream "stream:int16_1000"   100  "int16"   1000
+generate_big_stream "stream:uint16_500"   100  "uint16"  500
+generate_big_stream "stream:uint8_2000"   100  "uint8"   2000
+generate_big_stream "stream:int8_1000"    100  "int8"    1000
+generate_big_stream "stream:int32_200"    100  "int32"   200
+generate_big_stream "stream:uint32_200"   100  "uint32"  200
 
 # ─── Large streams for FFT stress testing ─────────────────
 echo "[*] Generating large streams..."
-generate_big_stream "stream:large_f32_10k"  50  "float32" "<10000f" 10000
-generate_big_stream "stream:large_i16_20k"  50  "int16"   "<20000h" 20000
+generate_big_stream "stream:large_f32_10k"  50  "float32" 10000
+generate_big_stream "stream:large_i16_20k"  50  "int16"   20000
 
 # ─── Some keys in DB 1 ────────────────────────────────────
 $CLI -n 1 SET "db1:test_key" "This is in database 1" >/dev/null